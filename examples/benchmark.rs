@@ -0,0 +1,217 @@
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+        RegisterDiagnostic,
+    },
+    prelude::*,
+    render::{renderer::render_system, Render, RenderApp, RenderSet},
+    utils::{HashMap, Instant},
+    window::PresentMode,
+};
+use bevy_egui::{update_egui_textures_system, EguiContexts, EguiPlugin, EguiSet};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Reading input events and passing them to Egui.
+static INPUT: DiagnosticPath = DiagnosticPath::const_new("egui/input");
+/// Running the systems that build the UI.
+static UI: DiagnosticPath = DiagnosticPath::const_new("egui/ui");
+/// Ending Egui frames, which includes tessellation.
+static TESSELLATION: DiagnosticPath = DiagnosticPath::const_new("egui/tessellation");
+/// Applying Egui texture deltas to Bevy images.
+static TEXTURES: DiagnosticPath = DiagnosticPath::const_new("egui/textures");
+/// Running the whole render graph (includes uploading textures and drawing the scene).
+static RENDER: DiagnosticPath = DiagnosticPath::const_new("render");
+
+static PHASES: [&DiagnosticPath; 5] = [&INPUT, &UI, &TESSELLATION, &TEXTURES, &RENDER];
+
+/// Measures the cost of an Egui frame with a configurable amount of widgets.
+///
+/// Run it with `cargo run --release --example benchmark -- --windows 10 --widgets 100`,
+/// average per-phase timings are logged every second. Vsync is disabled to keep the frame rate
+/// from hiding the difference.
+fn main() {
+    let config = BenchmarkConfig::from_args();
+    let timings = PhaseTimings::default();
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            present_mode: PresentMode::AutoNoVsync,
+            ..default()
+        }),
+        ..default()
+    }))
+    .add_plugins(EguiPlugin)
+    .add_plugins((FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin::default()))
+    .insert_resource(config)
+    .insert_resource(timings.clone())
+    .add_systems(Update, ui_system)
+    .add_systems(
+        PreUpdate,
+        (
+            start_phase(&INPUT).before(EguiSet::ProcessInput),
+            end_phase(&INPUT).after(EguiSet::ProcessInput),
+        ),
+    )
+    .add_systems(
+        Update,
+        (
+            start_phase(&UI).before(ui_system),
+            end_phase(&UI).after(ui_system),
+        ),
+    )
+    .add_systems(
+        PostUpdate,
+        (
+            start_phase(&TESSELLATION).before(EguiSet::EndPass),
+            end_phase(&TESSELLATION).after(EguiSet::EndPass),
+            start_phase(&TEXTURES)
+                .after(EguiSet::EndPass)
+                .before(update_egui_textures_system),
+            end_phase(&TEXTURES).after(update_egui_textures_system),
+        ),
+    )
+    .add_systems(Last, report_timings_system);
+    for phase in PHASES {
+        app.register_diagnostic(Diagnostic::new(phase.clone()).with_suffix("ms"));
+    }
+
+    // The render app runs in parallel with the main one, timings are shared through a mutex.
+    app.sub_app_mut(RenderApp)
+        .insert_resource(timings)
+        .add_systems(
+            Render,
+            (
+                start_phase(&RENDER).before(render_system),
+                end_phase(&RENDER).after(render_system),
+            )
+                .in_set(RenderSet::Render),
+        );
+
+    app.run();
+}
+
+#[derive(Resource)]
+struct BenchmarkConfig {
+    windows: usize,
+    widgets: usize,
+}
+
+impl BenchmarkConfig {
+    fn from_args() -> Self {
+        let mut config = Self {
+            windows: 5,
+            widgets: 50,
+        };
+        // There are no arguments on web, the defaults are used.
+        let args: Vec<String> = std::env::args().collect();
+        for pair in args.windows(2) {
+            let value = || {
+                pair[1]
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid value for {}: {}", pair[0], pair[1]))
+            };
+            match pair[0].as_str() {
+                "--windows" => config.windows = value(),
+                "--widgets" => config.widgets = value(),
+                _ => {}
+            }
+        }
+        info!(
+            "Running with {} windows, {} widgets each",
+            config.windows, config.widgets
+        );
+        config
+    }
+}
+
+#[derive(Default)]
+struct PhaseTiming {
+    start: Option<Instant>,
+    /// The last measured duration in milliseconds, until it's reported.
+    duration: Option<f64>,
+}
+
+#[derive(Resource, Clone, Default)]
+struct PhaseTimings(Arc<Mutex<HashMap<DiagnosticPath, PhaseTiming>>>);
+
+impl PhaseTimings {
+    fn start(&self, phase: &DiagnosticPath) {
+        let mut timings = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        timings.entry(phase.clone()).or_default().start = Some(Instant::now());
+    }
+
+    fn end(&self, phase: &DiagnosticPath) {
+        let mut timings = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let timing = timings.entry(phase.clone()).or_default();
+        if let Some(start) = timing.start.take() {
+            timing.duration = Some(start.elapsed().as_secs_f64() * 1000.0);
+        }
+    }
+
+    fn take_duration(&self, phase: &DiagnosticPath) -> Option<f64> {
+        let mut timings = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        timings
+            .get_mut(phase)
+            .and_then(|timing| timing.duration.take())
+    }
+}
+
+fn start_phase(phase: &'static DiagnosticPath) -> impl Fn(Res<PhaseTimings>) {
+    move |timings: Res<PhaseTimings>| timings.start(phase)
+}
+
+fn end_phase(phase: &'static DiagnosticPath) -> impl Fn(Res<PhaseTimings>) {
+    move |timings: Res<PhaseTimings>| timings.end(phase)
+}
+
+fn report_timings_system(timings: Res<PhaseTimings>, mut diagnostics: Diagnostics) {
+    for phase in PHASES {
+        if let Some(duration) = timings.take_duration(phase) {
+            diagnostics.add_measurement(phase, || duration);
+        }
+    }
+}
+
+fn ui_system(
+    mut contexts: EguiContexts,
+    config: Res<BenchmarkConfig>,
+    mut values: Local<Vec<f32>>,
+    mut checked: Local<Vec<bool>>,
+) {
+    let widget_count = config.windows * config.widgets;
+    values.resize(widget_count, 0.5);
+    checked.resize(widget_count, false);
+
+    let ctx = contexts.ctx_mut();
+    for window in 0..config.windows {
+        egui::Window::new(format!("Window {window}"))
+            .default_pos(egui::pos2(
+                20.0 + 30.0 * window as f32,
+                20.0 + 30.0 * window as f32,
+            ))
+            .vscroll(true)
+            .show(ctx, |ui| {
+                for widget in 0..config.widgets {
+                    let i = window * config.widgets + widget;
+                    // Cycle through a few widget types to get a mix of text and shapes.
+                    match widget % 4 {
+                        0 => {
+                            ui.label(format!("Label {widget}: the quick brown fox"));
+                        }
+                        1 => {
+                            if ui.button(format!("Button {widget}")).clicked() {
+                                values[i] = 0.5;
+                            }
+                        }
+                        2 => {
+                            ui.add(egui::Slider::new(&mut values[i], 0.0..=1.0).text("Slider"));
+                        }
+                        _ => {
+                            ui.checkbox(&mut checked[i], format!("Checkbox {widget}"));
+                        }
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContextSettings, EguiContexts, EguiPlugin};
+
+/// Duration of fading the menu in or out, in seconds.
+const FADE_DURATION: f32 = 0.5;
+
+#[derive(Resource)]
+struct Menu {
+    visible: bool,
+    opacity: f32,
+}
+
+// Fades the whole UI with `EguiContextSettings::tint`, without changing any widget colors.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        // The menu starts transparent and fades in.
+        .insert_resource(Menu {
+            visible: true,
+            opacity: 0.0,
+        })
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(Camera2dBundle::default());
+        })
+        .add_systems(Update, (toggle_menu_system, fade_system, ui_system).chain())
+        .run();
+}
+
+fn toggle_menu_system(keys: Res<ButtonInput<KeyCode>>, mut menu: ResMut<Menu>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        menu.visible = !menu.visible;
+    }
+}
+
+// Moves the opacity towards the target and applies it to the context of the primary window.
+fn fade_system(
+    time: Res<Time>,
+    mut menu: ResMut<Menu>,
+    mut context_settings: Query<&mut EguiContextSettings, With<PrimaryWindow>>,
+) {
+    let target = if menu.visible { 1.0 } else { 0.0 };
+    let step = time.delta_seconds() / FADE_DURATION;
+    menu.opacity = if menu.opacity < target {
+        (menu.opacity + step).min(target)
+    } else {
+        (menu.opacity - step).max(target)
+    };
+
+    let tint = egui::Rgba::from_white_alpha(menu.opacity);
+    for mut context_settings in context_settings.iter_mut() {
+        if context_settings.tint != tint {
+            context_settings.tint = tint;
+        }
+    }
+}
+
+fn ui_system(mut contexts: EguiContexts, mut menu: ResMut<Menu>) {
+    // A faded out context still receives input, so the menu isn't shown after fading out.
+    if !menu.visible && menu.opacity == 0.0 {
+        return;
+    }
+    egui::Window::new("Menu")
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading("Paused");
+                if ui.button("Resume").clicked() {
+                    menu.visible = false;
+                }
+                ui.label("Press Escape to toggle the menu.");
+            });
+        });
+}
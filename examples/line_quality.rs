@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiPlugin, EguiSettings};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Update, ui_example_system)
+        .run();
+}
+
+// Draws a grid of 1 point wide lines to compare how hairlines look with different settings.
+// Try switching between the 1.0 and 1.5 scale factors: without feathering or the minimum stroke
+// width, some of the lines get blurrier or thinner than others at fractional scale factors.
+fn ui_example_system(mut contexts: EguiContexts, mut egui_settings: ResMut<EguiSettings>) {
+    egui::SidePanel::left("settings").show(contexts.ctx_mut(), |ui| {
+        ui.heading("Line quality");
+        ui.horizontal(|ui| {
+            ui.label("Scale factor:");
+            for scale_factor in [1.0, 1.5] {
+                if ui
+                    .selectable_label(
+                        egui_settings.scale_factor == scale_factor,
+                        format!("{scale_factor}"),
+                    )
+                    .clicked()
+                {
+                    egui_settings.scale_factor = scale_factor;
+                }
+            }
+        });
+        let mut feathering = egui_settings.feathering;
+        if ui.checkbox(&mut feathering, "Feathering").changed() {
+            egui_settings.feathering = feathering;
+        }
+        let mut min_stroke_width = egui_settings.min_stroke_width_in_pixels;
+        if ui
+            .add(egui::Slider::new(&mut min_stroke_width, 0.0..=2.0).text("Min stroke width (px)"))
+            .changed()
+        {
+            egui_settings.min_stroke_width_in_pixels = min_stroke_width;
+        }
+    });
+
+    egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+        let rect = ui.available_rect_before_wrap();
+        let painter = ui.painter_at(rect);
+        let stroke = egui::Stroke::new(1.0, ui.visuals().text_color());
+        const STEP: f32 = 10.0;
+
+        let mut x = rect.left();
+        while x <= rect.right() {
+            painter.vline(x, rect.y_range(), stroke);
+            x += STEP;
+        }
+        let mut y = rect.top();
+        while y <= rect.bottom() {
+            painter.hline(rect.x_range(), y, stroke);
+            y += STEP;
+        }
+    });
+}
@@ -0,0 +1,179 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_phase::TrackedRenderPass,
+        render_resource::{
+            CachedRenderPipelineId, ColorTargetState, ColorWrites, CompareFunction,
+            DepthStencilState, FragmentState, MultisampleState, PipelineCache, PrimitiveState,
+            RenderPipelineDescriptor, SpecializedRenderPipeline, SpecializedRenderPipelines,
+            TextureFormat, VertexState,
+        },
+        RenderApp,
+    },
+    utils::HashMap,
+};
+use bevy_egui::{
+    egui_node::{new_paint_callback, EguiPaintCallback, EguiPaintCallbackInfo},
+    EguiContexts, EguiPlugin,
+};
+
+const TRIANGLE_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(1622460126914855977);
+
+const TRIANGLE_SHADER: &str = r"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var positions = array<vec2<f32>, 3>(vec2(0.0, 1.0), vec2(-1.0, -1.0), vec2(1.0, -1.0));
+    var colors = array<vec3<f32>, 3>(vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0));
+    var out: VertexOutput;
+    out.position = vec4(positions[index], 0.0, 1.0);
+    out.color = vec4(colors[index], 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
+";
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_plugins(TrianglePlugin)
+        .add_systems(Update, ui_example_system)
+        .run();
+}
+
+fn ui_example_system(mut contexts: EguiContexts) {
+    egui::Window::new("Paint callback").show(contexts.ctx_mut(), |ui| {
+        ui.label("The triangle is drawn by a custom render pipeline.");
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(256.0, 256.0), egui::Sense::hover());
+        ui.painter().add(new_paint_callback(rect, TriangleCallback));
+    });
+}
+
+struct TrianglePlugin;
+
+impl Plugin for TrianglePlugin {
+    fn build(&self, app: &mut App) {
+        app.world_mut().resource_mut::<Assets<Shader>>().insert(
+            TRIANGLE_SHADER_HANDLE.id(),
+            Shader::from_wgsl(TRIANGLE_SHADER, file!()),
+        );
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .init_resource::<TrianglePipeline>()
+            .init_resource::<TrianglePipelineIds>()
+            .init_resource::<SpecializedRenderPipelines<TrianglePipeline>>();
+    }
+}
+
+#[derive(Resource, Default)]
+struct TrianglePipeline;
+
+impl SpecializedRenderPipeline for TrianglePipeline {
+    type Key = (TextureFormat, u32, Option<TextureFormat>);
+
+    fn specialize(
+        &self,
+        (format, sample_count, depth_format): Self::Key,
+    ) -> RenderPipelineDescriptor {
+        RenderPipelineDescriptor {
+            label: Some("triangle pipeline".into()),
+            layout: vec![],
+            push_constant_ranges: vec![],
+            vertex: VertexState {
+                shader: TRIANGLE_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "vs_main".into(),
+                buffers: vec![],
+            },
+            primitive: PrimitiveState::default(),
+            // Depth-tested contexts are drawn in a pass with a depth attachment, the triangle is
+            // drawn over what's behind it.
+            depth_stencil: depth_format.map(|format| DepthStencilState {
+                format,
+                depth_write_enabled: false,
+                depth_compare: CompareFunction::Always,
+                stencil: default(),
+                bias: default(),
+            }),
+            multisample: MultisampleState {
+                count: sample_count,
+                ..default()
+            },
+            fragment: Some(FragmentState {
+                shader: TRIANGLE_SHADER_HANDLE,
+                shader_defs: vec![],
+                entry_point: "fs_main".into(),
+                targets: vec![Some(ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+        }
+    }
+}
+
+// Pipelines specialized for the formats and sample counts of the passes the triangle is drawn in.
+#[derive(Resource, Default)]
+struct TrianglePipelineIds(
+    HashMap<(TextureFormat, u32, Option<TextureFormat>), CachedRenderPipelineId>,
+);
+
+struct TriangleCallback;
+
+impl EguiPaintCallback for TriangleCallback {
+    fn update(&self, info: &EguiPaintCallbackInfo, world: &mut World) {
+        let key = (info.texture_format, info.sample_count, info.depth_format);
+        if world.resource::<TrianglePipelineIds>().0.contains_key(&key) {
+            return;
+        }
+        let pipeline_id = world.resource_scope(
+            |world, mut pipelines: Mut<SpecializedRenderPipelines<TrianglePipeline>>| {
+                pipelines.specialize(
+                    world.resource::<PipelineCache>(),
+                    world.resource::<TrianglePipeline>(),
+                    key,
+                )
+            },
+        );
+        world
+            .resource_mut::<TrianglePipelineIds>()
+            .0
+            .insert(key, pipeline_id);
+    }
+
+    fn render<'pass>(
+        &'pass self,
+        info: &EguiPaintCallbackInfo,
+        render_pass: &mut TrackedRenderPass<'pass>,
+        world: &'pass World,
+    ) {
+        let Some(pipeline_id) = world.resource::<TrianglePipelineIds>().0.get(&(
+            info.texture_format,
+            info.sample_count,
+            info.depth_format,
+        )) else {
+            return;
+        };
+        // The pipeline may still be compiling.
+        let Some(pipeline) = world
+            .resource::<PipelineCache>()
+            .get_render_pipeline(*pipeline_id)
+        else {
+            return;
+        };
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.draw(0..3, 0..1);
+    }
+}
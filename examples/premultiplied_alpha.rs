@@ -0,0 +1,128 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+};
+use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSettings};
+
+const GRADIENT_SIZE: u32 = 128;
+
+// Semi-transparent panels and a soft-edged texture drawn over colored stripes.
+// Switching where textures get premultiplied shouldn't change how anything looks:
+// too dark or too bright edges indicate double or missing premultiplication.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup_system)
+        .add_systems(Update, ui_system)
+        .run();
+}
+
+#[derive(Resource)]
+struct Gradients {
+    unmultiplied: Handle<Image>,
+    premultiplied: Handle<Image>,
+}
+
+fn setup_system(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    // A red disc fading out towards the edges.
+    let gradient = |premultiplied: bool| {
+        let mut data = Vec::new();
+        for y in 0..GRADIENT_SIZE {
+            for x in 0..GRADIENT_SIZE {
+                let offset = Vec2::new(x as f32, y as f32) / GRADIENT_SIZE as f32 - 0.5;
+                let alpha = (1.0 - offset.length() * 2.0).clamp(0.0, 1.0);
+                let color = egui::Rgba::from_rgba_unmultiplied(1.0, 0.0, 0.0, alpha);
+                let color = egui::Color32::from(color);
+                if premultiplied {
+                    data.extend(color.to_array());
+                } else {
+                    data.extend(color.to_srgba_unmultiplied());
+                }
+            }
+        }
+        Image::new(
+            Extent3d {
+                width: GRADIENT_SIZE,
+                height: GRADIENT_SIZE,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        )
+    };
+    commands.insert_resource(Gradients {
+        unmultiplied: images.add(gradient(false)),
+        premultiplied: images.add(gradient(true)),
+    });
+}
+
+fn ui_system(
+    mut contexts: EguiContexts,
+    mut egui_settings: ResMut<EguiSettings>,
+    gradients: Res<Gradients>,
+) {
+    // User textures have to match the chosen mode.
+    let gradient = if egui_settings.premultiplied_textures {
+        gradients.premultiplied.clone_weak()
+    } else {
+        gradients.unmultiplied.clone_weak()
+    };
+    let gradient_id = contexts.add_image(gradient);
+
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let screen_rect = ctx.screen_rect();
+    let stripes = [
+        egui::Color32::from_rgb(255, 50, 50),
+        egui::Color32::from_rgb(50, 255, 50),
+        egui::Color32::from_rgb(50, 50, 255),
+        egui::Color32::WHITE,
+        egui::Color32::BLACK,
+    ];
+    let stripe_width = screen_rect.width() / stripes.len() as f32;
+    for (i, color) in stripes.into_iter().enumerate() {
+        let min = screen_rect.left_top() + egui::vec2(stripe_width * i as f32, 0.0);
+        let rect = egui::Rect::from_min_size(min, egui::vec2(stripe_width, screen_rect.height()));
+        painter.rect_filled(rect, 0.0, color);
+    }
+
+    egui::Window::new("Premultiplied alpha").show(ctx, |ui| {
+        let mut premultiplied_textures = egui_settings.premultiplied_textures;
+        if ui
+            .checkbox(
+                &mut premultiplied_textures,
+                "Premultiply textures on the CPU",
+            )
+            .changed()
+        {
+            egui_settings.premultiplied_textures = premultiplied_textures;
+        }
+        ui.label("Drag the windows over the stripes, nothing should change when switching.");
+        ui.image(egui::load::SizedTexture::new(
+            gradient_id,
+            [GRADIENT_SIZE as f32; 2],
+        ));
+    });
+
+    for (i, fill) in [
+        egui::Color32::from_rgba_unmultiplied(255, 255, 255, 128),
+        egui::Color32::from_rgba_unmultiplied(0, 0, 0, 128),
+        egui::Color32::from_rgba_unmultiplied(255, 128, 0, 64),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        egui::Window::new(format!("Panel {i}"))
+            .default_pos([300.0, 50.0 + 150.0 * i as f32])
+            .frame(egui::Frame::window(&ctx.style()).fill(fill))
+            .show(ctx, |ui| {
+                ui.label(format!("Fill: {fill:?}"));
+            });
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+    },
+};
+use bevy_egui::{EguiContexts, EguiMeshPointerInput, EguiPlugin, EguiRenderToTexture};
+
+/// Size of the panel mesh in world units.
+const PANEL_SIZE: Vec2 = Vec2::new(2.0, 1.0);
+/// Size of the texture Egui renders the panel to.
+const TEXTURE_SIZE: UVec2 = UVec2::new(512, 256);
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, setup_system)
+        .add_systems(Update, ui_system)
+        .run();
+}
+
+fn setup_system(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: TEXTURE_SIZE.x,
+            height: TEXTURE_SIZE.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Egui renders to the image, and the material samples it.
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+
+    // The panel entity holds both the mesh displaying the texture and the Egui context.
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Rectangle::from_size(PANEL_SIZE)),
+            material: materials.add(StandardMaterial {
+                base_color_texture: Some(image.clone()),
+                // Egui textures have premultiplied alpha.
+                alpha_mode: AlphaMode::Premultiplied,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            }),
+            transform: Transform::from_rotation(Quat::from_rotation_y(0.4)),
+            ..default()
+        },
+        EguiRenderToTexture(image),
+        // Raycasts the cursor onto the panel mesh and sends it to the context.
+        EguiMeshPointerInput,
+    ));
+
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Cuboid::new(0.5, 0.5, 0.5)),
+        material: materials.add(Color::srgb(0.8, 0.7, 0.6)),
+        transform: Transform::from_xyz(0.0, 0.0, -1.5),
+        ..default()
+    });
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(2.0, 3.0, 2.0),
+        ..default()
+    });
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 0.5, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+fn ui_system(
+    mut contexts: EguiContexts,
+    mut panels: Query<(Entity, &mut Transform), With<EguiRenderToTexture>>,
+    mut clicks: Local<u32>,
+) {
+    let Ok((panel, mut transform)) = panels.get_single_mut() else {
+        return;
+    };
+    egui::CentralPanel::default().show(contexts.ctx_for_window_mut(panel), |ui| {
+        ui.heading("Egui in a 3D scene");
+        if ui.button("Click me").clicked() {
+            *clicks += 1;
+        }
+        ui.label(format!("Clicked {} times", *clicks));
+
+        let (mut angle, _, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        if ui
+            .add(egui::Slider::new(&mut angle, -1.0..=1.0).text("Rotation"))
+            .changed()
+        {
+            transform.rotation = Quat::from_rotation_y(angle);
+        }
+    });
+}
@@ -5,8 +5,8 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugins(EguiPlugin)
-        // Systems that create Egui widgets should be run during the `CoreSet::Update` set,
-        // or after the `EguiSet::BeginFrame` system (which belongs to the `CoreSet::PreUpdate` set).
+        // Systems that create Egui widgets should be run during the `Update` schedule
+        // (the `EguiSet::UserUi` set), after the Egui pass begins in `PreUpdate`.
         .add_systems(Update, ui_example_system)
         .run();
 }
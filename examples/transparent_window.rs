@@ -0,0 +1,47 @@
+use bevy::{prelude::*, window::CompositeAlphaMode};
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+// A frameless transparent window showing Egui widgets over the desktop.
+fn main() {
+    App::new()
+        // The camera clears the window with a fully transparent color.
+        .insert_resource(ClearColor(Color::NONE))
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                transparent: true,
+                decorations: false,
+                // Egui writes premultiplied colors. Metal doesn't report the `PreMultiplied`
+                // mode, but composites non-opaque layers as premultiplied anyway.
+                #[cfg(target_os = "macos")]
+                composite_alpha_mode: CompositeAlphaMode::PostMultiplied,
+                #[cfg(not(target_os = "macos"))]
+                composite_alpha_mode: CompositeAlphaMode::PreMultiplied,
+                ..default()
+            }),
+            ..default()
+        }))
+        .add_plugins(EguiPlugin)
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(Camera2dBundle::default());
+        })
+        .add_systems(Update, ui_system)
+        .run();
+}
+
+fn ui_system(mut contexts: EguiContexts, mut opacity: Local<Option<f32>>) {
+    let opacity = opacity.get_or_insert(0.8);
+    let ctx = contexts.ctx_mut();
+    egui::Window::new("Transparent window").show(ctx, |ui| {
+        ui.label("Only Egui widgets are visible, the rest of the window is see-through.");
+        ui.add(egui::Slider::new(opacity, 0.0..=1.0).text("Panel opacity"));
+    });
+
+    // Panels have opaque fills by default, a frame with a transparent fill has to be set
+    // for the desktop to be visible through them.
+    let fill = ctx.style().visuals.panel_fill.gamma_multiply(*opacity);
+    egui::TopBottomPanel::bottom("bottom_panel")
+        .frame(egui::Frame::side_top_panel(&ctx.style()).fill(fill))
+        .show(ctx, |ui| {
+            ui.label("A semi-transparent panel");
+        });
+}
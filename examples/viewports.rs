@@ -0,0 +1,66 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContextSettings, EguiContexts, EguiPlugin};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+// The viewport callback outlives the frame, so the state it shares with systems is reference counted.
+#[derive(Resource, Default)]
+struct Inspector {
+    open: Arc<AtomicBool>,
+}
+
+// Shows a deferred Egui viewport in its own Bevy window.
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(EguiPlugin)
+        .init_resource::<Inspector>()
+        .add_systems(Startup, disable_embedding_system)
+        .add_systems(Update, ui_system)
+        .run();
+}
+
+// Without this, viewports are drawn inside the primary window.
+fn disable_embedding_system(
+    mut context_settings: Query<&mut EguiContextSettings, With<PrimaryWindow>>,
+) {
+    for mut context_settings in context_settings.iter_mut() {
+        context_settings.embed_viewports = false;
+    }
+}
+
+fn ui_system(mut contexts: EguiContexts, inspector: Res<Inspector>) {
+    let ctx = contexts.ctx_mut();
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let mut open = inspector.open.load(Ordering::Relaxed);
+        if ui.checkbox(&mut open, "Show the inspector").changed() {
+            inspector.open.store(open, Ordering::Relaxed);
+        }
+    });
+
+    if !inspector.open.load(Ordering::Relaxed) {
+        return;
+    }
+    let open = inspector.open.clone();
+    ctx.show_viewport_deferred(
+        egui::ViewportId::from_hash_of("inspector"),
+        egui::ViewportBuilder::default()
+            .with_title("Inspector")
+            .with_inner_size([320.0, 240.0]),
+        move |ctx, _class| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.label(format!("Pointer: {:?}", ctx.pointer_latest_pos()));
+                if ui.button("Close").clicked() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            });
+            // Closing the window (or sending the `Close` command) asks the app to stop showing
+            // the viewport.
+            if ctx.input(|input| input.viewport().close_requested()) {
+                open.store(false, Ordering::Relaxed);
+            }
+        },
+    );
+}
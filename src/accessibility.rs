@@ -0,0 +1,206 @@
+use crate::{EguiContext, EguiContextSettings, EguiInput, EguiOutput};
+use bevy::{
+    a11y::{accesskit, AccessibilityRequested, ActionRequest, ManageAccessibilityUpdates},
+    ecs::system::NonSendMut,
+    log,
+    prelude::{Entity, EventReader, Query, Res, ResMut, With},
+    window::Window,
+    winit::accessibility::AccessKitAdapters,
+};
+
+/// Stops Bevy from publishing its own accessibility tree, which would replace the one Egui
+/// builds for the window.
+///
+/// This also disables the accessibility nodes of `bevy_ui`. Set
+/// [`ManageAccessibilityUpdates`] back to `true` after this system runs (in
+/// [`bevy::app::Startup`], for example) to make Bevy manage the tree again.
+pub fn disable_bevy_accessibility_updates_system(
+    manage_accessibility_updates: Option<ResMut<ManageAccessibilityUpdates>>,
+) {
+    if let Some(mut manage_accessibility_updates) = manage_accessibility_updates {
+        manage_accessibility_updates.set(false);
+    }
+}
+
+/// Enables AccessKit output once an assistive technology is active, and passes its action
+/// requests (clicking a button or setting a slider value, for example) to Egui.
+///
+/// Action requests aren't tied to a window, so each of them is sent to every window context.
+/// Egui ignores the requests that target widgets it doesn't have.
+pub fn process_accessibility_input_system(
+    accessibility_requested: Option<Res<AccessibilityRequested>>,
+    mut action_requests: EventReader<ActionRequest>,
+    mut contexts: Query<(&mut EguiContext, &EguiContextSettings, &mut EguiInput), With<Window>>,
+) {
+    if !accessibility_requested.is_some_and(|requested| requested.get()) {
+        action_requests.clear();
+        return;
+    }
+
+    let action_requests: Vec<_> = action_requests
+        .read()
+        .filter_map(|request| convert_action_request(request))
+        .collect();
+    for (mut context, context_settings, mut egui_input) in contexts.iter_mut() {
+        context.get_mut().enable_accesskit();
+        if context_settings.display_only {
+            continue;
+        }
+        egui_input.events.extend(
+            action_requests
+                .iter()
+                .cloned()
+                .map(egui::Event::AccessKitActionRequest),
+        );
+    }
+}
+
+/// Sends the accessibility tree of each window context to the AccessKit adapter of the window.
+pub fn update_accessibility_system(
+    adapters: Option<NonSendMut<AccessKitAdapters>>,
+    contexts: Query<(Entity, &EguiOutput), With<Window>>,
+) {
+    let Some(mut adapters) = adapters else {
+        return;
+    };
+    for (window_entity, egui_output) in contexts.iter() {
+        let Some(update) = &egui_output.platform_output.accesskit_update else {
+            continue;
+        };
+        let Some(adapter) = adapters.get_mut(&window_entity) else {
+            continue;
+        };
+        // The conversion is only done if an assistive technology is listening.
+        adapter.update_if_active(|| {
+            convert_tree_update(update).unwrap_or_else(|| empty_tree_update(window_entity))
+        });
+    }
+}
+
+// Egui and Bevy depend on different AccessKit versions. Their serialized formats are the same,
+// apart from the roles and properties that Bevy's version has renamed or removed.
+pub(crate) fn convert_tree_update(
+    update: &egui::accesskit::TreeUpdate,
+) -> Option<accesskit::TreeUpdate> {
+    let mut value = serde_json::to_value(update).ok()?;
+    if let Some(nodes) = value
+        .get_mut("nodes")
+        .and_then(serde_json::Value::as_array_mut)
+    {
+        for node in nodes.iter_mut().filter_map(|node| node.get_mut(1)) {
+            let Some(node) = node.as_object_mut() else {
+                continue;
+            };
+            if let Some(checked) = node.remove("checked") {
+                node.insert("toggled".to_owned(), checked);
+            }
+            let role = match node.get("role").and_then(serde_json::Value::as_str) {
+                Some("toggleButton") => "button",
+                Some("column" | "tableHeaderContainer") => "genericContainer",
+                _ => continue,
+            };
+            node.insert("role".to_owned(), role.into());
+        }
+    }
+    match serde_json::from_value(value) {
+        Ok(update) => Some(update),
+        Err(err) => {
+            log::warn!("Failed to convert the Egui accessibility tree: {err}");
+            None
+        }
+    }
+}
+
+fn convert_action_request(
+    request: &accesskit::ActionRequest,
+) -> Option<egui::accesskit::ActionRequest> {
+    let value = serde_json::to_value(request).ok()?;
+    serde_json::from_value(value).ok()
+}
+
+// Keeps the adapter in a valid state if the tree couldn't be converted.
+fn empty_tree_update(window_entity: Entity) -> accesskit::TreeUpdate {
+    let root = accesskit::NodeId(window_entity.to_bits());
+    accesskit::TreeUpdate {
+        nodes: vec![(
+            root,
+            accesskit::NodeBuilder::new(accesskit::Role::Window).build(),
+        )],
+        tree: Some(accesskit::Tree::new(root)),
+        focus: root,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContexts};
+    use bevy::prelude::Resource;
+
+    #[test]
+    fn test_accessibility_tree_and_action_requests() {
+        #[derive(Resource, Default)]
+        struct Checkbox {
+            checked: bool,
+            id: Option<egui::Id>,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, mut checkbox: ResMut<Checkbox>) {
+            egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+                let mut checked = checkbox.checked;
+                checkbox.id = Some(ui.checkbox(&mut checked, "Check").id);
+                checkbox.checked = checked;
+                ui.toggle_value(&mut false, "Toggle");
+            });
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Checkbox>()
+            .add_systems(bevy::app::Update, ui_system);
+        app.update();
+        assert!(app
+            .world()
+            .get::<EguiOutput>(window)
+            .unwrap()
+            .platform_output
+            .accesskit_update
+            .is_none());
+
+        app.world()
+            .resource::<bevy::a11y::AccessibilityRequested>()
+            .set(true);
+        app.update();
+        let update = app
+            .world()
+            .get::<EguiOutput>(window)
+            .unwrap()
+            .platform_output
+            .accesskit_update
+            .clone()
+            .unwrap();
+        let update = convert_tree_update(&update).unwrap();
+        let checkbox_id = app.world().resource::<Checkbox>().id.unwrap();
+        let node = |role| {
+            update
+                .nodes
+                .iter()
+                .find(|(_, node)| node.role() == role)
+                .map(|(_, node)| node)
+                .unwrap()
+        };
+        assert_eq!(
+            node(accesskit::Role::CheckBox).toggled(),
+            Some(accesskit::Toggled::False)
+        );
+        assert_eq!(node(accesskit::Role::Button).name(), Some("Toggle"));
+
+        app.world_mut()
+            .send_event(bevy::a11y::ActionRequest(accesskit::ActionRequest {
+                action: accesskit::Action::Default,
+                target: accesskit::NodeId(checkbox_id.value()),
+                data: None,
+            }));
+        app.update();
+        assert!(app.world().resource::<Checkbox>().checked);
+    }
+}
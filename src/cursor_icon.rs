@@ -0,0 +1,111 @@
+use crate::systems;
+use bevy::prelude::{Entity, Resource};
+
+/// Type of the mapping set with [`EguiCursorIconMapping::set`].
+pub type EguiCursorIconMapper =
+    Box<dyn Fn(Entity, egui::CursorIcon) -> Option<bevy::window::CursorIcon> + Send + Sync>;
+
+/// A resource that maps cursor icons requested by Egui to the icons applied to windows
+/// during the [`EguiSet::EndPass`](crate::EguiSet::EndPass) system.
+///
+/// Egui sets the icon of a window while the pointer is over an Egui area, while Egui is using
+/// the pointer (dragging a window edge, for example), or if it requests a specific icon.
+/// Elsewhere, the icon set by the app is kept: once the pointer leaves Egui areas, the icon the
+/// window had before (or the one the app has set since) is restored. On web, winit applies the
+/// icons as CSS cursors of the canvas.
+///
+/// By default, the icon Egui requests is applied as is (see [`EguiCursorIconMapping::egui_default`]).
+/// A mapping receives the window entity and the icon requested by Egui, and returns the icon to
+/// apply, or `None` to leave the window's cursor untouched. This allows suppressing specific
+/// icons, or keeping a game's own cursor even when it's over Egui:
+///
+/// ```rust
+/// use bevy::{prelude::*, window::CursorIcon};
+/// use bevy_egui::{egui, EguiCursorIconMapping};
+///
+/// fn setup_cursor_icons(mut cursor_icons: ResMut<EguiCursorIconMapping>) {
+///     cursor_icons.set(|_window, icon| match icon {
+///         // Keep the arrow over text edits.
+///         egui::CursorIcon::Text => Some(CursorIcon::Default),
+///         icon => Some(EguiCursorIconMapping::egui_default(icon)),
+///     });
+/// }
+///
+/// fn keep_game_cursor(mut cursor_icons: ResMut<EguiCursorIconMapping>) {
+///     cursor_icons.set(|_window, _icon| None);
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct EguiCursorIconMapping {
+    mapper: Option<EguiCursorIconMapper>,
+}
+
+impl EguiCursorIconMapping {
+    /// Replaces the mapping.
+    pub fn set(
+        &mut self,
+        mapper: impl Fn(Entity, egui::CursorIcon) -> Option<bevy::window::CursorIcon>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.mapper = Some(Box::new(mapper));
+    }
+
+    /// Restores the default mapping, which applies the icons requested by Egui.
+    pub fn reset(&mut self) {
+        self.mapper = None;
+    }
+
+    /// Returns the icon to apply to a window for the icon requested by Egui,
+    /// `None` if the window's cursor should be left untouched.
+    #[must_use]
+    pub fn map(&self, window: Entity, icon: egui::CursorIcon) -> Option<bevy::window::CursorIcon> {
+        match &self.mapper {
+            Some(mapper) => mapper(window, icon),
+            None => Some(Self::egui_default(icon)),
+        }
+    }
+
+    /// Returns the window icon that corresponds to an Egui icon. Icons that don't have
+    /// an equivalent (such as [`egui::CursorIcon::None`]) are mapped to the default arrow.
+    #[must_use]
+    pub fn egui_default(icon: egui::CursorIcon) -> bevy::window::CursorIcon {
+        systems::egui_to_winit_cursor_icon(icon).unwrap_or(bevy::window::CursorIcon::Default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_icon_mapping() {
+        let window = Entity::from_raw(0);
+        let mut mapping = EguiCursorIconMapping::default();
+        assert_eq!(
+            mapping.map(window, egui::CursorIcon::Text),
+            Some(bevy::window::CursorIcon::Text)
+        );
+        assert_eq!(
+            mapping.map(window, egui::CursorIcon::None),
+            Some(bevy::window::CursorIcon::Default)
+        );
+
+        mapping.set(|_window, icon| match icon {
+            egui::CursorIcon::Text => None,
+            icon => Some(EguiCursorIconMapping::egui_default(icon)),
+        });
+        assert_eq!(mapping.map(window, egui::CursorIcon::Text), None);
+        assert_eq!(
+            mapping.map(window, egui::CursorIcon::PointingHand),
+            Some(bevy::window::CursorIcon::Pointer)
+        );
+
+        mapping.reset();
+        assert_eq!(
+            mapping.map(window, egui::CursorIcon::Text),
+            Some(bevy::window::CursorIcon::Text)
+        );
+    }
+}
@@ -1,6 +1,10 @@
 struct Transform {
     scale: vec2<f32>,
     translation: vec2<f32>,
+    // Linear, premultiplied.
+    tint: vec4<f32>,
+    // Is only used when depth testing.
+    depth: f32,
 }
 
 struct VertexInput {
@@ -19,25 +23,72 @@ struct VertexOutput {
 @group(1) @binding(0) var image_texture: texture_2d<f32>;
 @group(1) @binding(1) var image_sampler: sampler;
 
+#ifdef ENCODE_SRGB_OUTPUT
+fn srgb_from_linear(linear: vec3<f32>) -> vec3<f32> {
+    let cutoff = linear < vec3<f32>(0.0031308);
+    let lower = linear * 12.92;
+    let higher = 1.055 * pow(linear, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, cutoff);
+}
+#else
 fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
     let cutoff = srgb < vec3<f32>(0.04045);
     let lower = srgb / 12.92;
     let higher = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
     return select(higher, lower, cutoff);
 }
+#endif
 
 @vertex
 fn vs_main(in: VertexInput) -> VertexOutput {
     let position = in.position * transform.scale + transform.translation;
+#ifdef ENCODE_SRGB_OUTPUT
+    // Vertex colors are premultiplied in gamma space, which is what the render target stores.
+    let color = in.color;
+#else
     let color = vec4<f32>(linear_from_srgb(in.color.rgb), in.color.a);
-    return VertexOutput(vec4<f32>(position, 0.0, 1.0), color, in.uv);
+#endif
+    return VertexOutput(vec4<f32>(position, transform.depth, 1.0), color, in.uv);
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    let texture_color = textureSample(image_texture, image_sampler, in.uv);
+#ifndef BLIT
+fn tint() -> vec4<f32> {
+#ifdef ENCODE_SRGB_OUTPUT
+    // Colors are computed in gamma space, the tint is unmultiplied to encode it.
+    if transform.tint.a <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+    let rgb = srgb_from_linear(transform.tint.rgb / transform.tint.a);
+    return vec4<f32>(rgb * transform.tint.a, transform.tint.a);
+#else
+    return transform.tint;
+#endif
+}
+#endif
+
+fn fragment_color(in: VertexOutput) -> vec4<f32> {
+    var texture_color = textureSample(image_texture, image_sampler, in.uv);
+#ifdef ENCODE_SRGB_OUTPUT
+    // The render target doesn't encode colors on write, so the color is computed in gamma space.
+    // Premultiplying gamma-encoded colors keeps semi-transparent pixels from getting brighter
+    // than the compositor expects on transparent windows.
+    texture_color = vec4<f32>(srgb_from_linear(texture_color.rgb), texture_color.a);
+#endif
+#ifdef PREMULTIPLIED_TEXTURE
+    // Offscreen render targets that get upscaled onto windows are already premultiplied.
+    return in.color * texture_color;
+#else
     // This assumes that texture images are not premultiplied.
-    let color = in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a);
+    return in.color * vec4<f32>(texture_color.rgb * texture_color.a, texture_color.a);
+#endif
+}
 
-    return color;
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+#ifdef BLIT
+    // Offscreen render targets have been tinted when Egui was painted to them.
+    return fragment_color(in);
+#else
+    return fragment_color(in) * tint();
+#endif
 }
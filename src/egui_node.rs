@@ -1,36 +1,57 @@
 use crate::{
     render_systems::{
-        EguiPipelines, EguiTextureBindGroups, EguiTextureId, EguiTransform, EguiTransforms,
+        EguiBlitPipelines, EguiContextPipelines, EguiPipelines, EguiScreenshotCaptures,
+        EguiScreenshotPipeline, EguiTextureBindGroups, EguiTextureId, EguiTransform,
+        EguiTransforms, ScreenshotCapture, EGUI_SCREENSHOT_FORMAT,
     },
-    EguiRenderOutput, EguiSettings, WindowSize,
+    viewports::EguiViewport,
+    EguiContextSettings, EguiRenderOutput, EguiRenderToTexture, EguiScreenLayout, EguiScreenshot,
+    EguiSettings, WindowSize,
 };
 use bevy::{
-    ecs::world::{FromWorld, World},
+    color::LinearRgba,
+    core_pipeline::core_3d::CORE_3D_DEPTH_FORMAT,
+    ecs::world::{FromWorld, Mut, World},
+    log,
     prelude::{Entity, Handle, Resource},
     render::{
-        render_asset::RenderAssetUsages,
-        render_graph::{Node, NodeRunError, RenderGraphContext},
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{InternedRenderLabel, Node, NodeRunError, RenderGraphContext},
+        render_phase::TrackedRenderPass,
         render_resource::{
-            BindGroupLayout, BindGroupLayoutEntry, BindingType, BlendComponent, BlendFactor,
-            BlendOperation, BlendState, Buffer, BufferAddress, BufferBindingType, BufferDescriptor,
-            BufferUsages, ColorTargetState, ColorWrites, Extent3d, FragmentState, FrontFace,
-            IndexFormat, LoadOp, MultisampleState, Operations, PipelineCache, PrimitiveState,
-            RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor,
-            SamplerBindingType, Shader, ShaderStages, ShaderType, SpecializedRenderPipeline,
-            StoreOp, TextureDimension, TextureFormat, TextureSampleType, TextureViewDimension,
-            VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+            BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource,
+            BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, Buffer,
+            BufferAddress, BufferBindingType, BufferDescriptor, BufferUsages, ColorTargetState,
+            ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, Extent3d, FilterMode,
+            FragmentState, FrontFace, ImageCopyBuffer, ImageDataLayout, IndexFormat, LoadOp,
+            MultisampleState, Operations, PipelineCache, PrimitiveState, RenderPassColorAttachment,
+            RenderPassDescriptor, RenderPipelineDescriptor, SamplerBindingType, SamplerDescriptor,
+            Shader, ShaderDefVal, ShaderStages, ShaderType, SpecializedRenderPipeline,
+            StencilState, StoreOp, Texture, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+            TextureViewDimension, VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
         },
         renderer::{RenderContext, RenderDevice, RenderQueue},
-        texture::{Image, ImageAddressMode, ImageFilterMode, ImageSampler, ImageSamplerDescriptor},
-        view::ExtractedWindows,
+        texture::{
+            GpuImage, Image, ImageAddressMode, ImageFilterMode, ImageSampler,
+            ImageSamplerDescriptor,
+        },
+        view::{ExtractedWindows, ViewDepthTexture, ViewTarget},
     },
+    utils::HashMap,
 };
 use bytemuck::cast_slice;
 use egui::{TextureFilter, TextureOptions};
+use std::ops::Range;
 
 /// Egui shader.
 pub const EGUI_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(9898276442290979394);
 
+/// Index format of the Egui index buffer.
+///
+/// Egui meshes use `u32` indices, which we keep as is instead of splitting meshes into `u16` chunks.
+pub const EGUI_INDEX_FORMAT: IndexFormat = IndexFormat::Uint32;
+
 /// Egui render pipeline.
 #[derive(Resource)]
 pub struct EguiPipeline {
@@ -48,7 +69,7 @@ impl FromWorld for EguiPipeline {
             "egui transform bind group layout",
             &[BindGroupLayoutEntry {
                 binding: 0,
-                visibility: ShaderStages::VERTEX,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
                 ty: BindingType::Buffer {
                     ty: BufferBindingType::Uniform,
                     has_dynamic_offset: true,
@@ -88,16 +109,73 @@ impl FromWorld for EguiPipeline {
 }
 
 /// Key for specialized pipeline.
+///
+/// Pipelines are specialized for the actual format of every render target (`Bgra8UnormSrgb`
+/// or `Rgba8UnormSrgb` for most surfaces), and cached per key by
+/// [`SpecializedRenderPipelines`](bevy::render::render_resource::SpecializedRenderPipelines).
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub struct EguiPipelineKey {
     /// Texture format of a window's swap chain to render to.
+    ///
+    /// Egui is rendered in linear color space, so for non-sRGB unorm formats (such as
+    /// `Rgb10a2Unorm`), which don't encode colors on write, the pipeline encodes them in the shader.
     pub texture_format: TextureFormat,
+    /// Whether sampled textures are premultiplied (see [`EguiSettings::premultiplied_textures`]),
+    /// which is always the case for offscreen render targets that get upscaled onto windows.
+    pub premultiplied_texture: bool,
+    /// Whether the pipeline upscales an offscreen render target onto a window
+    /// (see [`EguiContextSettings::render_scale`]).
+    pub blit: bool,
+    /// Number of samples per pixel of the render target (see
+    /// [`EguiContextSettings::msaa_samples`]).
+    pub sample_count: u32,
+    /// Whether the pipeline tests and writes the depth of a camera's main pass (see
+    /// [`EguiContextSettings::depth_test_distance`]).
+    pub depth_test: bool,
+}
+
+impl EguiPipelineKey {
+    fn shader_defs(&self) -> Vec<ShaderDefVal> {
+        let mut shader_defs = Vec::new();
+        if self.premultiplied_texture {
+            shader_defs.push("PREMULTIPLIED_TEXTURE".into());
+        }
+        // Offscreen render targets have the same format as windows, so their colors are already
+        // encoded by the first pass.
+        if needs_srgb_output_encoding(self.texture_format) && !self.blit {
+            shader_defs.push("ENCODE_SRGB_OUTPUT".into());
+        }
+        // The tint is applied by the first pass as well.
+        if self.blit {
+            shader_defs.push("BLIT".into());
+        }
+        shader_defs
+    }
+
+    fn color_target(&self) -> ColorTargetState {
+        // The output of the shader is always premultiplied.
+        let blend_component = BlendComponent {
+            src_factor: BlendFactor::One,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        };
+        ColorTargetState {
+            format: self.texture_format,
+            blend: Some(BlendState {
+                color: blend_component,
+                alpha: blend_component,
+            }),
+            write_mask: ColorWrites::ALL,
+        }
+    }
 }
 
 impl SpecializedRenderPipeline for EguiPipeline {
     type Key = EguiPipelineKey;
 
     fn specialize(&self, key: Self::Key) -> RenderPipelineDescriptor {
+        let shader_defs = key.shader_defs();
+
         RenderPipelineDescriptor {
             label: Some("egui render pipeline".into()),
             layout: vec![
@@ -106,7 +184,7 @@ impl SpecializedRenderPipeline for EguiPipeline {
             ],
             vertex: VertexState {
                 shader: EGUI_SHADER_HANDLE,
-                shader_defs: Vec::new(),
+                shader_defs: shader_defs.clone(),
                 entry_point: "vs_main".into(),
                 buffers: vec![VertexBufferLayout::from_vertex_formats(
                     VertexStepMode::Vertex,
@@ -119,301 +197,1478 @@ impl SpecializedRenderPipeline for EguiPipeline {
             },
             fragment: Some(FragmentState {
                 shader: EGUI_SHADER_HANDLE,
-                shader_defs: Vec::new(),
+                shader_defs,
                 entry_point: "fs_main".into(),
-                targets: vec![Some(ColorTargetState {
-                    format: key.texture_format,
-                    blend: Some(BlendState {
-                        color: BlendComponent {
-                            src_factor: BlendFactor::One,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                        alpha: BlendComponent {
-                            src_factor: BlendFactor::One,
-                            dst_factor: BlendFactor::OneMinusSrcAlpha,
-                            operation: BlendOperation::Add,
-                        },
-                    }),
-                    write_mask: ColorWrites::ALL,
-                })],
+                targets: vec![Some(key.color_target())],
             }),
             primitive: PrimitiveState {
                 front_face: FrontFace::Cw,
                 cull_mode: None,
                 ..Default::default()
             },
-            depth_stencil: None,
-            multisample: MultisampleState::default(),
+            // The UI is drawn at a single depth, which is compared with reverse-Z depth of the
+            // scene.
+            depth_stencil: key.depth_test.then(|| DepthStencilState {
+                format: CORE_3D_DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: CompareFunction::GreaterEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: MultisampleState {
+                count: key.sample_count,
+                ..Default::default()
+            },
             push_constant_ranges: vec![],
         }
     }
 }
 
-#[derive(Debug)]
+/// Returns `true` for formats that store colors as is, expecting them to be sRGB encoded
+/// (as opposed to sRGB formats, which encode colors on write, and float formats,
+/// which are linear).
+fn needs_srgb_output_encoding(texture_format: TextureFormat) -> bool {
+    matches!(
+        texture_format,
+        TextureFormat::Rgba8Unorm
+            | TextureFormat::Bgra8Unorm
+            | TextureFormat::Rgb10a2Unorm
+            | TextureFormat::Rgba16Unorm
+    )
+}
+
+/// Returns the load operation for a window's swapchain texture: unless a clear color is set
+/// (see [`EguiContextSettings::clear_color`]), Egui is painted over what's already rendered.
+fn window_load_op<C: From<LinearRgba>>(
+    clear_color: Option<egui::Color32>,
+    texture_format: TextureFormat,
+) -> LoadOp<C> {
+    let Some(clear_color) = clear_color else {
+        return LoadOp::Load;
+    };
+    // Non-sRGB formats store the color as is, so it has to be encoded beforehand.
+    let components = if needs_srgb_output_encoding(texture_format) {
+        clear_color.to_normalized_gamma_f32()
+    } else {
+        egui::Rgba::from(clear_color).to_array()
+    };
+    let [r, g, b, a] = components;
+    LoadOp::Clear(LinearRgba::new(r, g, b, a).into())
+}
+
+/// Custom rendering that's drawn inside the Egui render pass, analogous to `CallbackTrait`
+/// of `egui-wgpu`.
+///
+/// Wrap it into an [`egui::PaintCallback`] with [`new_paint_callback`] and add it to a
+/// [`egui::Painter`] (see the `paint_callback` example). The methods are called in the render
+/// world every frame the callback is painted.
+pub trait EguiPaintCallback: Send + Sync + 'static {
+    /// Prepares the resources needed to render the callback, such as specialized pipelines
+    /// or uniform buffers.
+    ///
+    /// Called from [`Node::update`] of the Egui node.
+    fn update(&self, _info: &EguiPaintCallbackInfo, _world: &mut World) {}
+
+    /// Records commands that have to run before the Egui render pass, such as rendering to
+    /// a texture that [`EguiPaintCallback::render`] samples.
+    fn prepare_render<'w>(
+        &self,
+        _info: &EguiPaintCallbackInfo,
+        _render_context: &mut RenderContext<'w>,
+        _world: &'w World,
+    ) {
+    }
+
+    /// Draws the callback into the Egui render pass.
+    ///
+    /// The viewport is set to the callback rect and the scissor rect to its clip rect. The
+    /// pass doesn't have a depth attachment, and the Egui pipeline is restored afterwards.
+    fn render<'pass>(
+        &'pass self,
+        info: &EguiPaintCallbackInfo,
+        render_pass: &mut TrackedRenderPass<'pass>,
+        world: &'pass World,
+    );
+}
+
+/// Creates an [`egui::PaintCallback`] that's drawn with `callback` by the Egui render node.
+pub fn new_paint_callback(
+    rect: egui::Rect,
+    callback: impl EguiPaintCallback,
+) -> egui::PaintCallback {
+    egui::PaintCallback {
+        rect,
+        callback: std::sync::Arc::new(BoxedPaintCallback(Box::new(callback))),
+    }
+}
+
+// `PaintCallback::callback` can only be downcast to a concrete type.
+struct BoxedPaintCallback(Box<dyn EguiPaintCallback>);
+
+/// Information about where an [`EguiPaintCallback`] is drawn.
+pub struct EguiPaintCallbackInfo {
+    /// Rect and clip rect of the callback, in points and in pixels of the render target.
+    pub egui_info: egui::PaintCallbackInfo,
+    /// Window (or [`EguiRenderToTexture`] entity) the callback is drawn to.
+    pub window_entity: Entity,
+    /// Format of the render target, which pipelines used by the callback have to match.
+    pub texture_format: TextureFormat,
+    /// Number of samples per pixel of the render target, which pipelines used by the callback
+    /// have to match as well (see [`EguiContextSettings::msaa_samples`]).
+    pub sample_count: u32,
+    /// Format of the depth attachment of the render pass, if it has one (see
+    /// [`EguiContextSettings::depth_test_distance`]), which pipelines used by the callback have to
+    /// declare.
+    pub depth_format: Option<TextureFormat>,
+}
+
+enum DrawPrimitive {
+    Egui {
+        indices: Range<u32>,
+        egui_texture: EguiTextureId,
+    },
+    PaintCallback {
+        callback: egui::PaintCallback,
+        info: EguiPaintCallbackInfo,
+        viewport: (f32, f32, f32, f32), // x, y, w, h
+    },
+}
+
 struct DrawCommand {
-    vertices_count: usize,
-    egui_texture: EguiTextureId,
+    primitive: DrawPrimitive,
     clipping_zone: (u32, u32, u32, u32), // x, y, w, h
 }
 
+impl DrawPrimitive {
+    fn paint_callback(&self) -> Option<(&dyn EguiPaintCallback, &EguiPaintCallbackInfo)> {
+        let DrawPrimitive::PaintCallback { callback, info, .. } = self else {
+            return None;
+        };
+        let callback = callback.callback.downcast_ref::<BoxedPaintCallback>()?;
+        Some((callback.0.as_ref(), info))
+    }
+}
+
+/// Offscreen texture Egui is rendered to before being upscaled onto a window
+/// (see [`EguiContextSettings::render_scale`]).
+struct EguiRenderTarget {
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    // The texture is kept alive for as long as the view and the bind group are used.
+    _texture: Texture,
+    texture_view: TextureView,
+    bind_group: BindGroup,
+}
+
+/// Texture a capture of the context is drawn to, and the buffer it's copied to
+/// (see [`EguiScreenshot`]).
+struct EguiScreenshotTarget {
+    width: u32,
+    height: u32,
+    texture: Texture,
+    texture_view: TextureView,
+    buffer: Buffer,
+    padded_bytes_per_row: u32,
+}
+
+/// Multisampled texture Egui is rendered to before being resolved into the render target
+/// (see [`EguiContextSettings::msaa_samples`]).
+struct EguiMultisampledTarget {
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+    sample_count: u32,
+    // The texture is kept alive for as long as the view is used.
+    _texture: Texture,
+    texture_view: TextureView,
+}
+
+/// The pass of a camera's render graph an [`EguiNode`] draws the context of the camera in
+/// (see [`EguiContextSettings::camera_pass`]).
+///
+/// The UI is drawn into the main texture of the camera, so the effects of the passes that follow
+/// apply to it. Except for [`Self::MainPass`], it's drawn into the resolved texture of
+/// multisampled cameras.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EguiCameraPass {
+    /// After the main transparent pass, with depth testing if
+    /// [`EguiContextSettings::depth_test_distance`] is set, over the whole scene otherwise.
+    /// Only the `Core3d` render graph is supported.
+    MainPass,
+    /// After the main pass, TAA and motion blur, before bloom, so the UI blooms together with the
+    /// scene.
+    BeforeBloom,
+    /// After bloom and depth of field, before tonemapping
+    /// (see [`EguiContextSettings::draw_before_tonemapping`]).
+    BeforeTonemapping,
+    /// After tonemapping, before anti-aliasing (FXAA and SMAA) and contrast adaptive sharpening.
+    AfterTonemapping,
+    /// After all post-processing, before the main texture is upscaled onto the camera's target.
+    /// `bevy_ui` draws between the same nodes and isn't ordered against the UI, use
+    /// [`Self::BeforeBevyUi`] or [`Self::AfterBevyUi`] to draw below or above it.
+    AfterPostProcessing,
+    /// After all post-processing, before the pass `bevy_ui` draws the nodes of the camera in
+    /// (`NodeUi::UiPass`), so the nodes are drawn above the UI
+    /// (see [`EguiContextSettings::draw_below_bevy_ui`]). The same as
+    /// [`Self::AfterPostProcessing`] if the graph doesn't have the pass.
+    BeforeBevyUi,
+    /// After the pass `bevy_ui` draws the nodes of the camera in, before the main texture is
+    /// upscaled, so the UI is drawn above the nodes. The same as [`Self::AfterPostProcessing`] if
+    /// the graph doesn't have the pass.
+    AfterBevyUi,
+    /// Between two nodes of the camera's render graph, which have to exist.
+    Custom {
+        /// The node the UI is drawn after.
+        after: InternedRenderLabel,
+        /// The node the UI is drawn before.
+        before: InternedRenderLabel,
+    },
+}
+
+impl EguiCameraPass {
+    /// Whether the UI is drawn before the pass of `bevy_ui`, so `bevy_ui` nodes cover it.
+    /// [`Self::Custom`] passes aren't known to be.
+    pub(crate) fn is_below_bevy_ui(self) -> bool {
+        match self {
+            Self::MainPass
+            | Self::BeforeBloom
+            | Self::BeforeTonemapping
+            | Self::AfterTonemapping
+            | Self::BeforeBevyUi => true,
+            Self::AfterPostProcessing | Self::AfterBevyUi | Self::Custom { .. } => false,
+        }
+    }
+}
+
 /// Egui render node.
 pub struct EguiNode {
     window_entity: Entity,
+    // The window a secondary context is drawn to, the same as `window_entity` otherwise.
+    target_entity: Entity,
+    // The pass of the camera with the context the node draws in, if it belongs to the render
+    // graph of the camera.
+    camera_pass: Option<EguiCameraPass>,
+    clear_color: Option<egui::Color32>,
+    render_target: Option<EguiRenderTarget>,
+    multisampled_target: Option<EguiMultisampledTarget>,
+    screenshot_target: Option<EguiScreenshotTarget>,
+    blit_indices: Range<u32>,
+    draw_commands: Vec<DrawCommand>,
+    // What the draw commands were built from, they are kept while it's the same.
+    mesh_key: Option<MeshKey>,
+}
+
+/// Keeps the vertex and index buffers of [`EguiNode`]s for each context entity, so that they
+/// are reused by nodes that are recreated (when an
+/// [`EguiSecondaryContext`](crate::EguiSecondaryContext) moves to another window, for example).
+///
+/// Buffers only grow, and only the meshes that have changed since the last frame are written to
+/// them. The buffers of contexts that haven't been drawn during a frame (despawned ones) are
+/// freed during [`RenderSet::Cleanup`](bevy::render::RenderSet::Cleanup).
+#[derive(Resource, Default)]
+pub struct EguiBufferPool {
+    buffers: HashMap<Entity, EguiMeshBuffers>,
+}
+
+impl EguiBufferPool {
+    /// Frees the buffers that haven't been used since the last call.
+    pub(crate) fn free_unused(&mut self) {
+        self.buffers
+            .retain(|_, buffers| std::mem::take(&mut buffers.used));
+    }
+}
+
+#[derive(Default)]
+struct EguiMeshBuffers {
+    // Scratch space for the data written to the buffers.
     vertex_data: Vec<u8>,
     vertex_buffer_capacity: usize,
     vertex_buffer: Option<Buffer>,
     index_data: Vec<u8>,
     index_buffer_capacity: usize,
     index_buffer: Option<Buffer>,
-    draw_commands: Vec<DrawCommand>,
+    // The meshes in the buffers, in the order they are drawn (the upscaling quad is the last one).
+    resident_meshes: Vec<ResidentMesh>,
+    // The number of vertices and indices up to the end of the last region in the buffers.
+    vertices_end: u32,
+    indices_end: u32,
+    // Whether a node has used the buffers since they were last checked by `free_unused`.
+    used: bool,
+}
+
+/// Everything the draw commands of [`EguiNode`] depend on.
+#[derive(Clone, Copy, PartialEq)]
+struct MeshKey {
+    paint_jobs_generation: u64,
+    window_size: WindowSize,
+    layout: EguiScreenLayout,
+    render_scale: Option<f32>,
+    texture_format: Option<TextureFormat>,
+    sample_count: u32,
+    texture_owner: Entity,
+    debug_disable_clipping: bool,
+}
+
+/// The place of a mesh in the vertex and index buffers of [`EguiNode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct MeshRegion {
+    first_vertex: u32,
+    vertices: u32,
+    first_index: u32,
+    indices: u32,
+}
+
+impl MeshRegion {
+    fn fits(&self, mesh: &egui::Mesh) -> bool {
+        mesh.vertices.len() as u32 <= self.vertices && mesh.indices.len() as u32 <= self.indices
+    }
+}
+
+/// A mesh in the buffers of [`EguiNode`], compared with the mesh drawn at the same position
+/// during the next frame.
+struct ResidentMesh {
+    mesh: egui::Mesh,
+    region: MeshRegion,
+}
+
+/// Where the meshes of a frame go in the buffers of [`EguiNode`].
+#[derive(Debug, PartialEq)]
+struct MeshPlacement {
+    regions: Vec<MeshRegion>,
+    // The meshes that have to be written to their regions.
+    changed: Vec<usize>,
+    vertices_end: u32,
+    indices_end: u32,
+}
+
+/// Keeps the meshes that haven't changed since the last frame where they are. Changed meshes
+/// take the region of the mesh drawn at the same position if they fit into it, or are added
+/// after the last region.
+fn place_meshes(
+    resident_meshes: &[ResidentMesh],
+    meshes: &[egui::Mesh],
+    (vertices_end, indices_end): (u32, u32),
+    (vertex_capacity, index_capacity): (u32, u32),
+) -> MeshPlacement {
+    let mut placement = MeshPlacement {
+        regions: Vec::with_capacity(meshes.len()),
+        changed: Vec::new(),
+        vertices_end,
+        indices_end,
+    };
+    for (i, mesh) in meshes.iter().enumerate() {
+        let region = match resident_meshes.get(i) {
+            Some(resident) if resident.mesh == *mesh => resident.region,
+            Some(resident) if resident.region.fits(mesh) => {
+                placement.changed.push(i);
+                resident.region
+            }
+            _ => {
+                let region = MeshRegion {
+                    first_vertex: placement.vertices_end,
+                    vertices: mesh.vertices.len() as u32,
+                    first_index: placement.indices_end,
+                    indices: mesh.indices.len() as u32,
+                };
+                placement.vertices_end += region.vertices;
+                placement.indices_end += region.indices;
+                placement.changed.push(i);
+                region
+            }
+        };
+        placement.regions.push(region);
+    }
+
+    // Once the meshes don't fit into the buffers, they are packed from the start again.
+    if placement.vertices_end > vertex_capacity || placement.indices_end > index_capacity {
+        placement.vertices_end = 0;
+        placement.indices_end = 0;
+        for (region, mesh) in placement.regions.iter_mut().zip(meshes) {
+            *region = MeshRegion {
+                first_vertex: placement.vertices_end,
+                vertices: mesh.vertices.len() as u32,
+                first_index: placement.indices_end,
+                indices: mesh.indices.len() as u32,
+            };
+            placement.vertices_end += region.vertices;
+            placement.indices_end += region.indices;
+        }
+        placement.changed = (0..meshes.len()).collect();
+    }
+    placement
 }
 
 impl EguiNode {
     /// Constructs Egui render node.
     pub fn new(window_entity: Entity) -> Self {
+        Self::new_secondary(window_entity, window_entity)
+    }
+
+    /// Constructs Egui render node for a context that's drawn to another window
+    /// (see [`EguiSecondaryContext`](crate::EguiSecondaryContext)).
+    pub fn new_secondary(context_entity: Entity, window_entity: Entity) -> Self {
         EguiNode {
-            window_entity,
+            window_entity: context_entity,
+            target_entity: window_entity,
+            camera_pass: None,
+            clear_color: None,
+            render_target: None,
+            multisampled_target: None,
+            screenshot_target: None,
+            blit_indices: 0..0,
             draw_commands: Vec::new(),
-            vertex_data: Vec::new(),
-            vertex_buffer_capacity: 0,
-            vertex_buffer: None,
-            index_data: Vec::new(),
-            index_buffer_capacity: 0,
-            index_buffer: None,
+            mesh_key: None,
+        }
+    }
+
+    /// Constructs Egui render node for the context of a camera that's drawn into the camera's
+    /// main texture (see [`EguiContextSettings::draw_before_tonemapping`] and
+    /// [`EguiContextSettings::depth_test_distance`]). The node has to be added to the camera's
+    /// render graph.
+    pub fn new_camera_pass(
+        camera_entity: Entity,
+        window_entity: Entity,
+        camera_pass: EguiCameraPass,
+    ) -> Self {
+        EguiNode {
+            camera_pass: Some(camera_pass),
+            ..Self::new_secondary(camera_entity, window_entity)
         }
     }
+
+    fn clear_meshes(&mut self) {
+        self.draw_commands.clear();
+        self.blit_indices = 0..0;
+        self.mesh_key = None;
+    }
 }
 
 impl Node for EguiNode {
     fn update(&mut self, world: &mut World) {
-        let mut window_sizes = world.query::<(&WindowSize, &mut EguiRenderOutput)>();
+        self.screenshot_target = None;
+        let mut window_sizes = world.query::<(
+            &WindowSize,
+            &mut EguiRenderOutput,
+            Option<&EguiContextSettings>,
+        )>();
 
-        let Ok((window_size, mut render_output)) = window_sizes.get_mut(world, self.window_entity)
+        let Ok((window_size, mut render_output, context_settings)) =
+            window_sizes.get_mut(world, self.window_entity)
         else {
             return;
         };
         let window_size = *window_size;
         let paint_jobs = std::mem::take(&mut render_output.paint_jobs);
+        let screen_layout = render_output.screen_layout;
+        let paint_jobs_generation = render_output.paint_jobs_generation;
+        let clear_color = context_settings.and_then(|settings| settings.clear_color);
+        let render_scale = context_settings.and_then(|settings| settings.render_target_scale());
+        let msaa_samples = context_settings.map_or(1, |settings| settings.msaa_samples);
+        world
+            .resource_mut::<EguiBufferPool>()
+            .buffers
+            .entry(self.window_entity)
+            .or_default()
+            .used = true;
+        // Offscreen render targets and clear colors are only used for the windows' own contexts.
+        let is_window = world
+            .get::<EguiRenderToTexture>(self.window_entity)
+            .is_none()
+            && self.target_entity == self.window_entity;
+        let render_scale = render_scale.filter(|_| is_window);
+        self.clear_color = clear_color.filter(|_| is_window);
+        // Secondary contexts draw with the pipelines of their windows.
+        let sample_count = if self.target_entity == self.window_entity {
+            msaa_samples.max(1)
+        } else {
+            1
+        };
 
         let egui_settings = &world.get_resource::<EguiSettings>().unwrap();
+        let debug_disable_clipping = egui_settings.debug_disable_clipping;
 
-        let render_device = world.get_resource::<RenderDevice>().unwrap();
-
-        let scale_factor = window_size.scale_factor * egui_settings.scale_factor;
         if window_size.physical_width == 0.0 || window_size.physical_height == 0.0 {
+            self.clear_meshes();
             return;
         }
+        // Contexts that haven't begun a frame yet have nothing to draw.
+        let Some(layout) = screen_layout else {
+            self.clear_meshes();
+            return;
+        };
+        let scale_factor = layout.pixels_per_point;
 
-        let mut index_offset = 0;
+        let swap_chain_texture_format = world
+            .get_resource::<ExtractedWindows>()
+            .unwrap()
+            .windows
+            .get(&self.target_entity)
+            .and_then(|window| window.swap_chain_texture_format);
+        // Paint callbacks are skipped until the format of the target is known.
+        let texture_format = if self.camera_pass.is_some() {
+            world
+                .get::<ViewTarget>(self.window_entity)
+                .map(|view_target| view_target.main_texture_format())
+        } else {
+            match world.get::<EguiRenderToTexture>(self.window_entity) {
+                Some(EguiRenderToTexture(image)) => world
+                    .get_resource::<RenderAssets<GpuImage>>()
+                    .and_then(|gpu_images| gpu_images.get(image))
+                    .map(|gpu_image| gpu_image.texture_format),
+                None => swap_chain_texture_format.map(|format| format.add_srgb_suffix()),
+            }
+        };
+
+        let (target_width, target_height) = match render_scale {
+            Some(render_scale) => {
+                let Some(swap_chain_texture_format) = swap_chain_texture_format else {
+                    self.render_target = None;
+                    self.clear_meshes();
+                    return;
+                };
+                let width = ((window_size.physical_width * render_scale).ceil() as u32).max(1);
+                let height = ((window_size.physical_height * render_scale).ceil() as u32).max(1);
+                self.prepare_render_target(
+                    world,
+                    width,
+                    height,
+                    swap_chain_texture_format.add_srgb_suffix(),
+                );
+                (width, height)
+            }
+            None => {
+                self.render_target = None;
+                (
+                    window_size.physical_width as u32,
+                    window_size.physical_height as u32,
+                )
+            }
+        };
+        // The multisampled texture is resolved into the render target (or the image).
+        match texture_format.filter(|_| sample_count > 1) {
+            Some(format) => self.prepare_multisampled_target(
+                world,
+                target_width,
+                target_height,
+                format,
+                sample_count,
+            ),
+            None => self.multisampled_target = None,
+        }
+        // Captures are drawn into new textures, as the buffers of the last ones may still be read.
+        if world.get::<EguiScreenshot>(self.window_entity).is_some()
+            && !world
+                .resource::<EguiScreenshotCaptures>()
+                .is_capturing(self.window_entity)
+        {
+            self.prepare_screenshot_target(world, target_width, target_height);
+        }
+        let (sample_count, depth_format) = match self.camera_pass {
+            // The main pass draws to the multisampled texture of the camera.
+            Some(EguiCameraPass::MainPass) => (
+                world
+                    .get::<ViewTarget>(self.window_entity)
+                    .and_then(|view_target| view_target.sampled_main_texture())
+                    .map_or(1, |texture| texture.sample_count()),
+                Some(CORE_3D_DEPTH_FORMAT),
+            ),
+            _ => (
+                self.multisampled_target
+                    .as_ref()
+                    .map_or(1, |multisampled_target| multisampled_target.sample_count),
+                None,
+            ),
+        };
+        // Scissor rectangles are calculated in the render target's pixels.
+        let clip_scale_factor = scale_factor * render_scale.unwrap_or(1.0);
+        let clip_offset = layout.offset * render_scale.unwrap_or(1.0);
+
+        // Viewport windows draw with the textures of the context that shows them.
+        let texture_owner = world
+            .get::<EguiViewport>(self.window_entity)
+            .map_or(self.window_entity, |viewport| viewport.context);
 
+        // Nothing is tessellated or uploaded again while the UI stays the same.
+        let mesh_key = Some(MeshKey {
+            paint_jobs_generation,
+            window_size,
+            layout,
+            render_scale,
+            texture_format,
+            sample_count,
+            texture_owner,
+            debug_disable_clipping,
+        });
+        if self.mesh_key == mesh_key {
+            for draw_command in &self.draw_commands {
+                if let Some((callback, info)) = draw_command.primitive.paint_callback() {
+                    callback.update(info, world);
+                }
+            }
+            return;
+        }
         self.draw_commands.clear();
-        self.vertex_data.clear();
-        self.index_data.clear();
+        self.blit_indices = 0..0;
+        self.mesh_key = mesh_key;
+        let mut meshes = Vec::new();
 
         for egui::epaint::ClippedPrimitive {
             clip_rect,
             primitive,
-        } in &paint_jobs
+        } in paint_jobs
         {
-            let mesh = match primitive {
-                egui::epaint::Primitive::Mesh(mesh) => mesh,
-                egui::epaint::Primitive::Callback(_) => {
-                    unimplemented!("Paint callbacks aren't supported")
-                }
+            let (x, y, w, h) = if debug_disable_clipping {
+                (0, 0, target_width, target_height)
+            } else {
+                // The UI can extend past the top left corner of the target when it's moved up
+                // (see `EguiVirtualKeyboard`), the rects are cut at the corner.
+                let min_x = (clip_rect.min.x * clip_scale_factor + clip_offset.x).round();
+                let min_y = (clip_rect.min.y * clip_scale_factor + clip_offset.y).round();
+                let max_x = min_x + (clip_rect.width() * clip_scale_factor).round();
+                let max_y = min_y + (clip_rect.height() * clip_scale_factor).round();
+                (
+                    min_x.max(0.0) as u32,
+                    min_y.max(0.0) as u32,
+                    (max_x - min_x.max(0.0)).max(0.0) as u32,
+                    (max_y - min_y.max(0.0)).max(0.0) as u32,
+                )
             };
 
-            let (x, y, w, h) = (
-                (clip_rect.min.x * scale_factor).round() as u32,
-                (clip_rect.min.y * scale_factor).round() as u32,
-                (clip_rect.width() * scale_factor).round() as u32,
-                (clip_rect.height() * scale_factor).round() as u32,
-            );
-
-            if w < 1
-                || h < 1
-                || x >= window_size.physical_width as u32
-                || y >= window_size.physical_height as u32
-            {
+            if w < 1 || h < 1 || x >= target_width || y >= target_height {
                 continue;
             }
+            let x_viewport_clamp = (x + w).saturating_sub(target_width);
+            let y_viewport_clamp = (y + h).saturating_sub(target_height);
+            let clipping_zone = (
+                x,
+                y,
+                w.saturating_sub(x_viewport_clamp).max(1),
+                h.saturating_sub(y_viewport_clamp).max(1),
+            );
 
-            self.vertex_data
-                .extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
-            let indices_with_offset = mesh
-                .indices
-                .iter()
-                .map(|i| i + index_offset)
-                .collect::<Vec<_>>();
-            self.index_data
-                .extend_from_slice(cast_slice(indices_with_offset.as_slice()));
-            index_offset += mesh.vertices.len() as u32;
+            let mesh = match primitive {
+                egui::epaint::Primitive::Mesh(mesh) => mesh,
+                egui::epaint::Primitive::Callback(callback) => {
+                    let Some(texture_format) = texture_format else {
+                        continue;
+                    };
+                    if !callback.callback.is::<BoxedPaintCallback>() {
+                        log::warn_once!(
+                            "Paint callbacks not created with `new_paint_callback` are ignored"
+                        );
+                        continue;
+                    }
+                    // The viewport can't extend past the render target.
+                    let min_x = (callback.rect.min.x * clip_scale_factor + clip_offset.x)
+                        .clamp(0.0, target_width as f32);
+                    let min_y = (callback.rect.min.y * clip_scale_factor + clip_offset.y)
+                        .clamp(0.0, target_height as f32);
+                    let max_x = (callback.rect.max.x * clip_scale_factor + clip_offset.x)
+                        .clamp(0.0, target_width as f32);
+                    let max_y = (callback.rect.max.y * clip_scale_factor + clip_offset.y)
+                        .clamp(0.0, target_height as f32);
+                    if max_x - min_x < 1.0 || max_y - min_y < 1.0 {
+                        continue;
+                    }
+                    self.draw_commands.push(DrawCommand {
+                        primitive: DrawPrimitive::PaintCallback {
+                            callback: callback.clone(),
+                            info: EguiPaintCallbackInfo {
+                                egui_info: egui::PaintCallbackInfo {
+                                    viewport: callback.rect,
+                                    clip_rect,
+                                    pixels_per_point: clip_scale_factor,
+                                    screen_size_px: [target_width, target_height],
+                                },
+                                window_entity: self.target_entity,
+                                texture_format,
+                                sample_count,
+                                depth_format,
+                            },
+                            viewport: (min_x, min_y, max_x - min_x, max_y - min_y),
+                        },
+                        clipping_zone,
+                    });
+                    continue;
+                }
+            };
 
             let texture_handle = match mesh.texture_id {
-                egui::TextureId::Managed(id) => EguiTextureId::Managed(self.window_entity, id),
+                egui::TextureId::Managed(id) => EguiTextureId::Managed(texture_owner, id),
                 egui::TextureId::User(id) => EguiTextureId::User(id),
             };
 
-            let x_viewport_clamp = (x + w).saturating_sub(window_size.physical_width as u32);
-            let y_viewport_clamp = (y + h).saturating_sub(window_size.physical_height as u32);
+            // The index range is known once the mesh is placed in the buffers.
             self.draw_commands.push(DrawCommand {
-                vertices_count: mesh.indices.len(),
-                egui_texture: texture_handle,
-                clipping_zone: (
-                    x,
-                    y,
-                    w.saturating_sub(x_viewport_clamp).max(1),
-                    h.saturating_sub(y_viewport_clamp).max(1),
-                ),
+                primitive: DrawPrimitive::Egui {
+                    indices: 0..0,
+                    egui_texture: texture_handle,
+                },
+                clipping_zone,
             });
+            meshes.push(mesh);
         }
 
-        if self.vertex_data.len() > self.vertex_buffer_capacity {
-            self.vertex_buffer_capacity = if self.vertex_data.len().is_power_of_two() {
-                self.vertex_data.len()
-            } else {
-                self.vertex_data.len().next_power_of_two()
-            };
-            self.vertex_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui vertex buffer"),
-                size: self.vertex_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::VERTEX,
-                mapped_at_creation: false,
-            }));
+        for draw_command in &self.draw_commands {
+            if let Some((callback, info)) = draw_command.primitive.paint_callback() {
+                callback.update(info, world);
+            }
         }
-        if self.index_data.len() > self.index_buffer_capacity {
-            self.index_buffer_capacity = if self.index_data.len().is_power_of_two() {
-                self.index_data.len()
-            } else {
-                self.index_data.len().next_power_of_two()
-            };
-            self.index_buffer = Some(render_device.create_buffer(&BufferDescriptor {
-                label: Some("egui index buffer"),
-                size: self.index_buffer_capacity as BufferAddress,
-                usage: BufferUsages::COPY_DST | BufferUsages::INDEX,
-                mapped_at_creation: false,
-            }));
+
+        if self.render_target.is_some() {
+            // A quad covering the whole window (in points) to upscale the render target with.
+            let mut blit_mesh = egui::Mesh::default();
+            blit_mesh.add_rect_with_uv(
+                egui::Rect::from_min_size(
+                    egui::pos2(
+                        -layout.offset.x / scale_factor,
+                        -layout.offset.y / scale_factor,
+                    ),
+                    egui::vec2(
+                        window_size.physical_width / scale_factor,
+                        window_size.physical_height / scale_factor,
+                    ),
+                ),
+                egui::Rect::from_min_max(egui::Pos2::ZERO, egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+            meshes.push(blit_mesh);
+        }
+
+        let mut index_ranges = world
+            .resource_scope(|world, mut buffer_pool: Mut<EguiBufferPool>| {
+                buffer_pool
+                    .buffers
+                    .entry(self.window_entity)
+                    .or_default()
+                    .upload_meshes(world, meshes)
+            })
+            .into_iter();
+        for draw_command in &mut self.draw_commands {
+            if let DrawPrimitive::Egui { indices, .. } = &mut draw_command.primitive {
+                *indices = index_ranges.next().unwrap_or_default();
+            }
+        }
+        if self.render_target.is_some() {
+            self.blit_indices = index_ranges.next().unwrap_or_default();
         }
     }
 
-    fn run(
+    fn run<'w>(
         &self,
-        _graph: &mut RenderGraphContext,
-        render_context: &mut RenderContext,
-        world: &World,
+        graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext<'w>,
+        world: &'w World,
     ) -> Result<(), NodeRunError> {
         let egui_pipelines = &world.get_resource::<EguiPipelines>().unwrap().0;
         let pipeline_cache = world.get_resource::<PipelineCache>().unwrap();
 
         let extracted_windows = &world.get_resource::<ExtractedWindows>().unwrap().windows;
-        let extracted_window =
-            if let Some(extracted_window) = extracted_windows.get(&self.window_entity) {
-                extracted_window
+        let (target_view, physical_width, physical_height, target_load) =
+            if self.camera_pass.is_some() {
+                // The node runs for every camera with the render graph.
+                if graph.get_view_entity() != Some(self.window_entity) {
+                    return Ok(());
+                }
+                let Some(view_target) = world.get::<ViewTarget>(self.window_entity) else {
+                    return Ok(());
+                };
+                // Post-processing passes read the resolved texture, so multisampled cameras are
+                // drawn to it as well.
+                let size = view_target.main_texture().size();
+                (
+                    view_target.main_texture_view(),
+                    size.width,
+                    size.height,
+                    LoadOp::Load,
+                )
+            } else if let Some(extracted_window) = extracted_windows.get(&self.target_entity) {
+                let (Some(swap_chain_texture_view), Some(swap_chain_texture_format)) = (
+                    extracted_window.swap_chain_texture_view.as_ref(),
+                    extracted_window.swap_chain_texture_format,
+                ) else {
+                    return Ok(()); // No swapchain texture
+                };
+                (
+                    swap_chain_texture_view,
+                    extracted_window.physical_width,
+                    extracted_window.physical_height,
+                    window_load_op(self.clear_color, swap_chain_texture_format),
+                )
+            } else if let Some(EguiRenderToTexture(image)) =
+                world.get::<EguiRenderToTexture>(self.window_entity)
+            {
+                let gpu_images = world.get_resource::<RenderAssets<GpuImage>>().unwrap();
+                let Some(gpu_image) = gpu_images.get(image) else {
+                    return Ok(()); // The image isn't loaded yet
+                };
+                (
+                    &gpu_image.texture_view,
+                    gpu_image.size.x,
+                    gpu_image.size.y,
+                    LoadOp::Clear(Default::default()),
+                )
             } else {
                 return Ok(()); // No window
             };
 
-        let swap_chain_texture_view = if let Some(swap_chain_texture_view) =
-            extracted_window.swap_chain_texture_view.as_ref()
-        {
-            swap_chain_texture_view
-        } else {
-            return Ok(()); // No swapchain texture
-        };
+        // Minimized windows have zero physical size.
+        if physical_width == 0 || physical_height == 0 {
+            return Ok(());
+        }
 
-        let render_queue = world.get_resource::<RenderQueue>().unwrap();
+        // Textures (and windows with a clear color) are cleared even if there's nothing to draw,
+        // to remove the previous frame's UI.
+        if self.draw_commands.is_empty() {
+            if let LoadOp::Clear(_) = target_load {
+                render_context
+                    .command_encoder()
+                    .begin_render_pass(&RenderPassDescriptor {
+                        label: Some("egui clear pass"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: target_view,
+                            resolve_target: None,
+                            ops: Operations {
+                                load: target_load,
+                                store: StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+            }
+            self.capture_screenshot(render_context, world);
+            return Ok(());
+        }
 
-        let (vertex_buffer, index_buffer) = match (&self.vertex_buffer, &self.index_buffer) {
-            (Some(vertex), Some(index)) => (vertex, index),
-            _ => return Ok(()),
+        let Some(EguiMeshBuffers {
+            vertex_buffer: Some(vertex_buffer),
+            index_buffer: Some(index_buffer),
+            ..
+        }) = world
+            .get_resource::<EguiBufferPool>()
+            .unwrap()
+            .buffers
+            .get(&self.window_entity)
+        else {
+            return Ok(());
         };
 
-        render_queue.write_buffer(vertex_buffer, 0, &self.vertex_data);
-        render_queue.write_buffer(index_buffer, 0, &self.index_data);
-
         let bind_groups = &world.get_resource::<EguiTextureBindGroups>().unwrap();
 
         let egui_transforms = world.get_resource::<EguiTransforms>().unwrap();
 
-        let mut render_pass =
-            render_context
-                .command_encoder()
-                .begin_render_pass(&RenderPassDescriptor {
-                    label: Some("egui render pass"),
-                    color_attachments: &[Some(RenderPassColorAttachment {
-                        view: swap_chain_texture_view,
-                        resolve_target: None,
-                        ops: Operations {
-                            load: LoadOp::Load,
-                            store: StoreOp::Store,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                    timestamp_writes: None,
-                    occlusion_query_set: None,
-                });
-
-        let Some(pipeline_id) = egui_pipelines.get(&extracted_window.entity) else {
+        let pipeline_id = if self.multisampled_target.is_some() || self.camera_pass.is_some() {
+            world
+                .get_resource::<EguiContextPipelines>()
+                .unwrap()
+                .0
+                .get(&self.window_entity)
+        } else {
+            egui_pipelines.get(&self.target_entity)
+        };
+        let Some(pipeline_id) = pipeline_id else {
             return Ok(());
         };
         let Some(pipeline) = pipeline_cache.get_render_pipeline(*pipeline_id) else {
             return Ok(());
         };
+        let blit_pipeline = match &self.render_target {
+            Some(_) => {
+                let blit_pipelines = &world.get_resource::<EguiBlitPipelines>().unwrap().0;
+                let Some(pipeline) = blit_pipelines
+                    .get(&self.window_entity)
+                    .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(*pipeline_id))
+                else {
+                    return Ok(());
+                };
+                Some(pipeline)
+            }
+            None => None,
+        };
 
-        render_pass.set_pipeline(pipeline);
-        render_pass.set_vertex_buffer(0, *self.vertex_buffer.as_ref().unwrap().slice(..));
-        render_pass.set_index_buffer(
-            *self.index_buffer.as_ref().unwrap().slice(..),
-            IndexFormat::Uint32,
-        );
+        let Some(&transform_buffer_offset) = egui_transforms.offsets.get(&self.window_entity)
+        else {
+            return Ok(());
+        };
+        let Some((_, transform_buffer_bind_group)) = egui_transforms.bind_group.as_ref() else {
+            return Ok(());
+        };
 
-        let transform_buffer_offset = egui_transforms.offsets[&self.window_entity];
-        let transform_buffer_bind_group = &egui_transforms.bind_group.as_ref().unwrap().1;
-        render_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+        let (color_view, load, target_width, target_height) = match &self.render_target {
+            Some(render_target) => (
+                &render_target.texture_view,
+                LoadOp::Clear(Default::default()),
+                render_target.width,
+                render_target.height,
+            ),
+            None => (target_view, target_load, physical_width, physical_height),
+        };
+        // Multisampled textures are cleared and resolved into the color view, which is cleared
+        // as well.
+        let (color_view, resolve_target, load, store) = match &self.multisampled_target {
+            Some(multisampled_target)
+                if multisampled_target.width == target_width
+                    && multisampled_target.height == target_height =>
+            {
+                (
+                    &multisampled_target.texture_view,
+                    Some(&**color_view),
+                    LoadOp::Clear(Default::default()),
+                    StoreOp::Discard,
+                )
+            }
+            // The image has been resized since the update.
+            Some(_) => return Ok(()),
+            None => (color_view, None, load, StoreOp::Store),
+        };
 
-        let mut vertex_offset: u32 = 0;
         for draw_command in &self.draw_commands {
-            if draw_command.clipping_zone.0 < extracted_window.physical_width
-                && draw_command.clipping_zone.1 < extracted_window.physical_height
+            if let Some((callback, info)) = draw_command.primitive.paint_callback() {
+                callback.prepare_render(info, render_context, world);
+            }
+        }
+
+        let mut color_attachment = RenderPassColorAttachment {
+            view: color_view,
+            resolve_target,
+            ops: Operations { load, store },
+        };
+        let mut depth_stencil_attachment = None;
+        if self.camera_pass == Some(EguiCameraPass::MainPass) {
+            let (Some(view_target), Some(view_depth_texture)) = (
+                world.get::<ViewTarget>(self.window_entity),
+                world.get::<ViewDepthTexture>(self.window_entity),
+            ) else {
+                return Ok(());
+            };
+            // Multisampled cameras are drawn to like the main pass does, and resolved.
+            color_attachment = view_target.get_color_attachment();
+            depth_stencil_attachment = Some(view_depth_texture.get_attachment(StoreOp::Store));
+        }
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("egui render pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        for draw_command in &self.draw_commands {
+            if draw_command.clipping_zone.0 < target_width
+                && draw_command.clipping_zone.1 < target_height
             {
-                let texture_bind_group = match bind_groups.get(&draw_command.egui_texture) {
-                    Some(texture_resource) => texture_resource,
-                    None => {
-                        vertex_offset += draw_command.vertices_count as u32;
+                let scissor_rect = (
+                    draw_command.clipping_zone.0,
+                    draw_command.clipping_zone.1,
+                    draw_command
+                        .clipping_zone
+                        .2
+                        .min(target_width.saturating_sub(draw_command.clipping_zone.0)),
+                    draw_command
+                        .clipping_zone
+                        .3
+                        .min(target_height.saturating_sub(draw_command.clipping_zone.1)),
+                );
+
+                let (indices, egui_texture) = match &draw_command.primitive {
+                    DrawPrimitive::Egui {
+                        indices,
+                        egui_texture,
+                    } => (indices, egui_texture),
+                    DrawPrimitive::PaintCallback { viewport, .. } => {
+                        let Some((callback, info)) = draw_command.primitive.paint_callback() else {
+                            continue;
+                        };
+                        let (x, y, w, h) = *viewport;
+                        render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+                        render_pass.set_scissor_rect(
+                            scissor_rect.0,
+                            scissor_rect.1,
+                            scissor_rect.2,
+                            scissor_rect.3,
+                        );
+                        callback.render(info, &mut render_pass, world);
+                        render_pass.set_viewport(
+                            0.0,
+                            0.0,
+                            target_width as f32,
+                            target_height as f32,
+                            0.0,
+                            1.0,
+                        );
                         continue;
                     }
                 };
+                let Some(texture_bind_group) = bind_groups.get(egui_texture) else {
+                    continue;
+                };
 
+                // Is a no-op unless a paint callback has changed the state of the pass.
+                render_pass.set_render_pipeline(pipeline);
+                render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+                render_pass.set_index_buffer(index_buffer.slice(..), 0, EGUI_INDEX_FORMAT);
+                render_pass.set_bind_group(
+                    0,
+                    transform_buffer_bind_group,
+                    &[transform_buffer_offset],
+                );
                 render_pass.set_bind_group(1, texture_bind_group, &[]);
 
                 render_pass.set_scissor_rect(
-                    draw_command.clipping_zone.0,
-                    draw_command.clipping_zone.1,
-                    draw_command.clipping_zone.2.min(
-                        extracted_window
-                            .physical_width
-                            .saturating_sub(draw_command.clipping_zone.0),
-                    ),
-                    draw_command.clipping_zone.3.min(
-                        extracted_window
-                            .physical_height
-                            .saturating_sub(draw_command.clipping_zone.1),
-                    ),
+                    scissor_rect.0,
+                    scissor_rect.1,
+                    scissor_rect.2,
+                    scissor_rect.3,
                 );
 
-                render_pass.draw_indexed(
-                    vertex_offset..(vertex_offset + draw_command.vertices_count as u32),
-                    0,
-                    0..1,
-                );
-                vertex_offset += draw_command.vertices_count as u32;
+                render_pass.draw_indexed(indices.clone(), 0, 0..1);
             }
         }
+        drop(render_pass);
+
+        if let (Some(render_target), Some(blit_pipeline)) = (&self.render_target, blit_pipeline) {
+            let mut render_pass =
+                render_context
+                    .command_encoder()
+                    .begin_render_pass(&RenderPassDescriptor {
+                        label: Some("egui upscale pass"),
+                        color_attachments: &[Some(RenderPassColorAttachment {
+                            view: target_view,
+                            resolve_target: None,
+                            ops: Operations {
+                                load: target_load,
+                                store: StoreOp::Store,
+                            },
+                        })],
+                        depth_stencil_attachment: None,
+                        timestamp_writes: None,
+                        occlusion_query_set: None,
+                    });
+
+            render_pass.set_pipeline(blit_pipeline);
+            render_pass.set_vertex_buffer(0, *vertex_buffer.slice(..));
+            render_pass.set_index_buffer(*index_buffer.slice(..), EGUI_INDEX_FORMAT);
+            render_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+            render_pass.set_bind_group(1, &render_target.bind_group, &[]);
+            render_pass.draw_indexed(self.blit_indices.clone(), 0, 0..1);
+        }
+
+        self.capture_screenshot(render_context, world);
 
         Ok(())
     }
 }
 
+impl EguiMeshBuffers {
+    /// Places the meshes in the buffers and writes the ones that have changed since the last
+    /// frame, returns the index ranges to draw the meshes with.
+    fn upload_meshes(&mut self, world: &World, meshes: Vec<egui::Mesh>) -> Vec<Range<u32>> {
+        const VERTEX_SIZE: usize = std::mem::size_of::<egui::epaint::Vertex>();
+        const INDEX_SIZE: usize = std::mem::size_of::<u32>();
+
+        let placement = place_meshes(
+            &self.resident_meshes,
+            &meshes,
+            (self.vertices_end, self.indices_end),
+            (
+                (self.vertex_buffer_capacity / VERTEX_SIZE) as u32,
+                (self.index_buffer_capacity / INDEX_SIZE) as u32,
+            ),
+        );
+
+        // The buffers are created with some room, so that changed meshes can be added after the
+        // last region for a while before everything is written again.
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let vertex_data_len = placement.vertices_end as usize * VERTEX_SIZE;
+        if vertex_data_len > self.vertex_buffer_capacity {
+            self.vertex_buffer_capacity = (vertex_data_len * 2).next_power_of_two();
+            self.vertex_buffer = Some(render_device.create_buffer(&BufferDescriptor {
+                label: Some("egui vertex buffer"),
+                size: self.vertex_buffer_capacity as BufferAddress,
+                usage: BufferUsages::COPY_DST | BufferUsages::VERTEX,
+                mapped_at_creation: false,
+            }));
+        }
+        let index_data_len = placement.indices_end as usize * INDEX_SIZE;
+        if index_data_len > self.index_buffer_capacity {
+            self.index_buffer_capacity = (index_data_len * 2).next_power_of_two();
+            self.index_buffer = Some(render_device.create_buffer(&BufferDescriptor {
+                label: Some("egui index buffer"),
+                size: self.index_buffer_capacity as BufferAddress,
+                usage: BufferUsages::COPY_DST | BufferUsages::INDEX,
+                mapped_at_creation: false,
+            }));
+        }
+
+        if let (Some(vertex_buffer), Some(index_buffer)) = (&self.vertex_buffer, &self.index_buffer)
+        {
+            let render_queue = world.get_resource::<RenderQueue>().unwrap();
+            // Changed meshes with adjacent regions are written at once.
+            let mut write_start = (0, 0);
+            let mut write_end = (0, 0);
+            for &i in &placement.changed {
+                let region = placement.regions[i];
+                let mesh = &meshes[i];
+                if (region.first_vertex, region.first_index) != write_end {
+                    write_mesh_data(
+                        render_queue,
+                        (vertex_buffer, index_buffer),
+                        write_start,
+                        (&mut self.vertex_data, &mut self.index_data),
+                    );
+                    write_start = (region.first_vertex, region.first_index);
+                }
+                append_mesh_data(
+                    &mut self.vertex_data,
+                    &mut self.index_data,
+                    mesh,
+                    region.first_vertex,
+                );
+                write_end = (
+                    region.first_vertex + mesh.vertices.len() as u32,
+                    region.first_index + mesh.indices.len() as u32,
+                );
+            }
+            write_mesh_data(
+                render_queue,
+                (vertex_buffer, index_buffer),
+                write_start,
+                (&mut self.vertex_data, &mut self.index_data),
+            );
+        }
+
+        let index_ranges = placement
+            .regions
+            .iter()
+            .zip(&meshes)
+            .map(|(region, mesh)| {
+                region.first_index..region.first_index + mesh.indices.len() as u32
+            })
+            .collect();
+        self.resident_meshes = meshes
+            .into_iter()
+            .zip(placement.regions)
+            .map(|(mesh, region)| ResidentMesh { mesh, region })
+            .collect();
+        self.vertices_end = placement.vertices_end;
+        self.indices_end = placement.indices_end;
+        index_ranges
+    }
+}
+
+impl EguiNode {
+    /// (Re)creates the offscreen render target if its size or format has changed.
+    fn prepare_render_target(
+        &mut self,
+        world: &World,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) {
+        if self.render_target.as_ref().is_some_and(|render_target| {
+            render_target.width == width
+                && render_target.height == height
+                && render_target.format == format
+        }) {
+            return;
+        }
+
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let egui_pipeline = world.get_resource::<EguiPipeline>().unwrap();
+
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("egui render target"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&TextureViewDescriptor::default());
+        let sampler = render_device.create_sampler(&SamplerDescriptor {
+            label: Some("egui render target sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+        let bind_group = render_device.create_bind_group(
+            Some("egui render target bind group"),
+            &egui_pipeline.texture_bind_group_layout,
+            &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&texture_view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&sampler),
+                },
+            ],
+        );
+
+        self.render_target = Some(EguiRenderTarget {
+            width,
+            height,
+            format,
+            _texture: texture,
+            texture_view,
+            bind_group,
+        });
+    }
+
+    /// (Re)creates the multisampled texture if its size, format or sample count has changed.
+    fn prepare_screenshot_target(&mut self, world: &World, width: u32, height: u32) {
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("egui screenshot texture"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: EGUI_SCREENSHOT_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&TextureViewDescriptor::default());
+        // Rows of copied textures are aligned.
+        let padded_bytes_per_row = RenderDevice::align_copy_bytes_per_row(width as usize * 4);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("egui screenshot buffer"),
+            size: (padded_bytes_per_row * height as usize) as BufferAddress,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        self.screenshot_target = Some(EguiScreenshotTarget {
+            width,
+            height,
+            texture,
+            texture_view,
+            buffer,
+            padded_bytes_per_row: padded_bytes_per_row as u32,
+        });
+    }
+
+    /// Draws the meshes of the context into the screenshot texture (without paint callbacks,
+    /// which draw with the pipelines of the target) and copies it into the screenshot buffer.
+    fn capture_screenshot(&self, render_context: &mut RenderContext, world: &World) {
+        let Some(screenshot_target) = &self.screenshot_target else {
+            return;
+        };
+        let pipeline_cache = world.get_resource::<PipelineCache>().unwrap();
+        let pipeline = world
+            .get_resource::<EguiScreenshotPipeline>()
+            .and_then(|screenshot_pipeline| screenshot_pipeline.0)
+            .and_then(|pipeline_id| pipeline_cache.get_render_pipeline(pipeline_id));
+        let buffers = world
+            .get_resource::<EguiBufferPool>()
+            .unwrap()
+            .buffers
+            .get(&self.window_entity);
+        let egui_transforms = world.get_resource::<EguiTransforms>().unwrap();
+        let transform = egui_transforms
+            .offsets
+            .get(&self.window_entity)
+            .zip(egui_transforms.bind_group.as_ref());
+        let draw = match (pipeline, buffers, transform) {
+            (
+                Some(pipeline),
+                Some(EguiMeshBuffers {
+                    vertex_buffer: Some(vertex_buffer),
+                    index_buffer: Some(index_buffer),
+                    ..
+                }),
+                Some((&transform_buffer_offset, (_, transform_buffer_bind_group))),
+            ) => Some((
+                pipeline,
+                vertex_buffer,
+                index_buffer,
+                transform_buffer_offset,
+                transform_buffer_bind_group,
+            )),
+            // Nothing is drawn to the capture of an empty UI.
+            _ if self.draw_commands.is_empty() => None,
+            // The pipeline may still be compiling, a later frame is captured then.
+            _ => return,
+        };
+
+        let (width, height) = (screenshot_target.width, screenshot_target.height);
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("egui screenshot pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: &screenshot_target.texture_view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Default::default()),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some((
+            pipeline,
+            vertex_buffer,
+            index_buffer,
+            transform_buffer_offset,
+            transform_buffer_bind_group,
+        )) = draw
+        {
+            let bind_groups = &world.get_resource::<EguiTextureBindGroups>().unwrap();
+            render_pass.set_render_pipeline(pipeline);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.set_index_buffer(index_buffer.slice(..), 0, EGUI_INDEX_FORMAT);
+            render_pass.set_bind_group(0, transform_buffer_bind_group, &[transform_buffer_offset]);
+            for draw_command in &self.draw_commands {
+                let DrawPrimitive::Egui {
+                    indices,
+                    egui_texture,
+                } = &draw_command.primitive
+                else {
+                    continue;
+                };
+                let (x, y, w, h) = draw_command.clipping_zone;
+                if x >= width || y >= height {
+                    continue;
+                }
+                let Some(texture_bind_group) = bind_groups.get(egui_texture) else {
+                    continue;
+                };
+                render_pass.set_bind_group(1, texture_bind_group, &[]);
+                render_pass.set_scissor_rect(x, y, w.min(width - x), h.min(height - y));
+                render_pass.draw_indexed(indices.clone(), 0, 0..1);
+            }
+        }
+        drop(render_pass);
+
+        render_context.command_encoder().copy_texture_to_buffer(
+            screenshot_target.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &screenshot_target.buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(screenshot_target.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        world
+            .resource::<EguiScreenshotCaptures>()
+            .push_copied(ScreenshotCapture {
+                window: self.window_entity,
+                buffer: screenshot_target.buffer.clone(),
+                width,
+                height,
+                padded_bytes_per_row: screenshot_target.padded_bytes_per_row,
+            });
+    }
+
+    fn prepare_multisampled_target(
+        &mut self,
+        world: &World,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        sample_count: u32,
+    ) {
+        if self
+            .multisampled_target
+            .as_ref()
+            .is_some_and(|multisampled_target| {
+                multisampled_target.width == width
+                    && multisampled_target.height == height
+                    && multisampled_target.format == format
+                    && multisampled_target.sample_count == sample_count
+            })
+        {
+            return;
+        }
+
+        let render_device = world.get_resource::<RenderDevice>().unwrap();
+        let texture = render_device.create_texture(&TextureDescriptor {
+            label: Some("egui multisampled render target"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        let texture_view = texture.create_view(&TextureViewDescriptor::default());
+
+        self.multisampled_target = Some(EguiMultisampledTarget {
+            width,
+            height,
+            format,
+            sample_count,
+            _texture: texture,
+            texture_view,
+        });
+    }
+}
+
+/// Appends mesh vertices and indices to the buffer data, offsetting the indices by `index_offset`.
+///
+/// Indices are always stored as `u32` (see [`EGUI_INDEX_FORMAT`]), so meshes with more than
+/// `u16::MAX` vertices (huge tables or plots) don't overflow.
+fn append_mesh_data(
+    vertex_data: &mut Vec<u8>,
+    index_data: &mut Vec<u8>,
+    mesh: &egui::Mesh,
+    index_offset: u32,
+) {
+    vertex_data.extend_from_slice(cast_slice::<_, u8>(mesh.vertices.as_slice()));
+    index_data.reserve(std::mem::size_of_val(mesh.indices.as_slice()));
+    for index in &mesh.indices {
+        index_data.extend_from_slice(&(index + index_offset).to_ne_bytes());
+    }
+}
+
+/// Writes the collected mesh data to the buffers, starting at the given vertex and index, and
+/// clears it.
+fn write_mesh_data(
+    render_queue: &RenderQueue,
+    (vertex_buffer, index_buffer): (&Buffer, &Buffer),
+    (first_vertex, first_index): (u32, u32),
+    (vertex_data, index_data): (&mut Vec<u8>, &mut Vec<u8>),
+) {
+    if !vertex_data.is_empty() {
+        let offset = first_vertex as usize * std::mem::size_of::<egui::epaint::Vertex>();
+        render_queue.write_buffer(vertex_buffer, offset as BufferAddress, vertex_data);
+    }
+    if !index_data.is_empty() {
+        let offset = first_index as usize * std::mem::size_of::<u32>();
+        render_queue.write_buffer(index_buffer, offset as BufferAddress, index_data);
+    }
+    vertex_data.clear();
+    index_data.clear();
+}
+
 pub(crate) fn as_color_image(image: egui::ImageData) -> egui::ColorImage {
     match image {
         egui::ImageData::Color(image) => (*image).clone(),
@@ -428,18 +1683,28 @@ fn alpha_image_as_color_image(image: &egui::FontImage) -> egui::ColorImage {
     }
 }
 
-pub(crate) fn color_image_as_bevy_image(
-    egui_image: &egui::ColorImage,
-    sampler_descriptor: ImageSampler,
-) -> Image {
-    let pixels = egui_image
+/// Returns the pixels of an Egui image, `premultiplied` can be `false` to unmultiply them
+/// (see [`EguiSettings::premultiplied_textures`]).
+pub(crate) fn color_image_as_bytes(egui_image: &egui::ColorImage, premultiplied: bool) -> Vec<u8> {
+    if premultiplied {
+        return cast_slice(&egui_image.pixels).to_vec();
+    }
+    egui_image
         .pixels
         .iter()
         // We unmultiply Egui textures to premultiply them later in the fragment shader.
         // As user textures loaded as Bevy assets are not premultiplied (and there seems to be no
         // convenient way to convert them to premultiplied ones), we do the this with Egui ones.
         .flat_map(|color| color.to_srgba_unmultiplied())
-        .collect();
+        .collect()
+}
+
+pub(crate) fn color_image_as_bevy_image(
+    egui_image: &egui::ColorImage,
+    sampler_descriptor: ImageSampler,
+    premultiplied: bool,
+) -> Image {
+    let pixels = color_image_as_bytes(egui_image, premultiplied);
 
     Image {
         sampler: sampler_descriptor,
@@ -479,3 +1744,356 @@ pub(crate) fn texture_options_as_sampler_descriptor(
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_srgb_output_encoding() {
+        assert!(!needs_srgb_output_encoding(TextureFormat::Bgra8UnormSrgb));
+        assert!(!needs_srgb_output_encoding(TextureFormat::Rgba8UnormSrgb));
+        assert!(!needs_srgb_output_encoding(TextureFormat::Rgba16Float));
+        assert!(needs_srgb_output_encoding(TextureFormat::Rgb10a2Unorm));
+        // Swap chain formats are queued with the sRGB suffix, if there's an sRGB variant.
+        assert!(!needs_srgb_output_encoding(
+            TextureFormat::Bgra8Unorm.add_srgb_suffix()
+        ));
+    }
+
+    #[test]
+    fn test_pipeline_key_matches_target_format() {
+        let key = |texture_format, blit| EguiPipelineKey {
+            texture_format,
+            premultiplied_texture: blit,
+            blit,
+            sample_count: 1,
+            depth_test: false,
+        };
+        let encodes_output =
+            |key: EguiPipelineKey| key.shader_defs().contains(&"ENCODE_SRGB_OUTPUT".into());
+
+        for texture_format in [
+            TextureFormat::Bgra8UnormSrgb,
+            TextureFormat::Rgba8UnormSrgb,
+            TextureFormat::Bgra8Unorm,
+            TextureFormat::Rgba16Float,
+        ] {
+            assert_eq!(
+                key(texture_format, false).color_target().format,
+                texture_format
+            );
+            assert_eq!(
+                key(texture_format, true).color_target().format,
+                texture_format
+            );
+            // Blitting copies colors already encoded by the first pass.
+            assert!(!encodes_output(key(texture_format, true)));
+        }
+        // BGRA surfaces get a pipeline of their own instead of an RGBA one.
+        assert!(
+            key(TextureFormat::Bgra8UnormSrgb, false) != key(TextureFormat::Rgba8UnormSrgb, false)
+        );
+        assert!(!encodes_output(key(TextureFormat::Bgra8UnormSrgb, false)));
+        assert!(encodes_output(key(TextureFormat::Bgra8Unorm, false)));
+        assert!(!encodes_output(key(TextureFormat::Rgba16Float, false)));
+    }
+
+    #[test]
+    fn test_pipeline_key_premultiplied_texture() {
+        let key = |premultiplied_texture| EguiPipelineKey {
+            texture_format: TextureFormat::Bgra8UnormSrgb,
+            premultiplied_texture,
+            blit: false,
+            sample_count: 1,
+            depth_test: false,
+        };
+        let premultiplies_in_shader =
+            |key: EguiPipelineKey| !key.shader_defs().contains(&"PREMULTIPLIED_TEXTURE".into());
+        assert!(premultiplies_in_shader(key(false)));
+        assert!(!premultiplies_in_shader(key(true)));
+        // Either way, the output is blended as premultiplied.
+        for premultiplied_texture in [false, true] {
+            let blend = key(premultiplied_texture).color_target().blend.unwrap();
+            assert_eq!(blend.color.src_factor, BlendFactor::One);
+            assert_eq!(blend.color.dst_factor, BlendFactor::OneMinusSrcAlpha);
+        }
+    }
+
+    #[test]
+    fn test_color_image_as_bytes() {
+        let color = egui::Color32::from_rgba_unmultiplied(255, 0, 0, 128);
+        let image = egui::ColorImage {
+            size: [1, 1],
+            pixels: vec![color],
+        };
+        assert_eq!(color_image_as_bytes(&image, true), color.to_array());
+        assert_eq!(color_image_as_bytes(&image, false), [255, 0, 0, 128]);
+    }
+
+    #[test]
+    fn test_window_load_op() {
+        // Egui is painted over the scene by default.
+        let load_op: LoadOp<LinearRgba> = window_load_op(None, TextureFormat::Bgra8UnormSrgb);
+        assert!(matches!(load_op, LoadOp::Load));
+
+        let gray = egui::Color32::from_gray(128);
+        let LoadOp::Clear(linear) =
+            window_load_op::<LinearRgba>(Some(gray), TextureFormat::Bgra8UnormSrgb)
+        else {
+            unreachable!()
+        };
+        assert!((linear.red - 0.2158605).abs() < 1e-4);
+        let LoadOp::Clear(encoded) =
+            window_load_op::<LinearRgba>(Some(gray), TextureFormat::Bgra8Unorm)
+        else {
+            unreachable!()
+        };
+        assert_eq!(encoded.red, 128.0 / 255.0);
+        assert_eq!(encoded.alpha, 1.0);
+    }
+
+    #[test]
+    fn test_place_meshes() {
+        let rect_mesh = |color| {
+            let mut mesh = egui::Mesh::default();
+            mesh.add_colored_rect(
+                egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(10.0, 10.0)),
+                color,
+            );
+            mesh
+        };
+        let region = |first_vertex, vertices, first_index, indices| MeshRegion {
+            first_vertex,
+            vertices,
+            first_index,
+            indices,
+        };
+        let resident_meshes = |meshes: &[egui::Mesh], placement: &MeshPlacement| {
+            meshes
+                .iter()
+                .cloned()
+                .zip(placement.regions.iter().copied())
+                .map(|(mesh, region)| ResidentMesh { mesh, region })
+                .collect::<Vec<_>>()
+        };
+        let red = rect_mesh(egui::Color32::RED);
+        let green = rect_mesh(egui::Color32::GREEN);
+        let mut two_rects = rect_mesh(egui::Color32::BLUE);
+        two_rects.append(rect_mesh(egui::Color32::BLUE));
+
+        // Meshes are packed into empty buffers.
+        let meshes = vec![red.clone(), red.clone()];
+        let placement = place_meshes(&[], &meshes, (0, 0), (16, 24));
+        assert_eq!(placement.regions, [region(0, 4, 0, 6), region(4, 4, 6, 6)]);
+        assert_eq!(placement.changed, [0, 1]);
+
+        // Unchanged meshes aren't written again, changed ones that fit are written in place.
+        let resident = resident_meshes(&meshes, &placement);
+        let meshes = vec![red.clone(), green.clone()];
+        let placement = place_meshes(&resident, &meshes, (8, 12), (16, 24));
+        assert_eq!(placement.regions, [region(0, 4, 0, 6), region(4, 4, 6, 6)]);
+        assert_eq!(placement.changed, [1]);
+
+        // Meshes that have grown are moved after the last region.
+        let resident = resident_meshes(&meshes, &placement);
+        let meshes = vec![two_rects.clone(), green.clone()];
+        let placement = place_meshes(&resident, &meshes, (8, 12), (16, 24));
+        assert_eq!(
+            placement.regions,
+            [region(8, 8, 12, 12), region(4, 4, 6, 6)]
+        );
+        assert_eq!(placement.changed, [0]);
+        assert_eq!((placement.vertices_end, placement.indices_end), (16, 24));
+
+        // Once the meshes don't fit into the buffers, everything is packed again.
+        let resident = resident_meshes(&meshes, &placement);
+        let meshes = vec![two_rects.clone(), two_rects, red];
+        let placement = place_meshes(&resident, &meshes, (16, 24), (16, 24));
+        assert_eq!(
+            placement.regions,
+            [
+                region(0, 8, 0, 12),
+                region(8, 8, 12, 12),
+                region(16, 4, 24, 6)
+            ]
+        );
+        assert_eq!(placement.changed, [0, 1, 2]);
+        assert_eq!((placement.vertices_end, placement.indices_end), (20, 30));
+    }
+
+    #[test]
+    fn test_append_mesh_data_with_more_than_u16_vertices() {
+        let ctx = egui::Context::default();
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                // A long feathered line tessellates into several vertices per point.
+                let points = (0..40_000)
+                    .map(|i| egui::pos2((i % 800) as f32, (i / 800) as f32))
+                    .collect::<Vec<_>>();
+                ui.painter().add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                ));
+            });
+        });
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        let mut vertex_data = Vec::new();
+        let mut index_data = Vec::new();
+        let mut index_offset = 0;
+        for primitive in &paint_jobs {
+            let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive else {
+                continue;
+            };
+            append_mesh_data(&mut vertex_data, &mut index_data, mesh, index_offset);
+            index_offset += mesh.vertices.len() as u32;
+        }
+
+        assert!(index_offset > u16::MAX as u32);
+        let indices: &[u32] = cast_slice(&index_data);
+        let max_index = indices.iter().copied().max().unwrap();
+        assert!(max_index > u16::MAX as u32);
+        assert!(max_index < index_offset);
+        assert_eq!(
+            vertex_data.len(),
+            index_offset as usize * std::mem::size_of::<egui::epaint::Vertex>()
+        );
+    }
+
+    #[cfg(feature = "test_renderer")]
+    #[test]
+    fn test_mesh_with_more_than_u16_vertices_renders_from_buffer_data() {
+        use crate::test_renderer::EguiTestRenderOutput;
+
+        let ctx = egui::Context::default();
+        let screen_rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(400.0, 100.0));
+        let input = egui::RawInput {
+            screen_rect: Some(screen_rect),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            // A zigzag of short feathered segments tessellates into one mesh with several
+            // vertices per point.
+            let points = (0..40_000)
+                .map(|i| egui::pos2((i % 400) as f32 + 0.5, (i / 400) as f32 + (i % 2) as f32))
+                .collect::<Vec<_>>();
+            ctx.layer_painter(egui::LayerId::background())
+                .add(egui::Shape::line(
+                    points,
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                ));
+        });
+        let textures_delta = output.textures_delta;
+        let paint_jobs = ctx.tessellate(output.shapes, output.pixels_per_point);
+
+        // The buffer data the node uploads, decoded the way the pipeline reads it.
+        let mut vertex_data = Vec::new();
+        let mut index_data = Vec::new();
+        let mut index_offset = 0;
+        for primitive in &paint_jobs {
+            let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive else {
+                continue;
+            };
+            append_mesh_data(&mut vertex_data, &mut index_data, mesh, index_offset);
+            index_offset += mesh.vertices.len() as u32;
+        }
+        assert!(index_offset > u16::MAX as u32);
+        let mesh = egui::Mesh {
+            indices: index_data
+                .chunks_exact(std::mem::size_of::<u32>())
+                .map(bytemuck::pod_read_unaligned)
+                .collect(),
+            vertices: vertex_data
+                .chunks_exact(std::mem::size_of::<egui::epaint::Vertex>())
+                .map(bytemuck::pod_read_unaligned)
+                .collect(),
+            texture_id: egui::TextureId::default(),
+        };
+        assert!(mesh.indices.iter().any(|&index| index > u16::MAX as u32));
+
+        let render = |paint_jobs: Vec<egui::ClippedPrimitive>| {
+            let mut output = EguiTestRenderOutput::default();
+            output.paint_jobs = paint_jobs;
+            output.pixels_per_point = 1.0;
+            output.update_textures(&textures_delta);
+            output.render(screen_rect, egui::Rgba::WHITE);
+            output
+        };
+        let expected = render(paint_jobs);
+        let rendered = render(vec![egui::ClippedPrimitive {
+            clip_rect: screen_rect,
+            primitive: egui::epaint::Primitive::Mesh(mesh),
+        }]);
+
+        // The last rows of the line are drawn by vertices past `u16::MAX`.
+        let last_row = egui::pos2(200.0, 99.5);
+        assert_ne!(
+            rendered.pixel_at(last_row),
+            Some(egui::Color32::TRANSPARENT)
+        );
+        assert_eq!(rendered.image, expected.image);
+    }
+
+    #[test]
+    fn test_paint_callback_downcast() {
+        struct NoopCallback;
+
+        impl EguiPaintCallback for NoopCallback {
+            fn render<'pass>(
+                &'pass self,
+                _info: &EguiPaintCallbackInfo,
+                _render_pass: &mut TrackedRenderPass<'pass>,
+                _world: &'pass World,
+            ) {
+            }
+        }
+
+        let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(10.0, 10.0));
+        let primitive = |callback| DrawPrimitive::PaintCallback {
+            callback,
+            info: EguiPaintCallbackInfo {
+                egui_info: egui::PaintCallbackInfo {
+                    viewport: rect,
+                    clip_rect: rect,
+                    pixels_per_point: 1.0,
+                    screen_size_px: [10, 10],
+                },
+                window_entity: Entity::PLACEHOLDER,
+                texture_format: TextureFormat::Bgra8UnormSrgb,
+                sample_count: 1,
+                depth_format: None,
+            },
+            viewport: (0.0, 0.0, 10.0, 10.0),
+        };
+
+        let callback = new_paint_callback(rect, NoopCallback);
+        assert_eq!(callback.rect, rect);
+        assert!(primitive(callback).paint_callback().is_some());
+        // Callbacks meant for other backends are ignored.
+        let foreign_callback = egui::PaintCallback {
+            rect,
+            callback: std::sync::Arc::new(()),
+        };
+        assert!(primitive(foreign_callback).paint_callback().is_none());
+    }
+
+    #[test]
+    fn test_free_unused_buffers() {
+        let used = Entity::from_raw(0);
+        let unused = Entity::from_raw(1);
+        let mut pool = EguiBufferPool::default();
+        for entity in [used, unused] {
+            pool.buffers.entry(entity).or_default().used = true;
+        }
+
+        // Buffers are kept for a frame after they were used.
+        pool.free_unused();
+        assert_eq!(pool.buffers.len(), 2);
+
+        pool.buffers.get_mut(&used).unwrap().used = true;
+        pool.free_unused();
+        assert!(pool.buffers.contains_key(&used));
+        assert!(!pool.buffers.contains_key(&unused));
+    }
+}
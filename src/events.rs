@@ -0,0 +1,221 @@
+use bevy::prelude::{Entity, Event};
+#[cfg(feature = "render")]
+use bevy::prelude::{Handle, Image};
+
+/// Is sent when a two-finger rotation gesture is recognized (only if
+/// [`EguiSettings::synthesize_touch_gestures`](crate::EguiSettings::synthesize_touch_gestures) is
+/// enabled).
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct EguiRotateGestureEvent {
+    /// Window entity.
+    pub window: Entity,
+    /// Rotation delta since the last touch event, in radians (clockwise, as the Y axis points down).
+    pub delta: f32,
+}
+
+/// Is sent for the keys that don't have an [`egui::Key`] equivalent and therefore aren't passed to
+/// Egui, such as `BrowserBack` or media keys. Modifier keys, which Egui receives as
+/// [`egui::Modifiers`], and unidentified keys are skipped.
+///
+/// The event is sent during the [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system for
+/// the windows that accept input, with the modifiers the other key events of Egui get. On web,
+/// browsers still handle some of these keys unless
+/// `web_keyboard::WebKeyboardSettings::capture_unmapped_keys` is enabled.
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct EguiUnmappedKeyEvent {
+    /// Window entity.
+    pub window: Entity,
+    /// The logical key, such as [`Key::BrowserBack`](bevy::input::keyboard::Key::BrowserBack).
+    pub logical_key: bevy::input::keyboard::Key,
+    /// The physical key.
+    pub key_code: bevy::input::keyboard::KeyCode,
+    /// Whether the key is pressed or released.
+    pub pressed: bool,
+    /// The modifiers held.
+    pub modifiers: egui::Modifiers,
+}
+
+/// Is sent when Egui reports a widget interaction, such as a click or a value change.
+///
+/// Events are derived from [`egui::PlatformOutput::events`] during the
+/// [`EguiSet::EndPass`](crate::EguiSet::EndPass) system and are sent once per interaction, which
+/// makes them handy for playing UI sounds or haptic feedback without checking every widget's
+/// [`egui::Response`].
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct EguiInteractionEvent {
+    /// Window entity.
+    pub window: Entity,
+    /// The interaction reported by Egui, contains the widget type and label.
+    pub event: egui::output::OutputEvent,
+}
+
+/// Is sent when Egui copies text (from a text field or a label, for example), during the
+/// [`EguiSet::EndPass`](crate::EguiSet::EndPass) system.
+///
+/// The text is put into the clipboard by `bevy_egui` (with the `manage_clipboard` feature), the
+/// event lets apps react to the copy, by playing a sound, for example.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct EguiCopyEvent {
+    /// Window entity.
+    pub window: Entity,
+    /// The copied text.
+    pub text: String,
+}
+
+/// Is sent when Egui asks to open a URL (when a hyperlink is clicked, for example), during the
+/// [`EguiSet::EndPass`](crate::EguiSet::EndPass) system.
+///
+/// The URL is opened by `bevy_egui` with the `open_url` feature, apps without it can open the URL
+/// themselves.
+#[derive(Event, Clone, Debug, PartialEq, Eq)]
+pub struct EguiOpenUrlEvent {
+    /// Window entity.
+    pub window: Entity,
+    /// The URL to open.
+    pub url: String,
+    /// Whether the URL is requested to be opened in a new tab (see
+    /// [`EguiSettings::default_open_url_target`](crate::EguiSettings::default_open_url_target)).
+    pub new_tab: bool,
+}
+
+/// Is sent during the [`EguiSet::EndPass`](crate::EguiSet::EndPass) system when the text cursor of
+/// the focused text field moves, or when a text field gains focus.
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub struct EguiTextCursorMoved {
+    /// Window entity.
+    pub window: Entity,
+    /// The text cursor, in Egui points.
+    pub cursor_rect: egui::Rect,
+}
+
+/// Is sent during the [`EguiSet::EndPass`](crate::EguiSet::EndPass) system when Egui wants the UI
+/// to be updated again, for example, while an animation is running or to hide a tooltip.
+///
+/// `bevy_egui` already requests a redraw for immediate repaints, and the `wake_on_repaint` feature
+/// wakes reactive apps up for delayed ones, so the event is mostly useful for apps that schedule
+/// updates themselves.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EguiWantsRepaint {
+    /// Window entity.
+    pub window: Entity,
+    /// How long Egui can wait before the next update, [`Duration::ZERO`](std::time::Duration::ZERO)
+    /// for an immediate repaint.
+    pub delay: std::time::Duration,
+}
+
+/// Is sent when an image is pasted from the clipboard, as egui doesn't have an event for it.
+///
+/// Pasted images are added to [`Assets<Image>`](bevy::asset::Assets) and registered in
+/// [`EguiUserTextures`](crate::EguiUserTextures). On native platforms, an image is pasted if the
+/// clipboard doesn't contain any text when the paste shortcut is pressed. On web, images pasted
+/// into the page are decoded by the browser, and the ones that fail to decode are skipped with a
+/// warning. Remove the image with
+/// [`EguiUserTextures::remove_image`](crate::EguiUserTextures::remove_image) once it's no longer
+/// needed.
+#[cfg(feature = "render")]
+#[derive(Event, Clone, Debug, PartialEq)]
+pub struct EguiClipboardImagePaste {
+    /// Window entity.
+    pub window: Entity,
+    /// The pasted image.
+    pub image: Handle<Image>,
+    /// Egui texture id of the image.
+    pub texture_id: egui::TextureId,
+    /// Size of the image in pixels.
+    pub size: egui::Vec2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{capture_input_events, test_app},
+        EguiContexts,
+    };
+
+    #[test]
+    fn test_unmapped_key_event() {
+        use bevy::{
+            ecs::event::Events,
+            input::{
+                keyboard::{Key, KeyCode, KeyboardInput},
+                ButtonState,
+            },
+        };
+
+        let (mut app, window) = test_app();
+        let events = capture_input_events(&mut app);
+        app.update();
+
+        for (key_code, logical_key) in [
+            (KeyCode::ShiftLeft, Key::Shift),
+            (KeyCode::BrowserBack, Key::BrowserBack),
+        ] {
+            app.world_mut().send_event(KeyboardInput {
+                key_code,
+                logical_key,
+                state: ButtonState::Pressed,
+                window,
+            });
+        }
+        app.update();
+        let unmapped_key_events = app
+            .world()
+            .resource::<Events<EguiUnmappedKeyEvent>>()
+            .iter_current_update_events()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unmapped_key_events,
+            [EguiUnmappedKeyEvent {
+                window,
+                logical_key: Key::BrowserBack,
+                key_code: KeyCode::BrowserBack,
+                pressed: true,
+                modifiers: egui::Modifiers::SHIFT,
+            }]
+        );
+        assert!(!events
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(_, event)| matches!(event, egui::Event::Key { .. })));
+    }
+
+    #[test]
+    fn test_output_events() {
+        fn ui_system(mut contexts: EguiContexts, mut frame: bevy::ecs::system::Local<u32>) {
+            *frame += 1;
+            let ctx = contexts.ctx_mut();
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let mut text = String::new();
+                ui.text_edit_singleline(&mut text).request_focus();
+            });
+            if *frame == 3 {
+                ctx.copy_text("copied".to_owned());
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        let mut copied = Vec::new();
+        let mut cursor_moves = 0;
+        for _ in 0..4 {
+            app.update();
+            let world = app.world_mut();
+            copied.extend(
+                world
+                    .resource_mut::<bevy::ecs::event::Events<EguiCopyEvent>>()
+                    .drain()
+                    .map(|event| (event.window, event.text)),
+            );
+            cursor_moves += world
+                .resource_mut::<bevy::ecs::event::Events<EguiTextCursorMoved>>()
+                .drain()
+                .count();
+        }
+        assert_eq!(copied, vec![(window, "copied".to_owned())]);
+        // The cursor only moves when the text field gains focus.
+        assert_eq!(cursor_moves, 1);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::EguiContext;
+use bevy::{
+    asset::{
+        io::Reader, Asset, AssetEvent, AssetLoader, Assets, AsyncReadExt, Handle, LoadContext,
+    },
+    prelude::{DetectChanges, EventReader, Local, Query, Res, Resource},
+    reflect::TypePath,
+};
+use std::ops::RangeInclusive;
+
+/// A TTF or OTF font loaded by [`EguiFontLoader`].
+///
+/// Fonts are passed to Egui as is, [`EguiFonts`] registers them in the contexts.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct EguiFont {
+    /// Contents of the font file.
+    pub data: Vec<u8>,
+}
+
+/// Loads TTF and OTF files as [`EguiFont`]s.
+///
+/// The loader only claims the `.egui.ttf` and `.egui.otf` extensions, leaving `.ttf` and `.otf`
+/// to Bevy's own `Font` loader. Any font file can still be loaded as an Egui font with an explicit
+/// type (`asset_server.load::<EguiFont>("fonts/FiraSans-Bold.ttf")`), which picks the loader by
+/// the type of the asset instead of the extension.
+#[derive(Default)]
+pub struct EguiFontLoader;
+
+impl AssetLoader for EguiFontLoader {
+    type Asset = EguiFont;
+    type Settings = ();
+    type Error = std::io::Error;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<EguiFont, std::io::Error> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await?;
+        Ok(EguiFont { data })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["egui.ttf", "egui.otf"]
+    }
+}
+
+/// A resource that adds fonts loaded with the asset server to every Egui context.
+///
+/// Once a font is added (with [`EguiFonts::add_font`] or [`EguiContexts::add_font`], for example)
+/// or the resource is replaced, [`EguiFonts::font_definitions`] are set with
+/// [`egui::Context::set_fonts`] once a context is created, when the resource changes, and when
+/// one of its fonts finishes loading or is reloaded (with the `file_watcher` Bevy feature,
+/// for example). Fonts that haven't been loaded yet are skipped, so the UI is drawn with
+/// the other fonts in the meantime.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{
+///     egui,
+///     fonts::{EguiFont, EguiFonts},
+/// };
+///
+/// fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let mut egui_fonts = EguiFonts::default();
+///     egui_fonts.add_font(
+///         "Fira Sans",
+///         asset_server.load::<EguiFont>("fonts/FiraSans-Bold.ttf"),
+///         egui::FontFamily::Proportional,
+///     );
+///     commands.insert_resource(egui_fonts);
+/// }
+/// ```
+///
+/// The plugin initializes an empty resource, which leaves the fonts of the contexts alone.
+/// Otherwise, the resource replaces the fonts of the contexts, including the ones set with
+/// [`egui::Context::set_fonts`] directly.
+///
+/// [`EguiContexts::add_font`]: crate::EguiContexts::add_font
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EguiFonts {
+    base: egui::FontDefinitions,
+    fonts: Vec<EguiFontEntry>,
+    // Whether the fonts of the contexts are set, so that the empty resource initialized by
+    // the plugin doesn't replace the fonts set with `egui::Context::set_fonts`.
+    used: bool,
+}
+
+#[derive(Clone, Debug)]
+struct EguiFontEntry {
+    name: String,
+    handle: Handle<EguiFont>,
+    family: egui::FontFamily,
+    fallback: bool,
+}
+
+impl EguiFonts {
+    /// Creates the resource with the font definitions the added fonts are inserted into
+    /// ([`egui::FontDefinitions::default`] by default).
+    #[must_use]
+    pub fn new(base: egui::FontDefinitions) -> Self {
+        Self {
+            base,
+            fonts: Vec::new(),
+            used: true,
+        }
+    }
+
+    /// Adds a font to a family, where it takes priority over the fonts that are already there.
+    ///
+    /// Use [`egui::FontFamily::Name`] to create a new family.
+    pub fn add_font(
+        &mut self,
+        name: impl Into<String>,
+        handle: Handle<EguiFont>,
+        family: egui::FontFamily,
+    ) -> &mut Self {
+        self.push(name.into(), handle, family, false)
+    }
+
+    /// Adds a font to the end of a family. It's used for the characters that the other fonts
+    /// of the family don't have (emojis or a different script, for example).
+    pub fn add_fallback_font(
+        &mut self,
+        name: impl Into<String>,
+        handle: Handle<EguiFont>,
+        family: egui::FontFamily,
+    ) -> &mut Self {
+        self.push(name.into(), handle, family, true)
+    }
+
+    /// Returns the base definitions with the fonts that have been loaded.
+    #[must_use]
+    pub fn font_definitions(&self, fonts: &Assets<EguiFont>) -> egui::FontDefinitions {
+        let mut font_definitions = self.base.clone();
+        for entry in &self.fonts {
+            let Some(font) = fonts.get(&entry.handle) else {
+                continue;
+            };
+            font_definitions.font_data.insert(
+                entry.name.clone(),
+                egui::FontData::from_owned(font.data.clone()),
+            );
+            let family = font_definitions
+                .families
+                .entry(entry.family.clone())
+                .or_default();
+            if entry.fallback {
+                family.push(entry.name.clone());
+            } else {
+                family.insert(0, entry.name.clone());
+            }
+        }
+        font_definitions
+    }
+
+    fn push(
+        &mut self,
+        name: String,
+        handle: Handle<EguiFont>,
+        family: egui::FontFamily,
+        fallback: bool,
+    ) -> &mut Self {
+        self.fonts.push(EguiFontEntry {
+            name,
+            handle,
+            family,
+            fallback,
+        });
+        self.used = true;
+        self
+    }
+}
+
+/// Sets the fonts of [`EguiFonts`] to the contexts, see its documentation for when
+/// that happens.
+pub fn update_egui_fonts_system(
+    egui_fonts: Option<Res<EguiFonts>>,
+    fonts: Res<Assets<EguiFont>>,
+    mut font_events: EventReader<AssetEvent<EguiFont>>,
+    mut contexts: Query<&mut EguiContext>,
+    // The definitions the contexts were last updated with.
+    mut font_definitions: Local<Option<egui::FontDefinitions>>,
+) {
+    let Some(egui_fonts) = egui_fonts.filter(|egui_fonts| egui_fonts.used) else {
+        font_events.clear();
+        *font_definitions = None;
+        return;
+    };
+
+    let mut fonts_loaded = false;
+    for event in font_events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            fonts_loaded |= egui_fonts
+                .fonts
+                .iter()
+                .any(|entry| entry.handle.id() == *id);
+        }
+    }
+    let updated = egui_fonts.is_changed() || fonts_loaded || font_definitions.is_none();
+    if updated {
+        *font_definitions = Some(egui_fonts.font_definitions(&fonts));
+    }
+    let Some(font_definitions) = font_definitions.as_ref() else {
+        return;
+    };
+
+    for mut ctx in contexts.iter_mut() {
+        if updated || ctx.is_added() {
+            ctx.get_mut().set_fonts(font_definitions.clone());
+        }
+    }
+}
+
+/// A resource that lists characters to add to the font atlas of every context in advance.
+///
+/// Egui rasterizes glyphs the first time they are laid out, and each time the font atlas grows, the
+/// whole font texture gets re-uploaded. This may cause a hitch when a screen with a lot of new text
+/// (a different script, for example) appears. If the resource is inserted, the characters are laid
+/// out with every text style of a context during the
+/// [`EguiSet::BeginPass`](crate::EguiSet::BeginPass) system once a context is created, and also
+/// once its pixels per point or this resource change.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::EguiFontAtlasPrewarm;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.insert_resource(
+///         EguiFontAtlasPrewarm::ascii().with_ranges(['\u{00a0}'..='\u{00ff}', 'А'..='я']),
+///     );
+/// }
+/// ```
+///
+/// Fonts set with [`egui::Context::set_fonts`] are applied by egui on the next frame only,
+/// so mark the resource as changed a frame later to pre-warm them.
+#[derive(Resource, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EguiFontAtlasPrewarm {
+    /// Characters to pre-warm, duplicates don't matter.
+    pub characters: String,
+}
+
+impl EguiFontAtlasPrewarm {
+    /// Pre-warms printable ASCII characters.
+    #[must_use]
+    pub fn ascii() -> Self {
+        Self::default().with_ranges([' '..='~'])
+    }
+
+    /// Adds character ranges to pre-warm.
+    #[must_use]
+    pub fn with_ranges(mut self, ranges: impl IntoIterator<Item = RangeInclusive<char>>) -> Self {
+        self.characters.extend(ranges.into_iter().flatten());
+        self
+    }
+
+    /// Adds characters of a string to pre-warm.
+    #[must_use]
+    pub fn with_text(mut self, text: &str) -> Self {
+        self.characters.push_str(text);
+        self
+    }
+}
+
+#[cfg(all(test, feature = "default_fonts"))]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContexts};
+    use bevy::{
+        asset::{io::embedded::EmbeddedAssetRegistry, AssetServer, LoadState},
+        ecs::system::RunSystemOnce,
+        prelude::With,
+        window::PrimaryWindow,
+    };
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_egui_fonts() {
+        let (mut app, _) = test_app();
+        app.update();
+
+        // Any valid font works, a default one is reused here.
+        let data = egui::FontDefinitions::default().font_data["Hack"]
+            .font
+            .to_vec();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<EguiFont>>()
+            .add(EguiFont { data });
+        let mut egui_fonts = EguiFonts::default();
+        egui_fonts.add_font("Custom", handle, egui::FontFamily::Name("custom".into()));
+        app.insert_resource(egui_fonts);
+        app.update();
+        app.update();
+
+        let mut contexts = app.world_mut().query::<&mut EguiContext>();
+        for mut ctx in contexts.iter_mut(app.world_mut()) {
+            let families = ctx.get_mut().fonts(|fonts| fonts.families());
+            assert!(families.contains(&egui::FontFamily::Name("custom".into())));
+        }
+    }
+
+    #[test]
+    fn test_add_font_from_asset_server() {
+        let (mut app, _) = test_app();
+        // Any valid font works, a default one is reused here.
+        let data = egui::FontDefinitions::default().font_data["Hack"]
+            .font
+            .to_vec();
+        app.world()
+            .resource::<EmbeddedAssetRegistry>()
+            .insert_asset(PathBuf::new(), Path::new("bevy_egui/font.ttf"), data);
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        let handle = asset_server.load::<EguiFont>("embedded://bevy_egui/font.ttf");
+        // Untyped loads of `.ttf` files are left to the `Font` loader of `bevy_text`.
+        let untyped = asset_server.load_untyped("embedded://bevy_egui/font.ttf");
+
+        app.world_mut()
+            .run_system_once(move |mut contexts: EguiContexts| {
+                contexts.add_font(
+                    "Custom",
+                    handle.clone(),
+                    egui::FontFamily::Name("custom".into()),
+                );
+            });
+
+        let custom = egui::FontFamily::Name("custom".into());
+        let mut has_custom_font = false;
+        for _ in 0..100 {
+            app.update();
+            let mut contexts = app.world_mut().query::<&mut EguiContext>();
+            has_custom_font = contexts.iter_mut(app.world_mut()).all(|mut ctx| {
+                ctx.get_mut()
+                    .fonts(|fonts| fonts.families())
+                    .contains(&custom)
+            });
+            if has_custom_font {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(has_custom_font);
+        assert!(matches!(
+            asset_server.load_state(untyped.id()),
+            LoadState::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn test_font_atlas_prewarm() {
+        fn font_atlas_fill_ratio(prewarm: Option<EguiFontAtlasPrewarm>) -> f32 {
+            let (mut app, _) = test_app();
+            if let Some(prewarm) = prewarm {
+                app.insert_resource(prewarm);
+            }
+            app.update();
+
+            let mut ctx = app
+                .world_mut()
+                .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+                .single_mut(app.world_mut());
+            ctx.get_mut().fonts(|fonts| fonts.font_atlas_fill_ratio())
+        }
+
+        let prewarm = EguiFontAtlasPrewarm::ascii().with_ranges(['А'..='я']);
+        assert!(font_atlas_fill_ratio(Some(prewarm)) > font_atlas_fill_ratio(None));
+    }
+}
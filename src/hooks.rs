@@ -0,0 +1,190 @@
+use bevy::prelude::{Entity, Resource};
+
+/// Type of hooks registered in [`EguiInputHooks`].
+pub type EguiInputHook = Box<dyn Fn(Entity, &mut egui::RawInput) + Send + Sync>;
+
+/// A resource for storing hooks that can inspect or modify [`egui::RawInput`] of every context
+/// right before it's consumed by [`egui::Context::begin_frame`].
+///
+/// Hooks are called during the [`EguiSet::BeginPass`](crate::EguiSet::BeginPass) system in the
+/// order they were added, after all the Bevy input has been processed by
+/// [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput). They receive the window entity and can
+/// inject synthetic events, filter events or adjust the screen rect:
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, EguiInputHooks};
+///
+/// fn setup_input_hooks(mut input_hooks: ResMut<EguiInputHooks>) {
+///     input_hooks.add(|_window, raw_input| {
+///         raw_input
+///             .events
+///             .retain(|event| !matches!(event, egui::Event::Zoom(_)));
+///     });
+/// }
+/// ```
+///
+/// Hooks can't access the Bevy world, as the contexts are mutably borrowed while they run.
+/// If you need to access other resources or components, add a system that modifies the
+/// [`EguiInput`](crate::EguiInput) component instead and order it with
+/// `system.after(EguiSet::ProcessInput).before(EguiSet::BeginPass)`.
+#[derive(Resource, Default)]
+pub struct EguiInputHooks {
+    hooks: Vec<EguiInputHook>,
+}
+
+impl EguiInputHooks {
+    /// Registers a hook that will be called for every context each frame.
+    pub fn add(&mut self, hook: impl Fn(Entity, &mut egui::RawInput) + Send + Sync + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Removes all the registered hooks.
+    pub fn clear(&mut self) {
+        self.hooks.clear();
+    }
+
+    /// Returns `true` if there are no registered hooks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Calls all the registered hooks for the input of a window.
+    pub fn run(&self, window: Entity, raw_input: &mut egui::RawInput) {
+        for hook in &self.hooks {
+            hook(window, raw_input);
+        }
+    }
+}
+
+/// Type of hooks registered in [`EguiPassHooks`].
+pub type EguiPassHook = Box<dyn Fn(Entity, &egui::Context) + Send + Sync>;
+
+/// A resource for storing hooks that are called with the context of every window (and of every
+/// secondary or render-to-texture context) at the start and at the end of each pass.
+///
+/// This lets plugins layer UI onto every context without adding a system per window: an
+/// inspector or a profiler can show its window when the pass begins, and notification toasts
+/// can be drawn on top of the app's UI when it ends. Hooks receive the context entity and are
+/// called in the order they were added:
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, EguiPassHooks};
+///
+/// fn setup_pass_hooks(mut pass_hooks: ResMut<EguiPassHooks>) {
+///     pass_hooks.add_end_pass(|_window, ctx| {
+///         egui::Area::new("fps".into())
+///             .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::ZERO)
+///             .show(ctx, |ui| ui.label("60 FPS"));
+///     });
+/// }
+/// ```
+///
+/// Begin pass hooks are called at the end of the [`EguiSet::BeginPass`](crate::EguiSet::BeginPass)
+/// system, end pass hooks are called at the start of the
+/// [`EguiSet::EndPass`](crate::EguiSet::EndPass) system, so they run after all the
+/// [`EguiSet::UserUi`](crate::EguiSet::UserUi) systems. Like [`EguiInputHooks`], they can't access
+/// the Bevy world.
+#[derive(Resource, Default)]
+pub struct EguiPassHooks {
+    pub(crate) begin_pass: Vec<EguiPassHook>,
+    pub(crate) end_pass: Vec<EguiPassHook>,
+}
+
+impl EguiPassHooks {
+    /// Registers a hook that will be called for every context right after its pass begins.
+    pub fn add_begin_pass(
+        &mut self,
+        hook: impl Fn(Entity, &egui::Context) + Send + Sync + 'static,
+    ) {
+        self.begin_pass.push(Box::new(hook));
+    }
+
+    /// Registers a hook that will be called for every context right before its pass ends.
+    pub fn add_end_pass(&mut self, hook: impl Fn(Entity, &egui::Context) + Send + Sync + 'static) {
+        self.end_pass.push(Box::new(hook));
+    }
+
+    /// Removes all the registered hooks.
+    pub fn clear(&mut self) {
+        self.begin_pass.clear();
+        self.end_pass.clear();
+    }
+
+    /// Returns `true` if there are no registered hooks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.begin_pass.is_empty() && self.end_pass.is_empty()
+    }
+
+    /// Calls all the registered begin pass hooks for a context.
+    pub fn run_begin_pass(&self, window: Entity, ctx: &egui::Context) {
+        for hook in &self.begin_pass {
+            hook(window, ctx);
+        }
+    }
+
+    /// Calls all the registered end pass hooks for a context.
+    pub fn run_end_pass(&self, window: Entity, ctx: &egui::Context) {
+        for hook in &self.end_pass {
+            hook(window, ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContext};
+
+    #[test]
+    fn test_input_hooks() {
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let hook_called = Arc::new(AtomicBool::new(false));
+        let (mut app, primary_window) = test_app();
+        let hook_called_clone = hook_called.clone();
+        app.world_mut()
+            .resource_mut::<EguiInputHooks>()
+            .add(move |window, raw_input| {
+                assert_eq!(window, primary_window);
+                assert!(raw_input.screen_rect.is_some());
+                hook_called_clone.store(true, Ordering::SeqCst);
+            });
+        app.update();
+
+        assert!(hook_called.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_pass_hooks() {
+        let begin_passes = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let (mut app, window) = test_app();
+        let mut pass_hooks = app.world_mut().resource_mut::<EguiPassHooks>();
+        pass_hooks.add_begin_pass({
+            let begin_passes = begin_passes.clone();
+            move |_window, _ctx| {
+                begin_passes.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        });
+        pass_hooks.add_end_pass(|_window, ctx| {
+            egui::Area::new("hook".into()).show(ctx, |ui| ui.label("Hook"));
+        });
+        app.update();
+        app.update();
+
+        assert_eq!(begin_passes.load(std::sync::atomic::Ordering::Relaxed), 2);
+        let area_rect = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .get_mut()
+            .memory(|memory| memory.area_rect(egui::Id::new("hook")));
+        assert!(area_rect.is_some());
+    }
+}
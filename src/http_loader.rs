@@ -0,0 +1,237 @@
+use crate::{viewports::EguiViewport, EguiContext};
+use bevy::{
+    asset::{
+        io::{AssetReader, ErasedAssetReader},
+        AsyncReadExt,
+    },
+    prelude::{Added, Query, Res, Resource, Without},
+    tasks::IoTaskPool,
+    utils::HashMap,
+};
+use egui::load::{BytesLoadResult, BytesPoll, LoadError};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// An [`egui::load::BytesLoader`] for `http://` and `https://` URIs, which reads them with Bevy
+/// [`AssetReader`]s on the [`IoTaskPool`].
+///
+/// On the web, URIs are fetched with Bevy's `HttpWasmAssetReader` by default. Bevy has no HTTP
+/// reader for the other platforms, set one with [`EguiHttpBytesLoader::set_reader`] (the
+/// `WebAssetReader` of the `bevy_web_asset` crate, for example). URIs of schemes that have no
+/// reader aren't supported, so that Egui passes them on to the other bytes loaders of the context.
+///
+/// A reader gets the URI without its scheme, `example.com/logo.png` for
+/// `https://example.com/logo.png`. The bytes are kept until Egui forgets the URI.
+pub struct EguiHttpBytesLoader {
+    readers: Mutex<HashMap<String, Arc<dyn ErasedAssetReader>>>,
+    // Shared with the tasks that read the URIs.
+    bytes: Arc<Mutex<HashMap<String, BytesState>>>,
+}
+
+enum BytesState {
+    Pending,
+    Ready(Arc<[u8]>),
+    Failed(String),
+}
+
+impl Default for EguiHttpBytesLoader {
+    fn default() -> Self {
+        let loader = Self {
+            readers: Mutex::default(),
+            bytes: Arc::default(),
+        };
+        #[cfg(target_arch = "wasm32")]
+        for scheme in ["http", "https"] {
+            loader.set_reader(
+                scheme,
+                bevy::asset::io::wasm::HttpWasmAssetReader::new(format!("{scheme}://")),
+            );
+        }
+        loader
+    }
+}
+
+impl EguiHttpBytesLoader {
+    /// The id of the loader, see [`egui::load::BytesLoader::id`].
+    pub const ID: &'static str = concat!(module_path!(), "::EguiHttpBytesLoader");
+
+    /// Sets the reader of the URIs with a scheme (`"https"`, for example).
+    pub fn set_reader(&self, scheme: impl Into<String>, reader: impl AssetReader) {
+        self.readers
+            .lock()
+            .unwrap()
+            .insert(scheme.into(), Arc::new(reader));
+    }
+}
+
+impl egui::load::BytesLoader for EguiHttpBytesLoader {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn load(&self, ctx: &egui::Context, uri: &str) -> BytesLoadResult {
+        let Some((scheme, path)) = uri.split_once("://") else {
+            return Err(LoadError::NotSupported);
+        };
+        let Some(reader) = self.readers.lock().unwrap().get(scheme).cloned() else {
+            return Err(LoadError::NotSupported);
+        };
+        {
+            let mut bytes = self.bytes.lock().unwrap();
+            match bytes.get(uri) {
+                Some(BytesState::Pending) => return Ok(BytesPoll::Pending { size: None }),
+                Some(BytesState::Ready(data)) => {
+                    return Ok(BytesPoll::Ready {
+                        size: None,
+                        bytes: egui::load::Bytes::Shared(data.clone()),
+                        mime: None,
+                    })
+                }
+                Some(BytesState::Failed(err)) => return Err(LoadError::Loading(err.clone())),
+                None => {}
+            }
+            bytes.insert(uri.to_owned(), BytesState::Pending);
+        }
+
+        // Without the `multi_threaded` Bevy feature (and on the web), the task may run right away,
+        // so the bytes aren't locked here.
+        let uri = uri.to_owned();
+        let path = PathBuf::from(path);
+        let bytes = self.bytes.clone();
+        let ctx = ctx.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let state = match read_bytes(&*reader, &path).await {
+                    Ok(data) => BytesState::Ready(data.into()),
+                    Err(err) => {
+                        bevy::log::warn!("Failed to load {uri:?}: {err}");
+                        BytesState::Failed(err)
+                    }
+                };
+                // The URI may have been forgotten in the meantime.
+                if let Some(current) = bytes.lock().unwrap().get_mut(&uri) {
+                    *current = state;
+                }
+                ctx.request_repaint();
+            })
+            .detach();
+        Ok(BytesPoll::Pending { size: None })
+    }
+
+    fn forget(&self, uri: &str) {
+        self.bytes.lock().unwrap().remove(uri);
+    }
+
+    fn forget_all(&self) {
+        self.bytes.lock().unwrap().clear();
+    }
+
+    fn byte_size(&self) -> usize {
+        self.bytes
+            .lock()
+            .unwrap()
+            .values()
+            .map(|state| match state {
+                BytesState::Ready(data) => data.len(),
+                BytesState::Pending | BytesState::Failed(_) => 0,
+            })
+            .sum()
+    }
+}
+
+async fn read_bytes(reader: &dyn ErasedAssetReader, path: &Path) -> Result<Vec<u8>, String> {
+    let mut reader = reader.read(path).await.map_err(|err| err.to_string())?;
+    let mut data = Vec::new();
+    reader
+        .read_to_end(&mut data)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(data)
+}
+
+/// A resource with the [`EguiHttpBytesLoader`] that is added to every context.
+///
+/// Is inserted by [`EguiPlugin`](struct@crate::EguiPlugin) with the `http_loader` feature.
+#[derive(Resource, Default)]
+pub struct EguiHttpLoader {
+    loader: Arc<EguiHttpBytesLoader>,
+}
+
+impl EguiHttpLoader {
+    /// The loader that is added to the contexts.
+    #[must_use]
+    pub fn loader(&self) -> &Arc<EguiHttpBytesLoader> {
+        &self.loader
+    }
+}
+
+/// Adds the loader of [`EguiHttpLoader`] to new contexts.
+pub fn add_http_loader_system(
+    http_loader: Res<EguiHttpLoader>,
+    // Viewport windows share the context of the window that shows them.
+    mut new_contexts: Query<&mut EguiContext, (Added<EguiContext>, Without<EguiViewport>)>,
+) {
+    for mut ctx in new_contexts.iter_mut() {
+        ctx.get_mut().add_bytes_loader(http_loader.loader.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_app;
+    use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+
+    fn poll(ctx: &egui::Context, uri: &str) -> BytesLoadResult {
+        for _ in 0..100 {
+            match ctx.try_load_bytes(uri) {
+                Ok(BytesPoll::Pending { .. }) => {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+                result => return result,
+            }
+        }
+        panic!("{uri:?} is still loading");
+    }
+
+    #[test]
+    fn test_http_uris_are_read_by_their_scheme() {
+        let (mut app, window) = test_app();
+        app.update();
+        let https = Dir::default();
+        https.insert_asset(Path::new("example.com/logo.png"), b"https".to_vec());
+        app.world()
+            .resource::<EguiHttpLoader>()
+            .loader()
+            .set_reader("https", MemoryAssetReader { root: https });
+        let ctx = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .into_inner()
+            .get_mut()
+            .clone();
+
+        let Ok(BytesPoll::Ready { bytes, .. }) = poll(&ctx, "https://example.com/logo.png") else {
+            panic!("the bytes aren't loaded");
+        };
+        assert_eq!(&*bytes, b"https");
+        assert!(matches!(
+            poll(&ctx, "https://example.com/missing.png"),
+            Err(LoadError::Loading(_))
+        ));
+        // The other URIs are left to the other loaders.
+        let loader = app.world().resource::<EguiHttpLoader>().loader().clone();
+        for uri in ["http://example.com/logo.png", "textures/logo.png"] {
+            assert!(matches!(
+                egui::load::BytesLoader::load(&*loader, &ctx, uri),
+                Err(LoadError::NotSupported)
+            ));
+        }
+
+        ctx.forget_image("https://example.com/logo.png");
+        assert_eq!(egui::load::BytesLoader::byte_size(&*loader), 0);
+    }
+}
@@ -0,0 +1,516 @@
+use crate::{
+    egui_node::texture_options_as_sampler_descriptor, viewports::EguiViewport, EguiContext,
+    EguiUserTextures,
+};
+use bevy::{
+    asset::{AssetEvent, AssetServer, Assets, Handle, LoadState},
+    log,
+    prelude::{Added, EventReader, Query, Res, ResMut, Resource, Without},
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::Image,
+    },
+    utils::HashMap,
+};
+use egui::load::{LoadError, SizedTexture, TextureLoadResult, TexturePoll};
+use std::sync::{Arc, Mutex};
+
+/// An [`egui::load::TextureLoader`] that loads the images of [`egui::Image`] as Bevy [`Image`]
+/// assets, so that `ui.image("textures/icon.png")` and `ui.image(egui::include_image!(...))`
+/// work without installing any other loaders.
+///
+/// The loader is added to every context by [`EguiImageLoader`], which loads the images and
+/// registers them in [`EguiUserTextures`]. URIs are mapped to asset paths like this:
+///
+/// - `bytes://` URIs (of [`egui::include_image`] and [`egui::Context::include_bytes`]) are decoded
+///   right away, with the image formats enabled for Bevy (its `png` feature, for example).
+/// - `file://` URIs are loaded from the file system, relative paths are relative to the asset
+///   folder.
+/// - `http://` and `https://` URIs are decoded once the bytes loaders of the context load them
+///   (the one of the `http_loader` feature, for example). Without such a loader, they're asset
+///   paths, loaded from the asset sources registered for them.
+/// - Other URIs are asset paths, loaded asynchronously by the [`AssetServer`].
+///
+/// SVG images (URIs ending with `.svg`) aren't supported, so that Egui passes them on to the other
+/// loaders of the context: the `svg` feature of `egui_extras` rasterizes them at the size they're
+/// shown with (call `egui_extras::install_image_loaders` once for every context).
+///
+/// Images are sampled with the texture options of the first request for their URI, and their
+/// textures are freed once Egui forgets the URI ([`egui::Context::forget_image`]).
+#[derive(Default)]
+pub struct EguiBevyTextureLoader {
+    state: Mutex<TextureLoaderState>,
+}
+
+#[derive(Default)]
+struct TextureLoaderState {
+    textures: HashMap<String, TextureState>,
+    requests: Vec<TextureRequest>,
+    // Other contexts that requested pending URIs, to repaint once they're loaded.
+    waiting: Vec<(String, egui::Context)>,
+    forgotten: Vec<String>,
+}
+
+enum TextureState {
+    Pending,
+    Ready(SizedTexture),
+    Failed(String),
+}
+
+struct TextureRequest {
+    uri: String,
+    bytes: Option<egui::load::Bytes>,
+    texture_options: egui::TextureOptions,
+    ctx: egui::Context,
+}
+
+impl EguiBevyTextureLoader {
+    /// The id of the loader, see [`egui::load::TextureLoader::id`].
+    pub const ID: &'static str = concat!(module_path!(), "::EguiBevyTextureLoader");
+}
+
+impl egui::load::TextureLoader for EguiBevyTextureLoader {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn load(
+        &self,
+        ctx: &egui::Context,
+        uri: &str,
+        texture_options: egui::TextureOptions,
+        _size_hint: egui::SizeHint,
+    ) -> TextureLoadResult {
+        if is_svg(uri) {
+            return Err(LoadError::NotSupported);
+        }
+        let mut state = self.state.lock().unwrap();
+        match state.textures.get(uri) {
+            Some(TextureState::Pending) => {
+                let is_waiting = state
+                    .waiting
+                    .iter()
+                    .any(|(waiting_uri, waiting_ctx)| waiting_uri == uri && waiting_ctx == ctx);
+                if !is_waiting {
+                    state.waiting.push((uri.to_owned(), ctx.clone()));
+                }
+                return Ok(TexturePoll::Pending { size: None });
+            }
+            Some(TextureState::Ready(texture)) => {
+                return Ok(TexturePoll::Ready { texture: *texture })
+            }
+            Some(TextureState::Failed(err)) => return Err(LoadError::Loading(err.clone())),
+            None => {}
+        }
+        let bytes = if uri.starts_with("bytes://") || is_http(uri) {
+            match ctx.try_load_bytes(uri) {
+                Ok(egui::load::BytesPoll::Ready { bytes, .. }) => Some(bytes),
+                Ok(egui::load::BytesPoll::Pending { size }) => {
+                    return Ok(TexturePoll::Pending { size });
+                }
+                Err(LoadError::NotSupported) if is_http(uri) => None,
+                Err(err) => return Err(err),
+            }
+        } else {
+            None
+        };
+        state.textures.insert(uri.to_owned(), TextureState::Pending);
+        state.requests.push(TextureRequest {
+            uri: uri.to_owned(),
+            bytes,
+            texture_options,
+            ctx: ctx.clone(),
+        });
+        Ok(TexturePoll::Pending { size: None })
+    }
+
+    fn forget(&self, uri: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.textures.remove(uri).is_some() {
+            state.forgotten.push(uri.to_owned());
+        }
+    }
+
+    fn forget_all(&self) {
+        let mut state = self.state.lock().unwrap();
+        let uris: Vec<_> = state.textures.drain().map(|(uri, _)| uri).collect();
+        state.forgotten.extend(uris);
+    }
+
+    fn byte_size(&self) -> usize {
+        // The textures are stored by Bevy.
+        0
+    }
+}
+
+/// A resource that loads the images requested by [`EguiBevyTextureLoader`], which it adds to
+/// every context.
+///
+/// Is inserted by [`EguiPlugin`](struct@crate::EguiPlugin) with the `image_loader` feature, if
+/// the app has an [`AssetServer`].
+#[derive(Resource, Default)]
+pub struct EguiImageLoader {
+    loader: Arc<EguiBevyTextureLoader>,
+    // Images that are loading or loaded, with the texture options and the contexts to repaint
+    // while they're loading.
+    images: HashMap<String, LoadingImage>,
+}
+
+struct LoadingImage {
+    handle: Handle<Image>,
+    texture_options: egui::TextureOptions,
+    contexts: Vec<egui::Context>,
+}
+
+impl EguiImageLoader {
+    /// The loader that is added to the contexts.
+    #[must_use]
+    pub fn loader(&self) -> &Arc<EguiBevyTextureLoader> {
+        &self.loader
+    }
+
+    /// Returns the handle of the image loaded for a URI.
+    #[must_use]
+    pub fn image(&self, uri: &str) -> Option<&Handle<Image>> {
+        self.images.get(uri).map(|image| &image.handle)
+    }
+}
+
+/// Adds the loader to new contexts, starts loading the requested images and passes the loaded
+/// ones to Egui.
+pub fn update_image_loader_system(
+    mut image_loader: ResMut<EguiImageLoader>,
+    // Viewport windows share the context of the window that shows them.
+    mut new_contexts: Query<&mut EguiContext, (Added<EguiContext>, Without<EguiViewport>)>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    mut image_events: EventReader<AssetEvent<Image>>,
+) {
+    let image_loader = &mut *image_loader;
+    for mut ctx in new_contexts.iter_mut() {
+        ctx.get_mut()
+            .add_texture_loader(image_loader.loader.clone());
+    }
+
+    let (requests, waiting, forgotten) = {
+        let mut state = image_loader.loader.state.lock().unwrap();
+        (
+            std::mem::take(&mut state.requests),
+            std::mem::take(&mut state.waiting),
+            std::mem::take(&mut state.forgotten),
+        )
+    };
+    for uri in forgotten {
+        if let Some(image) = image_loader.images.remove(&uri) {
+            user_textures.remove_image(&image.handle);
+        }
+    }
+    for request in requests {
+        let handle = match request.bytes {
+            Some(bytes) => match decode_image(&bytes) {
+                Ok(image) => images.add(image),
+                Err(err) => {
+                    log::warn!("Failed to decode the Egui image {:?}: {err}", request.uri);
+                    image_loader
+                        .loader
+                        .set_state(&request.uri, TextureState::Failed(err.to_string()));
+                    request.ctx.request_repaint();
+                    continue;
+                }
+            },
+            None => asset_server.load(asset_path(&request.uri).to_owned()),
+        };
+        image_loader.images.insert(
+            request.uri,
+            LoadingImage {
+                handle,
+                texture_options: request.texture_options,
+                contexts: vec![request.ctx],
+            },
+        );
+    }
+
+    for (uri, ctx) in waiting {
+        if let Some(image) = image_loader.images.get_mut(&uri) {
+            if !image.contexts.contains(&ctx) {
+                image.contexts.push(ctx);
+            }
+        }
+    }
+
+    // Reloaded images may change in size.
+    let mut modified = Vec::new();
+    for event in image_events.read() {
+        if let AssetEvent::Modified { id } = event {
+            modified.push(*id);
+        }
+    }
+
+    for (uri, image) in &mut image_loader.images {
+        let is_modified = modified.contains(&image.handle.id());
+        if image.contexts.is_empty() && !is_modified {
+            continue;
+        }
+        let state = if let Some(loaded) = images.get(&image.handle) {
+            let id = user_textures.add_image_with_sampler(
+                image.handle.clone(),
+                texture_options_as_sampler_descriptor(&image.texture_options),
+            );
+            let size = loaded.size_f32();
+            TextureState::Ready(SizedTexture::new(id, egui::vec2(size.x, size.y)))
+        } else if let Some(LoadState::Failed(err)) = asset_server.get_load_state(&image.handle) {
+            log::warn!("Failed to load the Egui image {uri:?}: {err}");
+            TextureState::Failed(err.to_string())
+        } else {
+            continue;
+        };
+        image_loader.loader.set_state(uri, state);
+        for ctx in image.contexts.drain(..) {
+            ctx.request_repaint();
+        }
+    }
+}
+
+impl EguiBevyTextureLoader {
+    fn set_state(&self, uri: &str, texture_state: TextureState) {
+        let mut state = self.state.lock().unwrap();
+        // The URI may have been forgotten in the meantime.
+        if let Some(current) = state.textures.get_mut(uri) {
+            *current = texture_state;
+        }
+    }
+}
+
+/// Maps a URI to an asset path, a `file://` URI is a path on the file system.
+fn asset_path(uri: &str) -> &str {
+    uri.strip_prefix("file://").unwrap_or(uri)
+}
+
+fn is_http(uri: &str) -> bool {
+    uri.starts_with("http://") || uri.starts_with("https://")
+}
+
+/// Is `true` for URIs of SVG images, which are left to the loaders of `egui_extras`.
+fn is_svg(uri: &str) -> bool {
+    let path = uri.split(['?', '#']).next().unwrap_or(uri);
+    path.rsplit_once('.')
+        .is_some_and(|(_, extension)| extension.eq_ignore_ascii_case("svg"))
+}
+
+fn decode_image(bytes: &[u8]) -> Result<Image, image::ImageError> {
+    let image = image::load_from_memory(bytes)?.into_rgba8();
+    Ok(Image::new(
+        Extent3d {
+            width: image.width(),
+            height: image.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        image.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContexts};
+    use bevy::prelude::Local;
+    use egui::load::TextureLoader;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn png_image(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_image_loader() {
+        fn ui_system(mut contexts: EguiContexts, mut image: Local<Vec<u8>>) {
+            if image.is_empty() {
+                *image = png_image(3, 2);
+            }
+            let ctx = contexts.ctx_mut();
+            ctx.include_bytes("bytes://test.png", image.clone());
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.image("bytes://test.png");
+            });
+        }
+
+        let (mut app, window) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        app.update();
+        app.update();
+        app.update();
+
+        let handle = app
+            .world()
+            .resource::<EguiImageLoader>()
+            .image("bytes://test.png")
+            .cloned()
+            .unwrap();
+        let texture_id = app
+            .world()
+            .resource::<EguiUserTextures>()
+            .image_id(&handle)
+            .unwrap();
+        let ctx = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .into_inner()
+            .get_mut()
+            .clone();
+        let texture = ctx
+            .try_load_texture(
+                "bytes://test.png",
+                egui::TextureOptions::default(),
+                egui::SizeHint::default(),
+            )
+            .unwrap();
+        let egui::load::TexturePoll::Ready { texture } = texture else {
+            panic!("the texture isn't loaded");
+        };
+        assert_eq!(texture.id, texture_id);
+        assert_eq!(texture.size, egui::vec2(3.0, 2.0));
+
+        // Forgetting the image frees its texture.
+        ctx.forget_image("bytes://test.png");
+        app.update();
+        assert!(app
+            .world()
+            .resource::<EguiUserTextures>()
+            .image_id(&handle)
+            .is_none());
+    }
+
+    #[cfg(feature = "http_loader")]
+    #[test]
+    fn test_http_image_is_decoded_from_loaded_bytes() {
+        use bevy::asset::io::memory::{Dir, MemoryAssetReader};
+
+        let (mut app, window) = test_app();
+        app.update();
+        let https = Dir::default();
+        https.insert_asset(
+            std::path::Path::new("example.com/logo.png"),
+            png_image(4, 3),
+        );
+        app.world()
+            .resource::<crate::http_loader::EguiHttpLoader>()
+            .loader()
+            .set_reader("https", MemoryAssetReader { root: https });
+        let loader = app.world().resource::<EguiImageLoader>().loader().clone();
+        let ctx = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .into_inner()
+            .get_mut()
+            .clone();
+
+        let mut texture = None;
+        for _ in 0..100 {
+            let poll = loader
+                .load(
+                    &ctx,
+                    "https://example.com/logo.png",
+                    egui::TextureOptions::default(),
+                    egui::SizeHint::default(),
+                )
+                .unwrap();
+            if let TexturePoll::Ready { texture: ready } = poll {
+                texture = Some(ready);
+                break;
+            }
+            app.update();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(texture.unwrap().size, egui::vec2(4.0, 3.0));
+        // The image is decoded from the bytes instead of being loaded as an asset path.
+        let handle = app
+            .world()
+            .resource::<EguiImageLoader>()
+            .image("https://example.com/logo.png")
+            .unwrap();
+        assert!(handle.path().is_none());
+    }
+
+    #[test]
+    fn test_svg_is_left_to_other_loaders() {
+        let loader = EguiBevyTextureLoader::default();
+        let ctx = egui::Context::default();
+        for uri in [
+            "icons/save.svg",
+            "bytes://icon.SVG",
+            "https://example.com/logo.svg?v=2",
+        ] {
+            assert!(matches!(
+                loader.load(
+                    &ctx,
+                    uri,
+                    egui::TextureOptions::default(),
+                    egui::SizeHint::default()
+                ),
+                Err(LoadError::NotSupported)
+            ));
+        }
+        assert!(loader.state.lock().unwrap().requests.is_empty());
+        assert!(!is_svg("textures/svg.png"));
+    }
+
+    #[test]
+    fn test_pending_image_repaints_every_context() {
+        let (mut app, window) = test_app();
+        app.update();
+        let loader = app.world().resource::<EguiImageLoader>().loader().clone();
+        let ctx = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .into_inner()
+            .get_mut()
+            .clone();
+        ctx.include_bytes("bytes://test.png", png_image(1, 1));
+        let other_ctx = egui::Context::default();
+        let repainted = Arc::new(AtomicBool::new(false));
+        let repainted_clone = repainted.clone();
+        other_ctx.set_request_repaint_callback(move |_| {
+            repainted_clone.store(true, Ordering::SeqCst);
+        });
+
+        // The second request finds the image pending.
+        for ctx in [&ctx, &other_ctx] {
+            let poll = loader
+                .load(
+                    ctx,
+                    "bytes://test.png",
+                    egui::TextureOptions::default(),
+                    egui::SizeHint::default(),
+                )
+                .unwrap();
+            assert!(matches!(poll, TexturePoll::Pending { .. }));
+        }
+        app.update();
+
+        assert!(repainted.load(Ordering::SeqCst));
+        let poll = loader
+            .load(
+                &other_ctx,
+                "bytes://test.png",
+                egui::TextureOptions::default(),
+                egui::SizeHint::default(),
+            )
+            .unwrap();
+        assert!(matches!(poll, TexturePoll::Ready { .. }));
+    }
+}
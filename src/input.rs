@@ -0,0 +1,738 @@
+use bevy::prelude::{Component, Deref, DerefMut, Entity, Res, Resource};
+#[cfg(feature = "render")]
+use bevy::{
+    ecs::{
+        query::QueryState,
+        reflect::{AppTypeRegistry, ReflectComponent},
+        world::{FilteredEntityRef, World},
+    },
+    prelude::{Local, QueryBuilder, With},
+    reflect::ReflectRef,
+};
+
+/// Stores whether Egui wants pointer or keyboard input, aggregated over all contexts.
+///
+/// The resource is updated during the [`EguiSet::EndPass`](crate::EguiSet::EndPass) system, so
+/// during the next frame it describes the UI the user currently sees. Use it (or the
+/// [`egui_wants_any_pointer_input`], [`egui_wants_any_keyboard_input`] and [`egui_wants_any_input`]
+/// run conditions) to keep Bevy UI, a picking backend or camera controls from reacting to input
+/// meant for Egui.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::egui_wants_any_pointer_input;
+///
+/// # fn pick_system() {}
+/// # let mut app = App::new();
+/// app.add_systems(Update, pick_system.run_if(not(egui_wants_any_pointer_input)));
+/// ```
+///
+/// Bevy 0.14 doesn't have a picking plugin of its own, so this crate doesn't integrate with one.
+/// `bevy_mod_picking` ships an Egui backend (its `backend_egui` feature) that keeps entities under
+/// Egui areas from being picked; with other backends, gate their systems on the run conditions
+/// above.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EguiWantsInput {
+    /// Is `true` if the pointer is over an Egui area (see
+    /// [`EguiContext::hit_test`](crate::EguiContext::hit_test)) or an Egui widget is being dragged.
+    pub wants_pointer_input: bool,
+    /// Is `true` if an Egui widget (a text edit, for example) has keyboard focus.
+    pub wants_keyboard_input: bool,
+    /// Is `true` if the pointer is over any Egui area (see [`egui::Context::is_pointer_over_area`]).
+    ///
+    /// Unlike [`EguiWantsInput::wants_pointer_input`], this includes the empty space of panels
+    /// and doesn't include drags that left the UI.
+    pub is_pointer_over_area: bool,
+}
+
+/// A run condition that returns `true` if any Egui context wants pointer input.
+///
+/// See [`EguiWantsInput::wants_pointer_input`].
+pub fn egui_wants_any_pointer_input(egui_wants_input: Res<EguiWantsInput>) -> bool {
+    egui_wants_input.wants_pointer_input
+}
+
+/// A run condition that returns `true` if any Egui context wants keyboard input.
+///
+/// See [`EguiWantsInput::wants_keyboard_input`].
+pub fn egui_wants_any_keyboard_input(egui_wants_input: Res<EguiWantsInput>) -> bool {
+    egui_wants_input.wants_keyboard_input
+}
+
+/// A run condition that returns `true` if any Egui context wants pointer or keyboard input.
+pub fn egui_wants_any_input(egui_wants_input: Res<EguiWantsInput>) -> bool {
+    egui_wants_input.wants_pointer_input || egui_wants_input.wants_keyboard_input
+}
+
+/// Keeps pointer input from reaching Egui while set to `true`.
+///
+/// This is the other direction of [`EguiWantsInput`]: set it before the
+/// [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system when Bevy UI or a picking backend
+/// captures the pointer, so clicks on geometry in front of an Egui window don't reach the window.
+/// While blocked, mouse buttons, scrolling and touches are ignored and cursor movement is passed as
+/// [`egui::Event::PointerGone`], so widgets stop being hovered. The resource isn't reset
+/// automatically.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct EguiBlockPointerInput(pub bool);
+
+/// Regions of a context where pointer input passes through Egui to the app, even if an Egui area
+/// (a full-screen HUD [`egui::Area`], for example) covers them.
+///
+/// Insert the component into a window entity and update the regions as the UI changes. Regions are in Egui points, so rects of Egui responses can be
+/// used as is. While the pointer is over a region, it's passed to Egui as
+/// [`egui::Event::PointerGone`], and presses, scrolling and touches that start there are ignored
+/// (which also keeps [`EguiWantsInput`] from reporting them). Drags that Egui has already started
+/// keep working inside the regions.
+///
+/// ```rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::{egui, EguiContexts, EguiPassthroughRegion, EguiPointerPassthrough};
+///
+/// fn hud_system(
+///     mut contexts: EguiContexts,
+///     mut passthrough: Query<&mut EguiPointerPassthrough, With<PrimaryWindow>>,
+/// ) {
+///     egui::CentralPanel::default()
+///         .frame(egui::Frame::none())
+///         .show(contexts.ctx_mut(), |ui| {
+///             ui.label("Score: 42");
+///             // Clicks on the rest of the screen go to the game.
+///             if let Ok(mut passthrough) = passthrough.get_single_mut() {
+///                 passthrough.regions = vec![EguiPassthroughRegion::Rect(
+///                     ui.available_rect_before_wrap(),
+///                 )];
+///             }
+///         });
+/// }
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct EguiPointerPassthrough {
+    /// Regions the pointer passes through.
+    pub regions: Vec<EguiPassthroughRegion>,
+}
+
+impl EguiPointerPassthrough {
+    /// Returns `true` if the position (in Egui points) is inside any of the regions.
+    #[must_use]
+    pub fn contains(&self, pos: egui::Pos2) -> bool {
+        self.regions.iter().any(|region| region.contains(pos))
+    }
+}
+
+/// A region of [`EguiPointerPassthrough`], in Egui points.
+#[derive(Clone)]
+pub enum EguiPassthroughRegion {
+    /// A rectangle.
+    Rect(egui::Rect),
+    /// A circle.
+    Circle {
+        /// Center of the circle.
+        center: egui::Pos2,
+        /// Radius of the circle.
+        radius: f32,
+    },
+    /// A custom shape, the function returns `true` for positions inside it.
+    Shape(std::sync::Arc<dyn Fn(egui::Pos2) -> bool + Send + Sync>),
+}
+
+impl EguiPassthroughRegion {
+    /// Returns `true` if the position is inside the region.
+    #[must_use]
+    pub fn contains(&self, pos: egui::Pos2) -> bool {
+        match self {
+            Self::Rect(rect) => rect.contains(pos),
+            Self::Circle { center, radius } => center.distance_sq(pos) <= radius * radius,
+            Self::Shape(contains) => contains(pos),
+        }
+    }
+}
+
+impl std::fmt::Debug for EguiPassthroughRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rect(rect) => f.debug_tuple("Rect").field(rect).finish(),
+            Self::Circle { center, radius } => f
+                .debug_struct("Circle")
+                .field("center", center)
+                .field("radius", radius)
+                .finish(),
+            Self::Shape(_) => f.write_str("Shape(..)"),
+        }
+    }
+}
+
+/// Type of predicates registered in [`EguiInputFilter`].
+pub type EguiInputPredicate = Box<dyn Fn(Entity, &egui::Event) -> bool + Send + Sync>;
+
+/// A resource for suppressing categories of input events without disabling Egui entirely.
+///
+/// The filter is applied to the events of every window context at the end of the
+/// [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system, after Bevy input has been
+/// translated into Egui events. Events written to [`EguiInput`](crate::EguiInput) by systems that
+/// run later, or added by [`EguiInputHooks`](crate::EguiInputHooks), aren't filtered. Suppressing
+/// the pointer keeps [`egui::Event::PointerGone`], so widgets don't stay hovered; use
+/// [`EguiBlockPointerInput`] to make Egui forget the pointer altogether.
+///
+/// Categories can be suppressed independently, for example, when the game handles scrolling
+/// itself, or to ignore the keyboard while a cutscene plays:
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, EguiInputFilter};
+///
+/// #[derive(Resource)]
+/// struct Cutscene {
+///     playing: bool,
+/// }
+///
+/// fn setup_input_filter(mut input_filter: ResMut<EguiInputFilter>) {
+///     // The mouse wheel zooms the camera.
+///     input_filter.suppress_scroll = true;
+///     // Events of other kinds can be suppressed with predicates.
+///     input_filter.add_predicate(|_window, event| !matches!(event, egui::Event::Zoom(_)));
+/// }
+///
+/// fn update_input_filter(mut input_filter: ResMut<EguiInputFilter>, cutscene: Res<Cutscene>) {
+///     input_filter.suppress_keyboard = cutscene.playing;
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct EguiInputFilter {
+    /// Suppresses cursor movement and mouse button events ([`egui::Event::PointerMoved`] and
+    /// [`egui::Event::PointerButton`]), including the ones emulated for touches.
+    pub suppress_pointer: bool,
+    /// Suppresses scrolling and zooming ([`egui::Event::MouseWheel`] and [`egui::Event::Zoom`]).
+    pub suppress_scroll: bool,
+    /// Suppresses keys, text input, IME and clipboard events ([`egui::Event::Key`],
+    /// [`egui::Event::Text`], [`egui::Event::Ime`], [`egui::Event::Copy`], [`egui::Event::Cut`]
+    /// and [`egui::Event::Paste`]). Modifier keys are still set on the input.
+    pub suppress_keyboard: bool,
+    /// Suppresses raw touch events ([`egui::Event::Touch`]).
+    pub suppress_touch: bool,
+    predicates: Vec<EguiInputPredicate>,
+}
+
+impl EguiInputFilter {
+    /// Registers a predicate that receives the window entity and an event, and returns `false`
+    /// to suppress the event.
+    pub fn add_predicate(
+        &mut self,
+        predicate: impl Fn(Entity, &egui::Event) -> bool + Send + Sync + 'static,
+    ) {
+        self.predicates.push(Box::new(predicate));
+    }
+
+    /// Removes all the registered predicates.
+    pub fn clear_predicates(&mut self) {
+        self.predicates.clear();
+    }
+
+    /// Returns `true` if an event of a window passes the filter.
+    pub fn allows(&self, window: Entity, event: &egui::Event) -> bool {
+        let suppressed = match event {
+            egui::Event::PointerMoved(_) | egui::Event::PointerButton { .. } => {
+                self.suppress_pointer
+            }
+            egui::Event::MouseWheel { .. } | egui::Event::Zoom(_) => self.suppress_scroll,
+            egui::Event::Key { .. }
+            | egui::Event::Text(_)
+            | egui::Event::Ime(_)
+            | egui::Event::Copy
+            | egui::Event::Cut
+            | egui::Event::Paste(_) => self.suppress_keyboard,
+            egui::Event::Touch { .. } => self.suppress_touch,
+            _ => false,
+        };
+        !suppressed
+            && self
+                .predicates
+                .iter()
+                .all(|predicate| predicate(window, event))
+    }
+}
+
+/// A resource that enables navigating Egui widgets with gamepads.
+///
+/// If the resource is inserted, gamepad input is translated into the key events Egui uses for
+/// keyboard navigation, which are sent to the focused window during the
+/// [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system:
+///
+/// - D-pad and left stick: arrow keys, which move the focus to the closest widget in that
+///   direction. If no widget has focus yet, the first (or last, for up and left) widget is
+///   focused instead.
+/// - [`activate_button`](Self::activate_button): Enter, which clicks the focused widget.
+/// - [`cancel_button`](Self::cancel_button): Escape, which removes the focus.
+/// - Right and left bumpers ([`GamepadButtonType::RightTrigger`] and
+///   [`GamepadButtonType::LeftTrigger`]): Tab and Shift+Tab.
+///
+/// Holding a direction repeats it. The events go through [`EguiInputFilter`] like keyboard events.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::EguiGamepadNavigation;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.insert_resource(EguiGamepadNavigation {
+///         // Nintendo's layout.
+///         activate_button: GamepadButtonType::East,
+///         cancel_button: GamepadButtonType::South,
+///         ..default()
+///     });
+/// }
+/// ```
+///
+/// [`GamepadButtonType::RightTrigger`]: bevy::input::gamepad::GamepadButtonType::RightTrigger
+/// [`GamepadButtonType::LeftTrigger`]: bevy::input::gamepad::GamepadButtonType::LeftTrigger
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct EguiGamepadNavigation {
+    /// The button that clicks the focused widget ([`GamepadButtonType::South`] by default).
+    ///
+    /// [`GamepadButtonType::South`]: bevy::input::gamepad::GamepadButtonType::South
+    pub activate_button: bevy::input::gamepad::GamepadButtonType,
+    /// The button that removes the focus ([`GamepadButtonType::East`] by default).
+    ///
+    /// [`GamepadButtonType::East`]: bevy::input::gamepad::GamepadButtonType::East
+    pub cancel_button: bevy::input::gamepad::GamepadButtonType,
+    /// How far the left stick has to be tilted to move the focus, from `0.0` to `1.0`
+    /// (`0.5` by default).
+    pub stick_threshold: f32,
+    /// Seconds a direction has to be held before it starts repeating (`0.4` by default).
+    pub repeat_delay: f32,
+    /// Seconds between repeats of a held direction (`0.1` by default).
+    pub repeat_interval: f32,
+}
+
+impl Default for EguiGamepadNavigation {
+    fn default() -> Self {
+        Self {
+            activate_button: bevy::input::gamepad::GamepadButtonType::South,
+            cancel_button: bevy::input::gamepad::GamepadButtonType::East,
+            stick_threshold: 0.5,
+            repeat_delay: 0.4,
+            repeat_interval: 0.1,
+        }
+    }
+}
+
+/// Keeps pointer input from reaching Egui while a `bevy_ui` node is hovered or pressed, if an
+/// [`EguiCameraContext`](crate::EguiCameraContext) is drawn below the nodes of its camera (see
+/// [`EguiContextSettings::draw_below_bevy_ui`](crate::EguiContextSettings::draw_below_bevy_ui)).
+///
+/// Sets [`EguiBlockPointerInput`] when the nodes start or stop being interacted with, so it can
+/// still be set by the app in between. The `Interaction` components of the nodes are looked up in
+/// the [`AppTypeRegistry`], as `bevy_ui` isn't a dependency of this crate. The system runs in
+/// [`Last`](bevy::app::Last), after `bevy_ui` has updated them, so pointer input is blocked from
+/// the next frame.
+#[cfg(feature = "render")]
+pub fn block_pointer_input_under_bevy_ui_system(
+    world: &mut World,
+    mut contexts: Local<QueryState<&crate::EguiContextSettings, With<crate::EguiCameraContext>>>,
+    mut blocked: Local<bool>,
+) {
+    let is_any_context_below_bevy_ui = contexts.iter(world).any(|context_settings| {
+        !context_settings.display_only
+            && context_settings
+                .resolved_camera_pass()
+                .is_some_and(crate::egui_node::EguiCameraPass::is_below_bevy_ui)
+    });
+    let block = is_any_context_below_bevy_ui && is_any_bevy_ui_node_interacted(world);
+    if block != *blocked {
+        *blocked = block;
+        world.resource_mut::<EguiBlockPointerInput>().0 = block;
+    }
+}
+
+/// Whether the `Interaction` of any `bevy_ui` node isn't `None`. Is `false` without `bevy_ui`.
+#[cfg(feature = "render")]
+fn is_any_bevy_ui_node_interacted(world: &mut World) -> bool {
+    let Some(type_registry) = world.get_resource::<AppTypeRegistry>().cloned() else {
+        return false;
+    };
+    let type_registry = type_registry.read();
+    let Some((registration, reflect_component)) = type_registry
+        .get_with_type_path("bevy_ui::focus::Interaction")
+        .and_then(|registration| Some((registration, registration.data::<ReflectComponent>()?)))
+    else {
+        return false;
+    };
+    let Some(component_id) = world.components().get_id(registration.type_id()) else {
+        return false;
+    };
+    let mut interactions = QueryBuilder::<FilteredEntityRef>::new(world)
+        .ref_id(component_id)
+        .build();
+    interactions.iter(world).any(|entity| {
+        reflect_component
+            .reflect(entity)
+            .is_some_and(|interaction| match interaction.reflect_ref() {
+                ReflectRef::Enum(interaction) => interaction.variant_name() != "None",
+                _ => false,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{capture_input_events, test_app},
+        EguiContextSettings, EguiContexts,
+    };
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_input_filter() {
+        use bevy::input::{
+            keyboard::{Key, KeyCode, KeyboardInput},
+            mouse::{MouseScrollUnit, MouseWheel},
+            ButtonState,
+        };
+
+        let (mut app, window) = test_app();
+        let events = capture_input_events(&mut app);
+        app.update();
+
+        // Returns whether scroll and key events reached Egui.
+        let scroll_and_type = |app: &mut App| {
+            events.lock().unwrap().clear();
+            app.world_mut().send_event(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: 1.0,
+                window,
+            });
+            app.world_mut().send_event(KeyboardInput {
+                key_code: KeyCode::KeyA,
+                logical_key: Key::Character("a".into()),
+                state: ButtonState::Pressed,
+                window,
+            });
+            app.update();
+            let events = events.lock().unwrap();
+            (
+                events
+                    .iter()
+                    .any(|(_, event)| matches!(event, egui::Event::MouseWheel { .. })),
+                events
+                    .iter()
+                    .any(|(_, event)| matches!(event, egui::Event::Key { .. })),
+            )
+        };
+
+        assert_eq!(scroll_and_type(&mut app), (true, true));
+
+        app.world_mut()
+            .resource_mut::<EguiInputFilter>()
+            .suppress_scroll = true;
+        assert_eq!(scroll_and_type(&mut app), (false, true));
+
+        let mut input_filter = app.world_mut().resource_mut::<EguiInputFilter>();
+        input_filter.suppress_scroll = false;
+        input_filter.suppress_keyboard = true;
+        assert_eq!(scroll_and_type(&mut app), (true, false));
+
+        let mut input_filter = app.world_mut().resource_mut::<EguiInputFilter>();
+        input_filter.suppress_keyboard = false;
+        input_filter
+            .add_predicate(|_window, event| !matches!(event, egui::Event::MouseWheel { .. }));
+        assert_eq!(scroll_and_type(&mut app), (false, true));
+    }
+
+    #[test]
+    fn test_egui_wants_pointer_input_blocks_picking() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonInput, ButtonState,
+            },
+            prelude::{not, ResMut, Resource},
+            window::CursorMoved,
+        };
+
+        #[derive(Resource, Default)]
+        struct Clicks {
+            egui: u32,
+            picking: u32,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, mut clicks: ResMut<Clicks>) {
+            egui::Area::new("area".into())
+                .fixed_pos(egui::pos2(100.0, 100.0))
+                .show(contexts.ctx_mut(), |ui| {
+                    // Sized explicitly, as the size of the text depends on the fonts.
+                    if ui
+                        .add_sized([100.0, 40.0], egui::Button::new("Button"))
+                        .clicked()
+                    {
+                        clicks.egui += 1;
+                    }
+                });
+        }
+
+        // Stands in for a picking backend reacting to clicks on the geometry behind the UI.
+        fn picking_system(mouse_input: Res<ButtonInput<MouseButton>>, mut clicks: ResMut<Clicks>) {
+            if mouse_input.just_pressed(MouseButton::Left) {
+                clicks.picking += 1;
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Clicks>().add_systems(
+            bevy::app::Update,
+            (
+                ui_system,
+                picking_system.run_if(not(egui_wants_any_pointer_input)),
+            ),
+        );
+        app.update();
+
+        let click_at = |app: &mut App, position: bevy::math::Vec2| {
+            app.world_mut().send_event(CursorMoved {
+                window,
+                position,
+                delta: None,
+            });
+            app.update();
+            for state in [ButtonState::Pressed, ButtonState::Released] {
+                app.world_mut().send_event(MouseButtonInput {
+                    button: MouseButton::Left,
+                    state,
+                    window,
+                });
+                app.update();
+            }
+        };
+
+        click_at(&mut app, bevy::math::Vec2::new(110.0, 105.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.picking), (1, 0));
+        assert!(
+            app.world()
+                .resource::<EguiWantsInput>()
+                .is_pointer_over_area
+        );
+
+        click_at(&mut app, bevy::math::Vec2::new(500.0, 400.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.picking), (1, 1));
+
+        // Egui ignores the pointer while it's blocked.
+        app.world_mut().resource_mut::<EguiBlockPointerInput>().0 = true;
+        click_at(&mut app, bevy::math::Vec2::new(110.0, 105.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.picking), (1, 2));
+
+        // Display-only contexts don't receive any input and never want it.
+        app.world_mut().resource_mut::<EguiBlockPointerInput>().0 = false;
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .display_only = true;
+        click_at(&mut app, bevy::math::Vec2::new(110.0, 105.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.picking), (1, 3));
+        assert_eq!(
+            *app.world().resource::<EguiWantsInput>(),
+            EguiWantsInput::default()
+        );
+    }
+
+    #[test]
+    fn test_pointer_passthrough() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonInput, ButtonState,
+            },
+            prelude::{not, ResMut, Resource},
+            window::CursorMoved,
+        };
+
+        #[derive(Resource, Default)]
+        struct Clicks {
+            egui: u32,
+            game: u32,
+        }
+
+        // A full-screen HUD that would capture every click without the pass-through region.
+        fn hud_system(mut contexts: EguiContexts, mut clicks: ResMut<Clicks>) {
+            let ctx = contexts.ctx_mut();
+            let screen_rect = ctx.screen_rect();
+            egui::Area::new("hud".into())
+                .fixed_pos(screen_rect.min)
+                .show(ctx, |ui| {
+                    if ui
+                        .allocate_rect(screen_rect, egui::Sense::click())
+                        .clicked()
+                    {
+                        clicks.egui += 1;
+                    }
+                });
+        }
+
+        fn game_system(mouse_input: Res<ButtonInput<MouseButton>>, mut clicks: ResMut<Clicks>) {
+            if mouse_input.just_pressed(MouseButton::Left) {
+                clicks.game += 1;
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Clicks>().add_systems(
+            bevy::app::Update,
+            (
+                hud_system,
+                game_system.run_if(not(egui_wants_any_pointer_input)),
+            ),
+        );
+        app.world_mut()
+            .entity_mut(window)
+            .insert(EguiPointerPassthrough {
+                regions: vec![
+                    EguiPassthroughRegion::Rect(egui::Rect::from_min_size(
+                        egui::pos2(400.0, 0.0),
+                        egui::vec2(100.0, 100.0),
+                    )),
+                    EguiPassthroughRegion::Circle {
+                        center: egui::pos2(100.0, 400.0),
+                        radius: 50.0,
+                    },
+                ],
+            });
+        app.update();
+
+        let click_at = |app: &mut App, position: bevy::math::Vec2| {
+            app.world_mut().send_event(CursorMoved {
+                window,
+                position,
+                delta: None,
+            });
+            app.update();
+            for state in [ButtonState::Pressed, ButtonState::Released] {
+                app.world_mut().send_event(MouseButtonInput {
+                    button: MouseButton::Left,
+                    state,
+                    window,
+                });
+                app.update();
+            }
+        };
+
+        click_at(&mut app, bevy::math::Vec2::new(300.0, 300.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.game), (1, 0));
+
+        click_at(&mut app, bevy::math::Vec2::new(450.0, 50.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.game), (1, 1));
+        assert!(
+            !app.world()
+                .resource::<EguiWantsInput>()
+                .is_pointer_over_area
+        );
+
+        click_at(&mut app, bevy::math::Vec2::new(120.0, 420.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.game), (1, 2));
+
+        click_at(&mut app, bevy::math::Vec2::new(300.0, 300.0));
+        let clicks = app.world().resource::<Clicks>();
+        assert_eq!((clicks.egui, clicks.game), (2, 2));
+    }
+
+    #[test]
+    fn test_gamepad_navigation() {
+        use bevy::{
+            input::gamepad::{
+                Gamepad, GamepadButtonChangedEvent, GamepadButtonType, GamepadConnection,
+                GamepadConnectionEvent, GamepadEvent, GamepadInfo,
+            },
+            prelude::{ResMut, Resource},
+        };
+
+        #[derive(Resource, Default)]
+        struct Clicks([u32; 2]);
+
+        fn ui_system(mut contexts: EguiContexts, mut clicks: ResMut<Clicks>) {
+            egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+                for clicks in &mut clicks.0 {
+                    if ui.button("Click").clicked() {
+                        *clicks += 1;
+                    }
+                }
+            });
+        }
+
+        let (mut app, _) = test_app();
+        app.init_resource::<Clicks>()
+            .insert_resource(EguiGamepadNavigation::default())
+            .add_systems(bevy::app::Update, ui_system);
+        let gamepad = Gamepad::new(0);
+        app.world_mut()
+            .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+                gamepad,
+                GamepadConnection::Connected(GamepadInfo {
+                    name: "Gamepad".to_owned(),
+                }),
+            )));
+        app.update();
+
+        let mut tap =
+            |button_type| {
+                for value in [1.0, 0.0] {
+                    app.world_mut().send_event(GamepadEvent::Button(
+                        GamepadButtonChangedEvent::new(gamepad, button_type, value),
+                    ));
+                    app.update();
+                }
+            };
+        // The first press focuses the first button, the second one moves to the next.
+        tap(GamepadButtonType::DPadDown);
+        tap(GamepadButtonType::DPadDown);
+        tap(GamepadButtonType::South);
+        assert_eq!(app.world().resource::<Clicks>().0, [0, 1]);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_bevy_ui_blocks_pointer_input() {
+        use crate::EguiCameraContext;
+
+        // Stands in for the component of `bevy_ui`, which is looked up by its type path.
+        #[derive(Component, Reflect, Clone, Copy, PartialEq)]
+        #[reflect(Component)]
+        #[type_path = "bevy_ui::focus"]
+        enum Interaction {
+            Pressed,
+            Hovered,
+            None,
+        }
+
+        let (mut app, _) = test_app();
+        app.register_type::<Interaction>();
+        let node = app.world_mut().spawn(Interaction::None).id();
+        let camera = app
+            .world_mut()
+            .spawn((EguiCameraContext, EguiContextSettings::default()))
+            .id();
+        let set_interaction = |app: &mut App, interaction| {
+            *app.world_mut().get_mut::<Interaction>(node).unwrap() = interaction;
+            app.update();
+            app.world().resource::<EguiBlockPointerInput>().0
+        };
+
+        // Egui is drawn above the nodes.
+        assert!(!set_interaction(&mut app, Interaction::Hovered));
+
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(camera)
+            .unwrap()
+            .draw_below_bevy_ui = true;
+        assert!(set_interaction(&mut app, Interaction::Hovered));
+        assert!(set_interaction(&mut app, Interaction::Pressed));
+        assert!(!set_interaction(&mut app, Interaction::None));
+
+        // The app can still set the resource while the nodes aren't interacted with.
+        app.world_mut().resource_mut::<EguiBlockPointerInput>().0 = true;
+        assert!(set_interaction(&mut app, Interaction::None));
+    }
+}
@@ -8,13 +8,39 @@
 //!
 //! **Features:**
 //! - Desktop and web platforms support
-//! - Clipboard (web support is limited to the same window, see [rust-windowing/winit#1829](https://github.com/rust-windowing/winit/issues/1829))
-//! - Opening URLs
+//! - Clipboard (`EguiClipboard`), with an in-app fallback when the system clipboard is unavailable (e.g. headless Wayland or X11 without a display)
+//! - Opening URLs of `ui.hyperlink` and other links in the system browser (`open_url` feature), in a new tab if `OpenUrl::new_tab` is set on web
+//! - Drag-and-drop of files (`egui::RawInput::hovered_files` and `dropped_files`), with the file contents on web
+//! - Touch input, with optional pinch zoom and two-finger scrolling (`EguiSettings::synthesize_touch_gestures`)
 //! - Multiple windows support (see [./examples/two_windows.rs](https://github.com/mvlabat/bevy_egui/blob/v0.20.1/examples/two_windows.rs))
+//! - Deferred Egui viewports in native windows, if `EguiContextSettings::embed_viewports` is disabled (see [./examples/viewports.rs](https://github.com/mvlabat/bevy_egui/blob/v0.20.1/examples/viewports.rs)).
+//!   Immediate viewports (`egui::Context::show_viewport_immediate`) are always embedded into the window that shows them
+//! - Paint callbacks for custom rendering inside Egui UIs (see [./examples/paint_callback.rs](https://github.com/mvlabat/bevy_egui/blob/v0.20.1/examples/paint_callback.rs))
+//! - Multisampled rendering of contexts (`EguiContextSettings::msaa_samples`), for paint callbacks with MSAA pipelines
+//! - Several contexts per window (`EguiSecondaryContext`), e.g. a debug overlay with its own memory and style, drawn and routed input in order
+//! - Per-camera contexts drawn into camera viewports (`EguiCameraContext`), for split-screen HUDs, optionally in a chosen pass of the camera's render graph, such as before bloom or tonemapping in HDR (`EguiContextSettings::camera_pass`) or below `bevy_ui` (`EguiContextSettings::draw_below_bevy_ui`), or depth-tested against the 3D scene (`EguiContextSettings::depth_test_distance`)
+//! - Pointer pass-through regions (`EguiPointerPassthrough`), so that full-screen HUD areas don't take clicks from the game
+//! - Hooks called with every context at the start and at the end of its pass (`EguiPassHooks`), for plugins that layer UI onto all contexts, such as inspectors or notification toasts
+//! - Bevy events for notable Egui output: widget interactions (`EguiInteractionEvent`), copied text (`EguiCopyEvent`), opened URLs (`EguiOpenUrlEvent`), text cursor moves (`EguiTextCursorMoved`) and repaint requests (`EguiWantsRepaint`), e.g. for UI sounds or telemetry
+//! - Animated user textures (`EguiUserTextures::add_animated_image`), which cycle through a sequence of Bevy images, e.g. for spinners or animated emotes
+//! - Captures of the Egui output alone, with transparency (`EguiScreenshot`), for visual regression tests or sharing UI layouts
 //!
-//! `bevy_egui` can be compiled with using only `bevy` and `egui` as dependencies: `manage_clipboard` and `open_url` features,
+//! `bevy_egui` can be compiled with using only `bevy`, `egui` and `bytemuck` as dependencies: `manage_clipboard` and `open_url` features,
 //! that require additional crates, can be disabled.
 //!
+//! Egui only requests redraws for repaints it needs right away (while animating, for example), and
+//! UI that hasn't changed since the last frame isn't tessellated or uploaded to the GPU again, so with
+//! `WinitSettings::desktop_app()` an idle app sleeps until the next input event.
+//! Enable the `wake_on_repaint` feature if your app uses the reactive `WinitSettings` update modes:
+//! repaints requested by egui from other threads (when an image finishes loading, for example) and
+//! repaints requested with a delay (`egui::Context::request_repaint_after`, used by blinking text cursors
+//! and tooltips) will wake up the event loop instead of waiting for the next input event.
+//!
+//! ## Cargo features
+//!
+//! The list is generated from `Cargo.toml` with the `document-features` feature, which docs.rs enables.
+#![cfg_attr(feature = "document-features", doc = document_features::document_features!())]
+//!
 //! ## Usage
 //!
 //! Here's a minimal usage example:
@@ -27,8 +53,8 @@
 //!     App::new()
 //!         .add_plugins(DefaultPlugins)
 //!         .add_plugins(EguiPlugin)
-//!         // Systems that create Egui widgets should be run during the `CoreSet::Update` set,
-//!         // or after the `EguiSet::BeginFrame` system (which belongs to the `CoreSet::PreUpdate` set).
+//!         // Systems that create Egui widgets should be run during the `Update` schedule
+//!         // (the `EguiSet::UserUi` set), after the Egui pass begins in `PreUpdate`.
 //!         .add_systems(Update, ui_example_system)
 //!         .run();
 //! }
@@ -46,6 +72,69 @@
 //! cargo run --example ui
 //! ```
 //!
+//! ## Transparent windows
+//!
+//! Egui is painted with premultiplied alpha, so a transparent window needs
+//! [`Window::transparent`](bevy::window::Window::transparent) enabled together with a
+//! premultiplied [`CompositeAlphaMode`](bevy::window::CompositeAlphaMode) (`PreMultiplied`,
+//! or `PostMultiplied` on macOS, where Metal composites non-opaque layers as premultiplied).
+//! The window also has to be cleared with a transparent color: set [`ClearColor`](bevy::render::camera::ClearColor)
+//! to `Color::NONE` if a camera renders to the window, or [`EguiContextSettings::clear_color`] to
+//! [`egui::Color32::TRANSPARENT`] otherwise. Egui panels have opaque fills by default.
+//!
+//! See [examples/transparent_window.rs](https://github.com/mvlabat/bevy_egui/blob/v0.20.1/examples/transparent_window.rs).
+//!
+//! ## Layering with `bevy_ui`
+//!
+//! Window contexts are drawn after every camera has rendered, so they're always above `bevy_ui`
+//! nodes. Give `bevy_ui` the pointer when Egui doesn't want it by running its interaction
+//! systems (or the game's reactions to [`Interaction`](https://docs.rs/bevy/0.14/bevy/ui/enum.Interaction.html)
+//! changes) with `not(egui_wants_any_pointer_input)`, see [`egui_wants_any_pointer_input`].
+//!
+//! To draw Egui below `bevy_ui`, show it in an [`EguiCameraContext`] of the camera that renders
+//! the nodes with [`EguiContextSettings::draw_below_bevy_ui`] (or with
+//! [`EguiCameraPass::AfterBevyUi`](egui_node::EguiCameraPass::AfterBevyUi) to draw it above them
+//! in the camera's render graph). Pointer input then doesn't reach Egui while a node is hovered
+//! or pressed, so the node gets the click:
+//!
+//! ```no_run
+//! # // Camera contexts need the `render` feature.
+//! # #[cfg(feature = "render")]
+//! # {
+//! use bevy::prelude::*;
+//! use bevy_egui::{egui, EguiCameraContext, EguiContextSettings, EguiContexts, EguiPlugin};
+//!
+//! fn setup(mut commands: Commands) {
+//!     commands.spawn((
+//!         Camera2dBundle::default(),
+//!         EguiCameraContext,
+//!         EguiContextSettings {
+//!             draw_below_bevy_ui: true,
+//!             ..default()
+//!         },
+//!     ));
+//! }
+//!
+//! fn ui_system(mut contexts: EguiContexts, cameras: Query<Entity, With<EguiCameraContext>>) {
+//!     let Some(ctx) = cameras
+//!         .get_single()
+//!         .ok()
+//!         .and_then(|camera| contexts.try_ctx_for_window_mut(camera))
+//!     else {
+//!         return;
+//!     };
+//!     egui::CentralPanel::default().show(ctx, |ui| ui.label("Behind the HUD"));
+//! }
+//!
+//! App::new()
+//!     .add_plugins(DefaultPlugins)
+//!     .add_plugins(EguiPlugin)
+//!     .add_systems(Startup, setup)
+//!     .add_systems(Update, ui_system)
+//!     .run();
+//! # }
+//! ```
+//!
 //! ## See also
 //!
 //! - [`bevy-inspector-egui`](https://github.com/jakobhellermann/bevy-inspector-egui)
@@ -57,14 +146,58 @@
 ))]
 compile_error!(include_str!("../static/error_web_sys_unstable_apis.txt"));
 
+/// AccessKit integration for screen readers.
+#[cfg(feature = "accesskit")]
+pub mod accessibility;
+/// Mapping the cursor icons requested by Egui to the cursors of windows.
+pub mod cursor_icon;
 /// Egui render node.
 #[cfg(feature = "render")]
 pub mod egui_node;
+/// Events sent for Egui input and output.
+pub mod events;
+/// Loading Egui fonts with the Bevy asset server.
+pub mod fonts;
+/// Hooks called for the input and the passes of every context.
+pub mod hooks;
+/// Loading `http://` and `https://` URIs with Bevy asset readers.
+#[cfg(feature = "http_loader")]
+pub mod http_loader;
+/// Loading the images of Egui widgets with the Bevy asset server.
+#[cfg(feature = "image_loader")]
+pub mod image_loader;
+/// Deciding which input reaches Egui and which reaches the app.
+pub mod input;
+/// Saving and restoring the Egui memory between sessions.
+#[cfg(feature = "persistence")]
+pub mod persistence;
+/// Recording and playing back Egui input.
+#[cfg(feature = "recording")]
+pub mod recording;
 /// Plugin systems for the render app.
 #[cfg(feature = "render")]
 pub mod render_systems;
+/// Screenshots and captures of the Egui output.
+#[cfg(feature = "render")]
+pub mod screenshot;
 /// Plugin systems.
 pub mod systems;
+/// Headless apps with simulated input for testing Egui UIs.
+#[cfg(feature = "test_harness")]
+pub mod test;
+/// In-memory renderer for testing Egui output.
+#[cfg(feature = "test_renderer")]
+pub mod test_renderer;
+/// Hidden text area for IME composition on web.
+#[cfg(target_arch = "wasm32")]
+pub mod text_agent;
+/// On-screen keyboards and hints for text fields.
+pub mod text_input;
+/// Egui styles loaded from asset files.
+#[cfg(feature = "theme")]
+pub mod theme;
+/// Native windows for Egui viewports.
+pub mod viewports;
 /// Clipboard management for web
 #[cfg(all(
     feature = "manage_clipboard",
@@ -72,9 +205,44 @@ pub mod systems;
     web_sys_unstable_apis
 ))]
 pub mod web_clipboard;
+/// Finding the canvas and event targets in the page, including shadow roots.
+#[cfg(target_arch = "wasm32")]
+mod web_dom;
+/// Passing files dropped onto the page to Egui on web.
+#[cfg(target_arch = "wasm32")]
+pub mod web_file_drop;
+/// Preventing browser shortcuts while Egui wants keyboard input.
+#[cfg(target_arch = "wasm32")]
+pub mod web_keyboard;
 
 pub use egui;
 
+pub use crate::{
+    cursor_icon::{EguiCursorIconMapper, EguiCursorIconMapping},
+    events::{
+        EguiCopyEvent, EguiInteractionEvent, EguiOpenUrlEvent, EguiRotateGestureEvent,
+        EguiTextCursorMoved, EguiUnmappedKeyEvent, EguiWantsRepaint,
+    },
+    fonts::EguiFontAtlasPrewarm,
+    hooks::{EguiInputHook, EguiInputHooks, EguiPassHook, EguiPassHooks},
+    input::{
+        egui_wants_any_input, egui_wants_any_keyboard_input, egui_wants_any_pointer_input,
+        EguiBlockPointerInput, EguiGamepadNavigation, EguiInputFilter, EguiInputPredicate,
+        EguiPassthroughRegion, EguiPointerPassthrough, EguiWantsInput,
+    },
+    text_input::{
+        EguiAutocapitalize, EguiEnterKeyHint, EguiFocusedText, EguiInputMode, EguiTextInputHints,
+        EguiVirtualKeyboard,
+    },
+};
+#[cfg(feature = "render")]
+pub use crate::{
+    events::EguiClipboardImagePaste,
+    screenshot::{
+        EguiCapturedScreenshots, EguiScreenshot, EguiScreenshotCaptured, EguiScreenshots,
+    },
+};
+
 use crate::systems::*;
 #[cfg(feature = "render")]
 use crate::{
@@ -88,36 +256,41 @@ use crate::{
 use arboard::Clipboard;
 #[allow(unused_imports)]
 use bevy::log;
+use bevy::{
+    app::{App, Last, Plugin, PostUpdate, PreStartup, PreUpdate, Update},
+    asset::{AssetApp, AssetServer, Handle},
+    ecs::{
+        query::{QueryData, QueryEntityError},
+        schedule::{apply_deferred, InternedScheduleLabel, ScheduleLabel},
+        system::{ResMut, SystemParam},
+        world::World,
+    },
+    input::InputSystem,
+    prelude::{
+        Added, Commands, Component, Deref, DerefMut, Entity, IntoSystemConfigs,
+        IntoSystemSetConfigs, Query, Ref, Resource, SystemSet, With, Without,
+    },
+    reflect::Reflect,
+    window::{PrimaryWindow, Window},
+};
 #[cfg(feature = "render")]
 use bevy::{
-    app::Last,
-    asset::{load_internal_asset, AssetEvent, Assets, Handle},
-    ecs::{event::EventReader, system::ResMut},
+    asset::{load_internal_asset, AssetEvent, Assets},
+    ecs::{
+        event::EventReader,
+        system::{Local, Res},
+    },
     prelude::Shader,
     render::{
         extract_component::{ExtractComponent, ExtractComponentPlugin},
         extract_resource::{ExtractResource, ExtractResourcePlugin},
         render_resource::SpecializedRenderPipelines,
-        texture::{Image, ImageSampler},
+        renderer::RenderDevice,
+        texture::{Image, ImageSampler, ImageSamplerDescriptor},
         ExtractSchedule, Render, RenderApp, RenderSet,
     },
     utils::HashMap,
 };
-use bevy::{
-    app::{App, Plugin, PostUpdate, PreStartup, PreUpdate},
-    ecs::{
-        query::{QueryData, QueryEntityError},
-        schedule::apply_deferred,
-        system::SystemParam,
-    },
-    input::InputSystem,
-    prelude::{
-        Added, Commands, Component, Deref, DerefMut, Entity, IntoSystemConfigs, Query, Resource,
-        SystemSet, With, Without,
-    },
-    reflect::Reflect,
-    window::{PrimaryWindow, Window},
-};
 #[cfg(all(
     feature = "manage_clipboard",
     not(any(target_arch = "wasm32", target_os = "android"))
@@ -125,7 +298,63 @@ use bevy::{
 use std::cell::{RefCell, RefMut};
 
 /// Adds all Egui resources and render graph nodes.
-pub struct EguiPlugin;
+///
+/// Adding the plugin more than once (a dependency may add it too, for example) is a no-op
+/// after the first time, apart from a logged warning.
+///
+/// UI systems run in [`Update`] by default, see [`EguiPlugin::with_ui_schedule`] for running
+/// them in another schedule.
+pub struct EguiPlugin {
+    ui_schedule: Option<InternedScheduleLabel>,
+}
+
+/// The plugin with the default settings, so that `app.add_plugins(EguiPlugin)` keeps working.
+#[allow(non_upper_case_globals)]
+pub const EguiPlugin: EguiPlugin = EguiPlugin { ui_schedule: None };
+
+impl Default for EguiPlugin {
+    fn default() -> Self {
+        EguiPlugin
+    }
+}
+
+impl EguiPlugin {
+    /// Runs Egui passes in `schedule` instead of spreading them over [`PreUpdate`] (beginning
+    /// the pass) and [`PostUpdate`] (ending it), so that UI systems can be run in that schedule.
+    ///
+    /// Every run of the schedule is a separate pass: [`EguiSet::BeginPass`], [`EguiSet::UserUi`]
+    /// and [`EguiSet::EndPass`] are chained in it. The schedule has to run after [`PreUpdate`] and
+    /// before [`PostUpdate`], this is the case for [`FixedUpdate`](bevy::app::FixedUpdate) or
+    /// schedules run by systems in [`Update`]. If it doesn't run during a frame, the output of the
+    /// last pass is drawn again and input is kept for the next pass.
+    ///
+    /// All the UI has to be created in the schedule: contexts aren't in a pass outside of it.
+    ///
+    /// ```no_run,rust
+    /// use bevy::prelude::*;
+    /// use bevy_egui::{egui, EguiContexts, EguiPlugin, EguiSet};
+    ///
+    /// fn main() {
+    ///     App::new()
+    ///         .add_plugins(DefaultPlugins)
+    ///         .add_plugins(EguiPlugin::with_ui_schedule(FixedUpdate))
+    ///         .add_systems(FixedUpdate, ui_system.in_set(EguiSet::UserUi))
+    ///         .run();
+    /// }
+    ///
+    /// fn ui_system(mut contexts: EguiContexts) {
+    ///     egui::Window::new("Hello").show(contexts.ctx_mut(), |ui| {
+    ///         ui.label("world");
+    ///     });
+    /// }
+    /// ```
+    #[must_use]
+    pub fn with_ui_schedule(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            ui_schedule: Some(schedule.intern()),
+        }
+    }
+}
 
 /// A resource for storing global UI settings.
 #[derive(Clone, Debug, Resource, Reflect)]
@@ -144,18 +373,170 @@ pub struct EguiSettings {
     ///     }
     /// }
     /// ```
+    ///
+    /// The factor is merged with the window's DPI scale factor into egui's native pixels per
+    /// point, and can be overridden for a context with [`EguiContextSettings::scale_factor`].
+    /// To let users enlarge the UI of a single context (an accessibility setting, for example),
+    /// use [`EguiContextSettings::ui_zoom_factor`] instead.
     pub scale_factor: f32,
+    /// Zoom factor applied to every context on top of [`EguiContextSettings::ui_zoom_factor`]
+    /// while an assistive technology is active (`1.0` by default, which disables it).
+    ///
+    /// The signal is Bevy's [`AccessibilityRequested`](bevy::a11y::AccessibilityRequested)
+    /// resource, which `bevy_winit` sets once a screen reader or another assistive technology
+    /// connects to the app. Bevy doesn't expose the OS text size preference, so the zoom isn't
+    /// applied if no assistive technology is running, or if the resource doesn't exist (without
+    /// `AccessibilityPlugin`). The OS display scaling is already part of the
+    /// window's scale factor. Apps that read other accessibility preferences themselves can
+    /// set [`EguiContextSettings::ui_zoom_factor`] instead. The zoom currently in effect is
+    /// stored in [`EguiAccessibilityZoom`].
+    pub accessibility_zoom_factor: f32,
     /// Will be used as a default value for hyperlink [target](https://www.w3schools.com/tags/att_a_target.asp) hints.
     /// If not specified, `_self` will be used. Only matters in a web browser.
+    ///
+    /// URLs opened with [`egui::OpenUrl::new_tab`] (clicking a hyperlink with a modifier held, or
+    /// with the middle button) always use `_blank`. Native platforms open URLs in the default
+    /// browser.
     #[cfg(feature = "open_url")]
     pub default_open_url_target: Option<String>,
+    /// Multiplier for the scroll speed when mouse wheel events are reported in lines
+    /// (applied to [`egui::Options::line_scroll_speed`] of every context).
+    ///
+    /// `bevy_egui` forwards [`bevy::input::mouse::MouseWheel`] deltas as is (with
+    /// [`bevy::input::mouse::MouseScrollUnit::Line`] mapped to [`egui::MouseWheelUnit::Line`]),
+    /// so this is the only place where line scrolling gets scaled.
+    /// Pixel scrolling isn't affected by this setting.
+    pub line_scroll_speed: f32,
+    /// Controls the speed of zooming with ctrl/cmd + scroll
+    /// (applied to [`egui::Options::scroll_zoom_speed`] of every context).
+    pub scroll_zoom_speed: f32,
+    /// If `true`, two-finger touch gestures are translated into high-level events:
+    /// scrolling is sent as [`egui::Event::MouseWheel`], pinching as [`egui::Event::Zoom`],
+    /// and rotation as [`EguiRotateGestureEvent`] (`false` by default).
+    ///
+    /// Raw [`egui::Event::Touch`] events are always sent, and egui is able to handle multi-touch
+    /// on its own, but some widgets expect mouse wheel or zoom events. When enabled, the emulated
+    /// mouse button is released once the second finger touches the screen.
+    pub synthesize_touch_gestures: bool,
+    /// If `true`, mouse events (cursor movement, button presses and scrolling) are passed only
+    /// to the context of the window that is under the cursor (`false` by default).
+    ///
+    /// This is useful for overlapping windows: the window under the cursor is determined by
+    /// [`Window::cursor_position`], which Bevy resets once the cursor leaves a window,
+    /// so clicks don't leak to contexts of background windows. A drag that leaves its window
+    /// still gets the button release, wherever it happens.
+    pub pointer_input_to_hovered_window_only: bool,
+    /// If `true`, egui keeps requesting redraws (see [`bevy::window::RequestRedraw`]) for
+    /// unfocused windows, so animations and other repaints requested by egui keep updating
+    /// (`true` by default).
+    ///
+    /// Setting this to `false` lets Bevy sleep according to
+    /// `WinitSettings::unfocused_mode` while no window is focused, which saves power,
+    /// but UIs of unfocused windows (a monitoring dashboard, for example) will update only when
+    /// the app gets updated for other reasons. It only affects the redraw requests: whenever the
+    /// app updates, the passes of unfocused windows are still run and rendered as usual.
+    pub run_in_background: bool,
+    /// The minimum interval in seconds between redraws requested by egui (`0.0` by default,
+    /// which doesn't limit them).
+    ///
+    /// While egui animates, it requests a redraw (see [`bevy::window::RequestRedraw`]) every
+    /// frame, so in the reactive `WinitSettings` update modes the app renders as fast as the
+    /// present mode allows. Setting this to `1.0 / 30.0`, for example, saves power by requesting
+    /// at most 30 redraws per second. A request that comes too soon is postponed until the
+    /// interval has passed, so the last frame of an animation is still drawn: the
+    /// `wake_on_repaint` feature wakes the app up for it, without the feature it's drawn once Bevy
+    /// wakes up for other reasons (set the reactive mode's `wait` to the same interval to keep
+    /// animations running at a steady rate). It doesn't limit frames caused by input or by Bevy
+    /// itself (the `Continuous` update mode renders every frame anyway, and vsync caps the frame
+    /// rate independently of this).
+    pub min_redraw_interval: f32,
+    /// Whether shapes are anti-aliased with feathering
+    /// (applied to [`egui::epaint::TessellationOptions::feathering`] of every context,
+    /// `true` by default).
+    ///
+    /// Disabling it makes edges sharp but aliased, and hairlines thinner than a physical pixel
+    /// may disappear at fractional scale factors.
+    pub feathering: bool,
+    /// The size of the feathered edge in physical pixels
+    /// (applied to [`egui::epaint::TessellationOptions::feathering_size_in_pixels`] of every
+    /// context, `1.0` by default).
+    pub feathering_size_in_pixels: f32,
+    /// The minimum width of strokes in physical pixels (`0.0` by default, which disables it).
+    ///
+    /// Strokes thinner than that (`1.0` point borders rendered at the `0.5` scale factor,
+    /// for example) are widened before tessellation, which keeps grids and thin borders
+    /// consistently visible at all scale factors. Strokes with zero width are left invisible.
+    pub min_stroke_width_in_pixels: f32,
+    /// Whether shapes are tessellated on the
+    /// [`AsyncComputeTaskPool`](bevy::tasks::AsyncComputeTaskPool) (`false` by default).
+    ///
+    /// Tessellating the shapes of UIs with thousands of widgets can take a few milliseconds.
+    /// When enabled, it runs on a task started during [`EguiSet::EndPass`], in parallel with the
+    /// systems that follow it (and with the tessellation of other contexts). The task is polled
+    /// in [`Last`] without waiting for it: if it has finished, the paint jobs are drawn in the
+    /// same frame, otherwise [`EguiRenderOutput`] keeps the paint jobs (and the textures delta)
+    /// of the previous frame, and a redraw is requested to pick up the result.
+    ///
+    /// Small UIs gain little from it, as the shapes have to be cloned and sent to another thread.
+    /// It's ignored in web builds, where tasks don't run in parallel, and with Bevy's
+    /// `multi_threaded` feature disabled the tasks run right away.
+    pub threaded_tessellation: bool,
+    /// Delay in seconds before showing tooltips after the pointer stops moving
+    /// (applied to [`egui::style::Interaction::tooltip_delay`] of every context).
+    pub tooltip_delay: f32,
+    /// If another widget is hovered within this many seconds after a tooltip was shown, its
+    /// tooltip is shown right away (applied to [`egui::style::Interaction::tooltip_grace_time`]
+    /// of every context).
+    pub tooltip_grace_time: f32,
+    /// If `false`, tooltips show up while the pointer is still moving
+    /// (applied to [`egui::style::Interaction::show_tooltips_only_when_still`]
+    /// of every context).
+    pub show_tooltips_only_when_still: bool,
+    /// Draws Egui meshes without clipping them to their clip rects (`false` by default).
+    ///
+    /// This is a debugging aid: it reveals geometry that is clipped away, which helps to find
+    /// widgets that are invisible because of a wrong clip rect. See [`EguiRenderStats`] for
+    /// the number of clipped meshes.
+    pub debug_disable_clipping: bool,
+    /// Whether textures are premultiplied on the CPU instead of the fragment shader
+    /// (`false` by default).
+    ///
+    /// By default, Egui textures are uploaded with unmultiplied alpha and premultiplied in the
+    /// fragment shader, as Bevy image assets (used for [`EguiUserTextures`]) aren't premultiplied.
+    /// If your user textures are already premultiplied (render targets with transparency, for
+    /// example), enable this to avoid premultiplying them twice, which makes semi-transparent
+    /// edges too dark. Egui textures are then uploaded with premultiplied alpha as well.
+    ///
+    /// Vertex colors are always premultiplied, and the output is always blended as premultiplied
+    /// alpha. Blending happens in linear space for sRGB and float render targets, and in gamma
+    /// space for non-sRGB unorm ones (see [`egui_node::EguiPipelineKey::texture_format`]).
+    /// With premultiplied textures sampled from sRGB formats, decoding happens after
+    /// premultiplication, which makes semi-transparent texels slightly darker in linear space
+    /// (this matches `egui-wgpu`).
+    pub premultiplied_textures: bool,
 }
 
 // Just to keep the PartialEq
 impl PartialEq for EguiSettings {
     #[allow(clippy::let_and_return)]
     fn eq(&self, other: &Self) -> bool {
-        let eq = self.scale_factor == other.scale_factor;
+        let eq = self.scale_factor == other.scale_factor
+            && self.accessibility_zoom_factor == other.accessibility_zoom_factor
+            && self.line_scroll_speed == other.line_scroll_speed
+            && self.scroll_zoom_speed == other.scroll_zoom_speed
+            && self.synthesize_touch_gestures == other.synthesize_touch_gestures
+            && self.pointer_input_to_hovered_window_only
+                == other.pointer_input_to_hovered_window_only
+            && self.run_in_background == other.run_in_background
+            && self.min_redraw_interval == other.min_redraw_interval
+            && self.feathering == other.feathering
+            && self.feathering_size_in_pixels == other.feathering_size_in_pixels
+            && self.min_stroke_width_in_pixels == other.min_stroke_width_in_pixels
+            && self.tooltip_delay == other.tooltip_delay
+            && self.tooltip_grace_time == other.tooltip_grace_time
+            && self.show_tooltips_only_when_still == other.show_tooltips_only_when_still
+            && self.debug_disable_clipping == other.debug_disable_clipping
+            && self.premultiplied_textures == other.premultiplied_textures;
         #[cfg(feature = "open_url")]
         let eq = eq && self.default_open_url_target == other.default_open_url_target;
         eq
@@ -164,14 +545,421 @@ impl PartialEq for EguiSettings {
 
 impl Default for EguiSettings {
     fn default() -> Self {
+        let egui_options = egui::Options::default();
+        let egui_interaction = egui::style::Interaction::default();
         Self {
             scale_factor: 1.0,
+            accessibility_zoom_factor: 1.0,
             #[cfg(feature = "open_url")]
             default_open_url_target: None,
+            line_scroll_speed: egui_options.line_scroll_speed,
+            scroll_zoom_speed: egui_options.scroll_zoom_speed,
+            synthesize_touch_gestures: false,
+            pointer_input_to_hovered_window_only: false,
+            run_in_background: true,
+            min_redraw_interval: 0.0,
+            feathering: egui_options.tessellation_options.feathering,
+            feathering_size_in_pixels: egui_options.tessellation_options.feathering_size_in_pixels,
+            min_stroke_width_in_pixels: 0.0,
+            threaded_tessellation: false,
+            tooltip_delay: egui_interaction.tooltip_delay,
+            tooltip_grace_time: egui_interaction.tooltip_grace_time,
+            show_tooltips_only_when_still: egui_interaction.show_tooltips_only_when_still,
+            debug_disable_clipping: false,
+            premultiplied_textures: false,
+        }
+    }
+}
+
+/// The zoom factor applied to every context on top of [`EguiContextSettings::ui_zoom_factor`]
+/// (`1.0` unless [`EguiSettings::accessibility_zoom_factor`] is in effect).
+///
+/// Is updated during the [`EguiSet::InitContexts`] system, changes made to it are overwritten.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "render", derive(ExtractResource))]
+pub struct EguiAccessibilityZoom(pub f32);
+
+impl Default for EguiAccessibilityZoom {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// A component for storing settings of a single context (window).
+///
+/// It's inserted together with [`EguiContext`] for every window, global settings are stored in the
+/// [`EguiSettings`] resource. Changes are applied during the [`EguiSet::InitContexts`] system.
+#[derive(Component, Clone, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "render", derive(ExtractComponent))]
+pub struct EguiContextSettings {
+    /// Is passed to [`egui::Context::set_embed_viewports`] (`true` by default).
+    ///
+    /// If `true`, viewports shown with [`egui::Context::show_viewport_immediate`] or
+    /// [`egui::Context::show_viewport_deferred`] are drawn as embedded areas inside the window.
+    /// If `false`, deferred viewports get their own Bevy windows, see
+    /// [`EguiViewport`](viewports::EguiViewport).
+    ///
+    /// Immediate viewports are always embedded: egui renders them with a callback stored in a
+    /// thread local ([`egui::Context::set_immediate_viewport_renderer`]), which would have to be
+    /// set on every thread Bevy runs systems on, and run a whole frame of another window in the
+    /// middle of the system that shows the viewport.
+    pub embed_viewports: bool,
+    /// Resolution of the offscreen texture Egui is rendered to, relative to the window's
+    /// physical size (`1.0` by default).
+    ///
+    /// Values below `1.0` make Egui render into a smaller texture, which is then upscaled onto the
+    /// window with linear filtering. This reduces fill rate costs on high-DPI screens or slow GPUs,
+    /// but text and thin lines get noticeably blurry. Input isn't affected.
+    /// Values outside of the `(0.0, 1.0)` range render directly to the window.
+    pub render_scale: f32,
+    /// Number of samples per pixel Egui is rendered with (`1` by default, which disables
+    /// multisampling).
+    ///
+    /// Egui anti-aliases shapes with feathering already (see [`EguiSettings::feathering`]), so
+    /// this is mostly useful for [paint callbacks](egui_node::EguiPaintCallback) that draw
+    /// geometry with pipelines sharing the main pass's sample count. Above `1`, the context is
+    /// rendered to a multisampled texture, which is resolved into the image of an
+    /// [`EguiRenderToTexture`] context, or into an offscreen texture that's then blended onto the
+    /// window (as with [`Self::render_scale`]). Pipelines are specialized for the sample count,
+    /// see [`egui_node::EguiPipelineKey::sample_count`] and
+    /// [`egui_node::EguiPaintCallbackInfo::sample_count`]. It can be changed at any time.
+    /// Counts other than `1` and `4` require the format of the target to support them.
+    /// Is ignored for [`EguiSecondaryContext`]s.
+    pub msaa_samples: u32,
+    /// Draws the UI of an [`EguiCameraContext`] into the camera's main texture before
+    /// tonemapping instead of onto the window after it (`false` by default).
+    ///
+    /// By default, the UI is drawn over the tonemapped output of the camera, so colors appear
+    /// exactly as Egui specifies them regardless of the camera's
+    /// [`Tonemapping`](bevy::core_pipeline::tonemapping::Tonemapping). When enabled, the UI is
+    /// drawn into the main texture of the camera (an `Rgba16Float` one for HDR cameras) after
+    /// the main pass, bloom and other HDR effects, and gets tonemapped and exposed together with
+    /// the scene, which keeps its brightness consistent with the world in HDR. Egui's sRGB
+    /// colors are converted to linear values where `1.0` is white, use [`Self::tint`] with
+    /// values above `1.0` to make the UI brighter. Only cameras with the `Core2d` and `Core3d`
+    /// render graphs are supported, and LDR cameras tonemap in their main pass, so their UI
+    /// isn't tonemapped either way. A shorthand for
+    /// [`EguiCameraPass::BeforeTonemapping`](egui_node::EguiCameraPass::BeforeTonemapping), see
+    /// [`Self::camera_pass`] for the other passes. Is ignored for contexts that aren't
+    /// [`EguiCameraContext`]s.
+    pub draw_before_tonemapping: bool,
+    /// Draws the UI of an [`EguiCameraContext`] below the `bevy_ui` nodes of its camera (`false`
+    /// by default, which draws it onto the window above them).
+    ///
+    /// A shorthand for
+    /// [`EguiCameraPass::BeforeBevyUi`](egui_node::EguiCameraPass::BeforeBevyUi), which
+    /// [`Self::draw_before_tonemapping`] and [`Self::camera_pass`] take precedence over. While a
+    /// context is drawn below `bevy_ui`, pointer input is kept from reaching Egui when a node is
+    /// hovered or pressed, see [`EguiBlockPointerInput`]. Is ignored for contexts that aren't
+    /// [`EguiCameraContext`]s.
+    pub draw_below_bevy_ui: bool,
+    /// Distance from the camera in world units at which the UI of an [`EguiCameraContext`] is
+    /// depth tested against the scene (`None` by default, which disables depth testing).
+    ///
+    /// When set, the UI is drawn in the camera's main pass (after transparent objects) at the
+    /// depth of a plane facing the camera at this distance, so 3D geometry closer to the camera
+    /// hides it: floating labels disappear behind walls, for example. The UI writes the depth
+    /// as well, and is multisampled, post-processed and tonemapped together with the scene
+    /// (unlike with [`Self::camera_pass`], which this takes precedence over). Paint
+    /// callbacks have to declare the depth attachment then, see
+    /// [`egui_node::EguiPaintCallbackInfo::depth_format`]. Only cameras with the `Core3d` render
+    /// graph are supported. Is ignored for contexts that aren't [`EguiCameraContext`]s.
+    pub depth_test_distance: Option<f32>,
+    /// The pass of its camera's render graph the UI of an [`EguiCameraContext`] is drawn in
+    /// (`None` by default, which draws it onto the window after the camera has rendered).
+    ///
+    /// Controls which post-processing effects apply to the UI: with
+    /// [`EguiCameraPass::BeforeBloom`](egui_node::EguiCameraPass::BeforeBloom) bright widgets
+    /// bloom together with the scene, for example, while
+    /// [`EguiCameraPass::AfterTonemapping`](egui_node::EguiCameraPass::AfterTonemapping) keeps
+    /// Egui's colors but still anti-aliases the UI with the scene.
+    /// [`EguiCameraPass::Custom`](egui_node::EguiCameraPass::Custom) places it between any two
+    /// nodes of the graph, such as the ones of `bevy_ui` or of custom post-processing. Takes
+    /// precedence over [`Self::draw_before_tonemapping`]. If the graph doesn't have the nodes of
+    /// the pass, a warning is logged and the UI is drawn onto the window. Is ignored for contexts
+    /// that aren't [`EguiCameraContext`]s.
+    #[cfg(feature = "render")]
+    #[reflect(ignore)]
+    pub camera_pass: Option<egui_node::EguiCameraPass>,
+    /// Overrides [`EguiSettings::scale_factor`] for this context (`None` by default).
+    ///
+    /// Lets windows use different UI scales, a higher one for a window on a TV, for example.
+    /// Changes take effect from the next frame: the current one is still laid out and rendered
+    /// with the previous scale, so the UI doesn't flicker.
+    pub scale_factor: Option<f32>,
+    /// Zoom factor of the UI, is passed to [`egui::Context::set_zoom_factor`] (`1.0` by default).
+    ///
+    /// Unlike [`EguiSettings::scale_factor`], which is combined with the window's DPI scale factor
+    /// into egui's native pixels per point, this is egui's own zoom on top of it: it enlarges
+    /// the context's UI without affecting anything else rendered by Bevy, which makes it a good
+    /// fit for a "UI scale" accessibility slider. Must be positive.
+    pub ui_zoom_factor: f32,
+    /// Color the window is cleared with before Egui is painted (`None` by default).
+    ///
+    /// If `None`, Egui is painted over what cameras have already rendered to the window, leaving
+    /// the pixels it doesn't cover (including the ones under fully transparent frames) intact.
+    /// Keep it `None` whenever Egui overlays a 3D or 2D scene, as clearing would erase the scene.
+    /// Setting a color is useful for apps that render only Egui, without any camera
+    /// ([`egui::Color32::TRANSPARENT`] for transparent windows, as Bevy clears windows that no
+    /// camera renders to with opaque black).
+    /// Is ignored for [`EguiRenderToTexture`] contexts, which are always cleared with
+    /// [`egui::Color32::TRANSPARENT`], and for [`EguiSecondaryContext`]s.
+    #[reflect(ignore)]
+    pub clear_color: Option<egui::Color32>,
+    /// Confines the cursor to the window while an Egui widget is being dragged (`false` by default).
+    ///
+    /// Keeps slider or color picker drags from ending when the cursor leaves the window.
+    /// The window's [`CursorGrabMode`](bevy::window::CursorGrabMode) is restored once the drag
+    /// ends. Has no effect if the cursor is already grabbed.
+    pub confine_cursor_on_drag: bool,
+    /// Hides the cursor while an Egui widget is being dragged (`false` by default).
+    ///
+    /// The cursor's visibility is restored once the drag ends.
+    pub hide_cursor_on_drag: bool,
+    /// Lays Egui out at a fixed size in points regardless of the window's size (`None` by default).
+    ///
+    /// The UI is scaled uniformly to fit the window and centered, leaving the uncovered parts of
+    /// the window (letterbox bars) untouched, and pointer positions are mapped accordingly.
+    /// [`EguiSettings::scale_factor`] and the window's DPI scale factor don't affect the size of
+    /// the UI then, [`EguiContextSettings::ui_zoom_factor`] still applies on top of the fit.
+    /// Is ignored for [`EguiRenderToTexture`] contexts.
+    pub fixed_logical_size: Option<bevy::math::Vec2>,
+    /// Makes the context ignore all input from its window (`false` by default).
+    ///
+    /// A display-only context still renders and gets time updates, but no pointer, touch,
+    /// keyboard, text, IME or clipboard events (and no modifier keys), and it's never counted
+    /// in [`EguiWantsInput`]. Useful for pure output windows, such as a dashboard on a secondary
+    /// monitor. Unlike [`EguiBlockPointerInput`], which only hides the pointer, this doesn't
+    /// track the pointer at all. Events written to [`EguiInput`] directly or added by
+    /// [`EguiInputHooks`] are still passed to the context.
+    pub display_only: bool,
+    /// Color the whole context is multiplied by when it's painted, in linear space with
+    /// premultiplied alpha ([`egui::Rgba::WHITE`] by default).
+    ///
+    /// Animating [`egui::Rgba::from_white_alpha`] from a system fades the UI in and out without
+    /// changing the colors of widgets (see the `fade_transition` example). As every channel is
+    /// multiplied, semi-transparent and transparent areas keep compositing correctly over what's
+    /// behind them. [`Self::clear_color`] isn't tinted. A faded out context still receives input,
+    /// set [`Self::display_only`] to ignore it.
+    #[reflect(ignore)]
+    pub tint: egui::Rgba,
+}
+
+impl Default for EguiContextSettings {
+    fn default() -> Self {
+        Self {
+            embed_viewports: true,
+            render_scale: 1.0,
+            msaa_samples: 1,
+            draw_before_tonemapping: false,
+            draw_below_bevy_ui: false,
+            depth_test_distance: None,
+            #[cfg(feature = "render")]
+            camera_pass: None,
+            scale_factor: None,
+            ui_zoom_factor: 1.0,
+            clear_color: None,
+            confine_cursor_on_drag: false,
+            hide_cursor_on_drag: false,
+            fixed_logical_size: None,
+            display_only: false,
+            tint: egui::Rgba::WHITE,
+        }
+    }
+}
+
+impl EguiContextSettings {
+    /// Whether the context is rendered to an offscreen texture (see [`Self::render_scale`] and
+    /// [`Self::msaa_samples`]).
+    #[cfg(feature = "render")]
+    pub(crate) fn uses_render_target(&self) -> bool {
+        self.render_target_scale().is_some()
+    }
+
+    /// Resolution of the offscreen texture relative to the window's physical size, if the
+    /// context is rendered to one.
+    #[cfg(feature = "render")]
+    pub(crate) fn render_target_scale(&self) -> Option<f32> {
+        if self.render_scale > 0.0 && self.render_scale < 1.0 {
+            Some(self.render_scale)
+        } else if self.msaa_samples > 1 {
+            Some(1.0)
+        } else {
+            None
+        }
+    }
+
+    /// The pass of its camera an [`EguiCameraContext`] is drawn in, if it isn't drawn onto the
+    /// window (see [`Self::depth_test_distance`], [`Self::camera_pass`] and
+    /// [`Self::draw_before_tonemapping`] and [`Self::draw_below_bevy_ui`]).
+    #[cfg(feature = "render")]
+    pub(crate) fn resolved_camera_pass(&self) -> Option<egui_node::EguiCameraPass> {
+        if self.depth_test_distance.is_some() {
+            return Some(egui_node::EguiCameraPass::MainPass);
         }
+        self.camera_pass
+            .or(self
+                .draw_before_tonemapping
+                .then_some(egui_node::EguiCameraPass::BeforeTonemapping))
+            .or(self
+                .draw_below_bevy_ui
+                .then_some(egui_node::EguiCameraPass::BeforeBevyUi))
+    }
+
+    /// The scale factor of the context, see [`Self::scale_factor`].
+    pub(crate) fn egui_scale_factor(&self, egui_settings: &EguiSettings) -> f32 {
+        self.scale_factor.unwrap_or(egui_settings.scale_factor)
     }
 }
 
+/// Makes Egui render the context of an entity to an image instead of a window.
+///
+/// Inserting the component into an entity creates a context for it (with the same components
+/// as window contexts have, see [`EguiContextQuery`]), which can be accessed with
+/// [`EguiContexts::ctx_for_window_mut`]. The image size defines the screen rect of the context
+/// (scaled by [`EguiSettings::scale_factor`]), and the image is cleared with transparent color
+/// before Egui paints to it every frame, producing premultiplied alpha.
+///
+/// The image must have the [`TextureUsages::RENDER_ATTACHMENT`](bevy::render::render_resource::TextureUsages::RENDER_ATTACHMENT)
+/// usage. It's rendered before cameras, so a material that uses the image as its texture
+/// (with [`AlphaMode::Premultiplied`](bevy::prelude::AlphaMode::Premultiplied)) displays
+/// the UI of the same frame. For the same reason, the image can be sampled by post-processing
+/// passes or shown on in-game screens and `bevy_ui` nodes. Each such context gets its own render
+/// graph node, and the handle can be replaced at any time to retarget it.
+///
+/// As there's no window to receive input from, `bevy_egui` doesn't send any input except time
+/// and modifier keys to such contexts. Add [`EguiMeshPointerInput`] to map the cursor onto the
+/// mesh that displays the image (see the `render_egui_to_texture` example), or write pointer
+/// events to the [`EguiInput`] component yourself (after [`EguiSet::ProcessInput`] and before
+/// [`EguiSet::BeginPass`]).
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Debug, ExtractComponent)]
+pub struct EguiRenderToTexture(pub Handle<Image>);
+
+/// Creates an additional context that's drawn to a window on top of (or below) the window's own
+/// context, such as a debug overlay that shouldn't share [`egui::Memory`] and styles with the
+/// game's menus.
+///
+/// Inserting the component into an entity (a camera or any other entity) creates a context for
+/// it with the same components as window contexts have, which can be accessed with
+/// [`EguiContexts::ctx_for_window_mut`] by passing the entity. The context gets the screen rect
+/// of the window, scaled by its own [`EguiContextSettings`].
+///
+/// Contexts of a window are drawn in the ascending [`EguiSecondaryContext::order`], the window's
+/// own context has the order of `0` and is drawn before the secondary contexts of the same
+/// order. Input of the window is routed between them every frame:
+/// - pointer and touch events go to the context that has the pointer captured (by dragging a
+///   widget, for example) or that has the UI under the pointer, preferring the topmost one;
+///   if there's no UI under the pointer, all the contexts get the events.
+/// - keyboard, text and clipboard events go to the topmost context that
+///   [wants keyboard input](egui::Context::wants_keyboard_input), or to all the contexts if none
+///   of them does.
+///
+/// Other events (screenshots, for example) are only sent to the window's own context, and the
+/// secondary contexts don't support [`EguiContextSettings::clear_color`] and
+/// [`EguiContextSettings::render_scale`].
+///
+/// ```rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::{egui, EguiContexts, EguiSecondaryContext};
+///
+/// #[derive(Component)]
+/// struct DebugOverlay;
+///
+/// fn setup(mut commands: Commands, primary_window: Query<Entity, With<PrimaryWindow>>) {
+///     commands.spawn((
+///         DebugOverlay,
+///         EguiSecondaryContext {
+///             window: primary_window.single(),
+///             order: 1,
+///         },
+///     ));
+/// }
+///
+/// fn debug_overlay_system(mut contexts: EguiContexts, overlay: Query<Entity, With<DebugOverlay>>) {
+///     let Ok(overlay) = overlay.get_single() else {
+///         return;
+///     };
+///     let Some(ctx) = contexts.try_ctx_for_window_mut(overlay) else {
+///         return;
+///     };
+///     egui::Window::new("Debug").show(ctx, |ui| ui.label("Isolated from the game's UI"));
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "render", derive(ExtractComponent))]
+pub struct EguiSecondaryContext {
+    /// The window the context is drawn to and gets input from.
+    pub window: Entity,
+    /// Contexts with a higher order are drawn on top and get input first.
+    pub order: i32,
+}
+
+/// Creates a context that's drawn into the viewport of the camera it's inserted into, for
+/// split-screen setups where each player gets their own HUD.
+///
+/// The camera gets an [`EguiSecondaryContext`] that's kept in sync with the window the camera
+/// renders to and with [`Camera::order`](bevy::render::camera::Camera::order), so contexts of
+/// cameras rendered later are drawn on top. The screen rect of the context is the camera's
+/// [viewport](bevy::render::camera::Camera::viewport) (or the whole window if it's not set),
+/// scaled by the camera's [`EguiContextSettings`], and pointer positions are relative to the
+/// viewport's top left corner. Cameras that render to images aren't supported, use
+/// [`EguiRenderToTexture`] for them instead.
+///
+/// ```rust
+/// use bevy::{prelude::*, render::camera::Viewport};
+/// use bevy_egui::{egui, EguiCameraContext, EguiContexts};
+///
+/// fn setup(mut commands: Commands) {
+///     for player in 0..2 {
+///         commands.spawn((
+///             Camera3dBundle {
+///                 camera: Camera {
+///                     order: player,
+///                     viewport: Some(Viewport {
+///                         physical_position: UVec2::new(player as u32 * 640, 0),
+///                         physical_size: UVec2::new(640, 720),
+///                         ..default()
+///                     }),
+///                     ..default()
+///                 },
+///                 ..default()
+///             },
+///             EguiCameraContext,
+///         ));
+///     }
+/// }
+///
+/// fn hud_system(mut contexts: EguiContexts, cameras: Query<(Entity, &Camera), With<EguiCameraContext>>) {
+///     for (camera_entity, camera) in cameras.iter() {
+///         let Some(ctx) = contexts.try_ctx_for_window_mut(camera_entity) else {
+///             continue;
+///         };
+///         egui::Window::new("HUD").show(ctx, |ui| ui.label(format!("Player {}", camera.order)));
+///     }
+/// }
+/// ```
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EguiCameraContext;
+
+/// Sends the cursor to the context of an [`EguiRenderToTexture`] entity when it points at the
+/// entity's mesh, making UIs displayed on objects in a 3D scene interactive.
+///
+/// The cursor is raycast from the camera that renders to the hovered window (the one with the
+/// highest order, if several of them do), and the UV coordinates of the closest hit are mapped
+/// to the context's screen rect. Pointer moves, mouse button and mouse wheel events are sent to
+/// the context while the cursor is over the mesh, and [`egui::Event::PointerGone`] once it
+/// leaves (after releasing the buttons that were held).
+///
+/// The entity needs a [`Handle<Mesh>`](bevy::render::mesh::Mesh) with
+/// [`Mesh::ATTRIBUTE_UV_0`](bevy::render::mesh::Mesh::ATTRIBUTE_UV_0) and the triangle list
+/// topology. Both sides of the triangles are hit, and other meshes of the scene don't block
+/// the ray. The cursor isn't raycast while it's over the UI of the window context.
+#[cfg(feature = "render")]
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct EguiMeshPointerInput;
+
 /// Is used for storing Egui context input..
 ///
 /// It gets reset during the [`EguiSet::ProcessInput`] system.
@@ -181,6 +969,14 @@ pub struct EguiInput(pub egui::RawInput);
 /// A resource for accessing clipboard.
 ///
 /// The resource is available only if `manage_clipboard` feature is enabled.
+///
+/// On native platforms, if the system clipboard is unavailable (in a headless Wayland session or
+/// on X11 without a display, for example) or fails to store the contents, they are kept in the
+/// resource instead, so copying and pasting still works within the app.
+///
+/// On web, clipboard events are received from the page. If Bevy renders to a canvas embedded among
+/// other page content, set [`Window::canvas`](bevy::window::Window::canvas) of the primary window
+/// to a selector of that canvas: only the events dispatched to it will be handled then.
 #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
 #[derive(Default, Resource)]
 pub struct EguiClipboard {
@@ -188,6 +984,38 @@ pub struct EguiClipboard {
     clipboard: thread_local::ThreadLocal<Option<RefCell<Clipboard>>>,
     #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
     clipboard: web_clipboard::WebClipboard,
+    #[cfg(all(feature = "render", not(target_arch = "wasm32")))]
+    pasted_images: Vec<PastedImage>,
+    // Contents that couldn't be placed to the system clipboard. They are returned instead of the
+    // system clipboard contents until something is copied to it successfully.
+    #[cfg(not(target_arch = "wasm32"))]
+    fallback: Option<FallbackContents>,
+}
+
+#[cfg(all(
+    feature = "manage_clipboard",
+    not(target_os = "android"),
+    not(target_arch = "wasm32")
+))]
+enum FallbackContents {
+    Text(String),
+    Image(arboard::ImageData<'static>),
+}
+
+/// An image pasted from the clipboard, which is yet to be sent with [`EguiClipboardImagePaste`].
+#[cfg(all(
+    feature = "render",
+    feature = "manage_clipboard",
+    not(target_os = "android"),
+    not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+))]
+pub(crate) struct PastedImage {
+    /// The window the image was pasted into, `None` for the page's `paste` events on web.
+    pub window: Option<Entity>,
+    pub width: u32,
+    pub height: u32,
+    /// Pixels in the RGBA8 format, without premultiplied alpha.
+    pub rgba: Vec<u8>,
 }
 
 #[cfg(all(
@@ -222,6 +1050,62 @@ impl EguiClipboard {
         self.get_contents_impl()
     }
 
+    /// Reads the clipboard with the asynchronous Clipboard API and pastes the text into the
+    /// focused widget once it's read, as if the user pressed the paste shortcut.
+    ///
+    /// Keyboard shortcuts already receive the text with the page's `paste` event, this is meant
+    /// for paste buttons and context menus. Browsers may ask the user for a permission first.
+    #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
+    pub fn request_paste(&self) {
+        self.clipboard.request_paste();
+    }
+
+    /// Places an image to the clipboard, as Egui doesn't have an output for copied images.
+    ///
+    /// On web, the image is encoded as PNG and written with the asynchronous Clipboard API,
+    /// browsers may ask the user for a permission first.
+    pub fn set_image(&mut self, image: &egui::ColorImage) {
+        self.set_image_impl(image);
+    }
+
+    /// Gets an image from the clipboard. Returns [`None`] if the clipboard doesn't contain
+    /// an image or the clipboard provider is unavailable.
+    ///
+    /// On web, the clipboard can only be read asynchronously: pasted images are sent with
+    /// [`EguiClipboardImagePaste`] instead.
+    #[must_use]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn get_image(&mut self) -> Option<egui::ColorImage> {
+        let image = self.get_image_data()?;
+        Some(egui::ColorImage::from_rgba_unmultiplied(
+            [image.width, image.height],
+            &image.bytes,
+        ))
+    }
+
+    /// Queues the image of the clipboard (if any) to be sent with [`EguiClipboardImagePaste`].
+    #[cfg(all(feature = "render", not(target_arch = "wasm32")))]
+    pub(crate) fn paste_image(&mut self, window: Entity) {
+        let Some(image) = self.get_image_data() else {
+            return;
+        };
+        self.pasted_images.push(PastedImage {
+            window: Some(window),
+            width: image.width as u32,
+            height: image.height as u32,
+            rgba: image.bytes.into_owned(),
+        });
+    }
+
+    /// Takes the images pasted since the last call.
+    #[cfg(feature = "render")]
+    pub(crate) fn take_pasted_images(&mut self) -> Vec<PastedImage> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return std::mem::take(&mut self.pasted_images);
+        #[cfg(target_arch = "wasm32")]
+        return self.clipboard.take_pasted_images();
+    }
+
     /// Receives a clipboard event sent by the `copy`/`cut`/`paste` listeners.
     #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
     pub fn try_receive_clipboard_event(&self) -> Option<web_clipboard::WebClipboardEvent> {
@@ -230,11 +1114,13 @@ impl EguiClipboard {
 
     #[cfg(not(target_arch = "wasm32"))]
     fn set_contents_impl(&mut self, contents: &str) {
-        if let Some(mut clipboard) = self.get() {
-            if let Err(err) = clipboard.set_text(contents.to_owned()) {
-                log::error!("Failed to set clipboard contents: {:?}", err);
-            }
-        }
+        let stored = self.get().is_some_and(|mut clipboard| {
+            clipboard
+                .set_text(contents.to_owned())
+                .map_err(|err| log::error!("Failed to set clipboard contents: {:?}", err))
+                .is_ok()
+        });
+        self.fallback = (!stored).then(|| FallbackContents::Text(contents.to_owned()));
     }
 
     #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
@@ -244,15 +1130,67 @@ impl EguiClipboard {
 
     #[cfg(not(target_arch = "wasm32"))]
     fn get_contents_impl(&mut self) -> Option<String> {
+        if let Some(fallback) = &self.fallback {
+            return match fallback {
+                FallbackContents::Text(contents) => Some(contents.clone()),
+                FallbackContents::Image(_) => None,
+            };
+        }
         if let Some(mut clipboard) = self.get() {
             match clipboard.get_text() {
                 Ok(contents) => return Some(contents),
+                // The clipboard is empty or contains something else (an image, for example).
+                Err(arboard::Error::ContentNotAvailable) => {}
                 Err(err) => log::error!("Failed to get clipboard contents: {:?}", err),
             }
         };
         None
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn set_image_impl(&mut self, image: &egui::ColorImage) {
+        let image = arboard::ImageData {
+            width: image.width(),
+            height: image.height(),
+            bytes: image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_srgba_unmultiplied())
+                .collect(),
+        };
+        let stored = self.get().is_some_and(|mut clipboard| {
+            clipboard
+                .set_image(image.clone())
+                .map_err(|err| log::error!("Failed to set clipboard image: {:?}", err))
+                .is_ok()
+        });
+        self.fallback = (!stored).then_some(FallbackContents::Image(image));
+    }
+
+    #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
+    fn set_image_impl(&mut self, image: &egui::ColorImage) {
+        self.clipboard.set_image(image);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn get_image_data(&mut self) -> Option<arboard::ImageData<'static>> {
+        if let Some(fallback) = &self.fallback {
+            return match fallback {
+                FallbackContents::Text(_) => None,
+                FallbackContents::Image(image) => Some(image.clone()),
+            };
+        }
+        let mut clipboard = self.get()?;
+        match clipboard.get_image() {
+            Ok(image) => Some(image),
+            Err(arboard::Error::ContentNotAvailable) => None,
+            Err(err) => {
+                log::error!("Failed to get clipboard image: {:?}", err);
+                None
+            }
+        }
+    }
+
     #[cfg(all(target_arch = "wasm32", web_sys_unstable_apis))]
     #[allow(clippy::unnecessary_wraps)]
     fn get_contents_impl(&mut self) -> Option<String> {
@@ -260,13 +1198,17 @@ impl EguiClipboard {
     }
 
     #[cfg(not(target_arch = "wasm32"))]
-    fn get(&self) -> Option<RefMut<Clipboard>> {
+    fn get(&self) -> Option<RefMut<'_, Clipboard>> {
         self.clipboard
             .get_or(|| {
                 Clipboard::new()
                     .map(RefCell::new)
                     .map_err(|err| {
-                        log::error!("Failed to initialize clipboard: {:?}", err);
+                        log::error!(
+                            "Failed to initialize clipboard, copied contents will only be \
+                            available within the app: {:?}",
+                            err
+                        );
                     })
                     .ok()
             })
@@ -281,11 +1223,63 @@ impl EguiClipboard {
 pub struct EguiRenderOutput {
     /// Pairs of rectangles and paint commands.
     ///
-    /// The field gets populated during the [`EguiSet::ProcessOutput`] system (belonging to bevy's [`PostUpdate`]) and reset during `EguiNode::update`.
+    /// The field gets populated during the [`EguiSet::EndPass`] system (belonging to bevy's [`PostUpdate`] by default) and reset during `EguiNode::update`.
     pub paint_jobs: Vec<egui::ClippedPrimitive>,
 
     /// The change in egui textures since last frame.
     pub textures_delta: egui::TexturesDelta,
+
+    /// Statistics of the paint jobs, populated together with them.
+    pub stats: EguiRenderStats,
+
+    // Layout the current frame was begun with, which the paint jobs are rendered with even
+    // if the settings have changed since.
+    pub(crate) screen_layout: Option<EguiScreenLayout>,
+    // Incremented when the paint jobs are tessellated anew, the render node only uploads them
+    // then.
+    pub(crate) paint_jobs_generation: u64,
+}
+
+/// Statistics of the meshes tessellated for a context during the last frame.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct EguiRenderStats {
+    /// Number of non-empty meshes.
+    pub meshes: usize,
+    /// Number of vertices in all meshes.
+    pub vertices: usize,
+    /// Number of meshes that are partially outside their clip rects.
+    pub partially_clipped_meshes: usize,
+    /// Number of meshes that are completely outside their clip rects, which makes them invisible
+    /// (unless [`EguiSettings::debug_disable_clipping`] is enabled).
+    pub fully_clipped_meshes: usize,
+}
+
+impl EguiRenderStats {
+    /// Collects statistics of tessellated paint jobs.
+    pub fn from_paint_jobs(paint_jobs: &[egui::ClippedPrimitive]) -> Self {
+        let mut stats = Self::default();
+        for egui::ClippedPrimitive {
+            clip_rect,
+            primitive,
+        } in paint_jobs
+        {
+            let egui::epaint::Primitive::Mesh(mesh) = primitive else {
+                continue;
+            };
+            if mesh.is_empty() {
+                continue;
+            }
+            stats.meshes += 1;
+            stats.vertices += mesh.vertices.len();
+            let bounds = mesh.calc_bounds();
+            if !clip_rect.intersects(bounds) {
+                stats.fully_clipped_meshes += 1;
+            } else if !clip_rect.contains_rect(bounds) {
+                stats.partially_clipped_meshes += 1;
+            }
+        }
+        stats
+    }
 }
 
 impl EguiRenderOutput {
@@ -298,17 +1292,52 @@ impl EguiRenderOutput {
 /// Is used for storing Egui output.
 #[derive(Component, Clone, Default)]
 pub struct EguiOutput {
-    /// The field gets updated during the [`EguiSet::ProcessOutput`] system (belonging to [`PostUpdate`]).
+    /// The field gets updated during the [`EguiSet::EndPass`] system (belonging to [`PostUpdate`] by default).
+    ///
+    /// `bevy_egui` applies the cursor icon, IME state, copied text and opened URLs itself. Other
+    /// fields are left for apps to handle: for example, [`egui::PlatformOutput::mutable_text_under_cursor`]
+    /// tells whether the pointer is over an editable text field, which can be used to decide
+    /// whether to show a custom on-screen keyboard on touch devices (system virtual keyboards
+    /// follow [`Window::ime_enabled`](bevy::window::Window::ime_enabled), which is set while a
+    /// text field has focus).
     pub platform_output: egui::PlatformOutput,
+    /// Hints for the on-screen keyboard of the focused text field, `None` if no text field has
+    /// focus. Is updated together with `platform_output`.
+    pub text_input_hints: Option<EguiTextInputHints>,
+    /// The text and the selection of the focused text field, `None` if no text field has focus
+    /// or its text isn't known yet. Is updated together with `platform_output`.
+    pub focused_text: Option<EguiFocusedText>,
+    // Output of the viewports shown during the last frame, except the context's own one.
+    pub(crate) viewport_output: egui::ViewportIdMap<egui::ViewportOutput>,
 }
 
 /// A component for storing `bevy_egui` context.
+///
+/// Every window gets its own [`egui::Context`] (apart from the windows of
+/// [viewports](viewports::EguiViewport)), so contexts don't share any state
+/// (including [`egui::Memory`] and widget ids) by default. If you share memory between contexts
+/// or draw the same UI in multiple windows and want the ids to stay distinct,
+/// see [`EguiContexts::window_id_salt`].
 #[derive(Clone, Component, Default)]
 #[cfg_attr(feature = "render", derive(ExtractComponent))]
 pub struct EguiContext {
     ctx: egui::Context,
     mouse_position: egui::Pos2,
     pointer_touch_id: Option<u64>,
+    // Whether the mouse is over a region of `EguiPointerPassthrough`, and whether a button was
+    // pressed there and hasn't been released yet.
+    pointer_in_passthrough: bool,
+    passthrough_press_held: bool,
+    // Whether the cursor left the window while a button was held, `PointerGone` is sent once
+    // the buttons are released.
+    pointer_gone_on_release: bool,
+    // Touches that started in a region of `EguiPointerPassthrough`.
+    passthrough_touches: bevy::utils::HashSet<u64>,
+    // How far the UI is moved up to show the text cursor above `EguiVirtualKeyboard`, in logical
+    // pixels.
+    pub(crate) keyboard_offset: f32,
+    // Rects painted on background layers (panels, for example) during the last frame.
+    pub(crate) background_rects: Vec<egui::Rect>,
 }
 
 impl EguiContext {
@@ -340,6 +1369,29 @@ impl EguiContext {
     pub fn get_mut(&mut self) -> &mut egui::Context {
         &mut self.ctx
     }
+
+    /// Returns the layer that would capture a click at the given position (in egui points),
+    /// or `None` if the position is over empty space and the click would pass through.
+    ///
+    /// Windows and other areas capture clicks if the position is inside their rects.
+    /// Panels and background areas capture clicks only where something was painted, so empty
+    /// space and transparent frames (such as `egui::Frame::none()` of a
+    /// [`egui::CentralPanel`]) don't capture input.
+    ///
+    /// The result is based on the last frame that was processed by the
+    /// [`EguiSet::EndPass`] system.
+    #[must_use]
+    pub fn hit_test(&mut self, pos: egui::Pos2) -> Option<egui::LayerId> {
+        if let Some(layer_id) = self.ctx.layer_id_at(pos) {
+            if layer_id.order != egui::Order::Background {
+                return Some(layer_id);
+            }
+        }
+        self.background_rects
+            .iter()
+            .any(|rect| rect.contains(pos))
+            .then(egui::LayerId::background)
+    }
 }
 
 #[derive(SystemParam)]
@@ -354,21 +1406,28 @@ pub struct EguiContexts<'w, 's> {
             &'static mut EguiContext,
             Option<&'static PrimaryWindow>,
         ),
-        With<Window>,
     >,
     #[cfg(feature = "render")]
     user_textures: ResMut<'w, EguiUserTextures>,
+    fonts: Option<ResMut<'w, fonts::EguiFonts>>,
 }
 
 impl<'w, 's> EguiContexts<'w, 's> {
     /// Egui context of the primary window.
+    ///
+    /// Panics if there's no primary window (for example, if it was closed, or
+    /// [`bevy::window::WindowPlugin::primary_window`] is set to [`None`]) or its context isn't
+    /// initialized yet. Use [`EguiContexts::try_ctx_mut`] if that's expected.
     #[must_use]
+    #[track_caller]
     pub fn ctx_mut(&mut self) -> &mut egui::Context {
         self.try_ctx_mut()
-            .expect("`EguiContexts::ctx_mut` was called for an uninitialized context (primary window), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)")
+            .expect("`EguiContexts::ctx_mut` was called, but there's no primary window or its context is uninitialized: make sure the primary window exists and your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)")
     }
 
     /// Fallible variant of [`EguiContexts::ctx_mut`].
+    ///
+    /// Returns [`None`] if there's no primary window or its context isn't initialized yet.
     #[must_use]
     pub fn try_ctx_mut(&mut self) -> Option<&mut egui::Context> {
         self.q
@@ -382,7 +1441,8 @@ impl<'w, 's> EguiContexts<'w, 's> {
             })
     }
 
-    /// Egui context of a specific window.
+    /// Egui context of a specific window (or an entity with [`EguiRenderToTexture`] or
+    /// [`EguiSecondaryContext`]).
     #[must_use]
     pub fn ctx_for_window_mut(&mut self, window: Entity) -> &mut egui::Context {
         self.try_ctx_for_window_mut(window)
@@ -404,6 +1464,32 @@ impl<'w, 's> EguiContexts<'w, 's> {
             })
     }
 
+    /// Returns an id salt unique to a window.
+    ///
+    /// Contexts of different windows are isolated from each other, but if you copy
+    /// [`egui::Memory`] between them or otherwise rely on ids being unique across windows,
+    /// you can use this salt to scope the UI shared between windows:
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_egui::{egui, EguiContexts};
+    ///
+    /// fn ui_system(mut contexts: EguiContexts, windows: Query<Entity, With<Window>>) {
+    ///     for window in windows.iter() {
+    ///         let id_salt = EguiContexts::window_id_salt(window);
+    ///         egui::Window::new("Settings")
+    ///             .id(id_salt.with("Settings"))
+    ///             .show(contexts.ctx_for_window_mut(window), |ui| {
+    ///                 ui.push_id(id_salt, |ui| ui.label("Shared UI"));
+    ///             });
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn window_id_salt(window: Entity) -> egui::Id {
+        egui::Id::new(("bevy_egui_window", window))
+    }
+
     /// Allows to get multiple contexts at the same time. This function is useful when you want
     /// to get multiple window contexts without using the `immutable_ctx` feature.
     #[track_caller]
@@ -427,9 +1513,10 @@ impl<'w, 's> EguiContexts<'w, 's> {
     /// instead of busy-waiting.
     #[cfg(feature = "immutable_ctx")]
     #[must_use]
+    #[track_caller]
     pub fn ctx(&self) -> &egui::Context {
         self.try_ctx()
-            .expect("`EguiContexts::ctx` was called for an uninitialized context (primary window), make sure your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)")
+            .expect("`EguiContexts::ctx` was called, but there's no primary window or its context is uninitialized: make sure the primary window exists and your system is run after [`EguiSet::InitContexts`] (or [`EguiStartupSet::InitContexts`] for startup systems)")
     }
 
     /// Fallible variant of [`EguiContexts::ctx`].
@@ -507,20 +1594,122 @@ impl<'w, 's> EguiContexts<'w, 's> {
         self.user_textures.add_image(image)
     }
 
-    /// Removes the image handle and an Egui texture id associated with it.
+    /// Adds an image that's sampled with `sampler` instead of its own sampler.
+    /// See [`EguiUserTextures::add_image_with_sampler`].
     #[cfg(feature = "render")]
-    #[track_caller]
-    pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
-        self.user_textures.remove_image(image)
+    pub fn add_image_with_sampler(
+        &mut self,
+        image: Handle<Image>,
+        sampler: ImageSamplerDescriptor,
+    ) -> egui::TextureId {
+        self.user_textures.add_image_with_sampler(image, sampler)
     }
 
-    /// Returns an associated Egui texture id.
+    /// Adds an animated image, see [`EguiUserTextures::add_animated_image`].
+    #[cfg(feature = "render")]
+    pub fn add_animated_image(&mut self, image: EguiAnimatedImage) -> egui::TextureId {
+        self.user_textures.add_animated_image(image)
+    }
+
+    /// Removes an animated image, see [`EguiUserTextures::remove_animated_image`].
+    #[cfg(feature = "render")]
+    pub fn remove_animated_image(&mut self, id: egui::TextureId) -> Option<EguiAnimatedImage> {
+        self.user_textures.remove_animated_image(id)
+    }
+
+    /// Removes the image handle and an Egui texture id associated with it.
+    #[cfg(feature = "render")]
+    #[track_caller]
+    pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
+        self.user_textures.remove_image(image)
+    }
+
+    /// Returns an associated Egui texture id.
     #[cfg(feature = "render")]
     #[must_use]
     #[track_caller]
     pub fn image_id(&self, image: &Handle<Image>) -> Option<egui::TextureId> {
         self.user_textures.image_id(image)
     }
+
+    /// Returns an iterator over all the added images and their Egui texture ids.
+    #[cfg(feature = "render")]
+    pub fn user_textures(&self) -> impl Iterator<Item = (&Handle<Image>, egui::TextureId)> + '_ {
+        self.user_textures.iter()
+    }
+
+    /// Removes all the image handles and Egui texture ids associated with them.
+    /// See [`EguiUserTextures::remove_all_images`].
+    #[cfg(feature = "render")]
+    pub fn free_all_user_textures(&mut self) -> Vec<egui::TextureId> {
+        self.user_textures.remove_all_images()
+    }
+
+    /// Adds a font loaded with the asset server to a family of every context, once it's loaded.
+    /// See [`EguiFonts::add_font`](fonts::EguiFonts::add_font).
+    ///
+    /// Panics if the app has no [`AssetPlugin`](bevy::asset::AssetPlugin).
+    #[track_caller]
+    pub fn add_font(
+        &mut self,
+        name: impl Into<String>,
+        handle: Handle<fonts::EguiFont>,
+        family: egui::FontFamily,
+    ) {
+        self.fonts
+            .as_mut()
+            .expect("`EguiContexts::add_font` was called, but the app has no `AssetPlugin`")
+            .add_font(name, handle, family);
+    }
+}
+
+/// Provides Egui contexts to code that has `&mut World` instead of [`EguiContexts`], such as
+/// exclusive systems, custom [`Command`](bevy::ecs::world::Command)s or `World::resource_scope`
+/// closures.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, EguiWorldExt};
+///
+/// fn exclusive_ui_system(world: &mut World) {
+///     let entity_count = world.entities().len();
+///     let Some(ctx) = world.egui_context_mut() else {
+///         return;
+///     };
+///     egui::Window::new("Stats").show(ctx, |ui| {
+///         ui.label(format!("Entities: {entity_count}"));
+///     });
+/// }
+/// ```
+///
+/// The contexts are mutably borrowed from the world, so clone the returned
+/// [`egui::Context`] if the UI needs to access the world as well.
+pub trait EguiWorldExt {
+    /// Egui context of the primary window, see [`EguiContexts::try_ctx_mut`].
+    ///
+    /// Returns [`None`] if there's no primary window or its context isn't initialized yet.
+    fn egui_context_mut(&mut self) -> Option<&mut egui::Context>;
+
+    /// Egui context of a specific window (or an entity with [`EguiRenderToTexture`] or
+    /// [`EguiSecondaryContext`]), see [`EguiContexts::try_ctx_for_window_mut`].
+    ///
+    /// Returns [`None`] if the entity doesn't exist or its context isn't initialized yet.
+    fn egui_context_for_window_mut(&mut self, window: Entity) -> Option<&mut egui::Context>;
+}
+
+impl EguiWorldExt for World {
+    fn egui_context_mut(&mut self) -> Option<&mut egui::Context> {
+        let window = self
+            .query_filtered::<Entity, (With<EguiContext>, With<PrimaryWindow>)>()
+            .iter(self)
+            .next()?;
+        self.egui_context_for_window_mut(window)
+    }
+
+    fn egui_context_for_window_mut(&mut self, window: Entity) -> Option<&mut egui::Context> {
+        self.get_mut::<EguiContext>(window)
+            .map(|ctx| ctx.into_inner().get_mut())
+    }
 }
 
 /// A resource for storing `bevy_egui` user textures.
@@ -529,6 +1718,73 @@ impl<'w, 's> EguiContexts<'w, 's> {
 pub struct EguiUserTextures {
     textures: HashMap<Handle<Image>, u64>,
     last_texture_id: u64,
+    // Samplers that replace the ones of the images, by texture id. Each descriptor gets a new
+    // revision, so that the render world knows when to recreate a sampler.
+    samplers: HashMap<u64, (u64, ImageSamplerDescriptor)>,
+    last_sampler_revision: u64,
+    animations: HashMap<u64, AnimatedTexture>,
+}
+
+/// A sequence of images shown one after another as a single Egui texture, such as the frames of
+/// a spinner or of an animated emote (see [`EguiUserTextures::add_animated_image`]).
+///
+/// The animation loops, starting from the moment it's added.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg(feature = "render")]
+pub struct EguiAnimatedImage {
+    /// The frames and how long each of them is shown.
+    pub frames: Vec<(Handle<Image>, std::time::Duration)>,
+}
+
+#[cfg(feature = "render")]
+impl EguiAnimatedImage {
+    /// Creates an animation from its frames and their durations.
+    #[must_use]
+    pub fn new(frames: impl IntoIterator<Item = (Handle<Image>, std::time::Duration)>) -> Self {
+        Self {
+            frames: frames.into_iter().collect(),
+        }
+    }
+
+    /// The duration of a single loop of the animation.
+    #[must_use]
+    pub fn duration(&self) -> std::time::Duration {
+        self.frames.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// Returns the index of the frame shown at a time since the animation started, and how long
+    /// it's shown after that time. Returns `None` if the animation has no frames.
+    #[must_use]
+    pub fn frame_at(&self, elapsed: std::time::Duration) -> Option<(usize, std::time::Duration)> {
+        let duration = self.duration();
+        if duration.is_zero() {
+            return (!self.frames.is_empty()).then_some((0, std::time::Duration::MAX));
+        }
+        let mut time =
+            std::time::Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64);
+        for (index, (_, frame_duration)) in self.frames.iter().enumerate() {
+            if time < *frame_duration {
+                return Some((index, *frame_duration - time));
+            }
+            time -= *frame_duration;
+        }
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg(feature = "render")]
+struct AnimatedTexture {
+    image: EguiAnimatedImage,
+    elapsed: std::time::Duration,
+    frame: usize,
+}
+
+#[cfg(feature = "render")]
+impl AnimatedTexture {
+    fn current_frame(&self) -> Option<&Handle<Image>> {
+        self.image.frames.get(self.frame).map(|(image, _)| image)
+    }
 }
 
 #[cfg(feature = "render")]
@@ -550,13 +1806,100 @@ impl EguiUserTextures {
         egui::TextureId::User(id)
     }
 
+    /// Same as [`EguiUserTextures::add_image`], but Egui samples the image with `sampler`
+    /// instead of the image's own sampler ([`ImageSamplerDescriptor::nearest`] keeps
+    /// pixel art sharp, for example).
+    ///
+    /// Calling it again for an image that has already been added replaces its sampler.
+    pub fn add_image_with_sampler(
+        &mut self,
+        image: Handle<Image>,
+        sampler: ImageSamplerDescriptor,
+    ) -> egui::TextureId {
+        let texture_id = self.add_image(image);
+        if let egui::TextureId::User(id) = texture_id {
+            self.last_sampler_revision += 1;
+            self.samplers
+                .insert(id, (self.last_sampler_revision, sampler));
+        }
+        texture_id
+    }
+
     /// Removes the image handle and an Egui texture id associated with it.
     pub fn remove_image(&mut self, image: &Handle<Image>) -> Option<egui::TextureId> {
         let id = self.textures.remove(image);
         log::debug!("Remove image (id: {:?}, handle: {:?})", id, image);
+        if let Some(id) = id {
+            self.samplers.remove(&id);
+        }
         id.map(egui::TextureId::User)
     }
 
+    /// Adds an animated image, which is shown with a single Egui texture id whose image changes
+    /// as the animation plays.
+    ///
+    /// The frames are advanced during the [`EguiSet::EndPass`] system, and the contexts that
+    /// have drawn the texture during the last frame are repainted when it changes.
+    pub fn add_animated_image(&mut self, image: EguiAnimatedImage) -> egui::TextureId {
+        let id = self.last_texture_id;
+        log::debug!("Add a new animated image (id: {})", id);
+        self.last_texture_id += 1;
+        self.animations.insert(
+            id,
+            AnimatedTexture {
+                image,
+                elapsed: std::time::Duration::ZERO,
+                frame: 0,
+            },
+        );
+        egui::TextureId::User(id)
+    }
+
+    /// Removes an animated image, returns its frames.
+    pub fn remove_animated_image(&mut self, id: egui::TextureId) -> Option<EguiAnimatedImage> {
+        let egui::TextureId::User(id) = id else {
+            return None;
+        };
+        let animation = self.animations.remove(&id)?;
+        log::debug!("Remove animated image (id: {})", id);
+        self.samplers.remove(&id);
+        Some(animation.image)
+    }
+
+    /// Returns the animated image of a texture id.
+    #[must_use]
+    pub fn animated_image(&self, id: egui::TextureId) -> Option<&EguiAnimatedImage> {
+        let egui::TextureId::User(id) = id else {
+            return None;
+        };
+        self.animations.get(&id).map(|animation| &animation.image)
+    }
+
+    /// Returns the frame of an animated image that is currently shown.
+    #[must_use]
+    pub fn animated_image_frame(&self, id: egui::TextureId) -> Option<&Handle<Image>> {
+        let egui::TextureId::User(id) = id else {
+            return None;
+        };
+        self.animations.get(&id)?.current_frame()
+    }
+
+    // Advances the animations, returns the ids of the ones whose frame has changed.
+    fn advance_animations(&mut self, delta: std::time::Duration) -> Vec<u64> {
+        let mut changed = Vec::new();
+        for (id, animation) in &mut self.animations {
+            animation.elapsed += delta;
+            let Some((frame, _)) = animation.image.frame_at(animation.elapsed) else {
+                continue;
+            };
+            if animation.frame != frame {
+                animation.frame = frame;
+                changed.push(*id);
+            }
+        }
+        changed
+    }
+
     /// Returns an associated Egui texture id.
     #[must_use]
     pub fn image_id(&self, image: &Handle<Image>) -> Option<egui::TextureId> {
@@ -564,6 +1907,36 @@ impl EguiUserTextures {
             .get(image)
             .map(|&id| egui::TextureId::User(id))
     }
+
+    /// Returns an iterator over all the added images and their Egui texture ids.
+    pub fn iter(&self) -> impl Iterator<Item = (&Handle<Image>, egui::TextureId)> + '_ {
+        self.textures
+            .iter()
+            .map(|(handle, &id)| (handle, egui::TextureId::User(id)))
+    }
+
+    /// Removes all the image handles (including animated images) and Egui texture ids associated
+    /// with them, returns the removed ids.
+    ///
+    /// Strong handles get dropped, so images that aren't used anywhere else are freed
+    /// (together with their GPU textures). Texture ids are never reused: drawing with a removed id
+    /// doesn't sample anything, and adding the same image again assigns it a new id.
+    ///
+    /// Note that this doesn't affect textures managed by Egui (such as the font atlas),
+    /// which are freed when Egui requests it.
+    pub fn remove_all_images(&mut self) -> Vec<egui::TextureId> {
+        log::debug!(
+            "Remove all images ({} in total)",
+            self.textures.len() + self.animations.len()
+        );
+        self.samplers.clear();
+        self.textures
+            .drain()
+            .map(|(_, id)| id)
+            .chain(self.animations.drain().map(|(id, _)| id))
+            .map(egui::TextureId::User)
+            .collect()
+    }
 }
 
 /// Stores physical size and scale factor, is used as a helper to calculate logical size.
@@ -578,6 +1951,75 @@ pub struct WindowSize {
     pub scale_factor: f32,
 }
 
+/// Describes how the Egui screen of a context maps onto the physical pixels of its window.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct EguiScreenLayout {
+    /// Egui's native pixels per point, which doesn't include the zoom factor.
+    pub native_pixels_per_point: f32,
+    /// Physical pixels per Egui point.
+    pub pixels_per_point: f32,
+    /// Offset of the Egui screen from the window's top left corner, in physical pixels.
+    pub offset: bevy::math::Vec2,
+    /// Size of the Egui screen in points.
+    pub screen_size: bevy::math::Vec2,
+}
+
+impl EguiScreenLayout {
+    /// Calculates the layout, see [`EguiContextSettings::fixed_logical_size`].
+    pub(crate) fn new(
+        window_size: WindowSize,
+        egui_scale_factor: f32,
+        ui_zoom_factor: f32,
+        fixed_logical_size: Option<bevy::math::Vec2>,
+    ) -> Self {
+        let physical_size =
+            bevy::math::Vec2::new(window_size.physical_width, window_size.physical_height);
+        match fixed_logical_size {
+            Some(fixed_logical_size) => {
+                let native_pixels_per_point = (physical_size / fixed_logical_size).min_element();
+                Self {
+                    native_pixels_per_point,
+                    pixels_per_point: native_pixels_per_point * ui_zoom_factor,
+                    // Whole pixels keep the UI crisp.
+                    offset: ((physical_size - fixed_logical_size * native_pixels_per_point) / 2.0)
+                        .floor(),
+                    screen_size: fixed_logical_size / ui_zoom_factor,
+                }
+            }
+            None => {
+                let native_pixels_per_point = window_size.scale_factor * egui_scale_factor;
+                let pixels_per_point = native_pixels_per_point * ui_zoom_factor;
+                Self {
+                    native_pixels_per_point,
+                    pixels_per_point,
+                    offset: bevy::math::Vec2::ZERO,
+                    screen_size: physical_size / pixels_per_point,
+                }
+            }
+        }
+    }
+
+    /// Converts a position in the window's logical pixels to Egui points.
+    pub(crate) fn to_egui_pos(
+        self,
+        window_size: &WindowSize,
+        position: bevy::math::Vec2,
+    ) -> egui::Pos2 {
+        let position = (position * window_size.scale_factor - self.offset) / self.pixels_per_point;
+        egui::pos2(position.x, position.y)
+    }
+
+    /// Converts a position in Egui points to the window's logical pixels.
+    pub(crate) fn to_window_pos(
+        self,
+        window_size: &WindowSize,
+        position: egui::Pos2,
+    ) -> bevy::math::Vec2 {
+        (bevy::math::Vec2::new(position.x, position.y) * self.pixels_per_point + self.offset)
+            / window_size.scale_factor
+    }
+}
+
 impl WindowSize {
     fn new(physical_width: f32, physical_height: f32, scale_factor: f32) -> Self {
         Self {
@@ -614,28 +2056,125 @@ pub enum EguiStartupSet {
 }
 
 /// The `bevy_egui` plugin system sets.
+///
+/// The sets of the main app run in this order during a frame:
+///
+/// 1. [`EguiSet::InitContexts`] and [`EguiSet::ProcessInput`] in [`PreUpdate`].
+/// 2. [`EguiSet::BeginPass`] at the end of [`PreUpdate`].
+/// 3. [`EguiSet::UserUi`] in [`Update`].
+/// 4. [`EguiSet::EndPass`] in [`PostUpdate`].
+///
+/// With [`EguiPlugin::with_ui_schedule`], the last three are chained in the chosen schedule
+/// instead.
 #[derive(SystemSet, Clone, Hash, Debug, Eq, PartialEq)]
 pub enum EguiSet {
     /// Initializes Egui contexts for newly created windows.
     InitContexts,
     /// Reads Egui inputs (keyboard, mouse, etc) and writes them into the [`EguiInput`] resource.
     ///
+    /// Runs after [`EguiSet::InitContexts`] and Bevy's [`InputSystem`].
     /// To modify the input, you can hook your system like this:
     ///
-    /// `system.after(EguiSet::ProcessInput).before(EguiSet::BeginFrame)`.
+    /// `system.after(EguiSet::ProcessInput).before(EguiSet::BeginPass)`.
     ProcessInput,
-    /// Begins the `egui` frame.
-    BeginFrame,
-    /// Processes the [`EguiOutput`] resource.
-    ProcessOutput,
+    /// Routes window input to [`EguiSecondaryContext`]s and begins the `egui` pass (after
+    /// running [`EguiInputHooks`]).
+    ///
+    /// Runs after [`EguiSet::ProcessInput`].
+    BeginPass,
+    /// The set for systems creating the UI, configured in the UI schedule ([`Update`] unless
+    /// set with [`EguiPlugin::with_ui_schedule`]).
+    ///
+    /// Runs after [`EguiSet::BeginPass`] and before [`EguiSet::EndPass`]. The systems in the set
+    /// aren't reported as ambiguous with each other, so order them explicitly if the order
+    /// matters (for panels, for example).
+    UserUi,
+    /// Ends the `egui` pass and processes the [`EguiOutput`] resource.
+    EndPass,
+    /// The systems of the render app, in its `ExtractSchedule` and `Render` schedules (during
+    /// `RenderSet::Prepare` and `RenderSet::Queue`).
+    Render,
+}
+
+#[allow(non_upper_case_globals)]
+impl EguiSet {
+    /// Renamed to [`EguiSet::BeginPass`].
+    #[deprecated(note = "renamed to `EguiSet::BeginPass`")]
+    pub const BeginFrame: Self = Self::BeginPass;
+    /// Renamed to [`EguiSet::EndPass`].
+    #[deprecated(note = "renamed to `EguiSet::EndPass`")]
+    pub const ProcessOutput: Self = Self::EndPass;
 }
 
 impl Plugin for EguiPlugin {
+    // Duplicates are ignored in `build` instead of making Bevy panic.
+    fn is_unique(&self) -> bool {
+        false
+    }
+
     fn build(&self, app: &mut App) {
+        if app.is_plugin_added::<Self>() {
+            log::warn!("`EguiPlugin` has already been added to the app, ignoring the duplicate");
+            return;
+        }
+
+        // Passes run in the UI schedule if it's set, otherwise they span the frame.
+        let ui_schedule = self
+            .ui_schedule
+            .filter(|ui_schedule| *ui_schedule != Update.intern());
+        let (begin_pass_schedule, end_pass_schedule) = match ui_schedule {
+            Some(ui_schedule) => (ui_schedule, ui_schedule),
+            None => (PreUpdate.intern(), PostUpdate.intern()),
+        };
+        app.configure_sets(
+            PreUpdate,
+            (
+                EguiSet::InitContexts,
+                EguiSet::ProcessInput.after(InputSystem),
+            )
+                .chain(),
+        );
+        app.configure_sets(
+            begin_pass_schedule,
+            EguiSet::BeginPass.after(EguiSet::ProcessInput),
+        );
+        app.configure_sets(
+            ui_schedule.unwrap_or(Update.intern()),
+            EguiSet::UserUi
+                .after(EguiSet::BeginPass)
+                .before(EguiSet::EndPass)
+                .ambiguous_with(EguiSet::UserUi),
+        );
+
         app.register_type::<EguiSettings>();
+        app.register_type::<EguiContextSettings>();
+        app.add_event::<EguiRotateGestureEvent>();
+        app.add_event::<EguiUnmappedKeyEvent>();
+        app.add_event::<EguiInteractionEvent>();
+        app.add_event::<EguiCopyEvent>();
+        app.add_event::<EguiOpenUrlEvent>();
+        app.add_event::<EguiTextCursorMoved>();
+        app.add_event::<EguiWantsRepaint>();
+        #[cfg(feature = "render")]
+        app.add_event::<EguiClipboardImagePaste>();
+        #[cfg(feature = "render")]
+        app.add_event::<EguiScreenshotCaptured>();
 
         let world = app.world_mut();
         world.init_resource::<EguiSettings>();
+        world.init_resource::<EguiInputHooks>();
+        world.init_resource::<EguiPassHooks>();
+        world.init_resource::<EguiInputFilter>();
+        world.init_resource::<EguiCursorIconMapping>();
+        world.init_resource::<EguiWantsInput>();
+        world.init_resource::<EguiBlockPointerInput>();
+        world.init_resource::<EguiAccessibilityZoom>();
+        world.init_resource::<EguiVirtualKeyboard>();
+        world.init_resource::<EguiTessellationCaches>();
+        #[cfg(feature = "render")]
+        world.init_resource::<EguiScreenshots>();
+        #[cfg(feature = "render")]
+        world.init_resource::<EguiCapturedScreenshots>();
         #[cfg(feature = "render")]
         world.init_resource::<EguiManagedTextures>();
         #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
@@ -646,6 +2185,14 @@ impl Plugin for EguiPlugin {
             web_sys_unstable_apis
         ))]
         world.init_non_send_resource::<web_clipboard::SubscribedEvents>();
+        #[cfg(target_arch = "wasm32")]
+        world.init_resource::<web_keyboard::WebKeyboardSettings>();
+        #[cfg(target_arch = "wasm32")]
+        world.init_non_send_resource::<web_file_drop::WebFileDropListener>();
+        #[cfg(target_arch = "wasm32")]
+        world.init_non_send_resource::<web_keyboard::WebKeyboardListener>();
+        #[cfg(target_arch = "wasm32")]
+        world.init_non_send_resource::<text_agent::TextAgent>();
         #[cfg(feature = "render")]
         world.init_resource::<EguiUserTextures>();
         #[cfg(feature = "render")]
@@ -655,11 +2202,23 @@ impl Plugin for EguiPlugin {
         #[cfg(feature = "render")]
         app.add_plugins(ExtractResourcePlugin::<EguiSettings>::default());
         #[cfg(feature = "render")]
+        app.add_plugins(ExtractResourcePlugin::<EguiAccessibilityZoom>::default());
+        #[cfg(feature = "render")]
         app.add_plugins(ExtractComponentPlugin::<EguiContext>::default());
         #[cfg(feature = "render")]
         app.add_plugins(ExtractComponentPlugin::<WindowSize>::default());
         #[cfg(feature = "render")]
         app.add_plugins(ExtractComponentPlugin::<EguiRenderOutput>::default());
+        #[cfg(feature = "render")]
+        app.add_plugins(ExtractComponentPlugin::<EguiContextSettings>::default());
+        #[cfg(feature = "render")]
+        app.add_plugins(ExtractComponentPlugin::<EguiRenderToTexture>::default());
+        #[cfg(feature = "render")]
+        app.add_plugins(ExtractComponentPlugin::<EguiSecondaryContext>::default());
+        #[cfg(feature = "render")]
+        app.add_plugins(ExtractComponentPlugin::<viewports::EguiViewport>::default());
+        #[cfg(feature = "render")]
+        app.add_plugins(ExtractComponentPlugin::<EguiScreenshot>::default());
 
         #[cfg(all(
             feature = "manage_clipboard",
@@ -667,11 +2226,26 @@ impl Plugin for EguiPlugin {
             web_sys_unstable_apis
         ))]
         app.add_systems(PreStartup, web_clipboard::startup_setup_web_events);
+        #[cfg(all(
+            feature = "render",
+            feature = "manage_clipboard",
+            not(target_os = "android"),
+            not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+        ))]
+        app.add_systems(
+            PreUpdate,
+            process_clipboard_image_paste_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system),
+        );
         app.add_systems(
             PreStartup,
             (
+                #[cfg(feature = "render")]
+                update_camera_contexts_system,
                 setup_new_windows_system,
                 apply_deferred,
+                update_accessibility_zoom_system,
                 update_window_contexts_system,
             )
                 .chain()
@@ -680,48 +2254,208 @@ impl Plugin for EguiPlugin {
         app.add_systems(
             PreUpdate,
             (
+                #[cfg(feature = "render")]
+                update_camera_contexts_system,
                 setup_new_windows_system,
                 apply_deferred,
+                update_accessibility_zoom_system,
                 update_window_contexts_system,
+                #[cfg(feature = "wake_on_repaint")]
+                set_repaint_callbacks_system,
             )
                 .chain()
                 .in_set(EguiSet::InitContexts),
         );
+        // Assets can't be registered without `AssetPlugin`.
+        if app.world().contains_resource::<AssetServer>() {
+            app.init_asset::<fonts::EguiFont>()
+                .init_asset_loader::<fonts::EguiFontLoader>()
+                .init_resource::<fonts::EguiFonts>()
+                .add_systems(
+                    PreUpdate,
+                    fonts::update_egui_fonts_system
+                        .in_set(EguiSet::InitContexts)
+                        .after(update_window_contexts_system),
+                );
+            #[cfg(feature = "theme")]
+            app.init_asset::<theme::EguiTheme>()
+                .init_asset_loader::<theme::EguiThemeLoader>()
+                .add_systems(
+                    PreUpdate,
+                    theme::apply_egui_themes_system
+                        .in_set(EguiSet::InitContexts)
+                        .after(update_window_contexts_system),
+                );
+            #[cfg(feature = "image_loader")]
+            app.init_resource::<image_loader::EguiImageLoader>()
+                .add_systems(
+                    PreUpdate,
+                    image_loader::update_image_loader_system
+                        .in_set(EguiSet::InitContexts)
+                        .after(update_window_contexts_system),
+                );
+        }
+        #[cfg(feature = "http_loader")]
+        app.init_resource::<http_loader::EguiHttpLoader>()
+            .add_systems(
+                PreUpdate,
+                http_loader::add_http_loader_system
+                    .in_set(EguiSet::InitContexts)
+                    .after(update_window_contexts_system),
+            );
+        #[cfg(feature = "persistence")]
+        app.add_systems(
+            PreUpdate,
+            persistence::load_egui_memory_system
+                .in_set(EguiSet::InitContexts)
+                .after(update_window_contexts_system),
+        )
+        .add_systems(
+            PostUpdate,
+            persistence::save_egui_memory_system.after(EguiSet::EndPass),
+        );
+        #[cfg(feature = "recording")]
+        app.add_systems(
+            PreUpdate,
+            (
+                recording::play_back_egui_input_system,
+                recording::record_egui_input_system,
+            )
+                .chain()
+                .after(EguiSet::ProcessInput)
+                .before(EguiSet::BeginPass),
+        );
+        app.add_systems(
+            PreUpdate,
+            process_input_system.in_set(EguiSet::ProcessInput),
+        );
+        #[cfg(feature = "render")]
+        app.add_systems(
+            PreUpdate,
+            (process_screenshots_system, send_captured_screenshots_system)
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system),
+        );
+        app.add_systems(
+            PreUpdate,
+            process_gamepad_navigation_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system),
+        );
+        app.add_systems(
+            PreUpdate,
+            process_file_drag_and_drop_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system),
+        );
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(
+            PreUpdate,
+            web_file_drop::process_web_file_drop_system
+                .in_set(EguiSet::ProcessInput)
+                .after(process_input_system),
+        );
+        #[cfg(feature = "render")]
         app.add_systems(
             PreUpdate,
-            process_input_system
+            process_mesh_pointer_input_system
                 .in_set(EguiSet::ProcessInput)
-                .after(InputSystem)
-                .after(EguiSet::InitContexts),
+                .after(process_input_system),
+        );
+        #[cfg(feature = "accesskit")]
+        app.add_event::<bevy::a11y::ActionRequest>()
+            .add_systems(
+                PreStartup,
+                accessibility::disable_bevy_accessibility_updates_system,
+            )
+            .add_systems(
+                PreUpdate,
+                accessibility::process_accessibility_input_system
+                    .in_set(EguiSet::ProcessInput)
+                    .after(process_input_system),
+            )
+            .add_systems(
+                PostUpdate,
+                accessibility::update_accessibility_system
+                    .after(EguiSet::EndPass)
+                    .before(bevy::a11y::AccessibilitySystem::Update),
+            );
+        app.add_systems(
+            begin_pass_schedule,
+            (
+                route_secondary_context_input_system,
+                begin_frame_system,
+                prewarm_font_atlas_system,
+                run_begin_pass_hooks_system,
+            )
+                .chain()
+                .in_set(EguiSet::BeginPass),
+        );
+        app.add_systems(
+            end_pass_schedule,
+            (
+                run_end_pass_hooks_system,
+                process_output_system,
+                update_keyboard_offset_system,
+                update_egui_wants_input_system,
+                viewports::update_viewport_windows_system,
+                #[cfg(feature = "render")]
+                advance_animated_images_system,
+            )
+                .chain()
+                .in_set(EguiSet::EndPass),
+        );
+        app.add_systems(Last, finish_tessellation_system);
+        #[cfg(feature = "render")]
+        app.add_systems(Last, input::block_pointer_input_under_bevy_ui_system);
+        #[cfg(target_arch = "wasm32")]
+        app.add_systems(
+            PostUpdate,
+            web_keyboard::update_web_keyboard_system.after(update_egui_wants_input_system),
         );
+        #[cfg(target_arch = "wasm32")]
         app.add_systems(
             PreUpdate,
-            begin_frame_system
-                .in_set(EguiSet::BeginFrame)
-                .after(EguiSet::ProcessInput),
+            (
+                text_agent::process_text_agent_input_system,
+                text_agent::update_virtual_keyboard_system,
+            )
+                .in_set(EguiSet::ProcessInput)
+                .before(process_input_system),
+        )
+        .add_systems(
+            PostUpdate,
+            text_agent::update_text_agent_system.after(EguiSet::EndPass),
         );
+        #[cfg(all(feature = "android_soft_keyboard", target_os = "android"))]
         app.add_systems(
             PostUpdate,
-            process_output_system.in_set(EguiSet::ProcessOutput),
+            update_android_soft_keyboard_system.after(EguiSet::EndPass),
         );
         #[cfg(feature = "render")]
         app.add_systems(
             PostUpdate,
-            update_egui_textures_system.after(EguiSet::ProcessOutput),
+            update_egui_textures_system.after(EguiSet::EndPass),
         );
         #[cfg(feature = "render")]
         app.add_systems(Last, free_egui_textures_system)
             .add_systems(
                 Render,
-                render_systems::prepare_egui_transforms_system.in_set(RenderSet::Prepare),
+                render_systems::prepare_egui_transforms_system
+                    .in_set(RenderSet::Prepare)
+                    .in_set(EguiSet::Render),
             )
             .add_systems(
                 Render,
-                render_systems::queue_bind_groups_system.in_set(RenderSet::Queue),
+                render_systems::queue_bind_groups_system
+                    .in_set(RenderSet::Queue)
+                    .in_set(EguiSet::Render),
             )
             .add_systems(
                 Render,
-                render_systems::queue_pipelines_system.in_set(RenderSet::Queue),
+                render_systems::queue_pipelines_system
+                    .in_set(RenderSet::Queue)
+                    .in_set(EguiSet::Render),
             );
 
         #[cfg(feature = "render")]
@@ -730,26 +2464,54 @@ impl Plugin for EguiPlugin {
 
     #[cfg(feature = "render")]
     fn finish(&self, app: &mut App) {
+        let captured_screenshots = app.world().resource::<EguiCapturedScreenshots>().clone();
         if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
+            // `finish` is called for duplicate plugins as well.
+            if render_app
+                .world()
+                .contains_resource::<egui_node::EguiPipeline>()
+            {
+                return;
+            }
+
             render_app
                 .init_resource::<egui_node::EguiPipeline>()
                 .init_resource::<SpecializedRenderPipelines<EguiPipeline>>()
                 .init_resource::<EguiTransforms>()
+                .init_resource::<egui_node::EguiBufferPool>()
+                .insert_resource(render_systems::EguiScreenshotCaptures::new(
+                    captured_screenshots,
+                ))
                 .add_systems(
                     ExtractSchedule,
-                    render_systems::setup_new_windows_render_system,
+                    render_systems::setup_new_windows_render_system.in_set(EguiSet::Render),
+                )
+                .add_systems(
+                    Render,
+                    render_systems::prepare_egui_transforms_system
+                        .in_set(RenderSet::Prepare)
+                        .in_set(EguiSet::Render),
                 )
                 .add_systems(
                     Render,
-                    render_systems::prepare_egui_transforms_system.in_set(RenderSet::Prepare),
+                    render_systems::queue_bind_groups_system
+                        .in_set(RenderSet::Queue)
+                        .in_set(EguiSet::Render),
                 )
                 .add_systems(
                     Render,
-                    render_systems::queue_bind_groups_system.in_set(RenderSet::Queue),
+                    render_systems::queue_pipelines_system
+                        .in_set(RenderSet::Queue)
+                        .in_set(EguiSet::Render),
                 )
                 .add_systems(
                     Render,
-                    render_systems::queue_pipelines_system.in_set(RenderSet::Queue),
+                    (
+                        render_systems::free_unused_buffers_system,
+                        render_systems::read_screenshots_system,
+                    )
+                        .in_set(RenderSet::Cleanup)
+                        .in_set(EguiSet::Render),
                 );
         }
     }
@@ -771,8 +2533,59 @@ pub struct EguiContextQuery {
     pub egui_output: &'static mut EguiOutput,
     /// Stores physical size of the window and its scale factor.
     pub window_size: &'static mut WindowSize,
+    /// Settings of the context.
+    pub context_settings: Ref<'static, EguiContextSettings>,
     /// [`Window`] component.
     pub window: &'static mut Window,
+    /// Regions the pointer passes through, if any.
+    pub pointer_passthrough: Option<&'static EguiPointerPassthrough>,
+}
+
+/// Queries all the Egui related components of contexts rendered to textures
+/// (see [`EguiRenderToTexture`]).
+#[cfg(feature = "render")]
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct EguiRenderToTextureContextQuery {
+    /// Context entity.
+    pub entity: Entity,
+    /// Egui context associated with the entity.
+    pub ctx: &'static mut EguiContext,
+    /// Encapsulates [`egui::RawInput`].
+    pub egui_input: &'static mut EguiInput,
+    /// Egui shapes and textures delta.
+    pub render_output: &'static mut EguiRenderOutput,
+    /// Encapsulates [`egui::PlatformOutput`].
+    pub egui_output: &'static mut EguiOutput,
+    /// Stores physical size of the image (the scale factor is always `1.0`).
+    pub window_size: &'static mut WindowSize,
+    /// Settings of the context.
+    pub context_settings: Ref<'static, EguiContextSettings>,
+    /// The image the context is rendered to.
+    pub render_to_texture: &'static EguiRenderToTexture,
+}
+
+/// Queries all the Egui related components of secondary contexts (see
+/// [`EguiSecondaryContext`]).
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct EguiSecondaryContextQuery {
+    /// Context entity.
+    pub entity: Entity,
+    /// Egui context associated with the entity.
+    pub ctx: &'static mut EguiContext,
+    /// Encapsulates [`egui::RawInput`].
+    pub egui_input: &'static mut EguiInput,
+    /// Egui shapes and textures delta.
+    pub render_output: &'static mut EguiRenderOutput,
+    /// Encapsulates [`egui::PlatformOutput`].
+    pub egui_output: &'static mut EguiOutput,
+    /// Stores physical size of the window the context is drawn to and its scale factor.
+    pub window_size: &'static mut WindowSize,
+    /// Settings of the context.
+    pub context_settings: Ref<'static, EguiContextSettings>,
+    /// The window the context is drawn to and its order.
+    pub secondary_context: &'static EguiSecondaryContext,
 }
 
 /// Contains textures allocated and painted by Egui.
@@ -789,18 +2602,28 @@ pub struct EguiManagedTexture {
     pub color_image: egui::ColorImage,
 }
 
-/// Adds bevy_egui components to newly created windows.
+/// Adds bevy_egui components to newly created windows (and entities with
+/// [`EguiRenderToTexture`] or [`EguiSecondaryContext`]).
 pub fn setup_new_windows_system(
     mut commands: Commands,
     new_windows: Query<Entity, (Added<Window>, Without<EguiContext>)>,
+    #[cfg(feature = "render")] new_render_to_texture: Query<
+        Entity,
+        (Added<EguiRenderToTexture>, Without<EguiContext>),
+    >,
+    new_secondary_contexts: Query<Entity, (Added<EguiSecondaryContext>, Without<EguiContext>)>,
 ) {
-    for window in new_windows.iter() {
+    let new_windows = new_windows.iter().chain(new_secondary_contexts.iter());
+    #[cfg(feature = "render")]
+    let new_windows = new_windows.chain(new_render_to_texture.iter());
+    for window in new_windows {
         commands.entity(window).insert((
             EguiContext::default(),
             EguiRenderOutput::default(),
             EguiInput::default(),
             EguiOutput::default(),
             WindowSize::default(),
+            EguiContextSettings::default(),
         ));
     }
 }
@@ -808,21 +2631,54 @@ pub fn setup_new_windows_system(
 /// Updates textures painted by Egui.
 #[cfg(feature = "render")]
 pub fn update_egui_textures_system(
-    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<Window>>,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput)>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
     mut image_assets: ResMut<Assets<Image>>,
+    render_device: Option<Res<RenderDevice>>,
+    egui_settings: Res<EguiSettings>,
+    mut uploaded_premultiplied: Local<bool>,
 ) {
+    let max_texture_side = render_device
+        .as_ref()
+        .map(|render_device| render_device.limits().max_texture_dimension_2d as usize);
+    let premultiplied = egui_settings.premultiplied_textures;
+
+    // Re-upload the existing textures if the alpha mode has changed.
+    if *uploaded_premultiplied != premultiplied {
+        *uploaded_premultiplied = premultiplied;
+        for managed_texture in egui_managed_textures.values() {
+            if let Some(image) = image_assets.get_mut(&managed_texture.handle) {
+                image.data =
+                    egui_node::color_image_as_bytes(&managed_texture.color_image, premultiplied);
+            }
+        }
+    }
+
     for (window_id, mut egui_render_output) in egui_render_output.iter_mut() {
         let set_textures = std::mem::take(&mut egui_render_output.textures_delta.set);
 
         for (texture_id, image_delta) in set_textures {
-            let color_image = egui_node::as_color_image(image_delta.image);
-
             let texture_id = match texture_id {
                 egui::TextureId::Managed(texture_id) => texture_id,
                 egui::TextureId::User(_) => continue,
             };
 
+            let [width, height] = image_delta.image.size();
+            if let Some(max_texture_side) = max_texture_side {
+                if width > max_texture_side || height > max_texture_side {
+                    log::error!(
+                        "Failed to upload an Egui texture (id: {:?}): its size ({}x{}) exceeds the maximum texture dimension supported by the GPU ({})",
+                        texture_id,
+                        width,
+                        height,
+                        max_texture_side
+                    );
+                    continue;
+                }
+            }
+
+            let color_image = egui_node::as_color_image(image_delta.image);
+
             let sampler = ImageSampler::Descriptor(
                 egui_node::texture_options_as_sampler_descriptor(&image_delta.options),
             );
@@ -833,15 +2689,19 @@ pub fn update_egui_textures_system(
                 {
                     // TODO: when bevy supports it, only update the part of the texture that changes.
                     update_image_rect(&mut managed_texture.color_image, pos, &color_image);
-                    let image =
-                        egui_node::color_image_as_bevy_image(&managed_texture.color_image, sampler);
+                    let image = egui_node::color_image_as_bevy_image(
+                        &managed_texture.color_image,
+                        sampler,
+                        premultiplied,
+                    );
                     managed_texture.handle = image_assets.add(image);
                 } else {
                     log::warn!("Partial update of a missing texture (id: {:?})", texture_id);
                 }
             } else {
                 // Full update.
-                let image = egui_node::color_image_as_bevy_image(&color_image, sampler);
+                let image =
+                    egui_node::color_image_as_bevy_image(&color_image, sampler, premultiplied);
                 let handle = image_assets.add(image);
                 egui_managed_textures.insert(
                     (window_id, texture_id),
@@ -866,7 +2726,7 @@ pub fn update_egui_textures_system(
 #[cfg(feature = "render")]
 fn free_egui_textures_system(
     mut egui_user_textures: ResMut<EguiUserTextures>,
-    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput), With<Window>>,
+    mut egui_render_output: Query<(Entity, &mut EguiRenderOutput)>,
     mut egui_managed_textures: ResMut<EguiManagedTextures>,
     mut image_assets: ResMut<Assets<Image>>,
     mut image_events: EventReader<AssetEvent<Image>>,
@@ -895,16 +2755,107 @@ mod tests {
     use super::*;
     use bevy::{
         app::PluginGroup,
+        ecs::system::Res,
         render::{settings::WgpuSettings, RenderPlugin},
         winit::WinitPlugin,
         DefaultPlugins,
     };
 
+    fn headless_plugins() -> bevy::app::PluginGroupBuilder {
+        DefaultPlugins
+            .set(RenderPlugin {
+                render_creation: bevy::render::settings::RenderCreation::Automatic(WgpuSettings {
+                    backends: None,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .build()
+            .disable::<WinitPlugin>()
+    }
+
+    /// Returns an app with [`EguiPlugin`] and its primary window.
+    pub(crate) fn test_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(headless_plugins()).add_plugins(EguiPlugin);
+        let window = app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(app.world());
+        (app, window)
+    }
+
+    /// Collects the input events of every context, with the window of the context.
+    pub(crate) fn capture_input_events(
+        app: &mut App,
+    ) -> std::sync::Arc<std::sync::Mutex<Vec<(Entity, egui::Event)>>> {
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_clone = events.clone();
+        app.world_mut()
+            .resource_mut::<EguiInputHooks>()
+            .add(move |window, raw_input| {
+                events_clone
+                    .lock()
+                    .unwrap()
+                    .extend(raw_input.events.iter().map(|event| (window, event.clone())));
+            });
+        events
+    }
+
     #[test]
     fn test_readme_deps() {
         version_sync::assert_markdown_deps_updated!("README.md");
     }
 
+    #[test]
+    fn test_readme_features() {
+        let readme = include_str!("../README.md");
+        let lines_after = |text: &'static str, heading: &str| -> Vec<&'static str> {
+            text.lines()
+                .skip_while(|line| *line != heading)
+                .skip(1)
+                .skip_while(|line| line.is_empty())
+                .take_while(|line| !line.is_empty())
+                .collect()
+        };
+
+        // The list of the crate documentation matches the README one.
+        let crate_features: Vec<_> = include_str!("lib.rs")
+            .lines()
+            .skip_while(|line| *line != "//! **Features:**")
+            .skip(1)
+            .map_while(|line| line.strip_prefix("//! "))
+            .collect();
+        assert_eq!(crate_features, lines_after(readme, "**Features:**"));
+
+        // The Cargo features of the README are the ones of `Cargo.toml`, with their descriptions.
+        let manifest = include_str!("../Cargo.toml");
+        let default_features = manifest
+            .lines()
+            .find_map(|line| line.strip_prefix("default = ["))
+            .unwrap();
+        let mut cargo_features = Vec::new();
+        let mut description = Vec::new();
+        for line in lines_after(manifest, "[features]") {
+            if let Some(line) = line.strip_prefix("## ") {
+                description.push(line);
+            } else if let Some((name, _)) = line.split_once(" = ") {
+                if name == "default" {
+                    continue;
+                }
+                let default = if default_features.contains(&format!("\"{name}\"")) {
+                    " (enabled by default)"
+                } else {
+                    ""
+                };
+                assert!(!description.is_empty(), "`{name}` has no description");
+                cargo_features.push(format!("- `{name}`{default}: {}", description.join(" ")));
+                description.clear();
+            }
+        }
+        assert_eq!(cargo_features, lines_after(readme, "## Cargo features"));
+    }
+
     #[test]
     fn test_headless_mode() {
         App::new()
@@ -925,4 +2876,1692 @@ mod tests {
             .add_plugins(EguiPlugin)
             .update();
     }
+
+    #[test]
+    fn test_world_ext() {
+        use bevy::app::Update;
+
+        #[derive(Resource, Default)]
+        struct WindowShown(bool);
+
+        fn exclusive_ui_system(world: &mut World) {
+            let Some(ctx) = world.egui_context_mut() else {
+                return;
+            };
+            let shown = egui::Window::new("Exclusive")
+                .show(ctx, |ui| ui.label("UI"))
+                .is_some();
+            world.resource_mut::<WindowShown>().0 = shown;
+        }
+
+        let (mut app, primary_window) = test_app();
+        app.init_resource::<WindowShown>()
+            .add_systems(Update, exclusive_ui_system);
+        app.update();
+        assert!(app.world().resource::<WindowShown>().0);
+        assert!(app
+            .world_mut()
+            .egui_context_for_window_mut(primary_window)
+            .is_some());
+        let entity = app.world_mut().spawn_empty().id();
+        assert!(app
+            .world_mut()
+            .egui_context_for_window_mut(entity)
+            .is_none());
+
+        app.world_mut().entity_mut(primary_window).despawn();
+        assert!(app.world_mut().egui_context_mut().is_none());
+    }
+
+    #[test]
+    fn test_keys_ignored_during_ime_composition() {
+        use bevy::{
+            input::{
+                keyboard::{Key, KeyCode, KeyboardInput},
+                ButtonState,
+            },
+            window::Ime,
+        };
+
+        let (mut app, window) = test_app();
+        let events = capture_input_events(&mut app);
+        app.update();
+        let text_events = || {
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, event)| matches!(event, egui::Event::Text(_)))
+                .map(|(_, event)| event.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let press = |app: &mut App, logical_key: Key| {
+            app.world_mut().send_event(KeyboardInput {
+                key_code: KeyCode::KeyA,
+                logical_key,
+                state: ButtonState::Pressed,
+                window,
+            });
+            app.update();
+        };
+        let a = || Key::Character("a".into());
+
+        press(&mut app, Key::Process);
+        app.world_mut().send_event(Ime::Preedit {
+            window,
+            value: "あ".to_string(),
+            cursor: Some((0, 3)),
+        });
+        app.update();
+        press(&mut app, a());
+        assert!(text_events().is_empty());
+
+        app.world_mut().send_event(Ime::Commit {
+            window,
+            value: "あ".to_string(),
+        });
+        app.update();
+        press(&mut app, a());
+        assert_eq!(text_events(), [egui::Event::Text("a".to_string())]);
+    }
+
+    #[test]
+    fn test_physical_key_fallback() {
+        use bevy::input::{
+            keyboard::{Key, KeyCode, KeyboardInput},
+            ButtonState,
+        };
+
+        let (mut app, window) = test_app();
+        let events = capture_input_events(&mut app);
+        app.update();
+
+        // The C key on a Russian layout.
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::KeyC,
+            logical_key: Key::Character("с".into()),
+            state: ButtonState::Pressed,
+            window,
+        });
+        app.update();
+        let events = events.lock().unwrap();
+        let key_events = events
+            .iter()
+            .filter(|(_, event)| matches!(event, egui::Event::Key { .. }))
+            .map(|(_, event)| event)
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            key_events.as_slice(),
+            [egui::Event::Key {
+                key: egui::Key::C,
+                physical_key: Some(egui::Key::C),
+                pressed: true,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_pointer_input_to_hovered_window_only() {
+        use bevy::input::{
+            mouse::{MouseButton, MouseButtonInput},
+            ButtonState,
+        };
+
+        let (mut app, background_window) = test_app();
+        app.insert_resource(EguiSettings {
+            pointer_input_to_hovered_window_only: true,
+            ..Default::default()
+        });
+        let mut window = Window::default();
+        window.set_cursor_position(Some(bevy::math::Vec2::new(10.0, 10.0)));
+        let front_window = app.world_mut().spawn(window).id();
+        app.update();
+
+        let events = capture_input_events(&mut app);
+        for window in [background_window, front_window] {
+            app.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state: ButtonState::Pressed,
+                window,
+            });
+        }
+        app.update();
+
+        let clicked_windows = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, event)| matches!(event, egui::Event::PointerButton { .. }))
+            .map(|(window, _)| *window)
+            .collect::<Vec<_>>();
+        assert_eq!(clicked_windows, vec![front_window]);
+    }
+
+    #[test]
+    fn test_drag_out_of_window() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonState,
+            },
+            window::{CursorLeft, CursorMoved},
+        };
+
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            pointer_input_to_hovered_window_only: true,
+            ..Default::default()
+        });
+        let position = bevy::math::Vec2::new(10.0, 10.0);
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(Some(position));
+        app.update();
+        let events = capture_input_events(&mut app);
+        let button_input = |state| MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        };
+        let take_pointer_events = || {
+            std::mem::take(&mut *events.lock().unwrap())
+                .into_iter()
+                .filter_map(|(_, event)| match event {
+                    egui::Event::PointerGone => Some("gone"),
+                    egui::Event::PointerButton { pressed: true, .. } => Some("pressed"),
+                    egui::Event::PointerButton { pressed: false, .. } => Some("released"),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        app.world_mut().send_event(CursorMoved {
+            window,
+            position,
+            delta: None,
+        });
+        app.world_mut()
+            .send_event(button_input(ButtonState::Pressed));
+        app.update();
+        assert_eq!(take_pointer_events(), ["pressed"]);
+
+        // The drag goes on outside the window, and ends when the button is released there.
+        app.world_mut()
+            .get_mut::<Window>(window)
+            .unwrap()
+            .set_cursor_position(None);
+        app.world_mut().send_event(CursorLeft { window });
+        app.update();
+        assert!(take_pointer_events().is_empty());
+        app.world_mut()
+            .send_event(button_input(ButtonState::Released));
+        app.update();
+        assert_eq!(take_pointer_events(), ["released", "gone"]);
+
+        // Without a button held, the pointer is gone as soon as it leaves.
+        app.world_mut().send_event(CursorLeft { window });
+        app.update();
+        assert_eq!(take_pointer_events(), ["gone"]);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_remove_all_images() {
+        let mut user_textures = EguiUserTextures::default();
+        let first_image = Handle::<Image>::weak_from_u128(1);
+        let second_image = Handle::<Image>::weak_from_u128(2);
+        let first_id = user_textures.add_image(first_image.clone());
+        user_textures.add_image(second_image);
+        assert_eq!(user_textures.iter().count(), 2);
+
+        assert_eq!(user_textures.remove_all_images().len(), 2);
+        assert_eq!(user_textures.iter().count(), 0);
+        assert_eq!(user_textures.image_id(&first_image), None);
+        assert_ne!(user_textures.add_image(first_image), first_id);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_animated_images() {
+        use std::time::Duration;
+
+        let mut user_textures = EguiUserTextures::default();
+        let frames = [
+            Handle::<Image>::weak_from_u128(1),
+            Handle::<Image>::weak_from_u128(2),
+        ];
+        let id = user_textures.add_animated_image(EguiAnimatedImage::new([
+            (frames[0].clone(), Duration::from_millis(100)),
+            (frames[1].clone(), Duration::from_millis(50)),
+        ]));
+        assert_eq!(user_textures.animated_image_frame(id), Some(&frames[0]));
+        // The frames aren't added as images of their own.
+        assert_eq!(user_textures.image_id(&frames[0]), None);
+
+        assert!(user_textures
+            .advance_animations(Duration::from_millis(60))
+            .is_empty());
+        let egui::TextureId::User(raw_id) = id else {
+            panic!("expected a user texture id");
+        };
+        assert_eq!(
+            user_textures.advance_animations(Duration::from_millis(60)),
+            vec![raw_id]
+        );
+        assert_eq!(user_textures.animated_image_frame(id), Some(&frames[1]));
+        // The animation loops.
+        user_textures.advance_animations(Duration::from_millis(40));
+        assert_eq!(user_textures.animated_image_frame(id), Some(&frames[0]));
+
+        assert!(user_textures.remove_animated_image(id).is_some());
+        assert_eq!(user_textures.animated_image_frame(id), None);
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_add_image_with_sampler() {
+        let mut user_textures = EguiUserTextures::default();
+        let image = Handle::<Image>::weak_from_u128(1);
+        let id =
+            user_textures.add_image_with_sampler(image.clone(), ImageSamplerDescriptor::nearest());
+        let egui::TextureId::User(raw_id) = id else {
+            panic!("expected a user texture id");
+        };
+        let (revision, _) = user_textures.samplers[&raw_id];
+
+        // The same image keeps its id, and gets a new sampler revision.
+        assert_eq!(
+            user_textures.add_image_with_sampler(image.clone(), ImageSamplerDescriptor::linear()),
+            id
+        );
+        assert_ne!(user_textures.samplers[&raw_id].0, revision);
+
+        user_textures.remove_image(&image);
+        assert!(user_textures.samplers.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "render")]
+    fn test_premultiplied_textures() {
+        let fill = egui::Color32::from_rgba_unmultiplied(255, 0, 0, 128);
+        let (mut app, window) = test_app();
+        app.add_systems(Update, move |mut contexts: EguiContexts| {
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none().fill(fill))
+                .show(contexts.ctx_mut(), |ui| ui.label("Text"));
+        });
+        // Returns a semi-transparent texel of the font atlas with the uploaded bytes of it, and
+        // whether the panel is painted with premultiplied vertex colors.
+        fn frame(
+            app: &mut App,
+            window: Entity,
+            fill: egui::Color32,
+        ) -> (egui::Color32, [u8; 4], bool) {
+            app.update();
+            let managed_textures = app.world().resource::<EguiManagedTextures>();
+            let font_texture = &managed_textures.0[&(window, 0)];
+            let image = app
+                .world()
+                .resource::<Assets<Image>>()
+                .get(&font_texture.handle)
+                .unwrap();
+            let (index, color) = font_texture
+                .color_image
+                .pixels
+                .iter()
+                .enumerate()
+                .find(|(_, color)| color.a() > 0 && color.a() < 255)
+                .unwrap();
+            let color = *color;
+            let texel = image.data[index * 4..index * 4 + 4].try_into().unwrap();
+
+            let mut render_outputs = app.world_mut().query::<&EguiRenderOutput>();
+            let has_fill = render_outputs
+                .get(app.world(), window)
+                .unwrap()
+                .paint_jobs
+                .iter()
+                .any(|paint_job| match &paint_job.primitive {
+                    egui::epaint::Primitive::Mesh(mesh) => {
+                        mesh.vertices.iter().any(|vertex| vertex.color == fill)
+                    }
+                    egui::epaint::Primitive::Callback(_) => false,
+                });
+            (color, texel, has_fill)
+        }
+
+        // Egui textures are unmultiplied to be premultiplied in the shader.
+        let (color, texel, has_fill) = frame(&mut app, window, fill);
+        assert_eq!(texel, color.to_srgba_unmultiplied());
+        assert_ne!(texel, color.to_array());
+        assert!(has_fill);
+
+        // Existing textures are uploaded again, vertex colors are premultiplied either way.
+        app.world_mut()
+            .resource_mut::<EguiSettings>()
+            .premultiplied_textures = true;
+        let (color, texel, has_fill) = frame(&mut app, window, fill);
+        assert_eq!(texel, color.to_array());
+        assert!(has_fill);
+    }
+
+    #[test]
+    fn test_minimized_window() {
+        fn paint_jobs(app: &mut App) -> usize {
+            let mut render_outputs = app.world_mut().query::<&EguiRenderOutput>();
+            render_outputs.single(app.world()).paint_jobs.len()
+        }
+        fn set_physical_resolution(app: &mut App, window: Entity, width: u32, height: u32) {
+            let mut window = app.world_mut().get_mut::<Window>(window).unwrap();
+            window.resolution.set_physical_resolution(width, height);
+        }
+
+        let (mut app, window) = test_app();
+        app.add_systems(bevy::app::Update, animating_ui_system);
+        count_redraw_requests(&mut app);
+        app.update();
+        app.update();
+        assert!(paint_jobs(&mut app) > 0);
+        let screen_rect = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .get_mut()
+            .screen_rect();
+
+        // A minimized window keeps its screen rect, isn't painted to and doesn't request redraws,
+        // even though its UI keeps asking for repaints.
+        set_physical_resolution(&mut app, window, 0, 0);
+        app.update();
+        let redraws = redraw_requests(&app);
+        app.update();
+        app.update();
+        assert_eq!(paint_jobs(&mut app), 0);
+        assert_eq!(redraws, redraw_requests(&app));
+        let (window_size, mut ctx) = app
+            .world_mut()
+            .query::<(&WindowSize, &mut EguiContext)>()
+            .get_mut(app.world_mut(), window)
+            .unwrap();
+        assert!(window_size.physical_width > 0.0);
+        assert_eq!(ctx.get_mut().screen_rect(), screen_rect);
+
+        // Restoring the window tessellates its UI again.
+        set_physical_resolution(&mut app, window, 1280, 720);
+        app.update();
+        assert!(paint_jobs(&mut app) > 0);
+        assert!(redraw_requests(&app) > redraws);
+    }
+
+    #[test]
+    fn test_hit_test() {
+        fn ui_system(mut contexts: EguiContexts) {
+            let ctx = contexts.ctx_mut();
+            egui::SidePanel::left("left_panel")
+                .exact_width(100.0)
+                .show(ctx, |ui| ui.label("Panel"));
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none())
+                .show(ctx, |_ui| {});
+            egui::Window::new("Window")
+                .fixed_pos(egui::pos2(400.0, 300.0))
+                .show(ctx, |ui| ui.label("Window"));
+        }
+
+        let (mut app, _) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        app.update();
+        app.update();
+
+        let mut ctx = app
+            .world_mut()
+            .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+            .single_mut(app.world_mut());
+        assert_eq!(
+            ctx.hit_test(egui::pos2(50.0, 300.0)),
+            Some(egui::LayerId::background())
+        );
+        assert_eq!(ctx.hit_test(egui::pos2(300.0, 100.0)), None);
+        let window_layer = ctx.hit_test(egui::pos2(410.0, 310.0)).unwrap();
+        assert_eq!(window_layer.order, egui::Order::Middle);
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_render_to_texture_context() {
+        let (mut app, _) = test_app();
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(Image::new_fill(
+                bevy::render::render_resource::Extent3d {
+                    width: 256,
+                    height: 128,
+                    depth_or_array_layers: 1,
+                },
+                bevy::render::render_resource::TextureDimension::D2,
+                &[0; 4],
+                bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb,
+                bevy::render::render_asset::RenderAssetUsages::all(),
+            ));
+        let entity = app.world_mut().spawn(EguiRenderToTexture(image)).id();
+        app.update();
+        app.update();
+
+        let (window_size, mut ctx) = app
+            .world_mut()
+            .query::<(&WindowSize, &mut EguiContext)>()
+            .get_mut(app.world_mut(), entity)
+            .unwrap();
+        assert_eq!(*window_size, WindowSize::new(256.0, 128.0, 1.0));
+        assert_eq!(
+            ctx.get_mut().screen_rect(),
+            egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(256.0, 128.0))
+        );
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_render_target_scale() {
+        let settings = |render_scale, msaa_samples| EguiContextSettings {
+            render_scale,
+            msaa_samples,
+            ..Default::default()
+        };
+        assert_eq!(settings(1.0, 1).render_target_scale(), None);
+        assert_eq!(settings(0.5, 1).render_target_scale(), Some(0.5));
+        // Multisampled contexts are resolved into a full resolution texture.
+        assert_eq!(settings(1.0, 4).render_target_scale(), Some(1.0));
+        assert_eq!(settings(0.5, 4).render_target_scale(), Some(0.5));
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_camera_pass() {
+        use crate::{
+            egui_node::EguiCameraPass,
+            render_systems::{bevy_ui_pass, camera_pass_edges},
+        };
+        use bevy::{
+            core_pipeline::{
+                core_2d::graph::{Core2d, Node2d},
+                core_3d::graph::{Core3d, Node3d},
+            },
+            render::render_graph::{EmptyNode, RenderGraph, RenderLabel, RenderSubGraph},
+        };
+
+        let mut settings = EguiContextSettings::default();
+        assert_eq!(settings.resolved_camera_pass(), None);
+        settings.draw_below_bevy_ui = true;
+        assert_eq!(
+            settings.resolved_camera_pass(),
+            Some(EguiCameraPass::BeforeBevyUi)
+        );
+        settings.draw_before_tonemapping = true;
+        assert_eq!(
+            settings.resolved_camera_pass(),
+            Some(EguiCameraPass::BeforeTonemapping)
+        );
+        settings.camera_pass = Some(EguiCameraPass::BeforeBloom);
+        assert_eq!(
+            settings.resolved_camera_pass(),
+            Some(EguiCameraPass::BeforeBloom)
+        );
+        settings.depth_test_distance = Some(2.0);
+        assert_eq!(
+            settings.resolved_camera_pass(),
+            Some(EguiCameraPass::MainPass)
+        );
+
+        assert!(camera_pass_edges(Core3d.intern(), EguiCameraPass::MainPass, None).is_some());
+        // 2D cameras don't share their depth textures.
+        assert!(camera_pass_edges(Core2d.intern(), EguiCameraPass::MainPass, None).is_none());
+        assert!(
+            camera_pass_edges(Core2d.intern(), EguiCameraPass::BeforeTonemapping, None).is_some()
+        );
+        let custom = EguiCameraPass::Custom {
+            after: Node3d::Tonemapping.intern(),
+            before: Node3d::Fxaa.intern(),
+        };
+        assert_eq!(
+            camera_pass_edges(Core3d.intern(), custom, None),
+            Some((
+                vec![Node3d::Tonemapping.intern()],
+                vec![Node3d::Fxaa.intern()]
+            ))
+        );
+
+        // The pass of `bevy_ui` is found by its name.
+        #[derive(RenderLabel, Clone, Debug, PartialEq, Eq, Hash)]
+        struct UiPass;
+        let mut sub_graph = RenderGraph::default();
+        sub_graph.add_node(Node2d::EndMainPassPostProcessing, EmptyNode);
+        assert_eq!(bevy_ui_pass(&sub_graph), None);
+        sub_graph.add_node(UiPass, EmptyNode);
+        let ui_pass = bevy_ui_pass(&sub_graph);
+        assert_eq!(ui_pass, Some(UiPass.intern()));
+        assert_eq!(
+            camera_pass_edges(Core2d.intern(), EguiCameraPass::BeforeBevyUi, ui_pass),
+            Some((
+                vec![Node2d::EndMainPassPostProcessing.intern()],
+                vec![UiPass.intern(), Node2d::Upscaling.intern()]
+            ))
+        );
+        assert_eq!(
+            camera_pass_edges(Core2d.intern(), EguiCameraPass::AfterBevyUi, ui_pass),
+            Some((
+                vec![Node2d::EndMainPassPostProcessing.intern(), UiPass.intern()],
+                vec![Node2d::Upscaling.intern()]
+            ))
+        );
+        // Without `bevy_ui`, the UI is drawn after post-processing.
+        assert_eq!(
+            camera_pass_edges(Core2d.intern(), EguiCameraPass::BeforeBevyUi, None),
+            camera_pass_edges(Core2d.intern(), EguiCameraPass::AfterPostProcessing, None)
+        );
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_mesh_pointer_input() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonState,
+            },
+            prelude::{Camera3dBundle, Mesh, Rectangle, ResMut, Resource, Transform, Vec2, Vec3},
+        };
+
+        #[derive(Resource)]
+        struct Panel {
+            entity: Entity,
+            clicks: u32,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, mut panel: ResMut<Panel>) {
+            egui::Area::new("button".into())
+                .fixed_pos(egui::pos2(96.0, 48.0))
+                .show(contexts.ctx_for_window_mut(panel.entity), |ui| {
+                    if ui
+                        .add_sized([64.0, 32.0], egui::Button::new("Hi"))
+                        .clicked()
+                    {
+                        panel.clicks += 1;
+                    }
+                });
+        }
+
+        let (mut app, _) = test_app();
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(Image::new_fill(
+                bevy::render::render_resource::Extent3d {
+                    width: 256,
+                    height: 128,
+                    depth_or_array_layers: 1,
+                },
+                bevy::render::render_resource::TextureDimension::D2,
+                &[0; 4],
+                bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb,
+                bevy::render::render_asset::RenderAssetUsages::all(),
+            ));
+        let mesh = app
+            .world_mut()
+            .resource_mut::<Assets<Mesh>>()
+            .add(Rectangle::new(2.0, 1.0));
+        // The camera looks at the center of the panel, which is the center of the texture.
+        let entity = app
+            .world_mut()
+            .spawn((
+                mesh,
+                bevy::prelude::SpatialBundle::default(),
+                EguiRenderToTexture(image),
+                EguiMeshPointerInput,
+            ))
+            .id();
+        app.world_mut().spawn(Camera3dBundle {
+            transform: Transform::from_xyz(0.0, 0.0, 3.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..Default::default()
+        });
+        app.insert_resource(Panel { entity, clicks: 0 })
+            .add_systems(bevy::app::Update, ui_system);
+        app.update();
+
+        let (window_entity, mut window) = app
+            .world_mut()
+            .query_filtered::<(Entity, &mut Window), With<PrimaryWindow>>()
+            .single_mut(app.world_mut());
+        let center = Vec2::new(window.width(), window.height()) / 2.0;
+        window.set_cursor_position(Some(center));
+        app.update();
+        assert_eq!(
+            app.world_mut()
+                .get_mut::<EguiContext>(entity)
+                .unwrap()
+                .get_mut()
+                .pointer_latest_pos(),
+            Some(egui::pos2(128.0, 64.0))
+        );
+
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            app.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window: window_entity,
+            });
+            app.update();
+        }
+        assert_eq!(app.world().resource::<Panel>().clicks, 1);
+
+        // The pointer leaves the context once the cursor doesn't point at the mesh.
+        app.world_mut()
+            .get_mut::<Window>(window_entity)
+            .unwrap()
+            .set_cursor_position(Some(Vec2::ZERO));
+        app.update();
+        assert_eq!(
+            app.world_mut()
+                .get_mut::<EguiContext>(entity)
+                .unwrap()
+                .get_mut()
+                .pointer_latest_pos(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_secondary_context_input_routing() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonState,
+            },
+            prelude::{ResMut, Resource},
+            window::CursorMoved,
+        };
+
+        #[derive(Resource, Default)]
+        struct Clicks {
+            window: u32,
+            overlay: u32,
+        }
+
+        #[derive(Component)]
+        struct Overlay;
+
+        fn clickable_area(ctx: &egui::Context, rect: egui::Rect) -> bool {
+            egui::Area::new("area".into())
+                .fixed_pos(rect.min)
+                .show(ctx, |ui| {
+                    ui.allocate_rect(rect, egui::Sense::click()).clicked()
+                })
+                .inner
+        }
+
+        fn ui_system(
+            mut contexts: EguiContexts,
+            overlay: Query<Entity, With<Overlay>>,
+            mut clicks: ResMut<Clicks>,
+        ) {
+            let rect = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(200.0, 200.0));
+            if clickable_area(contexts.ctx_mut(), rect) {
+                clicks.window += 1;
+            }
+            let overlay_ctx = contexts.ctx_for_window_mut(overlay.single());
+            if clickable_area(overlay_ctx, rect.translate(egui::vec2(100.0, 100.0))) {
+                clicks.overlay += 1;
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Clicks>()
+            .add_systems(bevy::app::Update, ui_system);
+        let overlay = app
+            .world_mut()
+            .spawn((Overlay, EguiSecondaryContext { window, order: 1 }))
+            .id();
+        app.update();
+        app.update();
+        let screen_rect = |app: &mut App, entity| {
+            app.world_mut()
+                .egui_context_for_window_mut(entity)
+                .unwrap()
+                .screen_rect()
+        };
+        assert_eq!(
+            screen_rect(&mut app, overlay),
+            screen_rect(&mut app, window)
+        );
+
+        let click_at = |app: &mut App, position: bevy::math::Vec2| {
+            app.world_mut().send_event(CursorMoved {
+                window,
+                position,
+                delta: None,
+            });
+            app.update();
+            for state in [ButtonState::Pressed, ButtonState::Released] {
+                app.world_mut().send_event(MouseButtonInput {
+                    button: MouseButton::Left,
+                    state,
+                    window,
+                });
+                app.update();
+            }
+            let clicks = app.world().resource::<Clicks>();
+            (clicks.window, clicks.overlay)
+        };
+
+        // The overlay is on top where the areas overlap.
+        assert_eq!(
+            click_at(&mut app, bevy::math::Vec2::new(150.0, 150.0)),
+            (0, 1)
+        );
+        assert_eq!(
+            click_at(&mut app, bevy::math::Vec2::new(50.0, 50.0)),
+            (1, 1)
+        );
+        assert_eq!(
+            click_at(&mut app, bevy::math::Vec2::new(250.0, 250.0)),
+            (1, 2)
+        );
+
+        // Drawn below the window's context, the overlay only gets the clicks outside of it.
+        app.world_mut()
+            .entity_mut(overlay)
+            .insert(EguiSecondaryContext { window, order: -1 });
+        assert_eq!(
+            click_at(&mut app, bevy::math::Vec2::new(150.0, 150.0)),
+            (2, 2)
+        );
+        assert_eq!(
+            click_at(&mut app, bevy::math::Vec2::new(250.0, 250.0)),
+            (2, 3)
+        );
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_camera_contexts() {
+        use bevy::{
+            input::{
+                mouse::{MouseButton, MouseButtonInput},
+                ButtonState,
+            },
+            math::UVec2,
+            prelude::{Camera, Camera2dBundle, ResMut, Resource},
+            render::camera::Viewport,
+            window::CursorMoved,
+        };
+
+        #[derive(Resource, Default)]
+        struct Clicks(Vec<isize>);
+
+        fn hud_system(
+            mut contexts: EguiContexts,
+            cameras: Query<(Entity, &Camera), With<EguiCameraContext>>,
+            mut clicks: ResMut<Clicks>,
+        ) {
+            for (entity, camera) in cameras.iter() {
+                let Some(ctx) = contexts.try_ctx_for_window_mut(entity) else {
+                    continue;
+                };
+                let rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(200.0, 200.0));
+                let clicked = egui::Area::new("hud".into())
+                    .fixed_pos(rect.min)
+                    .show(ctx, |ui| {
+                        ui.allocate_rect(rect, egui::Sense::click()).clicked()
+                    })
+                    .inner;
+                if clicked {
+                    clicks.0.push(camera.order);
+                }
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Clicks>()
+            .add_systems(bevy::app::Update, hud_system);
+        let cameras = [0, 1].map(|player| {
+            app.world_mut()
+                .spawn((
+                    Camera2dBundle {
+                        camera: Camera {
+                            order: player,
+                            viewport: Some(Viewport {
+                                physical_position: UVec2::new(player as u32 * 640, 0),
+                                physical_size: UVec2::new(640, 720),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    EguiCameraContext,
+                ))
+                .id()
+        });
+        app.update();
+        app.update();
+
+        for (order, camera) in cameras.into_iter().enumerate() {
+            assert_eq!(
+                app.world().get::<EguiSecondaryContext>(camera),
+                Some(&EguiSecondaryContext {
+                    window,
+                    order: order as i32
+                })
+            );
+            let screen_rect = app
+                .world_mut()
+                .egui_context_for_window_mut(camera)
+                .unwrap()
+                .screen_rect();
+            assert_eq!(screen_rect.size(), egui::vec2(640.0, 720.0));
+        }
+
+        let click_at = |app: &mut App, position: bevy::math::Vec2| {
+            app.world_mut().send_event(CursorMoved {
+                window,
+                position,
+                delta: None,
+            });
+            app.update();
+            for state in [ButtonState::Pressed, ButtonState::Released] {
+                app.world_mut().send_event(MouseButtonInput {
+                    button: MouseButton::Left,
+                    state,
+                    window,
+                });
+                app.update();
+            }
+            std::mem::take(&mut app.world_mut().resource_mut::<Clicks>().0)
+        };
+
+        // The positions are relative to the viewports.
+        assert_eq!(click_at(&mut app, bevy::math::Vec2::new(100.0, 100.0)), [0]);
+        assert_eq!(click_at(&mut app, bevy::math::Vec2::new(740.0, 100.0)), [1]);
+        assert!(click_at(&mut app, bevy::math::Vec2::new(300.0, 100.0)).is_empty());
+    }
+
+    #[test]
+    fn test_touch_gestures() {
+        use bevy::{
+            input::touch::{TouchInput, TouchPhase},
+            math::Vec2,
+            prelude::{ResMut, Resource},
+        };
+
+        #[derive(Resource, Default)]
+        struct Gestures {
+            zoom: f32,
+            scroll: egui::Vec2,
+        }
+
+        fn record_system(mut contexts: EguiContexts, mut gestures: ResMut<Gestures>) {
+            contexts.ctx_mut().input(|input| {
+                for event in &input.events {
+                    match event {
+                        egui::Event::Zoom(zoom) => gestures.zoom *= zoom,
+                        egui::Event::MouseWheel { delta, .. } => gestures.scroll += *delta,
+                        _ => {}
+                    }
+                }
+            });
+        }
+
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            synthesize_touch_gestures: true,
+            ..Default::default()
+        })
+        .init_resource::<Gestures>()
+        .add_systems(bevy::app::Update, record_system);
+        app.update();
+
+        let touch = |app: &mut App, id, phase, position| {
+            app.world_mut().send_event(TouchInput {
+                phase,
+                position,
+                window,
+                force: None,
+                id,
+            });
+        };
+        let reset = |app: &mut App| {
+            *app.world_mut().resource_mut::<Gestures>() = Gestures {
+                zoom: 1.0,
+                scroll: egui::Vec2::ZERO,
+            };
+        };
+
+        touch(&mut app, 0, TouchPhase::Started, Vec2::new(100.0, 100.0));
+        touch(&mut app, 1, TouchPhase::Started, Vec2::new(200.0, 100.0));
+        app.update();
+
+        // Pinching out.
+        reset(&mut app);
+        touch(&mut app, 1, TouchPhase::Moved, Vec2::new(300.0, 100.0));
+        app.update();
+        let gestures = app.world().resource::<Gestures>();
+        assert!((gestures.zoom - 2.0).abs() < 1e-4);
+
+        // Panning with both fingers.
+        reset(&mut app);
+        touch(&mut app, 0, TouchPhase::Moved, Vec2::new(100.0, 150.0));
+        touch(&mut app, 1, TouchPhase::Moved, Vec2::new(300.0, 150.0));
+        app.update();
+        let gestures = app.world().resource::<Gestures>();
+        assert!((gestures.zoom - 1.0).abs() < 1e-4);
+        assert!((gestures.scroll - egui::vec2(0.0, 50.0)).length() < 1e-4);
+
+        // Scrolling stops with the gesture.
+        touch(&mut app, 1, TouchPhase::Ended, Vec2::new(300.0, 150.0));
+        app.update();
+        reset(&mut app);
+        touch(&mut app, 0, TouchPhase::Moved, Vec2::new(100.0, 250.0));
+        app.update();
+        let gestures = app.world().resource::<Gestures>();
+        assert_eq!(gestures.scroll, egui::Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_ui_zoom_factor() {
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            scale_factor: 1.5,
+            ..Default::default()
+        });
+        app.update();
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .ui_zoom_factor = 2.0;
+        app.update();
+
+        let window_width = app.world().get::<Window>(window).unwrap().width();
+        let mut ctx = app.world_mut().get_mut::<EguiContext>(window).unwrap();
+        let ctx = ctx.get_mut();
+        assert_eq!(ctx.zoom_factor(), 2.0);
+        assert_eq!(ctx.native_pixels_per_point(), Some(1.5));
+        assert_eq!(ctx.pixels_per_point(), 3.0);
+        assert_eq!(ctx.screen_rect().width(), window_width / 3.0);
+    }
+
+    #[test]
+    fn test_context_scale_factor() {
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            scale_factor: 1.5,
+            ..Default::default()
+        });
+        app.update();
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .scale_factor = Some(2.0);
+        // The frame that has already begun is rendered with the previous layout.
+        let layout = app
+            .world()
+            .get::<EguiRenderOutput>(window)
+            .unwrap()
+            .screen_layout;
+        assert_eq!(layout.map(|layout| layout.pixels_per_point), Some(1.5));
+        app.update();
+
+        let layout = app
+            .world()
+            .get::<EguiRenderOutput>(window)
+            .unwrap()
+            .screen_layout;
+        assert_eq!(layout.map(|layout| layout.pixels_per_point), Some(2.0));
+        let mut ctx = app.world_mut().get_mut::<EguiContext>(window).unwrap();
+        assert_eq!(ctx.get_mut().native_pixels_per_point(), Some(2.0));
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let mesh = |rect: egui::Rect| {
+            let mut mesh = egui::Mesh::default();
+            mesh.add_colored_rect(rect, egui::Color32::WHITE);
+            egui::epaint::Primitive::Mesh(mesh)
+        };
+        let clip_rect = egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(10.0, 10.0));
+        let paint_jobs = [
+            egui::Rect::from_min_max(egui::pos2(1.0, 1.0), egui::pos2(5.0, 5.0)),
+            egui::Rect::from_min_max(egui::pos2(5.0, 5.0), egui::pos2(15.0, 15.0)),
+            egui::Rect::from_min_max(egui::pos2(20.0, 20.0), egui::pos2(30.0, 30.0)),
+        ]
+        .into_iter()
+        .map(|rect| egui::ClippedPrimitive {
+            clip_rect,
+            primitive: mesh(rect),
+        })
+        .chain(std::iter::once(egui::ClippedPrimitive {
+            clip_rect,
+            primitive: egui::epaint::Primitive::Mesh(egui::Mesh::default()),
+        }))
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            EguiRenderStats::from_paint_jobs(&paint_jobs),
+            EguiRenderStats {
+                meshes: 3,
+                vertices: 12,
+                partially_clipped_meshes: 1,
+                fully_clipped_meshes: 1,
+            }
+        );
+    }
+
+    /// Counts the redraws requested by the app, see [`count_redraw_requests`].
+    #[derive(Resource, Default)]
+    struct RedrawRequests(usize);
+
+    fn count_redraw_requests(app: &mut App) {
+        app.init_resource::<RedrawRequests>().add_systems(
+            bevy::app::Last,
+            |mut events: bevy::ecs::event::EventReader<bevy::window::RequestRedraw>,
+             mut redraw_requests: bevy::ecs::system::ResMut<RedrawRequests>| {
+                redraw_requests.0 += events.read().count();
+            },
+        );
+    }
+
+    fn redraw_requests(app: &App) -> usize {
+        app.world().resource::<RedrawRequests>().0
+    }
+
+    fn animating_ui_system(mut contexts: EguiContexts) {
+        let ctx = contexts.ctx_mut();
+        egui::CentralPanel::default().show(ctx, |ui| ui.label("Animating"));
+        ctx.request_repaint();
+    }
+
+    #[test]
+    fn test_min_redraw_interval() {
+        let animate = |min_redraw_interval| {
+            let (mut app, _) = test_app();
+            app.insert_resource(EguiSettings {
+                min_redraw_interval,
+                ..Default::default()
+            })
+            .add_systems(bevy::app::Update, animating_ui_system);
+            count_redraw_requests(&mut app);
+            for _ in 0..3 {
+                app.update();
+            }
+            redraw_requests(&app)
+        };
+
+        assert_eq!(animate(0.0), 3);
+        assert_eq!(animate(1000.0), 1);
+    }
+
+    #[test]
+    fn test_run_in_background() {
+        let animate = |run_in_background| {
+            let (mut app, window) = test_app();
+            app.insert_resource(EguiSettings {
+                run_in_background,
+                ..Default::default()
+            })
+            .add_systems(bevy::app::Update, animating_ui_system);
+            count_redraw_requests(&mut app);
+            app.world_mut().get_mut::<Window>(window).unwrap().focused = false;
+            for _ in 0..3 {
+                app.update();
+            }
+            redraw_requests(&app)
+        };
+
+        assert_eq!(animate(true), 3);
+        assert_eq!(animate(false), 0);
+    }
+
+    #[test]
+    fn test_throttled_redraw_is_requested_later() {
+        #[derive(Resource)]
+        struct Animating(bool);
+
+        fn ui_system(mut contexts: EguiContexts, animating: Res<Animating>) {
+            let ctx = contexts.ctx_mut();
+            egui::CentralPanel::default().show(ctx, |ui| ui.label("Animating"));
+            if animating.0 {
+                ctx.request_repaint();
+            }
+        }
+
+        let (mut app, _) = test_app();
+        app.insert_resource(EguiSettings {
+            min_redraw_interval: 1.0,
+            ..Default::default()
+        })
+        .insert_resource(Animating(true))
+        .add_systems(bevy::app::Update, ui_system);
+        count_redraw_requests(&mut app);
+        app.update();
+        // The last request of the animation comes before the interval has passed.
+        app.update();
+        app.world_mut().resource_mut::<Animating>().0 = false;
+        assert_eq!(redraw_requests(&app), 1);
+
+        app.update();
+        assert_eq!(redraw_requests(&app), 1);
+        std::thread::sleep(std::time::Duration::from_secs(1));
+        app.update();
+        assert_eq!(redraw_requests(&app), 2);
+        app.update();
+        assert_eq!(redraw_requests(&app), 2);
+    }
+
+    #[test]
+    fn test_idle_ui_isnt_repainted() {
+        fn ui_system(mut contexts: EguiContexts) {
+            let ctx = contexts.ctx_mut();
+            egui::CentralPanel::default().show(ctx, |ui| ui.label("Idle"));
+            ctx.request_repaint_after(std::time::Duration::from_secs(10));
+        }
+
+        let (mut app, _) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        count_redraw_requests(&mut app);
+        let paint_jobs_generation = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<&EguiRenderOutput, With<PrimaryWindow>>()
+                .single(app.world())
+                .paint_jobs_generation
+        };
+        // Egui repaints the first frames right away.
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world_mut().resource_mut::<RedrawRequests>().0 = 0;
+        let generation = paint_jobs_generation(&mut app);
+        for _ in 0..3 {
+            app.update();
+        }
+
+        // The delayed repaint doesn't request redraws, and the unchanged UI isn't tessellated.
+        assert_eq!(redraw_requests(&app), 0);
+        assert_eq!(paint_jobs_generation(&mut app), generation);
+        let render_output = app
+            .world_mut()
+            .query_filtered::<&EguiRenderOutput, With<PrimaryWindow>>()
+            .single(app.world());
+        assert!(!render_output.paint_jobs.is_empty());
+    }
+
+    #[test]
+    fn test_threaded_tessellation() {
+        fn ui_system(mut contexts: EguiContexts) {
+            // The fade-in depends on the frame times, which differ between the apps.
+            egui::Window::new("Tessellation")
+                .fade_in(false)
+                .show(contexts.ctx_mut(), |ui| {
+                    for i in 0..100 {
+                        ui.label(format!("Label {i}"));
+                    }
+                });
+        }
+
+        let render_stats = |threaded_tessellation: bool| {
+            let (mut app, _) = test_app();
+            app.insert_resource(EguiSettings {
+                threaded_tessellation,
+                ..Default::default()
+            })
+            .add_systems(bevy::app::Update, ui_system);
+            for _ in 0..3 {
+                app.update();
+            }
+            // The result of the last task is stored by the first frame that finds it ready.
+            for _ in 0..1000 {
+                if !has_pending_tasks(&app) {
+                    break;
+                }
+                app.update();
+            }
+            assert!(!has_pending_tasks(&app));
+            let render_output = app
+                .world_mut()
+                .query_filtered::<&EguiRenderOutput, With<PrimaryWindow>>()
+                .single(app.world());
+            render_output.stats
+        };
+        fn has_pending_tasks(app: &App) -> bool {
+            app.world()
+                .resource::<EguiTessellationCaches>()
+                .has_pending_tasks()
+        }
+
+        let stats = render_stats(true);
+        assert!(stats.vertices > 0);
+        assert_eq!(stats, render_stats(false));
+    }
+
+    #[test]
+    fn test_accessibility_zoom_factor() {
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            accessibility_zoom_factor: 2.0,
+            ..Default::default()
+        });
+        app.update();
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .ui_zoom_factor = 1.5;
+        app.update();
+        let mut ctx = app.world_mut().get_mut::<EguiContext>(window).unwrap();
+        assert_eq!(ctx.get_mut().zoom_factor(), 1.5);
+
+        // The zoom is multiplied once an assistive technology requests accessibility info.
+        app.world()
+            .resource::<bevy::a11y::AccessibilityRequested>()
+            .set(true);
+        app.update();
+        assert_eq!(app.world().resource::<EguiAccessibilityZoom>().0, 2.0);
+        let mut ctx = app.world_mut().get_mut::<EguiContext>(window).unwrap();
+        assert_eq!(ctx.get_mut().zoom_factor(), 3.0);
+    }
+
+    #[test]
+    fn test_fixed_logical_size() {
+        let mut app = App::new();
+        app.add_plugins(headless_plugins().set(bevy::window::WindowPlugin {
+            primary_window: Some(Window {
+                resolution: bevy::window::WindowResolution::new(1280.0, 800.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }))
+        .add_plugins(EguiPlugin)
+        .insert_resource(EguiSettings {
+            scale_factor: 1.5,
+            ..Default::default()
+        });
+        app.update();
+
+        let window = app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .single(app.world());
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .fixed_logical_size = Some(bevy::math::Vec2::new(640.0, 360.0));
+        app.update();
+
+        let mut ctx = app.world_mut().get_mut::<EguiContext>(window).unwrap();
+        let ctx = ctx.get_mut();
+        assert_eq!(ctx.pixels_per_point(), 2.0);
+        assert_eq!(ctx.screen_rect().size(), egui::vec2(640.0, 360.0));
+
+        // The UI is letterboxed vertically.
+        let window_size = WindowSize::new(1280.0, 800.0, 1.0);
+        let layout = EguiScreenLayout::new(
+            window_size,
+            1.5,
+            1.0,
+            Some(bevy::math::Vec2::new(640.0, 360.0)),
+        );
+        assert_eq!(layout.offset, bevy::math::Vec2::new(0.0, 40.0));
+        let pos = layout.to_egui_pos(&window_size, bevy::math::Vec2::new(640.0, 40.0));
+        assert_eq!(pos, egui::pos2(320.0, 0.0));
+        assert_eq!(
+            layout.to_window_pos(&window_size, pos),
+            bevy::math::Vec2::new(640.0, 40.0)
+        );
+    }
+
+    #[test]
+    fn test_ime_enabled_for_focused_window_only() {
+        fn ui_system(mut contexts: EguiContexts, windows: Query<Entity, With<Window>>) {
+            for window in windows.iter() {
+                egui::CentralPanel::default().show(contexts.ctx_for_window_mut(window), |ui| {
+                    let mut text = String::new();
+                    ui.text_edit_singleline(&mut text).request_focus();
+                });
+            }
+        }
+
+        let (mut app, unfocused_window) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        app.world_mut()
+            .get_mut::<Window>(unfocused_window)
+            .unwrap()
+            .focused = false;
+        let focused_window = app.world_mut().spawn(Window::default()).id();
+        app.update();
+        app.update();
+
+        let ime_enabled = |window| app.world().get::<Window>(window).unwrap().ime_enabled;
+        assert!(ime_enabled(focused_window));
+        assert!(!ime_enabled(unfocused_window));
+    }
+
+    #[test]
+    fn test_transparent_frames_paint_nothing() {
+        fn ui_system(mut contexts: EguiContexts) {
+            let ctx = contexts.ctx_mut();
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none().fill(egui::Color32::TRANSPARENT))
+                .show(ctx, |_ui| {});
+            egui::Window::new("Window")
+                .title_bar(false)
+                .frame(egui::Frame::none())
+                .show(ctx, |_ui| {});
+        }
+
+        let (mut app, _) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        app.update();
+        app.update();
+
+        // Nothing is drawn, so the window contents are left intact with the default load op.
+        let render_output = app
+            .world_mut()
+            .query_filtered::<&EguiRenderOutput, With<PrimaryWindow>>()
+            .single(app.world());
+        assert!(render_output.paint_jobs.iter().all(|paint_job| {
+            match &paint_job.primitive {
+                egui::epaint::Primitive::Mesh(mesh) => mesh.is_empty(),
+                egui::epaint::Primitive::Callback(_) => false,
+            }
+        }));
+    }
+
+    #[test]
+    fn test_duplicate_plugin() {
+        let (mut app, _) = test_app();
+        app.add_plugins(EguiPlugin);
+        app.update();
+
+        let contexts = app
+            .world_mut()
+            .query::<&EguiContext>()
+            .iter(app.world())
+            .count();
+        assert_eq!(contexts, 1);
+    }
+
+    #[test]
+    fn test_ui_schedule() {
+        use bevy::ecs::{
+            schedule::{LogLevel, ScheduleBuildSettings},
+            system::ResMut,
+        };
+
+        #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
+        struct UiSchedule;
+
+        #[derive(Resource, Default)]
+        struct Passes(Vec<u64>);
+
+        fn run_ui_schedule_twice(world: &mut World) {
+            world.run_schedule(UiSchedule);
+            world.run_schedule(UiSchedule);
+        }
+
+        fn window_system(mut contexts: EguiContexts) {
+            egui::Window::new("Window").show(contexts.ctx_mut(), |ui| {
+                ui.label("Label");
+            });
+        }
+
+        fn record_system(mut contexts: EguiContexts, mut passes: ResMut<Passes>) {
+            passes.0.push(contexts.ctx_mut().frame_nr());
+        }
+
+        let mut app = App::new();
+        app.add_plugins(headless_plugins())
+            .add_plugins(EguiPlugin::with_ui_schedule(UiSchedule))
+            .init_resource::<Passes>()
+            // Both systems use the context, but aren't reported as ambiguous.
+            .edit_schedule(UiSchedule, |schedule| {
+                schedule.set_build_settings(ScheduleBuildSettings {
+                    ambiguity_detection: LogLevel::Error,
+                    ..Default::default()
+                });
+            })
+            .add_systems(
+                UiSchedule,
+                (window_system, record_system).in_set(EguiSet::UserUi),
+            )
+            .add_systems(Update, run_ui_schedule_twice);
+        app.update();
+        app.update();
+
+        // Every run of the schedule is a pass of its own.
+        assert_eq!(app.world().resource::<Passes>().0, [0, 1, 2, 3]);
+        let render_output = app
+            .world_mut()
+            .query_filtered::<&EguiRenderOutput, With<PrimaryWindow>>()
+            .single(app.world());
+        assert!(!render_output.paint_jobs.is_empty());
+    }
+
+    #[test]
+    fn test_try_ctx_mut_without_primary_window() {
+        fn ui_system(mut contexts: EguiContexts) {
+            assert!(contexts.try_ctx_mut().is_none());
+        }
+
+        App::new()
+            .add_plugins(headless_plugins().set(bevy::window::WindowPlugin {
+                primary_window: None,
+                ..Default::default()
+            }))
+            .add_plugins(EguiPlugin)
+            .add_systems(bevy::app::Update, ui_system)
+            .update();
+    }
+
+    #[test]
+    fn test_deferred_viewport_windows() {
+        use bevy::prelude::{Res, Resource};
+        use std::sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        };
+
+        #[derive(Resource, Default)]
+        struct Viewport {
+            shown: bool,
+            frames: Arc<AtomicU32>,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, viewport: Res<Viewport>) {
+            if !viewport.shown {
+                return;
+            }
+            let frames = viewport.frames.clone();
+            contexts.ctx_mut().show_viewport_deferred(
+                egui::ViewportId::from_hash_of("child"),
+                egui::ViewportBuilder::default().with_title("Child"),
+                move |ctx, class| {
+                    assert!(class == egui::ViewportClass::Deferred);
+                    egui::CentralPanel::default().show(ctx, |ui| ui.label("Hi"));
+                    frames.fetch_add(1, Ordering::Relaxed);
+                },
+            );
+        }
+
+        fn disable_embedding_system(mut context_settings: Query<&mut EguiContextSettings>) {
+            for mut context_settings in context_settings.iter_mut() {
+                context_settings.embed_viewports = false;
+            }
+        }
+
+        let (mut app, _) = test_app();
+        app.init_resource::<Viewport>()
+            .add_systems(bevy::app::Startup, disable_embedding_system)
+            .add_systems(bevy::app::Update, ui_system);
+        app.update();
+
+        app.world_mut().resource_mut::<Viewport>().shown = true;
+        for _ in 0..3 {
+            app.update();
+        }
+        let mut viewports = app
+            .world_mut()
+            .query::<(&Window, &viewports::EguiViewport)>();
+        let titles: Vec<_> = viewports
+            .iter(app.world())
+            .map(|(window, _)| window.title.clone())
+            .collect();
+        assert_eq!(titles, ["Child"]);
+        assert!(
+            app.world()
+                .resource::<Viewport>()
+                .frames
+                .load(Ordering::Relaxed)
+                >= 1
+        );
+
+        app.world_mut().resource_mut::<Viewport>().shown = false;
+        app.update();
+        app.update();
+        assert_eq!(viewports.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn test_file_drag_and_drop() {
+        use bevy::{
+            prelude::{ResMut, Resource},
+            window::FileDragAndDrop,
+        };
+        use std::path::PathBuf;
+
+        #[derive(Resource, Default)]
+        struct Files {
+            hovered: Vec<egui::HoveredFile>,
+            dropped: Vec<egui::DroppedFile>,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, mut files: ResMut<Files>) {
+            contexts.ctx_mut().input(|input| {
+                files.hovered = input.raw.hovered_files.clone();
+                files
+                    .dropped
+                    .extend(input.raw.dropped_files.iter().cloned());
+            });
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Files>()
+            .add_systems(bevy::app::Update, ui_system);
+        app.update();
+        let path = PathBuf::from("assets").join("image.png");
+
+        app.world_mut().send_event(FileDragAndDrop::HoveredFile {
+            window,
+            path_buf: path.clone(),
+        });
+        app.update();
+        // Hovered files are kept until the drag ends.
+        app.update();
+        let files = app.world().resource::<Files>();
+        assert_eq!(files.hovered.len(), 1);
+        assert_eq!(files.hovered[0].path.as_ref(), Some(&path));
+        assert!(files.dropped.is_empty());
+
+        app.world_mut().send_event(FileDragAndDrop::DroppedFile {
+            window,
+            path_buf: path.clone(),
+        });
+        app.update();
+        app.update();
+        let files = app.world().resource::<Files>();
+        assert!(files.hovered.is_empty());
+        assert_eq!(files.dropped.len(), 1);
+        assert_eq!(files.dropped[0].path.as_ref(), Some(&path));
+        assert_eq!(files.dropped[0].name, "image.png");
+
+        app.world_mut().send_event(FileDragAndDrop::HoveredFile {
+            window,
+            path_buf: path.clone(),
+        });
+        app.update();
+        app.world_mut()
+            .send_event(FileDragAndDrop::HoveredFileCanceled { window });
+        app.update();
+        let files = app.world().resource::<Files>();
+        assert!(files.hovered.is_empty());
+        assert_eq!(files.dropped.len(), 1);
+    }
+
+    #[cfg(all(
+        feature = "manage_clipboard",
+        not(any(target_os = "android", target_arch = "wasm32"))
+    ))]
+    #[test]
+    fn test_clipboard_contents() {
+        // Works with the system clipboard, and with the fallback one if there's no display.
+        let mut clipboard = EguiClipboard::default();
+        clipboard.set_contents("copied");
+        assert_eq!(clipboard.get_contents().as_deref(), Some("copied"));
+
+        let image = egui::ColorImage::new([2, 1], egui::Color32::RED);
+        clipboard.set_image(&image);
+        assert_eq!(clipboard.get_image(), Some(image));
+        assert_eq!(clipboard.get_contents(), None);
+    }
+
+    #[cfg(all(
+        feature = "render",
+        feature = "manage_clipboard",
+        not(target_arch = "wasm32")
+    ))]
+    #[test]
+    fn test_clipboard_image_paste() {
+        use bevy::prelude::{EventReader, ResMut, Resource};
+
+        #[derive(Resource, Default)]
+        struct Pasted(Vec<EguiClipboardImagePaste>);
+
+        fn read_pastes_system(
+            mut ev_image_paste: EventReader<EguiClipboardImagePaste>,
+            mut pasted: ResMut<Pasted>,
+        ) {
+            pasted.0.extend(ev_image_paste.read().cloned());
+        }
+
+        let (mut app, window) = test_app();
+        app.init_resource::<Pasted>()
+            .add_systems(bevy::app::Update, read_pastes_system);
+        app.update();
+        app.world_mut()
+            .resource_mut::<EguiClipboard>()
+            .pasted_images
+            .push(PastedImage {
+                window: Some(window),
+                width: 2,
+                height: 1,
+                rgba: vec![255, 0, 0, 255, 0, 0, 255, 128],
+            });
+        app.update();
+
+        let pasted = &app.world().resource::<Pasted>().0;
+        assert_eq!(pasted.len(), 1);
+        assert_eq!(pasted[0].window, window);
+        assert_eq!(pasted[0].size, egui::vec2(2.0, 1.0));
+        let image = app
+            .world()
+            .resource::<Assets<Image>>()
+            .get(&pasted[0].image)
+            .unwrap();
+        assert_eq!(image.data, [255, 0, 0, 255, 0, 0, 255, 128]);
+        assert_eq!(
+            app.world()
+                .resource::<EguiUserTextures>()
+                .image_id(&pasted[0].image),
+            Some(pasted[0].texture_id)
+        );
+    }
 }
@@ -0,0 +1,260 @@
+use crate::EguiContext;
+use bevy::{
+    app::AppExit,
+    log,
+    prelude::{DetectChanges, EventReader, Local, Query, Ref, Res, Resource, With},
+    time::{Real, Time},
+    window::PrimaryWindow,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A resource that saves the [`egui::Memory`] of the primary window's context (window
+/// positions, collapsing header states, scroll offsets and other persisted widget states) and
+/// restores it when the app starts again.
+///
+/// The memory is stored in a file on native platforms and in `localStorage` on the web. It's
+/// saved when the app exits or the primary window is closed, and every
+/// [`EguiPersistence::save_interval`] in case the app doesn't exit cleanly. Insert the resource
+/// when building the app or in a startup system for the memory to be restored.
+///
+/// ```no_run,rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{persistence::EguiPersistence, EguiPlugin};
+///
+/// App::new()
+///     .insert_resource(EguiPersistence::new("my_app"))
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(EguiPlugin)
+///     .run();
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct EguiPersistence {
+    /// File the memory is stored in.
+    ///
+    /// Defaults to `egui_memory.ron` in a directory named after the app in the platform's data
+    /// directory (`~/.local/share`, `~/Library/Application Support` or `%APPDATA%`).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub path: PathBuf,
+    /// `localStorage` key the memory is stored with.
+    ///
+    /// Defaults to `<app_id>_egui_memory`.
+    #[cfg(target_arch = "wasm32")]
+    pub key: String,
+    /// How often the memory is saved while the app is running, `None` to only save it on exit.
+    ///
+    /// Defaults to 30 seconds.
+    pub save_interval: Option<Duration>,
+}
+
+impl EguiPersistence {
+    /// Creates the resource with the default storage location for the app.
+    ///
+    /// `app_id` should be unique to the app, as apps with the same id share the memory.
+    #[must_use]
+    pub fn new(app_id: &str) -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            path: data_dir()
+                .map(|data_dir| data_dir.join(app_id))
+                .unwrap_or_else(|| PathBuf::from(app_id))
+                .join("egui_memory.ron"),
+            #[cfg(target_arch = "wasm32")]
+            key: format!("{app_id}_egui_memory"),
+            save_interval: Some(Duration::from_secs(30)),
+        }
+    }
+
+    /// Reads the stored memory. Returns `None` if nothing has been stored yet or if it can't
+    /// be read.
+    #[must_use]
+    pub fn load(&self) -> Option<egui::Memory> {
+        let ron = self.read()?;
+        match ron::from_str(&ron) {
+            Ok(memory) => Some(memory),
+            Err(err) => {
+                log::warn!("Failed to parse the stored Egui memory: {err}");
+                None
+            }
+        }
+    }
+
+    /// Stores the memory, replacing the previously stored one.
+    pub fn save(&self, memory: &egui::Memory) {
+        match ron::to_string(memory) {
+            Ok(ron) => self.write(&ron),
+            Err(err) => log::warn!("Failed to serialize the Egui memory: {err}"),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read(&self) -> Option<String> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(ron) => Some(ron),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                log::warn!("Failed to read {}: {err}", self.path.display());
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write(&self, ron: &str) {
+        if let Some(parent) = self.path.parent() {
+            if let Err(err) = std::fs::create_dir_all(parent) {
+                log::warn!("Failed to create {}: {err}", parent.display());
+                return;
+            }
+        }
+        if let Err(err) = std::fs::write(&self.path, ron) {
+            log::warn!("Failed to write {}: {err}", self.path.display());
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read(&self) -> Option<String> {
+        local_storage()?.get_item(&self.key).ok().flatten()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn write(&self, ron: &str) {
+        let Some(local_storage) = local_storage() else {
+            log::warn!("Failed to store the Egui memory: localStorage isn't available");
+            return;
+        };
+        if let Err(err) = local_storage.set_item(&self.key, ron) {
+            log::warn!("Failed to store the Egui memory: {err:?}");
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_dir() -> Option<PathBuf> {
+    let env_path = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(target_os = "windows") {
+        env_path("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env_path("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env_path("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env_path("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// Restores the stored memory once the primary window's context is created.
+pub fn load_egui_memory_system(
+    persistence: Option<Res<EguiPersistence>>,
+    contexts: Query<Ref<EguiContext>, With<PrimaryWindow>>,
+) {
+    let Some(persistence) = persistence else {
+        return;
+    };
+    for ctx in contexts.iter() {
+        if !ctx.is_added() {
+            continue;
+        }
+        let Some(mut memory) = persistence.load() else {
+            continue;
+        };
+        let ctx = ctx.ctx.clone();
+        // The style isn't persisted, keep the one the context already has.
+        let style = ctx.style();
+        ctx.memory_mut(|current| {
+            memory.options.zoom_with_keyboard = current.options.zoom_with_keyboard;
+            *current = memory;
+        });
+        ctx.set_style(style);
+    }
+}
+
+/// Saves the memory of the primary window's context, see [`EguiPersistence`] for when that
+/// happens.
+pub fn save_egui_memory_system(
+    persistence: Option<Res<EguiPersistence>>,
+    time: Res<Time<Real>>,
+    mut app_exit: EventReader<AppExit>,
+    contexts: Query<&EguiContext, With<PrimaryWindow>>,
+    // The context is kept to save it after the primary window is despawned.
+    mut primary_ctx: Local<Option<egui::Context>>,
+    mut last_save: Local<Duration>,
+) {
+    let Some(persistence) = persistence else {
+        app_exit.clear();
+        *primary_ctx = None;
+        return;
+    };
+
+    let exiting = app_exit.read().count() > 0;
+    let closed = match contexts.get_single() {
+        Ok(ctx) => {
+            *primary_ctx = Some(ctx.ctx.clone());
+            false
+        }
+        Err(_) => primary_ctx.is_some(),
+    };
+    let elapsed = persistence
+        .save_interval
+        .is_some_and(|save_interval| time.elapsed() >= *last_save + save_interval);
+    if !(exiting || closed || elapsed) {
+        return;
+    }
+
+    if let Some(ctx) = primary_ctx.as_ref() {
+        ctx.memory(|memory| persistence.save(memory));
+        *last_save = time.elapsed();
+    }
+    if closed {
+        *primary_ctx = None;
+    }
+}
+
+// The test stores the memory in a file, which is only used on native platforms.
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use crate::tests::test_app;
+
+    #[test]
+    fn test_egui_persistence() {
+        let mut persistence = EguiPersistence::new("bevy_egui_test");
+        persistence.path = std::env::temp_dir()
+            .join(format!("bevy_egui_persistence_{}", std::process::id()))
+            .join("egui_memory.ron");
+        let id = egui::Id::new("persisted");
+
+        let (mut app, _) = test_app();
+        app.insert_resource(persistence.clone());
+        app.update();
+        let mut contexts = app
+            .world_mut()
+            .query_filtered::<&mut EguiContext, With<PrimaryWindow>>();
+        contexts
+            .single_mut(app.world_mut())
+            .get_mut()
+            .data_mut(|data| data.insert_persisted(id, 42u32));
+        app.world_mut().send_event(AppExit::Success);
+        app.update();
+        assert!(persistence.path.exists());
+
+        let (mut app, _) = test_app();
+        app.insert_resource(persistence.clone());
+        app.update();
+        let value = app
+            .world_mut()
+            .query_filtered::<&mut EguiContext, With<PrimaryWindow>>()
+            .single_mut(app.world_mut())
+            .get_mut()
+            .data_mut(|data| data.get_persisted::<u32>(id));
+        assert_eq!(value, Some(42));
+
+        std::fs::remove_dir_all(persistence.path.parent().unwrap()).unwrap();
+    }
+}
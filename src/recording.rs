@@ -0,0 +1,326 @@
+use crate::EguiInput;
+use bevy::{
+    log,
+    prelude::{Query, ResMut, Resource, With},
+    window::PrimaryWindow,
+};
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+/// A resource that records the [`egui::RawInput`] of the primary window's context every frame.
+///
+/// Each frame is written as a line of RON, and the writer is flushed after every frame, so the
+/// input leading to a crash is recorded as well. Recording stops when the resource is removed
+/// or if writing fails. Replay a recording with [`EguiInputPlayback`]:
+///
+/// ```no_run,rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{recording::EguiInputRecorder, EguiPlugin};
+///
+/// App::new()
+///     .insert_resource(EguiInputRecorder::create("egui_input.ron").unwrap())
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(EguiPlugin)
+///     .run();
+/// ```
+///
+/// The input is recorded after the [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput)
+/// system, so it includes what [`EguiInputFilter`](crate::EguiInputFilter) lets through, but
+/// not the changes made by [`EguiInputHooks`](crate::EguiInputHooks), which run again when the
+/// input is played back.
+#[derive(Resource)]
+pub struct EguiInputRecorder {
+    writer: Option<Box<dyn Write + Send + Sync>>,
+    frames: usize,
+}
+
+impl EguiInputRecorder {
+    /// Creates a recorder that writes the frames to a writer.
+    #[must_use]
+    pub fn new(writer: impl Write + Send + Sync + 'static) -> Self {
+        Self {
+            writer: Some(Box::new(writer)),
+            frames: 0,
+        }
+    }
+
+    /// Creates (or truncates) a file and records the frames to it.
+    pub fn create(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+
+    /// Returns `false` once writing a frame has failed.
+    #[must_use]
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Number of frames recorded so far.
+    #[must_use]
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Writes a frame of input.
+    pub fn record(&mut self, raw_input: &egui::RawInput) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+        let ron = match ron::to_string(raw_input) {
+            Ok(ron) => ron,
+            Err(err) => {
+                log::warn!("Failed to serialize the Egui input: {err}");
+                return;
+            }
+        };
+        if let Err(err) = writeln!(writer, "{ron}").and_then(|()| writer.flush()) {
+            log::error!("Failed to record the Egui input, recording stopped: {err}");
+            self.writer = None;
+            return;
+        }
+        self.frames += 1;
+    }
+}
+
+/// A resource that replays input recorded by [`EguiInputRecorder`] in the primary window's
+/// context, one recorded frame per frame.
+///
+/// While frames are left, the input of the context is replaced with the recorded one, including
+/// its time and screen rect, so Egui behaves exactly as it did when the input was recorded
+/// (given the same UI systems and state). Input from the window is ignored until the playback
+/// finishes. For the layout to match what was drawn, play the input back in a window of the same
+/// size and scale factor.
+///
+/// ```no_run,rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{recording::EguiInputPlayback, EguiPlugin};
+///
+/// App::new()
+///     .insert_resource(EguiInputPlayback::load("egui_input.ron").unwrap())
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(EguiPlugin)
+///     .run();
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct EguiInputPlayback {
+    frames: Vec<egui::RawInput>,
+    next_frame: usize,
+}
+
+impl EguiInputPlayback {
+    /// Creates a playback of frames of input.
+    #[must_use]
+    pub fn new(frames: Vec<egui::RawInput>) -> Self {
+        Self {
+            frames,
+            next_frame: 0,
+        }
+    }
+
+    /// Reads a recording from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EguiInputPlaybackError> {
+        let ron = std::fs::read_to_string(path).map_err(EguiInputPlaybackError::Io)?;
+        Self::from_ron(&ron)
+    }
+
+    /// Parses a recording, with a frame on every non-empty line.
+    pub fn from_ron(ron: &str) -> Result<Self, EguiInputPlaybackError> {
+        let frames = ron
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                ron::from_str(line).map_err(|err| EguiInputPlaybackError::Ron {
+                    line: index + 1,
+                    err,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(frames))
+    }
+
+    /// The recorded frames.
+    #[must_use]
+    pub fn frames(&self) -> &[egui::RawInput] {
+        &self.frames
+    }
+
+    /// Index of the frame that will be played back next.
+    #[must_use]
+    pub fn next_frame(&self) -> usize {
+        self.next_frame
+    }
+
+    /// Returns `true` once all the frames have been played back.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.frames.len()
+    }
+
+    /// Returns the next frame and moves past it, or `None` if the playback has finished.
+    pub fn advance(&mut self) -> Option<&egui::RawInput> {
+        let frame = self.frames.get(self.next_frame)?;
+        self.next_frame += 1;
+        Some(frame)
+    }
+}
+
+/// Errors that occur while loading an [`EguiInputPlayback`].
+#[derive(Debug)]
+pub enum EguiInputPlaybackError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// A frame couldn't be parsed.
+    Ron {
+        /// Line of the frame, starting from 1.
+        line: usize,
+        /// The parsing error.
+        err: ron::error::SpannedError,
+    },
+}
+
+impl std::fmt::Display for EguiInputPlaybackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the recording: {err}"),
+            Self::Ron { line, err } => {
+                write!(
+                    f,
+                    "failed to parse frame on line {line} of the recording: {err}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EguiInputPlaybackError {}
+
+/// Replaces the input of the primary window's context with the next frame of
+/// [`EguiInputPlayback`].
+pub fn play_back_egui_input_system(
+    playback: Option<ResMut<EguiInputPlayback>>,
+    mut contexts: Query<&mut EguiInput, With<PrimaryWindow>>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.is_finished() {
+        return;
+    }
+    let Ok(mut egui_input) = contexts.get_single_mut() else {
+        return;
+    };
+    if let Some(frame) = playback.advance() {
+        egui_input.0 = frame.clone();
+    }
+}
+
+/// Writes the input of the primary window's context to [`EguiInputRecorder`].
+pub fn record_egui_input_system(
+    recorder: Option<ResMut<EguiInputRecorder>>,
+    contexts: Query<&EguiInput, With<PrimaryWindow>>,
+) {
+    let Some(mut recorder) = recorder else {
+        return;
+    };
+    if !recorder.is_recording() {
+        return;
+    }
+    if let Ok(egui_input) = contexts.get_single() {
+        recorder.record(egui_input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContexts};
+    use bevy::{
+        input::{
+            keyboard::{Key, KeyboardInput},
+            mouse::{MouseButton, MouseButtonInput},
+            ButtonState,
+        },
+        prelude::KeyCode,
+        window::CursorMoved,
+    };
+
+    #[test]
+    fn test_egui_input_recording() {
+        #[derive(Resource, Default)]
+        struct Text(String);
+
+        fn ui_system(mut contexts: EguiContexts, mut text: ResMut<Text>) {
+            egui::Area::new("text".into())
+                .fixed_pos(egui::pos2(100.0, 100.0))
+                .show(contexts.ctx_mut(), |ui| {
+                    ui.text_edit_singleline(&mut text.0);
+                });
+        }
+
+        let new_app = || {
+            let (mut app, window) = test_app();
+            app.init_resource::<Text>()
+                .add_systems(bevy::app::Update, ui_system);
+            (app, window)
+        };
+        let path = std::env::temp_dir().join(format!(
+            "bevy_egui_input_recording_{}.ron",
+            std::process::id()
+        ));
+
+        let (mut app, window) = new_app();
+        app.insert_resource(EguiInputRecorder::create(&path).unwrap());
+        app.update();
+        app.update();
+        app.world_mut().send_event(CursorMoved {
+            window,
+            position: bevy::math::Vec2::new(110.0, 105.0),
+            delta: None,
+        });
+        app.update();
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            app.world_mut().send_event(MouseButtonInput {
+                button: MouseButton::Left,
+                state,
+                window,
+            });
+            app.update();
+        }
+        app.world_mut().send_event(KeyboardInput {
+            key_code: KeyCode::KeyA,
+            logical_key: Key::Character("a".into()),
+            state: ButtonState::Pressed,
+            window,
+        });
+        app.update();
+        assert_eq!(app.world().resource::<Text>().0, "a");
+        let recorder = app
+            .world_mut()
+            .remove_resource::<EguiInputRecorder>()
+            .unwrap();
+        assert!(recorder.is_recording());
+        assert_eq!(recorder.frames(), 6);
+        drop(recorder);
+
+        // The playback types the same text without any window input.
+        let playback = EguiInputPlayback::load(&path).unwrap();
+        assert_eq!(playback.frames().len(), 6);
+        let (mut app, _) = new_app();
+        app.insert_resource(playback);
+        for _ in 0..6 {
+            app.update();
+        }
+        assert!(app.world().resource::<EguiInputPlayback>().is_finished());
+        assert_eq!(app.world().resource::<Text>().0, "a");
+
+        assert!(matches!(
+            EguiInputPlayback::from_ron("\n(invalid"),
+            Err(EguiInputPlaybackError::Ron { line: 2, .. })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,25 +1,37 @@
 use crate::{
-    egui_node::{EguiNode, EguiPipeline, EguiPipelineKey},
-    EguiManagedTextures, EguiSettings, EguiUserTextures, WindowSize,
+    egui_node::{EguiBufferPool, EguiCameraPass, EguiNode, EguiPipeline, EguiPipelineKey},
+    EguiCameraContext, EguiCapturedScreenshots, EguiContextSettings, EguiManagedTextures,
+    EguiRenderOutput, EguiRenderToTexture, EguiScreenLayout, EguiScreenshot, EguiSecondaryContext,
+    EguiSettings, EguiUserTextures, WindowSize,
 };
 use bevy::{
+    core_pipeline::{
+        core_2d::graph::{Core2d, Node2d},
+        core_3d::graph::{Core3d, Node3d},
+    },
     ecs::system::SystemParam,
+    log,
     prelude::*,
     render::{
+        camera::CameraRenderGraph,
         extract_resource::ExtractResource,
-        render_asset::RenderAssets,
-        render_graph::{RenderGraph, RenderLabel},
+        render_asset::{RenderAssetUsages, RenderAssets},
+        render_graph::{
+            InternedRenderLabel, InternedRenderSubGraph, RenderGraph, RenderLabel, RenderSubGraph,
+        },
         render_resource::{
-            BindGroup, BindGroupEntry, BindingResource, BufferId, CachedRenderPipelineId,
-            DynamicUniformBuffer, PipelineCache, ShaderType, SpecializedRenderPipelines,
+            BindGroup, BindGroupEntry, BindingResource, Buffer, BufferAsyncError, BufferId,
+            CachedRenderPipelineId, DynamicUniformBuffer, Extent3d, MapMode, PipelineCache,
+            Sampler, SpecializedRenderPipelines, TextureDimension, TextureFormat,
         },
         renderer::{RenderDevice, RenderQueue},
-        texture::GpuImage,
-        view::ExtractedWindows,
+        texture::{GpuImage, Image},
+        view::{ExtractedView, ExtractedWindows, ViewTarget},
         Extract,
     },
-    utils::HashMap,
+    utils::{HashMap, HashSet},
 };
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// Extracted Egui settings.
 #[derive(Resource, Deref, DerefMut, Default)]
@@ -78,13 +90,40 @@ impl ExtractedEguiTextures<'_> {
                     .iter()
                     .map(|(handle, id)| (EguiTextureId::User(*id), handle.id())),
             )
+            .chain(
+                self.user_textures
+                    .animations
+                    .iter()
+                    .filter_map(|(id, animation)| {
+                        Some((EguiTextureId::User(*id), animation.current_frame()?.id()))
+                    }),
+            )
     }
 }
 
-/// Sets up the pipeline for newly created windows.
+/// Sets up the pipeline for newly created windows (and entities with [`EguiRenderToTexture`]
+/// or [`EguiSecondaryContext`]).
+#[allow(clippy::type_complexity)]
 pub fn setup_new_windows_render_system(
     windows: Extract<Query<Entity, Added<Window>>>,
+    render_to_texture: Extract<Query<Entity, Added<EguiRenderToTexture>>>,
+    secondary_contexts: Extract<
+        Query<(
+            Entity,
+            Ref<EguiSecondaryContext>,
+            Option<&EguiContextSettings>,
+            Option<&CameraRenderGraph>,
+            Has<EguiCameraContext>,
+        )>,
+    >,
     mut render_graph: ResMut<RenderGraph>,
+    // The windows secondary contexts were last drawn to, and the edges that order the nodes of
+    // the contexts of each window.
+    mut secondary_context_windows: Local<HashMap<Entity, Entity>>,
+    mut context_order_edges: Local<HashMap<Entity, Vec<(EguiPass, EguiPass)>>>,
+    // The camera sub-graphs (and passes) the nodes of camera contexts drawn into their cameras'
+    // main textures belong to.
+    mut camera_pass_graphs: Local<HashMap<Entity, (InternedRenderSubGraph, EguiCameraPass)>>,
 ) {
     for window in windows.iter() {
         let egui_pass = EguiPass {
@@ -98,6 +137,260 @@ pub fn setup_new_windows_render_system(
 
         render_graph.add_node_edge(bevy::render::graph::CameraDriverLabel, egui_pass);
     }
+
+    for entity in render_to_texture.iter() {
+        let egui_pass = EguiPass {
+            window_index: entity.index(),
+            window_generation: entity.generation(),
+        };
+
+        let new_node = EguiNode::new(entity);
+
+        render_graph.add_node(egui_pass.clone(), new_node);
+
+        // Render to textures before cameras, so that they can display the UI of the same frame.
+        render_graph.add_node_edge(egui_pass, bevy::render::graph::CameraDriverLabel);
+    }
+
+    let mut reordered_windows: HashSet<Entity> = windows.iter().collect();
+    for (entity, secondary_context, settings, camera_render_graph, is_camera_context) in
+        secondary_contexts.iter()
+    {
+        let camera_pass_graph = camera_render_graph
+            .zip(settings.and_then(EguiContextSettings::resolved_camera_pass))
+            .filter(|_| is_camera_context)
+            .map(|(camera_render_graph, camera_pass)| (**camera_render_graph, camera_pass));
+        let camera_pass_changed = camera_pass_graphs.get(&entity).copied() != camera_pass_graph;
+        if !secondary_context.is_changed() && !camera_pass_changed {
+            continue;
+        }
+        let egui_pass = EguiPass {
+            window_index: entity.index(),
+            window_generation: entity.generation(),
+        };
+        let last_window = secondary_context_windows.insert(entity, secondary_context.window);
+        if let Some(last_window) = last_window {
+            reordered_windows.insert(last_window);
+        }
+        reordered_windows.insert(secondary_context.window);
+        if last_window == Some(secondary_context.window) && !camera_pass_changed {
+            continue;
+        }
+
+        // The node is recreated to draw to the new window (or pass).
+        match camera_pass_graphs.remove(&entity) {
+            Some((sub_graph, _)) => {
+                if let Some(sub_graph) = render_graph.get_sub_graph_mut(sub_graph) {
+                    let _ = sub_graph.remove_node(egui_pass.clone());
+                }
+            }
+            None => {
+                let _ = render_graph.remove_node(egui_pass.clone());
+            }
+        }
+
+        if let Some((sub_graph_label, camera_pass)) = camera_pass_graph {
+            let sub_graph = render_graph.get_sub_graph_mut(sub_graph_label);
+            let edges = sub_graph.as_deref().and_then(|sub_graph| {
+                camera_pass_edges(sub_graph_label, camera_pass, bevy_ui_pass(sub_graph))
+            });
+            match (sub_graph, edges) {
+                // Post-processing nodes are only there if their plugins are added, but the node
+                // has to be ordered on both sides.
+                (Some(sub_graph), Some((preceding_nodes, following_nodes)))
+                    if [&preceding_nodes, &following_nodes]
+                        .into_iter()
+                        .all(|nodes| {
+                            nodes
+                                .iter()
+                                .any(|node| sub_graph.get_node_state(*node).is_ok())
+                        }) =>
+                {
+                    let new_node =
+                        EguiNode::new_camera_pass(entity, secondary_context.window, camera_pass);
+                    sub_graph.add_node(egui_pass.clone(), new_node);
+                    for preceding_node in preceding_nodes {
+                        let _ = sub_graph.try_add_node_edge(preceding_node, egui_pass.clone());
+                    }
+                    for following_node in following_nodes {
+                        let _ = sub_graph.try_add_node_edge(egui_pass.clone(), following_node);
+                    }
+                    camera_pass_graphs.insert(entity, (sub_graph_label, camera_pass));
+                    continue;
+                }
+                _ => log::warn!(
+                    "Can't draw the Egui context of a camera with the {sub_graph_label:?} render \
+                     graph in the {camera_pass:?} pass, falling back to drawing onto the window"
+                ),
+            }
+        }
+
+        let new_node = EguiNode::new_secondary(entity, secondary_context.window);
+
+        render_graph.add_node(egui_pass.clone(), new_node);
+
+        render_graph.add_node_edge(bevy::render::graph::CameraDriverLabel, egui_pass);
+    }
+
+    for window in reordered_windows {
+        for (output_node, input_node) in context_order_edges.remove(&window).unwrap_or_default() {
+            // The edges of removed nodes are already gone.
+            let _ = render_graph.remove_node_edge(output_node, input_node);
+        }
+        let mut passes: Vec<_> = secondary_contexts
+            .iter()
+            .filter(|(entity, secondary_context, ..)| {
+                secondary_context.window == window && !camera_pass_graphs.contains_key(entity)
+            })
+            .map(|(entity, secondary_context, ..)| ((secondary_context.order, true), entity))
+            .chain([((0, false), window)])
+            .collect();
+        passes.sort_by_key(|(order, _)| *order);
+        let mut edges = Vec::new();
+        for pair in passes.windows(2) {
+            let [(_, output), (_, input)] = [pair[0], pair[1]];
+            let output_node = EguiPass {
+                window_index: output.index(),
+                window_generation: output.generation(),
+            };
+            let input_node = EguiPass {
+                window_index: input.index(),
+                window_generation: input.generation(),
+            };
+            // The window's node doesn't exist if the entity isn't a window.
+            if render_graph
+                .try_add_node_edge(output_node.clone(), input_node.clone())
+                .is_ok()
+            {
+                edges.push((output_node, input_node));
+            }
+        }
+        context_order_edges.insert(window, edges);
+    }
+}
+
+/// Returns the nodes of a camera's render graph that the node of a context drawn in a pass of the
+/// camera follows, and the nodes it precedes (see [`EguiContextSettings::camera_pass`]).
+/// `bevy_ui_pass` is the pass of `bevy_ui` in the graph, see [`bevy_ui_pass`].
+pub(crate) fn camera_pass_edges(
+    sub_graph: InternedRenderSubGraph,
+    camera_pass: EguiCameraPass,
+    bevy_ui_pass: Option<InternedRenderLabel>,
+) -> Option<(Vec<InternedRenderLabel>, Vec<InternedRenderLabel>)> {
+    fn labels<const N: usize>(labels: [impl RenderLabel; N]) -> Vec<InternedRenderLabel> {
+        labels.into_iter().map(|label| label.intern()).collect()
+    }
+
+    if let EguiCameraPass::Custom { after, before } = camera_pass {
+        return Some((vec![after], vec![before]));
+    }
+    if let Some(bevy_ui_pass) = bevy_ui_pass.filter(|_| {
+        matches!(
+            camera_pass,
+            EguiCameraPass::BeforeBevyUi | EguiCameraPass::AfterBevyUi
+        )
+    }) {
+        let (end_post_processing, upscaling) = if sub_graph == Core3d.intern() {
+            (
+                Node3d::EndMainPassPostProcessing.intern(),
+                Node3d::Upscaling.intern(),
+            )
+        } else if sub_graph == Core2d.intern() {
+            (
+                Node2d::EndMainPassPostProcessing.intern(),
+                Node2d::Upscaling.intern(),
+            )
+        } else {
+            return None;
+        };
+        return Some(if camera_pass == EguiCameraPass::BeforeBevyUi {
+            (vec![end_post_processing], vec![bevy_ui_pass, upscaling])
+        } else {
+            (vec![end_post_processing, bevy_ui_pass], vec![upscaling])
+        });
+    }
+    // The auto exposure node can't be ordered against, as its label isn't public.
+    if sub_graph == Core3d.intern() {
+        Some(match camera_pass {
+            EguiCameraPass::MainPass => (
+                labels([Node3d::MainTransparentPass]),
+                labels([Node3d::EndMainPass]),
+            ),
+            EguiCameraPass::BeforeBloom => (
+                labels([Node3d::EndMainPass, Node3d::Taa, Node3d::MotionBlur]),
+                labels([Node3d::Bloom, Node3d::DepthOfField, Node3d::Tonemapping]),
+            ),
+            // The UI isn't blurred or smeared by the effects that precede tonemapping.
+            EguiCameraPass::BeforeTonemapping => (
+                labels([
+                    Node3d::EndMainPass,
+                    Node3d::Taa,
+                    Node3d::MotionBlur,
+                    Node3d::Bloom,
+                    Node3d::DepthOfField,
+                ]),
+                labels([Node3d::Tonemapping]),
+            ),
+            EguiCameraPass::AfterTonemapping => (
+                labels([Node3d::Tonemapping]),
+                labels([
+                    Node3d::Fxaa,
+                    Node3d::Smaa,
+                    Node3d::ContrastAdaptiveSharpening,
+                    Node3d::EndMainPassPostProcessing,
+                ]),
+            ),
+            // Without `bevy_ui`, there's nothing to draw below or above.
+            EguiCameraPass::AfterPostProcessing
+            | EguiCameraPass::BeforeBevyUi
+            | EguiCameraPass::AfterBevyUi => (
+                labels([Node3d::EndMainPassPostProcessing]),
+                labels([Node3d::Upscaling]),
+            ),
+            EguiCameraPass::Custom { .. } => unreachable!(),
+        })
+    } else if sub_graph == Core2d.intern() {
+        match camera_pass {
+            // The depth texture is only shared by the 3D passes.
+            EguiCameraPass::MainPass => None,
+            EguiCameraPass::BeforeBloom => Some((
+                labels([Node2d::EndMainPass]),
+                labels([Node2d::Bloom, Node2d::Tonemapping]),
+            )),
+            EguiCameraPass::BeforeTonemapping => Some((
+                labels([Node2d::EndMainPass, Node2d::Bloom]),
+                labels([Node2d::Tonemapping]),
+            )),
+            EguiCameraPass::AfterTonemapping => Some((
+                labels([Node2d::Tonemapping]),
+                labels([
+                    Node2d::Fxaa,
+                    Node2d::Smaa,
+                    Node2d::ContrastAdaptiveSharpening,
+                    Node2d::EndMainPassPostProcessing,
+                ]),
+            )),
+            EguiCameraPass::AfterPostProcessing
+            | EguiCameraPass::BeforeBevyUi
+            | EguiCameraPass::AfterBevyUi => Some((
+                labels([Node2d::EndMainPassPostProcessing]),
+                labels([Node2d::Upscaling]),
+            )),
+            EguiCameraPass::Custom { .. } => unreachable!(),
+        }
+    } else {
+        None
+    }
+}
+
+/// Finds the pass `bevy_ui` draws the nodes of cameras in (`NodeUi::UiPass`) in the render graph
+/// of a camera. `bevy_ui` isn't a dependency of this crate, so the node is found by the name of its
+/// label.
+pub(crate) fn bevy_ui_pass(sub_graph: &RenderGraph) -> Option<InternedRenderLabel> {
+    sub_graph
+        .iter_nodes()
+        .map(|node| node.label)
+        .find(|label| format!("{label:?}") == "UiPass")
 }
 
 /// Describes the transform buffer.
@@ -111,14 +404,40 @@ pub struct EguiTransforms {
     pub bind_group: Option<(BufferId, BindGroup)>,
 }
 
-/// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates from
-/// the screen space with the center at (0, 0) to the normalised viewport space.
-#[derive(ShaderType, Default)]
-pub struct EguiTransform {
-    /// Is affected by window size and [`EguiSettings::scale_factor`].
-    pub scale: Vec2,
-    /// Normally equals `Vec2::new(-1.0, 1.0)`.
-    pub translation: Vec2,
+pub use transform::EguiTransform;
+
+// The `ShaderType` derive generates assertion functions for the field types that are never
+// called, the module keeps the `allow` from covering anything else.
+#[allow(dead_code)]
+mod transform {
+    use bevy::{
+        math::{Vec2, Vec4},
+        render::render_resource::ShaderType,
+    };
+
+    /// Scale and translation for rendering Egui shapes. Is needed to transform Egui coordinates
+    /// from the screen space with the center at (0, 0) to the normalised viewport space.
+    #[derive(ShaderType, Default)]
+    pub struct EguiTransform {
+        /// Is affected by window size, [`EguiSettings::scale_factor`] and
+        /// [`EguiContextSettings::ui_zoom_factor`].
+        ///
+        /// [`EguiSettings::scale_factor`]: crate::EguiSettings::scale_factor
+        /// [`EguiContextSettings::ui_zoom_factor`]: crate::EguiContextSettings::ui_zoom_factor
+        pub scale: Vec2,
+        /// Normally equals `Vec2::new(-1.0, 1.0)`.
+        pub translation: Vec2,
+        /// Linear premultiplied color every fragment is multiplied by
+        /// (see [`EguiContextSettings::tint`]).
+        ///
+        /// [`EguiContextSettings::tint`]: crate::EguiContextSettings::tint
+        pub tint: Vec4,
+        /// Depth of the shapes in normalized device coordinates, is only used when depth
+        /// testing (see [`EguiContextSettings::depth_test_distance`]).
+        ///
+        /// [`EguiContextSettings::depth_test_distance`]: crate::EguiContextSettings::depth_test_distance
+        pub depth: f32,
+    }
 }
 
 impl EguiTransform {
@@ -130,15 +449,35 @@ impl EguiTransform {
                 -2.0 / (window_size.height() / scale_factor),
             ),
             translation: Vec2::new(-1.0, 1.0),
+            tint: Vec4::ONE,
+            depth: 0.0,
+        }
+    }
+
+    /// Calculates the transform from window size and the screen layout of its context.
+    pub(crate) fn from_layout(window_size: WindowSize, layout: &EguiScreenLayout) -> Self {
+        let physical_size = Vec2::new(window_size.physical_width, window_size.physical_height);
+        let offset = layout.offset / physical_size * 2.0;
+        EguiTransform {
+            scale: Vec2::new(2.0, -2.0) * layout.pixels_per_point / physical_size,
+            translation: Vec2::new(-1.0 + offset.x, 1.0 - offset.y),
+            tint: Vec4::ONE,
+            depth: 0.0,
         }
     }
 }
 
 /// Prepares Egui transforms.
+#[allow(clippy::type_complexity)]
 pub fn prepare_egui_transforms_system(
     mut egui_transforms: ResMut<EguiTransforms>,
-    window_sizes: Query<(Entity, &WindowSize)>,
-    egui_settings: Res<EguiSettings>,
+    window_sizes: Query<(
+        Entity,
+        &WindowSize,
+        &EguiRenderOutput,
+        Option<&EguiContextSettings>,
+        Option<&ExtractedView>,
+    )>,
 
     render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
@@ -148,13 +487,34 @@ pub fn prepare_egui_transforms_system(
     egui_transforms.buffer.clear();
     egui_transforms.offsets.clear();
 
-    for (window, size) in window_sizes.iter() {
-        let offset = egui_transforms
-            .buffer
-            .push(&EguiTransform::from_window_size(
-                *size,
-                egui_settings.scale_factor,
-            ));
+    for (window, size, render_output, context_settings, extracted_view) in window_sizes.iter() {
+        // Window size is zero until a window gets initialized, there's nothing to render yet.
+        if size.physical_width == 0.0 || size.physical_height == 0.0 || size.scale_factor == 0.0 {
+            continue;
+        }
+        // The layout the frame was begun with, which the paint jobs were tessellated for.
+        let Some(layout) = render_output.screen_layout else {
+            continue;
+        };
+        let mut transform = EguiTransform::from_layout(*size, &layout);
+        if let Some(context_settings) = context_settings {
+            transform.tint = Vec4::from_array(context_settings.tint.to_array());
+            if let Some(extracted_view) = extracted_view.filter(|_| {
+                context_settings.resolved_camera_pass() == Some(EguiCameraPass::MainPass)
+            }) {
+                transform.depth = match context_settings.depth_test_distance {
+                    // The depth of a point at the distance in front of the camera.
+                    Some(distance) => {
+                        let clip =
+                            extracted_view.clip_from_view * Vec4::new(0.0, 0.0, -distance, 1.0);
+                        (clip.z / clip.w).clamp(0.0, 1.0)
+                    }
+                    // The near plane, which passes the reverse-Z depth test everywhere.
+                    None => 1.0,
+                };
+            }
+        }
+        let offset = egui_transforms.buffer.push(&transform);
         egui_transforms.offsets.insert(window, offset);
     }
 
@@ -191,11 +551,28 @@ pub fn queue_bind_groups_system(
     render_device: Res<RenderDevice>,
     gpu_images: Res<RenderAssets<GpuImage>>,
     egui_pipeline: Res<EguiPipeline>,
+    // Samplers of the user textures added with a sampler, and their revisions.
+    mut user_texture_samplers: Local<HashMap<u64, (u64, Sampler)>>,
 ) {
+    let samplers = &egui_textures.user_textures.samplers;
+    user_texture_samplers.retain(|id, _| samplers.contains_key(id));
+    for (id, (revision, descriptor)) in samplers {
+        if !matches!(user_texture_samplers.get(id), Some((cached, _)) if cached == revision) {
+            let sampler = render_device.create_sampler(&descriptor.as_wgpu());
+            user_texture_samplers.insert(*id, (*revision, sampler));
+        }
+    }
+
     let bind_groups = egui_textures
         .handles()
         .filter_map(|(texture, handle_id)| {
             let gpu_image = gpu_images.get(&Handle::Weak(handle_id))?;
+            let sampler = match texture {
+                EguiTextureId::User(id) => user_texture_samplers
+                    .get(&id)
+                    .map_or(&gpu_image.sampler, |(_, sampler)| sampler),
+                EguiTextureId::Managed(..) => &gpu_image.sampler,
+            };
             let bind_group = render_device.create_bind_group(
                 None,
                 &egui_pipeline.texture_bind_group_layout,
@@ -206,7 +583,7 @@ pub fn queue_bind_groups_system(
                     },
                     BindGroupEntry {
                         binding: 1,
-                        resource: BindingResource::Sampler(&gpu_image.sampler),
+                        resource: BindingResource::Sampler(sampler),
                     },
                 ],
             );
@@ -221,25 +598,282 @@ pub fn queue_bind_groups_system(
 #[derive(Resource)]
 pub struct EguiPipelines(pub HashMap<Entity, CachedRenderPipelineId>);
 
-/// Queue [`EguiPipeline`]s specialized on each window's swap chain texture format.
+/// Cached Pipeline IDs for the specialized `EguiPipeline`s that upscale offscreen render targets
+/// onto windows (see [`EguiContextSettings::render_scale`]).
+#[derive(Resource)]
+pub struct EguiBlitPipelines(pub HashMap<Entity, CachedRenderPipelineId>);
+
+/// Cached Pipeline IDs for the specialized `EguiPipeline`s of contexts that don't draw with the
+/// pipelines of their windows: the ones rendered with multisampling (see
+/// [`EguiContextSettings::msaa_samples`]) and the ones drawn into cameras' main textures (see
+/// [`EguiContextSettings::draw_before_tonemapping`] and
+/// [`EguiContextSettings::depth_test_distance`]).
+#[derive(Resource)]
+pub struct EguiContextPipelines(pub HashMap<Entity, CachedRenderPipelineId>);
+
+/// Queue [`EguiPipeline`]s specialized on each window's swap chain texture format
+/// (or the image format for entities with [`EguiRenderToTexture`]).
+#[allow(clippy::too_many_arguments)]
 pub fn queue_pipelines_system(
     mut commands: Commands,
     pipeline_cache: Res<PipelineCache>,
     mut pipelines: ResMut<SpecializedRenderPipelines<EguiPipeline>>,
     egui_pipeline: Res<EguiPipeline>,
     windows: Res<ExtractedWindows>,
+    context_settings: Query<&EguiContextSettings>,
+    render_to_texture: Query<(Entity, &EguiRenderToTexture)>,
+    gpu_images: Res<RenderAssets<GpuImage>>,
+    egui_settings: Res<EguiSettings>,
+    view_targets: Query<(Entity, &ViewTarget, &EguiContextSettings)>,
+    screenshots: Query<(), With<EguiScreenshot>>,
 ) {
-    let pipelines = windows
+    let premultiplied_texture = egui_settings.premultiplied_textures;
+    let mut blit_pipelines = HashMap::default();
+    let mut egui_pipelines: HashMap<_, _> = windows
         .iter()
         .filter_map(|(window_id, window)| {
+            let texture_format = window.swap_chain_texture_format?.add_srgb_suffix();
             let key = EguiPipelineKey {
-                texture_format: window.swap_chain_texture_format?.add_srgb_suffix(),
+                texture_format,
+                premultiplied_texture,
+                blit: false,
+                sample_count: 1,
+                depth_test: false,
             };
             let pipeline_id = pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
 
+            if context_settings
+                .get(*window_id)
+                .is_ok_and(|settings| settings.uses_render_target())
+            {
+                let key = EguiPipelineKey {
+                    texture_format,
+                    premultiplied_texture: true,
+                    blit: true,
+                    sample_count: 1,
+                    depth_test: false,
+                };
+                let blit_pipeline_id = pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
+                blit_pipelines.insert(*window_id, blit_pipeline_id);
+            }
+
             Some((*window_id, pipeline_id))
         })
         .collect();
 
-    commands.insert_resource(EguiPipelines(pipelines));
+    for (entity, EguiRenderToTexture(image)) in render_to_texture.iter() {
+        let Some(gpu_image) = gpu_images.get(image) else {
+            continue;
+        };
+        let key = EguiPipelineKey {
+            texture_format: gpu_image.texture_format,
+            premultiplied_texture,
+            blit: false,
+            sample_count: 1,
+            depth_test: false,
+        };
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
+        egui_pipelines.insert(entity, pipeline_id);
+    }
+
+    // Secondary contexts draw with the pipelines of their windows, so the contexts rendered with
+    // multisampling get pipelines of their own.
+    let mut context_pipelines: HashMap<_, _> = windows
+        .iter()
+        .filter_map(|(window_id, window)| {
+            Some((
+                *window_id,
+                window.swap_chain_texture_format?.add_srgb_suffix(),
+            ))
+        })
+        .chain(
+            render_to_texture
+                .iter()
+                .filter_map(|(entity, EguiRenderToTexture(image))| {
+                    Some((entity, gpu_images.get(image)?.texture_format))
+                }),
+        )
+        .filter_map(|(entity, texture_format)| {
+            let sample_count = context_settings.get(entity).ok()?.msaa_samples;
+            if sample_count <= 1 {
+                return None;
+            }
+            let key = EguiPipelineKey {
+                texture_format,
+                premultiplied_texture,
+                blit: false,
+                sample_count,
+                depth_test: false,
+            };
+            Some((
+                entity,
+                pipelines.specialize(&pipeline_cache, &egui_pipeline, key),
+            ))
+        })
+        .collect();
+
+    commands.insert_resource(EguiPipelines(egui_pipelines));
+    commands.insert_resource(EguiBlitPipelines(blit_pipelines));
+    // Camera contexts drawn before tonemapping are always drawn into the resolved main texture,
+    // the depth-tested ones are drawn like the main pass is.
+    for (entity, view_target, settings) in view_targets.iter() {
+        let (sample_count, depth_test) = match settings.resolved_camera_pass() {
+            Some(EguiCameraPass::MainPass) => (
+                view_target
+                    .sampled_main_texture()
+                    .map_or(1, |texture| texture.sample_count()),
+                true,
+            ),
+            Some(_) => (1, false),
+            None => continue,
+        };
+        let key = EguiPipelineKey {
+            texture_format: view_target.main_texture_format(),
+            premultiplied_texture,
+            blit: false,
+            sample_count,
+            depth_test,
+        };
+        let pipeline_id = pipelines.specialize(&pipeline_cache, &egui_pipeline, key);
+        context_pipelines.insert(entity, pipeline_id);
+    }
+
+    commands.insert_resource(EguiContextPipelines(context_pipelines));
+
+    let screenshot_pipeline = (!screenshots.is_empty()).then(|| {
+        let key = EguiPipelineKey {
+            texture_format: EGUI_SCREENSHOT_FORMAT,
+            premultiplied_texture,
+            blit: false,
+            sample_count: 1,
+            depth_test: false,
+        };
+        pipelines.specialize(&pipeline_cache, &egui_pipeline, key)
+    });
+    commands.insert_resource(EguiScreenshotPipeline(screenshot_pipeline));
+}
+
+/// Frees the mesh buffers of the contexts that haven't been drawn during the frame.
+pub fn free_unused_buffers_system(mut buffer_pool: ResMut<EguiBufferPool>) {
+    buffer_pool.free_unused();
+}
+
+/// Cached Pipeline ID for the specialized `EguiPipeline` that draws into the textures of
+/// [`EguiScreenshot`] captures, `None` while nothing is captured.
+#[derive(Resource)]
+pub struct EguiScreenshotPipeline(pub Option<CachedRenderPipelineId>);
+
+/// A capture of a context copied into a buffer by [`EguiNode`].
+pub(crate) struct ScreenshotCapture {
+    pub(crate) window: Entity,
+    pub(crate) buffer: Buffer,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) padded_bytes_per_row: u32,
+}
+
+// Set by the callback of `map_async` once a buffer is mapped.
+type MapResult = Arc<Mutex<Option<Result<(), BufferAsyncError>>>>;
+
+/// The [`EguiScreenshot`] captures that are being read back from the GPU.
+#[derive(Resource)]
+pub struct EguiScreenshotCaptures {
+    // Captures copied into their buffers during the frame, the buffers are mapped once the
+    // commands are submitted.
+    copied: Mutex<Vec<ScreenshotCapture>>,
+    mapping: Vec<(ScreenshotCapture, MapResult)>,
+    captured_screenshots: EguiCapturedScreenshots,
+}
+
+impl EguiScreenshotCaptures {
+    pub(crate) fn new(captured_screenshots: EguiCapturedScreenshots) -> Self {
+        Self {
+            copied: Mutex::default(),
+            mapping: Vec::new(),
+            captured_screenshots,
+        }
+    }
+
+    /// Whether a capture of the context is already being read back.
+    pub(crate) fn is_capturing(&self, window: Entity) -> bool {
+        self.copied
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .chain(self.mapping.iter().map(|(capture, _)| capture))
+            .any(|capture| capture.window == window)
+    }
+
+    pub(crate) fn push_copied(&self, capture: ScreenshotCapture) {
+        self.copied
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(capture);
+    }
+}
+
+/// The format of the textures of [`EguiScreenshot`] captures.
+pub(crate) const EGUI_SCREENSHOT_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Reads the [`EguiScreenshot`] captures that have been mapped since the last frame, and maps the
+/// buffers of the ones copied during this frame.
+pub fn read_screenshots_system(mut screenshot_captures: ResMut<EguiScreenshotCaptures>) {
+    let screenshot_captures = &mut *screenshot_captures;
+    // The buffers are mapped while the device is polled, which happens when commands are submitted.
+    screenshot_captures.mapping.retain(|(capture, map_result)| {
+        let map_result = map_result
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        match map_result {
+            None => return true,
+            Some(Ok(())) => {
+                let row_bytes = capture.width as usize * 4;
+                let mut data = Vec::with_capacity(row_bytes * capture.height as usize);
+                {
+                    let mapped_range = capture.buffer.slice(..).get_mapped_range();
+                    for row in mapped_range.chunks(capture.padded_bytes_per_row as usize) {
+                        data.extend_from_slice(&row[..row_bytes]);
+                    }
+                }
+                capture.buffer.unmap();
+                let image = Image::new(
+                    Extent3d {
+                        width: capture.width,
+                        height: capture.height,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    data,
+                    EGUI_SCREENSHOT_FORMAT,
+                    RenderAssetUsages::MAIN_WORLD,
+                );
+                screenshot_captures
+                    .captured_screenshots
+                    .push(capture.window, image);
+            }
+            Some(Err(err)) => log::error!("Failed to read an Egui screenshot: {err}"),
+        }
+        false
+    });
+
+    let copied = std::mem::take(
+        &mut *screenshot_captures
+            .copied
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner),
+    );
+    for capture in copied {
+        let map_result = Arc::new(Mutex::new(None));
+        let callback_map_result = map_result.clone();
+        capture
+            .buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                *callback_map_result
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some(result);
+            });
+        screenshot_captures.mapping.push((capture, map_result));
+    }
 }
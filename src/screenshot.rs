@@ -0,0 +1,149 @@
+use bevy::{
+    prelude::{Component, Entity, Event, Image, Resource},
+    render::extract_component::ExtractComponent,
+};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Screenshots taken for [`egui::ViewportCommand::Screenshot`] requests, waiting to be passed
+/// to Egui as [`egui::Event::Screenshot`].
+///
+/// Requests are forwarded to Bevy's
+/// [`ScreenshotManager`](bevy::render::view::screenshot::ScreenshotManager) during the
+/// [`EguiSet::EndPass`](crate::EguiSet::EndPass) system. The screenshot is taken asynchronously and
+/// arrives a frame or two later, it's then added to the input of the window that requested it
+/// during the [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system. Only windows support
+/// screenshots, requests made by [`EguiRenderToTexture`](crate::EguiRenderToTexture) contexts are
+/// ignored.
+#[derive(Resource, Clone, Default)]
+pub struct EguiScreenshots(Arc<Mutex<Vec<(Entity, egui::ColorImage)>>>);
+
+impl EguiScreenshots {
+    pub(crate) fn push(&self, window: Entity, image: egui::ColorImage) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((window, image));
+    }
+
+    pub(crate) fn take(&self) -> Vec<(Entity, egui::ColorImage)> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Requests a capture of the Egui output of the context (window) entity it's inserted into.
+///
+/// Only Egui is captured, without anything cameras render to the window: the meshes of the next
+/// frame the context draws are rendered into a transparent offscreen texture, which is read back
+/// from the GPU. A frame or two later, the capture is sent as an [`EguiScreenshotCaptured`] event
+/// during the [`EguiSet::ProcessInput`](crate::EguiSet::ProcessInput) system, and the component is
+/// removed. Insert it again to capture another frame. Paint callbacks aren't drawn into the
+/// capture. Captures are taken at the resolution the context is rendered at (see
+/// [`EguiContextSettings::render_scale`](crate::EguiContextSettings::render_scale)), without
+/// multisampling.
+///
+/// ```no_run,rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::{EguiScreenshot, EguiScreenshotCaptured};
+///
+/// fn request_screenshot(mut commands: Commands, window: Query<Entity, With<PrimaryWindow>>) {
+///     commands.entity(window.single()).insert(EguiScreenshot);
+/// }
+///
+/// fn save_screenshot(mut screenshots: EventReader<EguiScreenshotCaptured>) {
+///     for screenshot in screenshots.read() {
+///         let size = screenshot.image.size();
+///         info!("Captured {}x{} pixels of Egui", size.x, size.y);
+///     }
+/// }
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default, ExtractComponent)]
+pub struct EguiScreenshot;
+
+/// A capture of the Egui output of a context, requested with [`EguiScreenshot`].
+#[derive(Event, Clone, Debug)]
+pub struct EguiScreenshotCaptured {
+    /// The context (window) entity the capture was requested for.
+    pub window: Entity,
+    /// The capture, an `Rgba8UnormSrgb` image with premultiplied alpha, in physical pixels.
+    /// Pixels that nothing was painted to are transparent, and [`Image::data`] can be compared
+    /// byte by byte in visual regression tests.
+    pub image: Image,
+}
+
+/// Captures of Egui outputs read back from the GPU, waiting to be sent as
+/// [`EguiScreenshotCaptured`] events.
+#[derive(Resource, Clone, Default)]
+pub struct EguiCapturedScreenshots(Arc<Mutex<Vec<(Entity, Image)>>>);
+
+impl EguiCapturedScreenshots {
+    pub(crate) fn push(&self, window: Entity, image: Image) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((window, image));
+    }
+
+    pub(crate) fn take(&self) -> Vec<(Entity, Image)> {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiInputHooks};
+
+    #[test]
+    fn test_screenshot_reply() {
+        use std::sync::{Arc, Mutex};
+
+        let (mut app, window) = test_app();
+        app.update();
+        let screenshot_sizes = Arc::new(Mutex::new(Vec::new()));
+        let screenshot_sizes_clone = screenshot_sizes.clone();
+        app.world_mut()
+            .resource_mut::<EguiInputHooks>()
+            .add(move |_, raw_input| {
+                for event in &raw_input.events {
+                    if let egui::Event::Screenshot { viewport_id, image } = event {
+                        assert_eq!(*viewport_id, egui::ViewportId::ROOT);
+                        screenshot_sizes_clone.lock().unwrap().push(image.size);
+                    }
+                }
+            });
+        app.world()
+            .resource::<EguiScreenshots>()
+            .push(window, egui::ColorImage::new([4, 2], egui::Color32::WHITE));
+        app.update();
+        app.update();
+
+        assert_eq!(*screenshot_sizes.lock().unwrap(), vec![[4, 2]]);
+    }
+
+    #[test]
+    fn test_captured_screenshots() {
+        use bevy::{ecs::event::Events, render::texture::Image};
+
+        let (mut app, window) = test_app();
+        app.update();
+        app.world_mut().entity_mut(window).insert(EguiScreenshot);
+        let captured_screenshots = app.world().resource::<EguiCapturedScreenshots>().clone();
+        // The frames captured before the request is removed, and captures without requests,
+        // aren't sent.
+        captured_screenshots.push(window, Image::default());
+        captured_screenshots.push(window, Image::default());
+        captured_screenshots.push(Entity::PLACEHOLDER, Image::default());
+        app.update();
+        captured_screenshots.push(window, Image::default());
+        app.update();
+
+        let windows: Vec<_> = app
+            .world_mut()
+            .resource_mut::<Events<EguiScreenshotCaptured>>()
+            .drain()
+            .map(|screenshot| screenshot.window)
+            .collect();
+        assert_eq!(windows, vec![window]);
+        assert!(!app.world().entity(window).contains::<EguiScreenshot>());
+    }
+}
@@ -1,44 +1,98 @@
+use crate::viewports::{run_viewport_ui, EguiViewport};
+#[cfg(all(
+    feature = "render",
+    feature = "manage_clipboard",
+    not(target_os = "android"),
+    not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+))]
+use crate::EguiClipboardImagePaste;
 use crate::{
-    EguiContext, EguiContextQuery, EguiContextQueryItem, EguiInput, EguiSettings, WindowSize,
+    EguiAccessibilityZoom, EguiBlockPointerInput, EguiContext, EguiContextQuery,
+    EguiContextQueryItem, EguiContextSettings, EguiCopyEvent, EguiCursorIconMapping,
+    EguiFocusedText, EguiFontAtlasPrewarm, EguiGamepadNavigation, EguiInput, EguiInputFilter,
+    EguiInputHooks, EguiInteractionEvent, EguiOpenUrlEvent, EguiOutput, EguiPassHooks,
+    EguiRenderOutput, EguiRenderStats, EguiRotateGestureEvent, EguiScreenLayout,
+    EguiSecondaryContext, EguiSecondaryContextQuery, EguiSettings, EguiTextCursorMoved,
+    EguiTextInputHints, EguiUnmappedKeyEvent, EguiVirtualKeyboard, EguiWantsInput,
+    EguiWantsRepaint, WindowSize,
+};
+#[cfg(feature = "render")]
+use crate::{
+    EguiCameraContext, EguiCapturedScreenshots, EguiMeshPointerInput, EguiRenderToTexture,
+    EguiRenderToTextureContextQuery, EguiScreenshot, EguiScreenshotCaptured, EguiScreenshots,
+    EguiUserTextures,
+};
+#[cfg(all(
+    feature = "render",
+    feature = "manage_clipboard",
+    not(target_os = "android"),
+    not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+))]
+use bevy::render::{
+    render_asset::RenderAssetUsages,
+    render_resource::{Extent3d, TextureDimension, TextureFormat},
+    texture::Image,
 };
 use bevy::{
+    a11y::AccessibilityRequested,
     ecs::{
+        change_detection::{DetectChanges, DetectChangesMut},
         event::EventWriter,
         query::QueryEntityError,
-        system::{Local, Res, SystemParam},
+        system::{Local, Res, ResMut, SystemParam},
     },
     input::{
+        gamepad::{GamepadAxis, GamepadAxisType, GamepadButton, GamepadButtonType, Gamepads},
         keyboard::{Key, KeyCode, KeyboardInput},
         mouse::{MouseButton, MouseButtonInput, MouseScrollUnit, MouseWheel},
         touch::TouchInput,
-        ButtonState,
+        Axis, ButtonInput, ButtonState,
     },
     log,
-    prelude::{Entity, EventReader, Query, Resource, Time},
+    prelude::{Entity, EventReader, Mut, Query, Ref, Resource, Time, Without},
+    tasks::AsyncComputeTaskPool,
     time::Real,
-    window::{CursorMoved, RequestRedraw},
+    utils::{synccell::SyncCell, HashMap, HashSet},
+    window::{
+        CursorGrabMode, CursorLeft, CursorMoved, FileDragAndDrop, Ime, RequestRedraw, Window,
+    },
 };
-use std::marker::PhantomData;
+#[cfg(feature = "render")]
+use bevy::{
+    asset::{Assets, Handle},
+    math::{Ray3d, Vec2},
+    prelude::{Camera, Commands, GlobalTransform},
+    render::{
+        camera::NormalizedRenderTarget,
+        mesh::{Mesh, PrimitiveTopology, VertexAttributeValues},
+    },
+};
+use bevy::{prelude::With, window::PrimaryWindow};
+use std::{marker::PhantomData, sync::mpsc};
 
 #[allow(missing_docs)]
 #[derive(SystemParam)]
 // IMPORTANT: remember to add the logic to clear event readers to the `clear` method.
 pub struct InputEvents<'w, 's> {
     pub ev_cursor: EventReader<'w, 's, CursorMoved>,
+    pub ev_cursor_left: EventReader<'w, 's, CursorLeft>,
     pub ev_mouse_button_input: EventReader<'w, 's, MouseButtonInput>,
     pub ev_mouse_wheel: EventReader<'w, 's, MouseWheel>,
     pub ev_keyboard_input: EventReader<'w, 's, KeyboardInput>,
     pub ev_touch: EventReader<'w, 's, TouchInput>,
+    pub ev_ime: EventReader<'w, 's, Ime>,
 }
 
 impl<'w, 's> InputEvents<'w, 's> {
     /// Consumes all the events.
     pub fn clear(&mut self) {
         self.ev_cursor.read().last();
+        self.ev_cursor_left.read().last();
         self.ev_mouse_button_input.read().last();
         self.ev_mouse_wheel.read().last();
         self.ev_keyboard_input.read().last();
         self.ev_touch.read().last();
+        self.ev_ime.read().last();
     }
 }
 
@@ -52,6 +106,93 @@ pub struct ModifierKeysState {
     win: bool,
 }
 
+/// Clipboard related keyboard shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardShortcut {
+    /// `Ctrl+C` (`Cmd+C` on macOS) or the dedicated `Copy` key.
+    Copy,
+    /// `Ctrl+X` (`Cmd+X` on macOS) or the dedicated `Cut` key.
+    Cut,
+    /// `Ctrl+V` (`Cmd+V` on macOS) or the dedicated `Paste` key.
+    Paste,
+}
+
+/// Browser `copy`/`cut` events received within this time after a keyboard shortcut are
+/// considered to be sent for the same shortcut.
+#[cfg(all(
+    feature = "manage_clipboard",
+    target_arch = "wasm32",
+    web_sys_unstable_apis
+))]
+const CLIPBOARD_SHORTCUT_DEDUP_SECS: f64 = 0.5;
+
+/// Detects clipboard shortcuts. `Select all` (`Ctrl+A`) doesn't have a high-level event in egui:
+/// text edits handle the [`egui::Event::Key`] event themselves.
+pub fn clipboard_shortcut(key: egui::Key, command: bool) -> Option<ClipboardShortcut> {
+    match key {
+        egui::Key::Copy => Some(ClipboardShortcut::Copy),
+        egui::Key::Cut => Some(ClipboardShortcut::Cut),
+        egui::Key::Paste => Some(ClipboardShortcut::Paste),
+        egui::Key::C if command => Some(ClipboardShortcut::Copy),
+        egui::Key::X if command => Some(ClipboardShortcut::Cut),
+        egui::Key::V if command => Some(ClipboardShortcut::Paste),
+        _ => None,
+    }
+}
+
+/// Tracks active touches of a window to synthesize gesture events.
+#[derive(Default, Clone, Debug)]
+pub struct TouchGestureState {
+    touches: HashMap<u64, egui::Pos2>,
+    last_gesture: Option<TwoFingerGesture>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TwoFingerGesture {
+    center: egui::Pos2,
+    distance: f32,
+    angle: f32,
+}
+
+impl TouchGestureState {
+    fn two_finger_gesture(&self) -> Option<TwoFingerGesture> {
+        if self.touches.len() != 2 {
+            return None;
+        }
+        let mut touches = self.touches.iter();
+        let (&first_id, &first) = touches.next()?;
+        let (&second_id, &second) = touches.next()?;
+        // Keep the order stable, so that the angle doesn't flip between frames.
+        let (first, second) = if first_id < second_id {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let offset = second - first;
+        Some(TwoFingerGesture {
+            center: first + offset / 2.0,
+            distance: offset.length(),
+            angle: offset.angle(),
+        })
+    }
+}
+
+/// Wraps an angle (in radians) into the `[-PI, PI]` range.
+fn wrap_angle(angle: f32) -> f32 {
+    use std::f32::consts::{PI, TAU};
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+#[allow(missing_docs)]
+#[derive(SystemParam)]
+pub struct OutputEvents<'w> {
+    pub ev_interaction: EventWriter<'w, EguiInteractionEvent>,
+    pub ev_copy: EventWriter<'w, EguiCopyEvent>,
+    pub ev_open_url: EventWriter<'w, EguiOpenUrlEvent>,
+    pub ev_text_cursor_moved: EventWriter<'w, EguiTextCursorMoved>,
+    pub ev_wants_repaint: EventWriter<'w, EguiWantsRepaint>,
+}
+
 #[allow(missing_docs)]
 #[derive(SystemParam)]
 pub struct InputResources<'w, 's> {
@@ -62,22 +203,37 @@ pub struct InputResources<'w, 's> {
     ))]
     pub egui_clipboard: bevy::ecs::system::ResMut<'w, crate::EguiClipboard>,
     pub modifier_keys_state: Local<'s, ModifierKeysState>,
-    #[system_param(ignore)]
-    _marker: PhantomData<&'w ()>,
+    #[cfg(all(
+        feature = "manage_clipboard",
+        target_arch = "wasm32",
+        web_sys_unstable_apis
+    ))]
+    pub last_clipboard_shortcut: Local<'s, Option<(ClipboardShortcut, f64)>>,
+    pub touch_gestures: Local<'s, HashMap<Entity, TouchGestureState>>,
+    /// Windows with an active IME composition, tracked from [`Ime`] events.
+    pub ime_composing: Local<'s, HashSet<Entity>>,
+    /// Keys (logical and physical) pressed while Cmd is held on macOS.
+    #[cfg(target_arch = "wasm32")]
+    pub keys_pressed_with_meta: Local<'s, HashMap<egui::Key, Option<egui::Key>>>,
+    pub ev_rotate_gesture: EventWriter<'w, EguiRotateGestureEvent>,
+    pub ev_unmapped_key: EventWriter<'w, EguiUnmappedKeyEvent>,
 }
 
 #[allow(missing_docs)]
 #[derive(SystemParam)]
 pub struct ContextSystemParams<'w, 's> {
     pub contexts: Query<'w, 's, EguiContextQuery>,
+    pub accessibility_zoom: Res<'w, EguiAccessibilityZoom>,
     pub is_macos: Local<'s, bool>,
     #[system_param(ignore)]
     _marker: PhantomData<&'s ()>,
 }
 
 impl<'w, 's> ContextSystemParams<'w, 's> {
-    fn window_context(&mut self, window: Entity) -> Option<EguiContextQueryItem> {
+    /// Returns the context of a window to pass its input events to, if it accepts input.
+    fn window_context(&mut self, window: Entity) -> Option<EguiContextQueryItem<'_>> {
         match self.contexts.get_mut(window) {
+            Ok(context) if context.context_settings.display_only => None,
             Ok(context) => Some(context),
             Err(err @ QueryEntityError::AliasedMutability(_)) => {
                 panic!("Failed to get an Egui context for a window ({window:?}): {err:?}");
@@ -94,12 +250,19 @@ impl<'w, 's> ContextSystemParams<'w, 's> {
 }
 
 /// Processes Bevy input and feeds it to Egui.
+#[allow(clippy::too_many_arguments)]
 pub fn process_input_system(
     mut input_events: InputEvents,
     mut input_resources: InputResources,
     mut context_params: ContextSystemParams,
     egui_settings: Res<EguiSettings>,
+    block_pointer_input: Res<EguiBlockPointerInput>,
+    input_filter: Res<EguiInputFilter>,
     time: Res<Time<Real>>,
+    #[cfg(feature = "render")] mut render_to_texture_contexts: Query<
+        EguiRenderToTextureContextQuery,
+        Without<Window>,
+    >,
 ) {
     // Test whether it's macOS or OS X.
     use std::sync::Once;
@@ -161,25 +324,82 @@ pub fn process_input_system(
         command,
     };
 
+    let hovered_window_only = egui_settings.pointer_input_to_hovered_window_only;
+    let accessibility_zoom = *context_params.accessibility_zoom;
+    let pointer_blocked = block_pointer_input.0;
+
     for event in input_events.ev_cursor.read() {
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
+        if hovered_window_only && window_context.window.cursor_position().is_none() {
+            continue;
+        }
 
-        let scale_factor = egui_settings.scale_factor;
-        let (x, y): (f32, f32) = (event.position / scale_factor).into();
-        let mouse_position = egui::pos2(x, y);
+        let (window_size, layout) =
+            screen_layout(&window_context, &egui_settings, accessibility_zoom);
+        let mouse_position = layout.to_egui_pos(&window_size, event.position);
         window_context.ctx.mouse_position = mouse_position;
+        window_context.ctx.pointer_gone_on_release = false;
+        let in_passthrough = window_context.ctx.passthrough_press_held
+            || window_context
+                .pointer_passthrough
+                .is_some_and(|passthrough| passthrough.contains(mouse_position))
+                // Don't interrupt drags that Egui has started.
+                && !window_context
+                    .ctx
+                    .ctx
+                    .input(|input| input.pointer.any_down());
+        window_context.ctx.pointer_in_passthrough = in_passthrough;
+        window_context
+            .egui_input
+            .events
+            .push(if pointer_blocked || in_passthrough {
+                egui::Event::PointerGone
+            } else {
+                egui::Event::PointerMoved(mouse_position)
+            });
+    }
+
+    for event in input_events.ev_cursor_left.read() {
+        let Some(mut window_context) = context_params.window_context(event.window) else {
+            continue;
+        };
+        // Drags continue outside the window until the button is released.
+        if window_context
+            .ctx
+            .ctx
+            .input(|input| input.pointer.any_down())
+        {
+            window_context.ctx.pointer_gone_on_release = true;
+            continue;
+        }
         window_context
             .egui_input
             .events
-            .push(egui::Event::PointerMoved(mouse_position));
+            .push(egui::Event::PointerGone);
     }
 
     for event in input_events.ev_mouse_button_input.read() {
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
+        // The release of a drag that left the window is still passed, so it isn't stuck.
+        let ends_drag =
+            window_context.ctx.pointer_gone_on_release && event.state == ButtonState::Released;
+        if pointer_blocked
+            || hovered_window_only
+                && window_context.window.cursor_position().is_none()
+                && !ends_drag
+        {
+            continue;
+        }
+        if window_context.ctx.pointer_in_passthrough && window_context.pointer_passthrough.is_some()
+        {
+            // Skip the release as well if the button was pressed over a pass-through region.
+            window_context.ctx.passthrough_press_held = event.state == ButtonState::Pressed;
+            continue;
+        }
 
         let button = match event.button {
             MouseButton::Left => Some(egui::PointerButton::Primary),
@@ -201,6 +421,22 @@ pub fn process_input_system(
                     pressed,
                     modifiers,
                 });
+            let other_buttons_down = window_context.ctx.ctx.input(|input| {
+                [
+                    egui::PointerButton::Primary,
+                    egui::PointerButton::Secondary,
+                    egui::PointerButton::Middle,
+                ]
+                .into_iter()
+                .any(|other| other != button && input.pointer.button_down(other))
+            });
+            if ends_drag && !other_buttons_down {
+                window_context.ctx.pointer_gone_on_release = false;
+                window_context
+                    .egui_input
+                    .events
+                    .push(egui::Event::PointerGone);
+            }
         }
     }
 
@@ -208,6 +444,13 @@ pub fn process_input_system(
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
+        if pointer_blocked
+            || hovered_window_only && window_context.window.cursor_position().is_none()
+            || window_context.ctx.pointer_in_passthrough
+                && window_context.pointer_passthrough.is_some()
+        {
+            continue;
+        }
 
         let delta = egui::vec2(event.x, event.y);
 
@@ -228,11 +471,23 @@ pub fn process_input_system(
 
     for event in keyboard_input_events {
         let text_event_allowed = !command && !win || !*context_params.is_macos && ctrl && alt;
+        #[cfg(target_arch = "wasm32")]
+        let is_macos = *context_params.is_macos;
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
 
-        if text_event_allowed && event.state.is_pressed() {
+        // Keys pressed during an IME composition belong to the input method, the composed text
+        // arrives with `Ime::Commit`. Browsers report them with the `Process` key (key code 229)
+        // or with `isComposing` set, but not consistently, so the composition state tracked from
+        // IME events is checked as well. Releases are still passed to avoid stuck keys.
+        if event.logical_key == Key::Process {
+            continue;
+        }
+        let composing =
+            event.state.is_pressed() && input_resources.ime_composing.contains(&event.window);
+
+        if text_event_allowed && event.state.is_pressed() && !composing {
             match &event.logical_key {
                 Key::Character(char) if char.matches(char::is_control).count() == 0 => {
                     (window_context.egui_input.events).push(egui::Event::Text(char.to_string()));
@@ -244,10 +499,41 @@ pub fn process_input_system(
             }
         }
 
-        let (Some(key), physical_key) = (
-            bevy_to_egui_key(&event.logical_key),
-            bevy_to_egui_physical_key(&event.key_code),
-        ) else {
+        // Browsers on macOS don't send `keyup` events for keys released while Cmd is held,
+        // so we release such keys ourselves once Cmd is released.
+        #[cfg(target_arch = "wasm32")]
+        if is_macos
+            && matches!(event.logical_key, Key::Super | Key::Meta)
+            && !event.state.is_pressed()
+        {
+            for (key, physical_key) in input_resources.keys_pressed_with_meta.drain() {
+                window_context.egui_input.events.push(egui::Event::Key {
+                    key,
+                    pressed: false,
+                    repeat: false,
+                    modifiers,
+                    physical_key,
+                });
+            }
+        }
+
+        if composing {
+            continue;
+        }
+
+        // Fall back to the physical key for the layouts that don't have a logical equivalent
+        // (Cyrillic letters, for example), so that shortcuts still work.
+        let physical_key = bevy_to_egui_physical_key(&event.key_code);
+        let Some(key) = bevy_to_egui_key(&event.logical_key).or(physical_key) else {
+            if is_unmapped_key(&event.logical_key) {
+                input_resources.ev_unmapped_key.send(EguiUnmappedKeyEvent {
+                    window: event.window,
+                    logical_key: event.logical_key.clone(),
+                    key_code: event.key_code,
+                    pressed: event.state.is_pressed(),
+                    modifiers,
+                });
+            }
             continue;
         };
 
@@ -260,34 +546,101 @@ pub fn process_input_system(
         };
         window_context.egui_input.events.push(egui_event);
 
+        #[cfg(target_arch = "wasm32")]
+        if is_macos {
+            if event.state.is_pressed() && win {
+                input_resources
+                    .keys_pressed_with_meta
+                    .insert(key, physical_key);
+            } else if !event.state.is_pressed() {
+                input_resources.keys_pressed_with_meta.remove(&key);
+            }
+        }
+
         // We also check that it's an `ButtonState::Pressed` event, as we don't want to
         // copy, cut or paste on the key release.
+        if !event.state.is_pressed() {
+            continue;
+        }
+        let Some(shortcut) = clipboard_shortcut(key, command)
+            .or_else(|| physical_key.and_then(|key| clipboard_shortcut(key, command)))
+        else {
+            continue;
+        };
+
+        // On web, browsers may also send their own `copy`/`cut` events for the same shortcut.
         #[cfg(all(
             feature = "manage_clipboard",
-            not(target_os = "android"),
-            not(target_arch = "wasm32")
+            target_arch = "wasm32",
+            web_sys_unstable_apis
         ))]
-        if command && event.state.is_pressed() {
-            match key {
-                egui::Key::C => {
-                    window_context.egui_input.events.push(egui::Event::Copy);
-                }
-                egui::Key::X => {
-                    window_context.egui_input.events.push(egui::Event::Cut);
-                }
-                egui::Key::V => {
-                    if let Some(contents) = input_resources.egui_clipboard.get_contents() {
-                        window_context
-                            .egui_input
-                            .events
-                            .push(egui::Event::Text(contents))
-                    }
+        {
+            *input_resources.last_clipboard_shortcut = Some((shortcut, time.elapsed_seconds_f64()));
+        }
+
+        match shortcut {
+            ClipboardShortcut::Copy => {
+                window_context.egui_input.events.push(egui::Event::Copy);
+            }
+            ClipboardShortcut::Cut => {
+                window_context.egui_input.events.push(egui::Event::Cut);
+            }
+            ClipboardShortcut::Paste => {
+                // On web, the contents are received with the `paste` event.
+                #[cfg(all(
+                    feature = "manage_clipboard",
+                    not(target_os = "android"),
+                    not(target_arch = "wasm32")
+                ))]
+                match input_resources.egui_clipboard.get_contents() {
+                    Some(contents) => window_context
+                        .egui_input
+                        .events
+                        .push(egui::Event::Text(contents)),
+                    // Egui doesn't have an event for images, see `EguiClipboardImagePaste`.
+                    #[cfg(feature = "render")]
+                    None => input_resources.egui_clipboard.paste_image(event.window),
+                    #[cfg(not(feature = "render"))]
+                    None => {}
                 }
-                _ => {}
             }
         }
     }
 
+    for event in input_events.ev_ime.read() {
+        let window = match event {
+            Ime::Preedit { window, .. }
+            | Ime::Commit { window, .. }
+            | Ime::Enabled { window }
+            | Ime::Disabled { window } => *window,
+        };
+        match event {
+            Ime::Preedit { value, cursor, .. } if !value.is_empty() && cursor.is_some() => {
+                input_resources.ime_composing.insert(window);
+            }
+            Ime::Preedit { .. } | Ime::Commit { .. } | Ime::Disabled { .. } => {
+                input_resources.ime_composing.remove(&window);
+            }
+            Ime::Enabled { .. } => {}
+        }
+        let Some(mut window_context) = context_params.window_context(window) else {
+            continue;
+        };
+
+        let ime_event = match event {
+            Ime::Enabled { .. } => egui::ImeEvent::Enabled,
+            // Preedit events without a cursor only signal that the composition has been cleared.
+            Ime::Preedit { cursor: None, .. } => continue,
+            Ime::Preedit { value, .. } => egui::ImeEvent::Preedit(value.clone()),
+            Ime::Commit { value, .. } => egui::ImeEvent::Commit(value.clone()),
+            Ime::Disabled { .. } => egui::ImeEvent::Disabled,
+        };
+        window_context
+            .egui_input
+            .events
+            .push(egui::Event::Ime(ime_event));
+    }
+
     #[cfg(all(
         feature = "manage_clipboard",
         target_arch = "wasm32",
@@ -296,13 +649,30 @@ pub fn process_input_system(
     while let Some(event) = input_resources.egui_clipboard.try_receive_clipboard_event() {
         // In web, we assume that we have only 1 window per app.
         let mut window_context = context_params.contexts.single_mut();
+        if window_context.context_settings.display_only {
+            continue;
+        }
+
+        // Skip the events that we've already sent when handling the keyboard shortcuts.
+        let handled_shortcut = |shortcut| {
+            input_resources
+                .last_clipboard_shortcut
+                .is_some_and(|(last_shortcut, last_time)| {
+                    last_shortcut == shortcut
+                        && time.elapsed_seconds_f64() - last_time < CLIPBOARD_SHORTCUT_DEDUP_SECS
+                })
+        };
 
         match event {
             crate::web_clipboard::WebClipboardEvent::Copy => {
-                window_context.egui_input.events.push(egui::Event::Copy);
+                if !handled_shortcut(ClipboardShortcut::Copy) {
+                    window_context.egui_input.events.push(egui::Event::Copy);
+                }
             }
             crate::web_clipboard::WebClipboardEvent::Cut => {
-                window_context.egui_input.events.push(egui::Event::Cut);
+                if !handled_shortcut(ClipboardShortcut::Cut) {
+                    window_context.egui_input.events.push(egui::Event::Cut);
+                }
             }
             crate::web_clipboard::WebClipboardEvent::Paste(contents) => {
                 input_resources
@@ -317,13 +687,45 @@ pub fn process_input_system(
     }
 
     for event in input_events.ev_touch.read() {
+        if pointer_blocked {
+            continue;
+        }
         let Some(mut window_context) = context_params.window_context(event.window) else {
             continue;
         };
 
         let touch_id = egui::TouchId::from(event.id);
-        let scale_factor = egui_settings.scale_factor;
-        let touch_position: (f32, f32) = (event.position / scale_factor).into();
+        let (window_size, layout) =
+            screen_layout(&window_context, &egui_settings, accessibility_zoom);
+        let touch_position: (f32, f32) = layout.to_egui_pos(&window_size, event.position).into();
+
+        // Touches that start over a pass-through region are skipped until they end.
+        let passthrough = match event.phase {
+            bevy::input::touch::TouchPhase::Started => {
+                let passthrough = window_context
+                    .pointer_passthrough
+                    .is_some_and(|passthrough| {
+                        passthrough.contains(egui::pos2(touch_position.0, touch_position.1))
+                    })
+                    && !window_context
+                        .ctx
+                        .ctx
+                        .input(|input| input.pointer.any_down());
+                if passthrough {
+                    window_context.ctx.passthrough_touches.insert(event.id);
+                }
+                passthrough
+            }
+            bevy::input::touch::TouchPhase::Moved => {
+                window_context.ctx.passthrough_touches.contains(&event.id)
+            }
+            bevy::input::touch::TouchPhase::Ended | bevy::input::touch::TouchPhase::Canceled => {
+                window_context.ctx.passthrough_touches.remove(&event.id)
+            }
+        };
+        if passthrough {
+            continue;
+        }
 
         // Emit touch event
         window_context.egui_input.events.push(egui::Event::Touch {
@@ -409,10 +811,102 @@ pub fn process_input_system(
                 }
             }
         }
+
+        if !egui_settings.synthesize_touch_gestures {
+            continue;
+        }
+
+        let position = egui::pos2(touch_position.0, touch_position.1);
+        let gesture_state = input_resources
+            .touch_gestures
+            .entry(event.window)
+            .or_default();
+        match event.phase {
+            bevy::input::touch::TouchPhase::Started | bevy::input::touch::TouchPhase::Moved => {
+                gesture_state.touches.insert(event.id, position);
+            }
+            bevy::input::touch::TouchPhase::Ended | bevy::input::touch::TouchPhase::Canceled => {
+                gesture_state.touches.remove(&event.id);
+            }
+        }
+
+        let gesture = gesture_state.two_finger_gesture();
+        if event.phase == bevy::input::touch::TouchPhase::Started && gesture.is_some() {
+            // Release the emulated mouse button, so that the first finger doesn't drag anything
+            // while the gesture is in progress.
+            let pointer_position = window_context.ctx.mouse_position;
+            window_context
+                .egui_input
+                .events
+                .push(egui::Event::PointerButton {
+                    pos: pointer_position,
+                    button: egui::PointerButton::Primary,
+                    pressed: false,
+                    modifiers,
+                });
+            window_context
+                .egui_input
+                .events
+                .push(egui::Event::PointerGone);
+        }
+
+        if let (Some(last_gesture), Some(gesture), bevy::input::touch::TouchPhase::Moved) =
+            (gesture_state.last_gesture, gesture, event.phase)
+        {
+            let delta = gesture.center - last_gesture.center;
+            if delta != egui::Vec2::ZERO {
+                window_context
+                    .egui_input
+                    .events
+                    .push(egui::Event::MouseWheel {
+                        unit: egui::MouseWheelUnit::Point,
+                        delta,
+                        modifiers,
+                    });
+            }
+            if last_gesture.distance > 0.0 && gesture.distance != last_gesture.distance {
+                window_context
+                    .egui_input
+                    .events
+                    .push(egui::Event::Zoom(gesture.distance / last_gesture.distance));
+            }
+            let rotation = wrap_angle(gesture.angle - last_gesture.angle);
+            if rotation != 0.0 {
+                input_resources
+                    .ev_rotate_gesture
+                    .send(EguiRotateGestureEvent {
+                        window: event.window,
+                        delta: rotation,
+                    });
+            }
+        }
+        gesture_state.last_gesture = gesture;
     }
 
     for mut context in context_params.contexts.iter_mut() {
-        context.egui_input.modifiers = modifiers;
+        let window = context.window_entity;
+        context
+            .egui_input
+            .events
+            .retain(|event| input_filter.allows(window, event));
+        context.egui_input.modifiers = if context.context_settings.display_only {
+            // Egui would keep hovering with the last known pointer position otherwise.
+            if context.context_settings.is_changed() {
+                context.egui_input.events.push(egui::Event::PointerGone);
+            }
+            egui::Modifiers::default()
+        } else {
+            modifiers
+        };
+        context.egui_input.time = Some(time.elapsed_seconds_f64());
+    }
+    #[cfg(feature = "render")]
+    for mut context in render_to_texture_contexts.iter_mut() {
+        context.egui_input.modifiers = if context.context_settings.display_only {
+            egui::Modifiers::default()
+        } else {
+            modifiers
+        };
         context.egui_input.time = Some(time.elapsed_seconds_f64());
     }
 
@@ -421,133 +915,2178 @@ pub fn process_input_system(
     input_events.clear();
 }
 
-/// Initialises Egui contexts (for multiple windows).
-pub fn update_window_contexts_system(
-    mut context_params: ContextSystemParams,
-    egui_settings: Res<EguiSettings>,
+/// Advances the animated images of [`EguiUserTextures`] and repaints the contexts that show them.
+#[cfg(feature = "render")]
+pub fn advance_animated_images_system(
+    time: Res<Time<Real>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    mut contexts: Query<(&mut EguiContext, &EguiRenderOutput)>,
 ) {
-    for mut context in context_params.contexts.iter_mut() {
-        let new_window_size = WindowSize::new(
-            context.window.physical_width() as f32,
-            context.window.physical_height() as f32,
-            context.window.scale_factor(),
-        );
-        let width = new_window_size.physical_width
-            / new_window_size.scale_factor
-            / egui_settings.scale_factor;
-        let height = new_window_size.physical_height
-            / new_window_size.scale_factor
-            / egui_settings.scale_factor;
+    if user_textures.animations.is_empty() {
+        return;
+    }
+    // Only extract the textures to the render world when a frame has changed.
+    let changed = user_textures
+        .bypass_change_detection()
+        .advance_animations(time.delta());
+    if !changed.is_empty() {
+        user_textures.set_changed();
+    }
 
-        if width < 1.0 || height < 1.0 {
-            continue;
+    for (mut ctx, render_output) in contexts.iter_mut() {
+        let mut next_frame_delay = None::<std::time::Duration>;
+        let mut frame_changed = false;
+        for primitive in &render_output.paint_jobs {
+            let egui::epaint::Primitive::Mesh(mesh) = &primitive.primitive else {
+                continue;
+            };
+            let egui::TextureId::User(id) = mesh.texture_id else {
+                continue;
+            };
+            let Some(animation) = user_textures.animations.get(&id) else {
+                continue;
+            };
+            frame_changed |= changed.contains(&id);
+            if let Some((_, remaining)) = animation.image.frame_at(animation.elapsed) {
+                next_frame_delay =
+                    Some(next_frame_delay.map_or(remaining, |delay| delay.min(remaining)));
+            }
+        }
+        // The UI doesn't change, but reactive apps have to update to render the next frame.
+        if frame_changed {
+            ctx.get_mut().request_repaint();
+        } else if let Some(delay) = next_frame_delay {
+            ctx.get_mut().request_repaint_after(delay);
         }
-
-        context.egui_input.screen_rect = Some(egui::Rect::from_min_max(
-            egui::pos2(0.0, 0.0),
-            egui::pos2(width, height),
-        ));
-
-        context
-            .ctx
-            .get_mut()
-            .set_pixels_per_point(new_window_size.scale_factor * egui_settings.scale_factor);
-
-        *context.window_size = new_window_size;
     }
 }
 
-/// Marks frame start for Egui.
-pub fn begin_frame_system(mut contexts: Query<(&mut EguiContext, &mut EguiInput)>) {
-    for (mut ctx, mut egui_input) in contexts.iter_mut() {
-        ctx.get_mut().begin_frame(egui_input.take());
+/// Adds images pasted from the clipboard to [`Assets<Image>`] and [`EguiUserTextures`],
+/// and sends [`EguiClipboardImagePaste`] events.
+#[cfg(all(
+    feature = "render",
+    feature = "manage_clipboard",
+    not(target_os = "android"),
+    not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+))]
+pub fn process_clipboard_image_paste_system(
+    mut egui_clipboard: ResMut<crate::EguiClipboard>,
+    mut images: ResMut<Assets<Image>>,
+    mut user_textures: ResMut<EguiUserTextures>,
+    windows: Query<Entity, (With<EguiContext>, With<Window>)>,
+    mut ev_image_paste: EventWriter<EguiClipboardImagePaste>,
+) {
+    for pasted_image in egui_clipboard.take_pasted_images() {
+        // Images pasted into a web page aren't tied to a window, we assume there's only one.
+        let Some(window) = pasted_image.window.or_else(|| windows.get_single().ok()) else {
+            continue;
+        };
+        let size = egui::vec2(pasted_image.width as f32, pasted_image.height as f32);
+        let image = images.add(Image::new(
+            Extent3d {
+                width: pasted_image.width,
+                height: pasted_image.height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pasted_image.rgba,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        ));
+        let texture_id = user_textures.add_image(image.clone());
+        ev_image_paste.send(EguiClipboardImagePaste {
+            window,
+            image,
+            texture_id,
+            size,
+        });
     }
 }
 
-/// Reads Egui output.
-pub fn process_output_system(
-    #[cfg_attr(not(feature = "open_url"), allow(unused_variables))] egui_settings: Res<
-        EguiSettings,
-    >,
-    mut contexts: Query<EguiContextQuery>,
-    #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
-    mut egui_clipboard: bevy::ecs::system::ResMut<crate::EguiClipboard>,
-    mut event: EventWriter<RequestRedraw>,
-    #[cfg(windows)] mut last_cursor_icon: Local<bevy::utils::HashMap<Entity, egui::CursorIcon>>,
+/// Translates gamepad input into navigation key events if [`EguiGamepadNavigation`] is inserted.
+#[allow(clippy::too_many_arguments)]
+pub fn process_gamepad_navigation_system(
+    gamepad_navigation: Option<Res<EguiGamepadNavigation>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    input_filter: Res<EguiInputFilter>,
+    time: Res<Time<Real>>,
+    mut contexts: Query<(
+        Entity,
+        &mut EguiContext,
+        &EguiContextSettings,
+        &mut EguiInput,
+        &Window,
+    )>,
+    // The held direction and when it repeats next.
+    mut held_direction: Local<Option<(egui::Key, std::time::Duration)>>,
 ) {
-    let mut should_request_redraw = false;
-
-    for mut context in contexts.iter_mut() {
-        let ctx = context.ctx.get_mut();
-        let full_output = ctx.end_frame();
-        let egui::FullOutput {
-            platform_output,
-            shapes,
-            textures_delta,
-            pixels_per_point,
-            viewport_output: _,
-        } = full_output;
-        let paint_jobs = ctx.tessellate(shapes, pixels_per_point);
-
-        context.render_output.paint_jobs = paint_jobs;
-        context.render_output.textures_delta.append(textures_delta);
-
-        context.egui_output.platform_output = platform_output.clone();
-
-        #[cfg(all(
-            feature = "manage_clipboard",
-            not(target_os = "android"),
-            not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
-        ))]
-        if !platform_output.copied_text.is_empty() {
-            egui_clipboard.set_contents(&platform_output.copied_text);
-        }
+    let Some(gamepad_navigation) = gamepad_navigation else {
+        *held_direction = None;
+        return;
+    };
 
-        let mut set_icon = || {
-            context.window.cursor.icon = egui_to_winit_cursor_icon(platform_output.cursor_icon)
-                .unwrap_or(bevy::window::CursorIcon::Default);
+    // Keys with their modifiers, and whether they're repeats.
+    let mut keys = Vec::new();
+    for button in gamepad_buttons.get_just_pressed() {
+        let key = match button.button_type {
+            button_type if button_type == gamepad_navigation.activate_button => {
+                (egui::Key::Enter, egui::Modifiers::NONE)
+            }
+            button_type if button_type == gamepad_navigation.cancel_button => {
+                (egui::Key::Escape, egui::Modifiers::NONE)
+            }
+            GamepadButtonType::RightTrigger => (egui::Key::Tab, egui::Modifiers::NONE),
+            GamepadButtonType::LeftTrigger => (egui::Key::Tab, egui::Modifiers::SHIFT),
+            _ => continue,
         };
+        keys.push((key, false));
+    }
 
-        #[cfg(windows)]
-        {
-            let last_cursor_icon = last_cursor_icon.entry(context.window_entity).or_default();
-            if *last_cursor_icon != platform_output.cursor_icon {
-                set_icon();
-                *last_cursor_icon = platform_output.cursor_icon;
+    let direction = gamepads.iter().find_map(|gamepad| {
+        let pressed =
+            |button_type| gamepad_buttons.pressed(GamepadButton::new(gamepad, button_type));
+        let axis = |axis_type| {
+            gamepad_axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        let (x, y) = (
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        );
+        if pressed(GamepadButtonType::DPadUp) {
+            Some(egui::Key::ArrowUp)
+        } else if pressed(GamepadButtonType::DPadDown) {
+            Some(egui::Key::ArrowDown)
+        } else if pressed(GamepadButtonType::DPadLeft) {
+            Some(egui::Key::ArrowLeft)
+        } else if pressed(GamepadButtonType::DPadRight) {
+            Some(egui::Key::ArrowRight)
+        } else if x.abs().max(y.abs()) < gamepad_navigation.stick_threshold {
+            None
+        } else if x.abs() > y.abs() {
+            Some(if x > 0.0 {
+                egui::Key::ArrowRight
+            } else {
+                egui::Key::ArrowLeft
+            })
+        } else {
+            // The Y axis of the stick points up.
+            Some(if y > 0.0 {
+                egui::Key::ArrowUp
+            } else {
+                egui::Key::ArrowDown
+            })
+        }
+    });
+    let now = time.elapsed();
+    match (direction, *held_direction) {
+        (Some(key), Some((held_key, next_repeat))) if key == held_key => {
+            if now >= next_repeat {
+                keys.push(((key, egui::Modifiers::NONE), true));
+                let interval =
+                    std::time::Duration::from_secs_f32(gamepad_navigation.repeat_interval);
+                *held_direction = Some((key, now + interval));
             }
         }
-        #[cfg(not(windows))]
-        set_icon();
-
-        let needs_repaint = !context.render_output.is_empty();
-        should_request_redraw |= ctx.has_requested_repaint() && needs_repaint;
+        (Some(key), _) => {
+            keys.push(((key, egui::Modifiers::NONE), false));
+            let delay = std::time::Duration::from_secs_f32(gamepad_navigation.repeat_delay);
+            *held_direction = Some((key, now + delay));
+        }
+        (None, _) => *held_direction = None,
+    }
+    if keys.is_empty() {
+        return;
+    }
 
-        #[cfg(feature = "open_url")]
-        if let Some(egui::output::OpenUrl { url, new_tab }) = platform_output.open_url {
-            let target = if new_tab {
-                "_blank"
-            } else {
-                egui_settings
-                    .default_open_url_target
-                    .as_deref()
-                    .unwrap_or("_self")
+    for (window_entity, mut ctx, context_settings, mut egui_input, window) in contexts.iter_mut() {
+        if context_settings.display_only || !window.focused {
+            continue;
+        }
+        // Egui only moves the focus in a direction once a widget has it.
+        let has_focus = ctx.get_mut().memory(|memory| memory.focused().is_some());
+        for &((key, modifiers), repeat) in &keys {
+            let (key, modifiers) = match key {
+                egui::Key::ArrowDown | egui::Key::ArrowRight if !has_focus => {
+                    (egui::Key::Tab, egui::Modifiers::NONE)
+                }
+                egui::Key::ArrowUp | egui::Key::ArrowLeft if !has_focus => {
+                    (egui::Key::Tab, egui::Modifiers::SHIFT)
+                }
+                _ => (key, modifiers),
             };
-            if let Err(err) = webbrowser::open_browser_with_options(
-                webbrowser::Browser::Default,
-                &url,
-                webbrowser::BrowserOptions::new().with_target_hint(target),
-            ) {
-                log::error!("Failed to open '{}': {:?}", url, err);
+            for pressed in [true, false] {
+                let event = egui::Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat,
+                    modifiers,
+                };
+                if input_filter.allows(window_entity, &event) {
+                    egui_input.events.push(event);
+                }
             }
         }
     }
+}
+
+#[cfg(feature = "render")]
+type EguiMeshPointerInputQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Handle<Mesh>,
+        &'static GlobalTransform,
+        &'static EguiContextSettings,
+        &'static mut EguiContext,
+        &'static mut EguiInput,
+    ),
+    (
+        With<EguiMeshPointerInput>,
+        With<EguiRenderToTexture>,
+        Without<Window>,
+    ),
+>;
+
+/// Sends the cursor to the contexts of meshes with [`EguiMeshPointerInput`].
+#[cfg(feature = "render")]
+#[allow(clippy::too_many_arguments)]
+pub fn process_mesh_pointer_input_system(
+    windows: Query<(Entity, &Window)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut window_contexts: Query<&mut EguiContext, (With<Window>, Without<EguiMeshPointerInput>)>,
+    mut mesh_contexts: EguiMeshPointerInputQuery,
+    meshes: Option<Res<Assets<Mesh>>>,
+    mut ev_mouse_button_input: EventReader<MouseButtonInput>,
+    mut ev_mouse_wheel: EventReader<MouseWheel>,
+    mut hovered_positions: Local<HashMap<Entity, egui::Pos2>>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    // Only one window can be hovered at a time.
+    let cursor_ray = windows.iter().find_map(|(window_entity, window)| {
+        let cursor_position = window.cursor_position()?;
+        if window_contexts
+            .get_mut(window_entity)
+            .is_ok_and(|mut ctx| ctx.get_mut().is_pointer_over_area())
+        {
+            return None;
+        }
+        let ray = cameras
+            .iter()
+            .filter(|(camera, _)| {
+                camera.is_active
+                    && matches!(
+                        camera.target.normalize(primary_window),
+                        Some(NormalizedRenderTarget::Window(window_ref))
+                            if window_ref.entity() == window_entity
+                    )
+            })
+            .filter_map(|(camera, camera_transform)| {
+                let viewport = camera.logical_viewport_rect()?;
+                if !viewport.contains(cursor_position) {
+                    return None;
+                }
+                let ray =
+                    camera.viewport_to_world(camera_transform, cursor_position - viewport.min)?;
+                Some((camera.order, ray))
+            })
+            .max_by_key(|(order, _)| *order)
+            .map(|(_, ray)| ray)?;
+        Some((window_entity, ray))
+    });
+
+    let mut closest_hit: Option<(Entity, f32, egui::Pos2)> = None;
+    if let (Some((_, ray)), Some(meshes)) = (cursor_ray, &meshes) {
+        for (entity, mesh, transform, context_settings, _, egui_input) in mesh_contexts.iter() {
+            let (Some(mesh), Some(screen_rect)) = (meshes.get(mesh), egui_input.screen_rect) else {
+                continue;
+            };
+            if context_settings.display_only {
+                continue;
+            }
+            let Some((distance, uv)) = ray_mesh_hit(ray, mesh, transform) else {
+                continue;
+            };
+            if !closest_hit.is_some_and(|(_, closest, _)| closest <= distance) {
+                let pos = screen_rect.min + egui::vec2(uv.x, uv.y) * screen_rect.size();
+                closest_hit = Some((entity, distance, pos));
+            }
+        }
+    }
+
+    let hovered_window = cursor_ray.map(|(window_entity, _)| window_entity);
+    let button_events: Vec<_> = ev_mouse_button_input
+        .read()
+        .filter(|event| Some(event.window) == hovered_window)
+        .filter_map(|event| {
+            let button = match event.button {
+                MouseButton::Left => egui::PointerButton::Primary,
+                MouseButton::Right => egui::PointerButton::Secondary,
+                MouseButton::Middle => egui::PointerButton::Middle,
+                _ => return None,
+            };
+            Some((button, event.state == ButtonState::Pressed))
+        })
+        .collect();
+    let wheel_events: Vec<_> = ev_mouse_wheel
+        .read()
+        .filter(|event| Some(event.window) == hovered_window)
+        .map(|event| {
+            let unit = match event.unit {
+                MouseScrollUnit::Line => egui::MouseWheelUnit::Line,
+                MouseScrollUnit::Pixel => egui::MouseWheelUnit::Point,
+            };
+            (unit, egui::vec2(event.x, event.y))
+        })
+        .collect();
+
+    for (entity, _, _, _, mut ctx, mut egui_input) in mesh_contexts.iter_mut() {
+        let modifiers = egui_input.modifiers;
+        match closest_hit {
+            Some((hit_entity, _, pos)) if hit_entity == entity => {
+                if hovered_positions.insert(entity, pos) != Some(pos) {
+                    egui_input.events.push(egui::Event::PointerMoved(pos));
+                }
+                for &(button, pressed) in &button_events {
+                    egui_input.events.push(egui::Event::PointerButton {
+                        pos,
+                        button,
+                        pressed,
+                        modifiers,
+                    });
+                }
+                for &(unit, delta) in &wheel_events {
+                    egui_input.events.push(egui::Event::MouseWheel {
+                        unit,
+                        delta,
+                        modifiers,
+                    });
+                }
+            }
+            _ => {
+                let Some(pos) = hovered_positions.remove(&entity) else {
+                    continue;
+                };
+                // Release the buttons if the cursor leaves the mesh while dragging.
+                for button in [
+                    egui::PointerButton::Primary,
+                    egui::PointerButton::Secondary,
+                    egui::PointerButton::Middle,
+                ] {
+                    if ctx
+                        .get_mut()
+                        .input(|input| input.pointer.button_down(button))
+                    {
+                        egui_input.events.push(egui::Event::PointerButton {
+                            pos,
+                            button,
+                            pressed: false,
+                            modifiers,
+                        });
+                    }
+                }
+                egui_input.events.push(egui::Event::PointerGone);
+            }
+        }
+    }
+    hovered_positions.retain(|entity, _| mesh_contexts.contains(*entity));
+}
+
+// Returns the distance to the closest triangle hit by the ray and the UV coordinates of the hit.
+#[cfg(feature = "render")]
+fn ray_mesh_hit(ray: Ray3d, mesh: &Mesh, transform: &GlobalTransform) -> Option<(f32, Vec2)> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return None;
+    }
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let Some(VertexAttributeValues::Float32x2(uvs)) = mesh.attribute(Mesh::ATTRIBUTE_UV_0) else {
+        return None;
+    };
+    // The ray keeps its parametrization in local space, so the distances stay in world units.
+    let local_from_world = transform.affine().inverse();
+    let origin = local_from_world.transform_point3(ray.origin);
+    let direction = local_from_world.transform_vector3(*ray.direction);
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+
+    let mut closest_hit: Option<(f32, Vec2)> = None;
+    for triangle in indices.chunks_exact(3) {
+        let (Some(a), Some(b), Some(c)) = (
+            positions.get(triangle[0]),
+            positions.get(triangle[1]),
+            positions.get(triangle[2]),
+        ) else {
+            continue;
+        };
+        let [a, b, c] = [*a, *b, *c].map(bevy::math::Vec3::from);
+        // Möller–Trumbore intersection.
+        let (ab, ac) = (b - a, c - a);
+        let p = direction.cross(ac);
+        let det = ab.dot(p);
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+        let inv_det = 1.0 / det;
+        let s = origin - a;
+        let u = s.dot(p) * inv_det;
+        let q = s.cross(ab);
+        let v = direction.dot(q) * inv_det;
+        if u < 0.0 || v < 0.0 || u + v > 1.0 {
+            continue;
+        }
+        let distance = ac.dot(q) * inv_det;
+        if distance < 0.0 || closest_hit.is_some_and(|(closest, _)| closest <= distance) {
+            continue;
+        }
+        let (Some(uv_a), Some(uv_b), Some(uv_c)) = (
+            uvs.get(triangle[0]),
+            uvs.get(triangle[1]),
+            uvs.get(triangle[2]),
+        ) else {
+            continue;
+        };
+        let [uv_a, uv_b, uv_c] = [*uv_a, *uv_b, *uv_c].map(Vec2::from);
+        closest_hit = Some((distance, uv_a * (1.0 - u - v) + uv_b * u + uv_c * v));
+    }
+    closest_hit
+}
+
+/// Updates [`EguiAccessibilityZoom`] from [`EguiSettings::accessibility_zoom_factor`] and
+/// Bevy's [`AccessibilityRequested`] resource.
+pub fn update_accessibility_zoom_system(
+    egui_settings: Res<EguiSettings>,
+    accessibility_requested: Option<Res<AccessibilityRequested>>,
+    mut accessibility_zoom: ResMut<EguiAccessibilityZoom>,
+) {
+    let requested = accessibility_requested.is_some_and(|requested| requested.get());
+    accessibility_zoom.set_if_neq(EguiAccessibilityZoom(if requested {
+        egui_settings.accessibility_zoom_factor
+    } else {
+        1.0
+    }));
+}
+
+/// Keeps the [`EguiSecondaryContext`] of cameras with [`EguiCameraContext`] in sync with the
+/// windows they render to and their orders.
+#[cfg(feature = "render")]
+pub fn update_camera_contexts_system(
+    mut commands: Commands,
+    cameras: Query<(Entity, &Camera, Option<&EguiSecondaryContext>), With<EguiCameraContext>>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    for (entity, camera, secondary_context) in cameras.iter() {
+        let Some(NormalizedRenderTarget::Window(window_ref)) =
+            camera.target.normalize(primary_window)
+        else {
+            continue;
+        };
+        let new_secondary_context = EguiSecondaryContext {
+            window: window_ref.entity(),
+            order: camera.order.clamp(i32::MIN as isize, i32::MAX as isize) as i32,
+        };
+        if secondary_context != Some(&new_secondary_context) {
+            commands.entity(entity).insert(new_secondary_context);
+        }
+    }
+}
+
+/// Initialises Egui contexts (for multiple windows).
+pub fn update_window_contexts_system(
+    mut context_params: ContextSystemParams,
+    egui_settings: Res<EguiSettings>,
+    #[cfg(feature = "render")] render_device: Option<Res<bevy::render::renderer::RenderDevice>>,
+    #[cfg(feature = "render")] mut render_to_texture_contexts: Query<
+        EguiRenderToTextureContextQuery,
+        (Without<Window>, Without<EguiSecondaryContext>),
+    >,
+    #[cfg(feature = "render")] images: Option<
+        Res<bevy::asset::Assets<bevy::render::texture::Image>>,
+    >,
+    mut secondary_contexts: Query<EguiSecondaryContextQuery, Without<Window>>,
+    #[cfg(feature = "render")] cameras: Query<&Camera, With<EguiCameraContext>>,
+) {
+    // Egui will use this to limit the size of the font atlas.
+    #[cfg(feature = "render")]
+    let max_texture_side = render_device
+        .as_ref()
+        .map(|render_device| render_device.limits().max_texture_dimension_2d as usize);
+    let accessibility_zoom = *context_params.accessibility_zoom;
+
+    for mut context in context_params.contexts.iter_mut() {
+        #[cfg(feature = "render")]
+        {
+            context.egui_input.max_texture_side = max_texture_side;
+        }
+
+        apply_context_settings(&mut context.ctx, &egui_settings, &context.context_settings);
+
+        let (new_window_size, layout) = screen_layout(&context, &egui_settings, accessibility_zoom);
+        // Minimized windows have zero physical size. We skip updating the screen rect and the
+        // window size until the window is restored: egui will keep using the last known screen
+        // rect (which avoids zero area rect warnings), and the render node won't draw anything.
+        if new_window_size.physical_width < 1.0 || new_window_size.physical_height < 1.0 {
+            continue;
+        }
+
+        // The DPI-derived factor is egui's native pixels per point, the zoom is applied on top.
+        let zoom_factor = context.context_settings.ui_zoom_factor * accessibility_zoom.0;
+        set_pixels_per_point(
+            &mut context.ctx,
+            &mut context.egui_input,
+            layout.native_pixels_per_point,
+            zoom_factor,
+        );
+        let (width, height) = layout.screen_size.into();
+        if width < 1.0 || height < 1.0 {
+            continue;
+        }
+
+        context.egui_input.screen_rect = Some(egui::Rect::from_min_max(
+            egui::pos2(0.0, 0.0),
+            egui::pos2(width, height),
+        ));
+
+        *context.window_size = new_window_size;
+        context.render_output.screen_layout = Some(layout);
+    }
+
+    for mut context in secondary_contexts.iter_mut() {
+        #[cfg(feature = "render")]
+        {
+            context.egui_input.max_texture_side = max_texture_side;
+        }
+
+        apply_context_settings(&mut context.ctx, &egui_settings, &context.context_settings);
+
+        let Ok(window_context) = context_params
+            .contexts
+            .get(context.secondary_context.window)
+        else {
+            continue;
+        };
+        #[cfg(feature = "render")]
+        let viewport = cameras
+            .get(context.entity)
+            .ok()
+            .and_then(Camera::physical_viewport_rect);
+        #[cfg(not(feature = "render"))]
+        let viewport = None;
+        let (new_window_size, layout) = context_screen_layout(
+            window_context.window,
+            viewport,
+            &context.ctx,
+            &context.context_settings,
+            &egui_settings,
+            accessibility_zoom,
+        );
+        if new_window_size.physical_width < 1.0 || new_window_size.physical_height < 1.0 {
+            continue;
+        }
+
+        let zoom_factor = context.context_settings.ui_zoom_factor * accessibility_zoom.0;
+        set_pixels_per_point(
+            &mut context.ctx,
+            &mut context.egui_input,
+            layout.native_pixels_per_point,
+            zoom_factor,
+        );
+        let (width, height) = layout.screen_size.into();
+        if width < 1.0 || height < 1.0 {
+            continue;
+        }
+
+        context.egui_input.screen_rect = Some(egui::Rect::from_min_max(
+            egui::pos2(0.0, 0.0),
+            egui::pos2(width, height),
+        ));
+
+        *context.window_size = new_window_size;
+        context.render_output.screen_layout = Some(layout);
+    }
+
+    #[cfg(feature = "render")]
+    for mut context in render_to_texture_contexts.iter_mut() {
+        context.egui_input.max_texture_side = max_texture_side;
+
+        apply_context_settings(&mut context.ctx, &egui_settings, &context.context_settings);
+
+        // The image may not be loaded yet.
+        let Some(image) = images
+            .as_ref()
+            .and_then(|images| images.get(&context.render_to_texture.0))
+        else {
+            continue;
+        };
+        let zoom_factor = context.context_settings.ui_zoom_factor * accessibility_zoom.0;
+        let size = image.size_f32();
+        let new_window_size = WindowSize::new(size.x, size.y, 1.0);
+        let layout = EguiScreenLayout::new(
+            new_window_size,
+            context.context_settings.egui_scale_factor(&egui_settings),
+            zoom_factor,
+            None,
+        );
+        set_pixels_per_point(
+            &mut context.ctx,
+            &mut context.egui_input,
+            layout.native_pixels_per_point,
+            zoom_factor,
+        );
+        let (width, height) = layout.screen_size.into();
+        if width < 1.0 || height < 1.0 {
+            continue;
+        }
+
+        context.egui_input.screen_rect = Some(egui::Rect::from_min_max(
+            egui::pos2(0.0, 0.0),
+            egui::pos2(width, height),
+        ));
+
+        *context.window_size = new_window_size;
+        context.render_output.screen_layout = Some(layout);
+    }
+}
+
+/// Returns the current size of a window and the screen layout of its context.
+fn screen_layout(
+    context: &EguiContextQueryItem,
+    egui_settings: &EguiSettings,
+    accessibility_zoom: EguiAccessibilityZoom,
+) -> (WindowSize, EguiScreenLayout) {
+    context_screen_layout(
+        &context.window,
+        None,
+        &context.ctx,
+        &context.context_settings,
+        egui_settings,
+        accessibility_zoom,
+    )
+}
+
+/// Returns the current size of a window and the screen layout of a context drawn to it, or to
+/// a viewport of it (in physical pixels, see [`EguiCameraContext`]).
+fn context_screen_layout(
+    window: &Window,
+    viewport: Option<bevy::math::URect>,
+    ctx: &EguiContext,
+    context_settings: &EguiContextSettings,
+    egui_settings: &EguiSettings,
+    accessibility_zoom: EguiAccessibilityZoom,
+) -> (WindowSize, EguiScreenLayout) {
+    let window_size = WindowSize::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+        window.scale_factor(),
+    );
+    let screen_size = viewport.map_or(window_size, |viewport| {
+        WindowSize::new(
+            viewport.width() as f32,
+            viewport.height() as f32,
+            window_size.scale_factor,
+        )
+    });
+    let mut layout = EguiScreenLayout::new(
+        screen_size,
+        context_settings.egui_scale_factor(egui_settings),
+        context_settings.ui_zoom_factor * accessibility_zoom.0,
+        context_settings.fixed_logical_size,
+    );
+    if let Some(viewport) = viewport {
+        layout.offset += viewport.min.as_vec2();
+    }
+    layout.offset.y -= (ctx.keyboard_offset * window_size.scale_factor).round();
+    (window_size, layout)
+}
+
+/// Passes the DPI-derived native pixels per point and the UI zoom factor to egui,
+/// which multiplies them to get the pixels per point of the next frame.
+fn set_pixels_per_point(
+    ctx: &mut EguiContext,
+    egui_input: &mut EguiInput,
+    native_pixels_per_point: f32,
+    zoom_factor: f32,
+) {
+    egui_input
+        .viewports
+        .entry(egui::ViewportId::ROOT)
+        .or_default()
+        .native_pixels_per_point = Some(native_pixels_per_point);
+    // Is a no-op if the zoom factor hasn't changed.
+    ctx.get_mut().set_zoom_factor(zoom_factor);
+}
+
+/// Applies [`EguiSettings`] and [`EguiContextSettings`] if they've changed.
+fn apply_context_settings(
+    ctx: &mut Mut<EguiContext>,
+    egui_settings: &Res<EguiSettings>,
+    context_settings: &Ref<EguiContextSettings>,
+) {
+    if egui_settings.is_changed() || ctx.is_added() {
+        ctx.get_mut().options_mut(|options| {
+            options.line_scroll_speed = egui_settings.line_scroll_speed;
+            options.scroll_zoom_speed = egui_settings.scroll_zoom_speed;
+            options.tessellation_options.feathering = egui_settings.feathering;
+            options.tessellation_options.feathering_size_in_pixels =
+                egui_settings.feathering_size_in_pixels;
+        });
+        apply_style_settings(ctx.get_mut(), egui_settings);
+    }
+    if context_settings.is_changed() {
+        let embed_viewports = context_settings.embed_viewports;
+        ctx.get_mut().set_embed_viewports(embed_viewports);
+    }
+}
+
+/// Applies the [`EguiSettings`] that are stored in [`egui::Style`].
+pub(crate) fn apply_style_settings(ctx: &egui::Context, egui_settings: &EguiSettings) {
+    ctx.style_mut(|style| {
+        style.interaction.tooltip_delay = egui_settings.tooltip_delay;
+        style.interaction.tooltip_grace_time = egui_settings.tooltip_grace_time;
+        style.interaction.show_tooltips_only_when_still =
+            egui_settings.show_tooltips_only_when_still;
+    });
+}
+
+/// Passes files dragged over or dropped onto windows to their contexts, as
+/// [`egui::RawInput::hovered_files`] and [`egui::RawInput::dropped_files`].
+///
+/// Bevy reports the files of native windows with [`FileDragAndDrop`] events. The contents of
+/// dropped files aren't read, [`egui::DroppedFile::path`] can be used to open them. On web,
+/// files are passed by `web_file_drop::process_web_file_drop_system` instead.
+pub fn process_file_drag_and_drop_system(
+    mut ev_file_drag_and_drop: EventReader<FileDragAndDrop>,
+    mut egui_inputs: Query<(&mut EguiInput, &EguiContextSettings), With<Window>>,
+) {
+    for event in ev_file_drag_and_drop.read() {
+        let window = match event {
+            FileDragAndDrop::DroppedFile { window, .. }
+            | FileDragAndDrop::HoveredFile { window, .. }
+            | FileDragAndDrop::HoveredFileCanceled { window } => *window,
+        };
+        let Ok((mut egui_input, context_settings)) = egui_inputs.get_mut(window) else {
+            continue;
+        };
+        if context_settings.display_only {
+            continue;
+        }
+        // Hovered files are kept by `egui::RawInput::take`, so they only have to be updated when
+        // they change.
+        match event {
+            FileDragAndDrop::HoveredFile { path_buf, .. } => {
+                egui_input.hovered_files.push(egui::HoveredFile {
+                    path: Some(path_buf.clone()),
+                    ..Default::default()
+                });
+            }
+            FileDragAndDrop::HoveredFileCanceled { .. } => egui_input.hovered_files.clear(),
+            FileDragAndDrop::DroppedFile { path_buf, .. } => {
+                egui_input.hovered_files.clear();
+                egui_input.dropped_files.push(egui::DroppedFile {
+                    path: Some(path_buf.clone()),
+                    name: path_buf
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+}
+
+/// Passes the screenshots stored in [`EguiScreenshots`] to the windows that requested them.
+#[cfg(feature = "render")]
+pub fn process_screenshots_system(
+    screenshots: Res<EguiScreenshots>,
+    mut egui_inputs: Query<&mut EguiInput, With<Window>>,
+) {
+    for (window, image) in screenshots.take() {
+        let Ok(mut egui_input) = egui_inputs.get_mut(window) else {
+            continue;
+        };
+        egui_input.events.push(egui::Event::Screenshot {
+            viewport_id: egui::ViewportId::ROOT,
+            image: std::sync::Arc::new(image),
+        });
+    }
+}
+
+/// Sends the captures stored in [`EguiCapturedScreenshots`] as [`EguiScreenshotCaptured`] events
+/// and removes the [`EguiScreenshot`] requests they fulfil.
+#[cfg(feature = "render")]
+pub fn send_captured_screenshots_system(
+    mut commands: Commands,
+    captured_screenshots: Res<EguiCapturedScreenshots>,
+    requests: Query<(), With<EguiScreenshot>>,
+    mut screenshot_events: EventWriter<EguiScreenshotCaptured>,
+) {
+    let mut fulfilled_requests = Vec::new();
+    for (window, image) in captured_screenshots.take() {
+        // A frame may be captured again before the request is removed.
+        if !requests.contains(window) || fulfilled_requests.contains(&window) {
+            continue;
+        }
+        fulfilled_requests.push(window);
+        commands.entity(window).remove::<EguiScreenshot>();
+        screenshot_events.send(EguiScreenshotCaptured { window, image });
+    }
+}
+
+/// Makes repaints requested by egui (from other threads as well) wake up the winit event loop,
+/// which otherwise may wait for input events in the reactive update modes.
+///
+/// Repaints requested with a delay wake the event loop up once the delay has passed (except on
+/// the web, where they wait for the next update).
+#[cfg(feature = "wake_on_repaint")]
+pub fn set_repaint_callbacks_system(
+    mut contexts: Query<&mut EguiContext, bevy::prelude::Added<EguiContext>>,
+    event_loop_proxy: Option<
+        bevy::ecs::system::NonSend<bevy::winit::EventLoopProxy<bevy::winit::WakeUp>>,
+    >,
+    #[cfg(not(target_arch = "wasm32"))] mut repaint_timer: Local<
+        Option<std::sync::Arc<RepaintTimer>>,
+    >,
+) {
+    let Some(event_loop_proxy) = event_loop_proxy else {
+        return;
+    };
+    for mut ctx in contexts.iter_mut() {
+        #[cfg(not(target_arch = "wasm32"))]
+        let repaint_timer = repaint_timer
+            .get_or_insert_with(|| RepaintTimer::spawn(event_loop_proxy.clone()))
+            .clone();
+        // The proxy isn't `Sync` on every platform, while egui requires the callback to be.
+        let event_loop_proxy = std::sync::Mutex::new(event_loop_proxy.clone());
+        ctx.get_mut()
+            .set_request_repaint_callback(move |info: egui::RequestRepaintInfo| {
+                if !info.delay.is_zero() {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    repaint_timer.wake_after(info.delay);
+                    return;
+                }
+                let event_loop_proxy = event_loop_proxy
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                // Fails only if the event loop has already exited.
+                let _ = event_loop_proxy.send_event(bevy::winit::WakeUp);
+            });
+    }
+}
+
+/// Wakes up the winit event loop for repaints requested with a delay, from a thread that sleeps
+/// until the earliest of them.
+#[cfg(all(feature = "wake_on_repaint", not(target_arch = "wasm32")))]
+pub struct RepaintTimer {
+    deadline: std::sync::Mutex<Option<std::time::Instant>>,
+    deadline_changed: std::sync::Condvar,
+}
+
+#[cfg(all(feature = "wake_on_repaint", not(target_arch = "wasm32")))]
+impl RepaintTimer {
+    fn spawn(
+        event_loop_proxy: bevy::winit::EventLoopProxy<bevy::winit::WakeUp>,
+    ) -> std::sync::Arc<Self> {
+        let timer = std::sync::Arc::new(Self {
+            deadline: std::sync::Mutex::new(None),
+            deadline_changed: std::sync::Condvar::new(),
+        });
+        let thread_timer = timer.clone();
+        let spawned = std::thread::Builder::new()
+            .name("egui repaint timer".to_string())
+            .spawn(move || {
+                let timer = thread_timer;
+                let mut deadline = timer
+                    .deadline
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                loop {
+                    let Some(wake_at) = *deadline else {
+                        deadline = timer
+                            .deadline_changed
+                            .wait(deadline)
+                            .unwrap_or_else(std::sync::PoisonError::into_inner);
+                        continue;
+                    };
+                    let now = std::time::Instant::now();
+                    if now < wake_at {
+                        deadline = timer
+                            .deadline_changed
+                            .wait_timeout(deadline, wake_at - now)
+                            .unwrap_or_else(std::sync::PoisonError::into_inner)
+                            .0;
+                        continue;
+                    }
+                    *deadline = None;
+                    // Fails only if the event loop has already exited.
+                    if event_loop_proxy.send_event(bevy::winit::WakeUp).is_err() {
+                        return;
+                    }
+                }
+            });
+        if let Err(err) = spawned {
+            log::error!("Failed to spawn the egui repaint timer thread: {err:?}");
+        }
+        timer
+    }
+
+    fn wake_after(&self, delay: std::time::Duration) {
+        // `Duration::MAX` means no repaint.
+        let Some(wake_at) = std::time::Instant::now().checked_add(delay) else {
+            return;
+        };
+        let mut deadline = self
+            .deadline
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !deadline.is_some_and(|deadline| deadline <= wake_at) {
+            *deadline = Some(wake_at);
+            self.deadline_changed.notify_one();
+        }
+    }
+}
+
+/// A context that window input is routed to, see [`route_secondary_context_input_system`].
+struct InputRecipient<'a> {
+    entity: Entity,
+    // The order of the context, and whether it's a secondary one (which is drawn on top of the
+    // window's own context of the same order).
+    order: (i32, bool),
+    ctx: Mut<'a, EguiContext>,
+    egui_input: Mut<'a, EguiInput>,
+    layout: Option<EguiScreenLayout>,
+}
+
+/// Which contexts of a window an input event is sent to.
+enum InputRoute {
+    Pointer,
+    Keyboard,
+    All,
+    WindowContext,
+}
+
+fn input_route(event: &egui::Event) -> InputRoute {
+    match event {
+        egui::Event::PointerMoved(_)
+        | egui::Event::MouseMoved(_)
+        | egui::Event::PointerButton { .. }
+        | egui::Event::PointerGone
+        | egui::Event::MouseWheel { .. }
+        | egui::Event::Zoom(_)
+        | egui::Event::Touch { .. } => InputRoute::Pointer,
+        egui::Event::Copy
+        | egui::Event::Cut
+        | egui::Event::Paste(_)
+        | egui::Event::Text(_)
+        | egui::Event::Key { .. }
+        | egui::Event::Ime(_) => InputRoute::Keyboard,
+        egui::Event::WindowFocused(_) => InputRoute::All,
+        _ => InputRoute::WindowContext,
+    }
+}
+
+fn event_pointer_pos(event: &egui::Event) -> Option<egui::Pos2> {
+    match event {
+        egui::Event::PointerMoved(pos)
+        | egui::Event::PointerButton { pos, .. }
+        | egui::Event::Touch { pos, .. } => Some(*pos),
+        _ => None,
+    }
+}
+
+/// Converts a position in the points of one context to the points of another context that's
+/// drawn to the same window.
+fn convert_egui_pos(
+    pos: egui::Pos2,
+    from: Option<EguiScreenLayout>,
+    to: Option<EguiScreenLayout>,
+) -> egui::Pos2 {
+    let (Some(from), Some(to)) = (from, to) else {
+        return pos;
+    };
+    let physical = bevy::math::Vec2::new(pos.x, pos.y) * from.pixels_per_point + from.offset;
+    let pos = (physical - to.offset) / to.pixels_per_point;
+    egui::pos2(pos.x, pos.y)
+}
+
+/// Routes the input of windows between their own contexts and their secondary contexts, see
+/// [`EguiSecondaryContext`] for the rules.
+#[allow(clippy::type_complexity)]
+pub fn route_secondary_context_input_system(
+    mut window_contexts: Query<
+        (
+            Entity,
+            &mut EguiContext,
+            &mut EguiInput,
+            &EguiRenderOutput,
+            &EguiContextSettings,
+        ),
+        With<Window>,
+    >,
+    mut secondary_contexts: Query<
+        (
+            Entity,
+            &EguiSecondaryContext,
+            &mut EguiContext,
+            &mut EguiInput,
+            &EguiRenderOutput,
+            &EguiContextSettings,
+        ),
+        Without<Window>,
+    >,
+    // The context that got the pointer events of a window during the last frame, or `None` if
+    // all of them did.
+    mut pointer_targets: Local<HashMap<Entity, Option<Entity>>>,
+) {
+    let mut recipients_by_window: HashMap<Entity, Vec<InputRecipient>> = HashMap::default();
+    let mut display_only_inputs: HashMap<Entity, Vec<Mut<EguiInput>>> = HashMap::default();
+    for (entity, secondary_context, ctx, egui_input, render_output, context_settings) in
+        secondary_contexts.iter_mut()
+    {
+        if context_settings.display_only {
+            display_only_inputs
+                .entry(secondary_context.window)
+                .or_default()
+                .push(egui_input);
+            continue;
+        }
+        recipients_by_window
+            .entry(secondary_context.window)
+            .or_default()
+            .push(InputRecipient {
+                entity,
+                order: (secondary_context.order, true),
+                ctx,
+                egui_input,
+                layout: render_output.screen_layout,
+            });
+    }
+    pointer_targets.retain(|window, _| recipients_by_window.contains_key(window));
+
+    for (window, mut ctx, mut egui_input, render_output, context_settings) in
+        window_contexts.iter_mut()
+    {
+        // Secondary contexts share the time, the modifiers and the focus of the window.
+        // Display-only ones get only the time, like display-only windows.
+        for mut secondary_input in display_only_inputs.remove(&window).unwrap_or_default() {
+            secondary_input.time = egui_input.time;
+        }
+        let Some(mut recipients) = recipients_by_window.remove(&window) else {
+            continue;
+        };
+        for recipient in &mut recipients {
+            recipient.egui_input.time = egui_input.time;
+            recipient.egui_input.modifiers = egui_input.modifiers;
+            recipient.egui_input.focused = egui_input.focused;
+        }
+
+        let window_layout = render_output.screen_layout;
+        let events = std::mem::take(&mut egui_input.events);
+        let pointer_pos = events
+            .iter()
+            .rev()
+            .find_map(event_pointer_pos)
+            .or_else(|| ctx.get_mut().pointer_latest_pos());
+        if !context_settings.display_only {
+            recipients.push(InputRecipient {
+                entity: window,
+                order: (0, false),
+                ctx,
+                egui_input,
+                layout: window_layout,
+            });
+        }
+        // The topmost contexts come first.
+        recipients.sort_by_key(|recipient| std::cmp::Reverse(recipient.order));
+
+        let captured = recipients.iter_mut().position(|recipient| {
+            let ctx = recipient.ctx.get_mut();
+            ctx.is_using_pointer() || ctx.input(|input| input.pointer.any_down())
+        });
+        let pointer_target = captured.or_else(|| {
+            let pointer_pos = pointer_pos?;
+            recipients.iter_mut().position(|recipient| {
+                let pos = convert_egui_pos(pointer_pos, window_layout, recipient.layout);
+                recipient.ctx.hit_test(pos).is_some()
+            })
+        });
+        // Clicking a context takes the keyboard focus from the others.
+        let pressed = events.iter().any(|event| {
+            matches!(event, egui::Event::PointerButton { pressed: true, .. })
+                || matches!(
+                    event,
+                    egui::Event::Touch {
+                        phase: egui::TouchPhase::Start,
+                        ..
+                    }
+                )
+        });
+        if let (true, Some(pointer_target)) = (pressed, pointer_target) {
+            for (index, recipient) in recipients.iter_mut().enumerate() {
+                if index == pointer_target {
+                    continue;
+                }
+                recipient.ctx.get_mut().memory_mut(|memory| {
+                    if let Some(focused) = memory.focused() {
+                        memory.surrender_focus(focused);
+                    }
+                });
+            }
+        }
+        let keyboard_target = recipients
+            .iter_mut()
+            .position(|recipient| recipient.ctx.get_mut().wants_keyboard_input());
+
+        // Release the buttons held in the contexts that stop getting the pointer events.
+        let pointer_target_entity = pointer_target.map(|index| recipients[index].entity);
+        let last_pointer_target = pointer_targets
+            .insert(window, pointer_target_entity)
+            .flatten();
+        for (index, recipient) in recipients.iter_mut().enumerate() {
+            let got_pointer =
+                last_pointer_target.is_none() || last_pointer_target == Some(recipient.entity);
+            let gets_pointer = pointer_target.is_none() || pointer_target == Some(index);
+            if !got_pointer || gets_pointer {
+                continue;
+            }
+            let modifiers = recipient.egui_input.modifiers;
+            let pos = pointer_pos
+                .map(|pos| convert_egui_pos(pos, window_layout, recipient.layout))
+                .unwrap_or_default();
+            for button in [
+                egui::PointerButton::Primary,
+                egui::PointerButton::Secondary,
+                egui::PointerButton::Middle,
+            ] {
+                if recipient
+                    .ctx
+                    .get_mut()
+                    .input(|input| input.pointer.button_down(button))
+                {
+                    recipient
+                        .egui_input
+                        .events
+                        .push(egui::Event::PointerButton {
+                            pos,
+                            button,
+                            pressed: false,
+                            modifiers,
+                        });
+                }
+            }
+            recipient.egui_input.events.push(egui::Event::PointerGone);
+        }
+
+        for event in events {
+            let target = match input_route(&event) {
+                InputRoute::Pointer => pointer_target,
+                InputRoute::Keyboard => keyboard_target,
+                InputRoute::All => None,
+                InputRoute::WindowContext => {
+                    if let Some(recipient) = recipients
+                        .iter_mut()
+                        .find(|recipient| recipient.entity == window)
+                    {
+                        recipient.egui_input.events.push(event);
+                    }
+                    continue;
+                }
+            };
+            for (index, recipient) in recipients.iter_mut().enumerate() {
+                if target.is_some_and(|target| target != index) {
+                    continue;
+                }
+                let mut event = event.clone();
+                if let egui::Event::PointerMoved(pos)
+                | egui::Event::PointerButton { pos, .. }
+                | egui::Event::Touch { pos, .. } = &mut event
+                {
+                    *pos = convert_egui_pos(*pos, window_layout, recipient.layout);
+                }
+                recipient.egui_input.events.push(event);
+            }
+        }
+    }
+}
+
+/// Marks frame start for Egui.
+pub fn begin_frame_system(
+    mut contexts: Query<(Entity, &mut EguiContext, &mut EguiInput), Without<EguiViewport>>,
+    input_hooks: Res<EguiInputHooks>,
+) {
+    for (window, mut ctx, mut egui_input) in contexts.iter_mut() {
+        let mut raw_input = egui_input.take();
+        input_hooks.run(window, &mut raw_input);
+        ctx.get_mut().begin_frame(raw_input);
+    }
+}
+
+/// Calls the begin pass hooks of [`EguiPassHooks`].
+pub fn run_begin_pass_hooks_system(
+    mut contexts: Query<(Entity, &mut EguiContext), Without<EguiViewport>>,
+    pass_hooks: Res<EguiPassHooks>,
+) {
+    if pass_hooks.begin_pass.is_empty() {
+        return;
+    }
+    for (window, mut ctx) in contexts.iter_mut() {
+        pass_hooks.run_begin_pass(window, ctx.get_mut());
+    }
+}
+
+/// Calls the end pass hooks of [`EguiPassHooks`].
+pub fn run_end_pass_hooks_system(
+    mut contexts: Query<(Entity, &mut EguiContext), Without<EguiViewport>>,
+    pass_hooks: Res<EguiPassHooks>,
+) {
+    if pass_hooks.end_pass.is_empty() {
+        return;
+    }
+    for (window, mut ctx) in contexts.iter_mut() {
+        pass_hooks.run_end_pass(window, ctx.get_mut());
+    }
+}
+
+/// Lays out [`EguiFontAtlasPrewarm`] characters to add them to the font atlas in advance.
+pub fn prewarm_font_atlas_system(
+    mut contexts: Query<(Entity, &mut EguiContext), Without<EguiViewport>>,
+    font_atlas_prewarm: Option<Res<EguiFontAtlasPrewarm>>,
+    // Pixels per point the atlas of a context was pre-warmed for.
+    mut prewarmed: Local<HashMap<Entity, f32>>,
+) {
+    let Some(font_atlas_prewarm) = font_atlas_prewarm else {
+        prewarmed.clear();
+        return;
+    };
+    if font_atlas_prewarm.is_changed() {
+        prewarmed.clear();
+    }
+    prewarmed.retain(|window, _| contexts.contains(*window));
+
+    for (window, mut ctx) in contexts.iter_mut() {
+        let ctx = ctx.get_mut();
+        let pixels_per_point = ctx.pixels_per_point();
+        if prewarmed.get(&window) == Some(&pixels_per_point) {
+            continue;
+        }
+        prewarmed.insert(window, pixels_per_point);
+
+        let font_ids = ctx
+            .style()
+            .text_styles
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        ctx.fonts(|fonts| {
+            for font_id in font_ids {
+                fonts.layout_no_wrap(
+                    font_atlas_prewarm.characters.clone(),
+                    font_id,
+                    egui::Color32::WHITE,
+                );
+            }
+        });
+    }
+}
+
+/// Reads Egui output.
+#[allow(clippy::too_many_arguments)]
+pub fn process_output_system(
+    egui_settings: Res<EguiSettings>,
+    mut contexts: Query<(EguiContextQuery, Option<&mut EguiViewport>)>,
+    #[cfg(feature = "render")] mut render_to_texture_contexts: Query<
+        EguiRenderToTextureContextQuery,
+        (Without<Window>, Without<EguiSecondaryContext>),
+    >,
+    #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
+    mut egui_clipboard: bevy::ecs::system::ResMut<crate::EguiClipboard>,
+    mut event: EventWriter<RequestRedraw>,
+    mut output_events: OutputEvents,
+    #[cfg(feature = "render")] mut screenshot_manager: Option<
+        bevy::ecs::system::ResMut<bevy::render::view::screenshot::ScreenshotManager>,
+    >,
+    #[cfg(feature = "render")] screenshots: Res<EguiScreenshots>,
+    time: Res<Time<Real>>,
+    mut redraw_throttle: Local<RedrawThrottle>,
+    cursor_icon_mapping: Res<EguiCursorIconMapping>,
+    accessibility_zoom: Res<EguiAccessibilityZoom>,
+    mut cursor_states: Local<HashMap<Entity, WindowCursorState>>,
+    input_hooks: Res<EguiInputHooks>,
+    mut secondary_contexts: Query<EguiSecondaryContextQuery, Without<Window>>,
+    mut tessellation_caches: ResMut<EguiTessellationCaches>,
+) {
+    // The contexts that want their next frame to be run right away.
+    let mut redrawing_contexts = Vec::new();
+    let mut tessellated_entities = Vec::new();
+
+    // The output of secondary contexts is applied to their windows along with the output of the
+    // windows' own contexts.
+    let mut secondary_outputs: HashMap<Entity, Vec<WindowContextOutput>> = HashMap::default();
+    for mut context in secondary_contexts.iter_mut() {
+        let minimized = contexts
+            .get(context.secondary_context.window)
+            .is_ok_and(|(window_context, _)| is_minimized(window_context.window));
+        let viewport_output = end_context_frame(
+            context.entity,
+            &mut context.ctx,
+            &mut context.render_output,
+            &mut context.egui_output,
+            &egui_settings,
+            &mut output_events,
+            tessellation_caches.0.entry(context.entity).or_default(),
+            minimized,
+        );
+        tessellated_entities.push(context.entity);
+        let platform_output = &context.egui_output.platform_output;
+        handle_platform_output(
+            platform_output,
+            &egui_settings,
+            #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
+            &mut egui_clipboard,
+        );
+        let ime_position = platform_output
+            .ime
+            .zip(context.render_output.screen_layout)
+            .map(|(ime, layout)| {
+                layout.to_window_pos(&context.window_size, ime.cursor_rect.left_bottom())
+            });
+        let cursor_icon = platform_output.cursor_icon;
+        let ctx = context.ctx.get_mut();
+        let dragging = ctx.dragged_id().is_some();
+        let uses_pointer = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+        secondary_outputs
+            .entry(context.secondary_context.window)
+            .or_default()
+            .push(WindowContextOutput {
+                order: (context.secondary_context.order, true),
+                cursor_icon,
+                uses_pointer,
+                ime_position,
+                dragging,
+            });
+
+        let needs_repaint = !context.render_output.is_empty();
+        if requests_immediate_repaint(&viewport_output, egui::ViewportId::ROOT)
+            && needs_repaint
+            && !minimized
+        {
+            redrawing_contexts.push(context.ctx.get_mut().clone());
+        }
+    }
 
-    if should_request_redraw {
+    // Viewport windows share the context of the window that shows them, so their frames are run
+    // once the frames of the other windows have ended.
+    let mut window_entities: Vec<_> = contexts
+        .iter()
+        .map(|(context, viewport)| (context.window_entity, viewport.is_some()))
+        .collect();
+    window_entities.sort_by_key(|(_, is_viewport)| *is_viewport);
+    let mut viewport_textures_deltas = Vec::new();
+
+    for (window_entity, _) in window_entities {
+        let Ok((mut context, mut viewport)) = contexts.get_mut(window_entity) else {
+            continue;
+        };
+        if let Some(viewport) = &viewport {
+            run_viewport_ui(
+                window_entity,
+                &mut context.ctx,
+                &mut context.egui_input,
+                viewport,
+                &input_hooks,
+            );
+        }
+        let minimized = is_minimized(&context.window);
+        let mut viewport_output = end_context_frame(
+            context.window_entity,
+            &mut context.ctx,
+            &mut context.render_output,
+            &mut context.egui_output,
+            &egui_settings,
+            &mut output_events,
+            tessellation_caches
+                .0
+                .entry(context.window_entity)
+                .or_default(),
+            minimized,
+        );
+        tessellated_entities.push(context.window_entity);
+        let viewport_id = viewport
+            .as_ref()
+            .map_or(egui::ViewportId::ROOT, |viewport| viewport.id);
+        let immediate_repaint = requests_immediate_repaint(&viewport_output, viewport_id);
+        let viewport_commands = viewport_output
+            .remove(&viewport_id)
+            .map(|output| output.commands)
+            .unwrap_or_default();
+        context.egui_output.viewport_output = viewport_output;
+        let platform_output = context.egui_output.platform_output.clone();
+        handle_platform_output(
+            &platform_output,
+            &egui_settings,
+            #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
+            &mut egui_clipboard,
+        );
+
+        // The topmost context that sets the cursor icon or has the IME enabled gets them.
+        let mut context_outputs = secondary_outputs
+            .remove(&context.window_entity)
+            .unwrap_or_default();
+        context_outputs.push(WindowContextOutput {
+            order: (0, false),
+            cursor_icon: platform_output.cursor_icon,
+            // Place the candidate window below the text cursor, in logical pixels.
+            ime_position: platform_output.ime.map(|ime| {
+                let (window_size, layout) =
+                    screen_layout(&context, &egui_settings, *accessibility_zoom);
+                layout.to_window_pos(&window_size, ime.cursor_rect.left_bottom())
+            }),
+            dragging: context.ctx.get_mut().dragged_id().is_some(),
+            uses_pointer: {
+                let ctx = context.ctx.get_mut();
+                ctx.is_pointer_over_area() || ctx.is_using_pointer()
+            },
+        });
+        context_outputs.sort_by_key(|output| std::cmp::Reverse(output.order));
+
+        let cursor_state = cursor_states.entry(context.window_entity).or_default();
+        let cursor_icon = context_outputs
+            .iter()
+            .map(|output| output.cursor_icon)
+            .find(|cursor_icon| *cursor_icon != egui::CursorIcon::Default);
+        // Egui only sets the icon while it uses the pointer or asks for a specific icon,
+        // otherwise the app's own icon is kept.
+        let cursor_icon = match cursor_icon {
+            Some(cursor_icon) => Some(cursor_icon),
+            None => context_outputs
+                .iter()
+                .any(|output| output.uses_pointer)
+                .then_some(egui::CursorIcon::Default),
+        }
+        .and_then(|cursor_icon| cursor_icon_mapping.map(context.window_entity, cursor_icon));
+        update_cursor_icon(&mut context.window, cursor_icon, &mut cursor_state.icon);
+
+        #[cfg(feature = "render")]
+        if viewport_commands.contains(&egui::ViewportCommand::Screenshot) {
+            request_screenshot(
+                context.window_entity,
+                screenshot_manager.as_deref_mut(),
+                &screenshots,
+            );
+        }
+        if let Some(viewport) = &mut viewport {
+            viewport.commands.extend(viewport_commands.iter().cloned());
+        }
+        #[cfg(not(feature = "render"))]
+        let _ = viewport_commands;
+
+        // Egui requests IME while a text edit has focus, `Window` changes are applied by `bevy_winit`.
+        // Each window keeps its own IME state, but only the OS-focused one has it enabled, so
+        // composition always goes to the text edit the user is typing into.
+        let ime_position = context_outputs
+            .iter()
+            .find_map(|output| output.ime_position);
+        let ime_enabled = ime_position.is_some() && context.window.focused;
+        if context.window.ime_enabled != ime_enabled {
+            context.window.ime_enabled = ime_enabled;
+        }
+        if let Some(ime_position) = ime_position {
+            if context.window.ime_position != ime_position {
+                context.window.ime_position = ime_position;
+            }
+        }
+
+        let dragging = context_outputs.iter().any(|output| output.dragging);
+        update_drag_cursor(
+            &mut context.window,
+            &context.context_settings,
+            dragging,
+            &mut cursor_states.entry(context.window_entity).or_default().drag,
+        );
+
+        let needs_repaint = !context.render_output.is_empty();
+        let redraws = egui_settings.run_in_background || context.window.focused;
+        if redraws && immediate_repaint && needs_repaint && !minimized {
+            redrawing_contexts.push(context.ctx.get_mut().clone());
+        }
+
+        // The textures of a context are managed by the window that shows the viewports.
+        if let Some(viewport) = &viewport {
+            let textures_delta = std::mem::take(&mut context.render_output.textures_delta);
+            viewport_textures_deltas.push((viewport.context, textures_delta));
+        }
+    }
+    for (owner, textures_delta) in viewport_textures_deltas {
+        if let Ok((mut context, _)) = contexts.get_mut(owner) {
+            context.render_output.textures_delta.append(textures_delta);
+        }
+    }
+
+    #[cfg(feature = "render")]
+    for mut context in render_to_texture_contexts.iter_mut() {
+        let viewport_output = end_context_frame(
+            context.entity,
+            &mut context.ctx,
+            &mut context.render_output,
+            &mut context.egui_output,
+            &egui_settings,
+            &mut output_events,
+            tessellation_caches.0.entry(context.entity).or_default(),
+            false,
+        );
+        tessellated_entities.push(context.entity);
+        let platform_output = context.egui_output.platform_output.clone();
+        handle_platform_output(
+            &platform_output,
+            &egui_settings,
+            #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
+            &mut egui_clipboard,
+        );
+
+        let needs_repaint = !context.render_output.is_empty();
+        if requests_immediate_repaint(&viewport_output, egui::ViewportId::ROOT) && needs_repaint {
+            redrawing_contexts.push(context.ctx.get_mut().clone());
+        }
+    }
+    tessellation_caches
+        .0
+        .retain(|entity, _| tessellated_entities.contains(entity));
+
+    if !redrawing_contexts.is_empty() || redraw_throttle.pending {
+        let now = time.elapsed();
+        let remaining = redraw_throttle.last_request.map_or(0.0, |last_request| {
+            egui_settings.min_redraw_interval - (now - last_request).as_secs_f32()
+        });
+        if remaining > 0.0 {
+            // The redraw is requested by the first update after the interval, the contexts wake
+            // the app up for it with the `wake_on_repaint` feature.
+            redraw_throttle.pending = true;
+            for ctx in redrawing_contexts {
+                ctx.request_repaint_after(std::time::Duration::from_secs_f32(remaining));
+            }
+        } else {
+            *redraw_throttle = RedrawThrottle {
+                last_request: Some(now),
+                pending: false,
+            };
+            event.send(RequestRedraw);
+        }
+    }
+}
+
+/// The last redraw requested by [`process_output_system`], see
+/// [`EguiSettings::min_redraw_interval`].
+#[derive(Default)]
+pub struct RedrawThrottle {
+    last_request: Option<std::time::Duration>,
+    // Whether a redraw was skipped because it came too soon after the last one.
+    pending: bool,
+}
+
+/// Returns `true` if egui wants the next frame to be run as soon as possible.
+///
+/// Repaints requested with a delay (`egui::Context::request_repaint_after`) don't request a
+/// redraw, they are run once the app updates for another reason or the `wake_on_repaint` feature
+/// wakes it up.
+fn requests_immediate_repaint(
+    viewport_output: &egui::ViewportIdMap<egui::ViewportOutput>,
+    viewport_id: egui::ViewportId,
+) -> bool {
+    viewport_output
+        .get(&viewport_id)
+        .is_some_and(|output| output.repaint_delay.is_zero())
+}
+
+/// The shapes the contexts were last tessellated with, and the tessellation tasks that are still
+/// running (see [`EguiSettings::threaded_tessellation`]).
+#[derive(Resource, Default)]
+pub struct EguiTessellationCaches(HashMap<Entity, TessellationCache>);
+
+/// The shapes a context was last tessellated with.
+#[derive(Default)]
+struct TessellationCache {
+    shapes: Vec<egui::epaint::ClippedShape>,
+    pixels_per_point: f32,
+    options: Option<egui::epaint::TessellationOptions>,
+    // Receives the paint jobs tessellated on the `AsyncComputeTaskPool`.
+    task: Option<SyncCell<mpsc::Receiver<Vec<egui::ClippedPrimitive>>>>,
+    // The textures delta of the frames the task is tessellating, which is held back until the
+    // paint jobs are ready, so that the previous paint jobs aren't drawn with the new textures.
+    pending_textures_delta: egui::TexturesDelta,
+}
+
+impl TessellationCache {
+    fn spawn_task(
+        &mut self,
+        ctx: egui::Context,
+        shapes: Vec<egui::epaint::ClippedShape>,
+        pixels_per_point: f32,
+    ) {
+        let (sender, receiver) = mpsc::channel();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                // The receiver is gone if the context has been tessellated again or despawned.
+                let _ = sender.send(ctx.tessellate(shapes, pixels_per_point));
+            })
+            .detach();
+        // Replacing a pending task drops its now outdated result.
+        self.task = Some(SyncCell::new(receiver));
+    }
+
+    /// Stops waiting for the task and returns the textures delta held back for it.
+    fn cancel_task(&mut self) -> egui::TexturesDelta {
+        self.task = None;
+        std::mem::take(&mut self.pending_textures_delta)
+    }
+}
+
+impl EguiTessellationCaches {
+    /// Returns `true` if the paint jobs of a context are still being tessellated.
+    #[cfg(test)]
+    pub(crate) fn has_pending_tasks(&self) -> bool {
+        self.0.values().any(|cache| cache.task.is_some())
+    }
+}
+
+fn set_paint_jobs(render_output: &mut EguiRenderOutput, paint_jobs: Vec<egui::ClippedPrimitive>) {
+    render_output.stats = EguiRenderStats::from_paint_jobs(&paint_jobs);
+    render_output.paint_jobs = paint_jobs;
+    render_output.paint_jobs_generation = render_output.paint_jobs_generation.wrapping_add(1);
+}
+
+/// Stores the paint jobs tessellated on the [`AsyncComputeTaskPool`] in [`EguiRenderOutput`] once
+/// they're ready (see [`EguiSettings::threaded_tessellation`]).
+///
+/// The system doesn't wait for the tasks: until a task finishes, the previous paint jobs are
+/// drawn, and a redraw is requested so that the result is picked up by the next frame.
+pub fn finish_tessellation_system(
+    mut tessellation_caches: ResMut<EguiTessellationCaches>,
+    mut render_outputs: Query<&mut EguiRenderOutput>,
+    mut event: EventWriter<RequestRedraw>,
+) {
+    let mut pending = false;
+    for (entity, tessellation_cache) in tessellation_caches.0.iter_mut() {
+        let Some(task) = &mut tessellation_cache.task else {
+            continue;
+        };
+        let Ok(mut render_output) = render_outputs.get_mut(*entity) else {
+            continue;
+        };
+        match task.get().try_recv() {
+            Ok(paint_jobs) => {
+                set_paint_jobs(&mut render_output, paint_jobs);
+                let textures_delta = tessellation_cache.cancel_task();
+                render_output.textures_delta.append(textures_delta);
+            }
+            Err(mpsc::TryRecvError::Empty) => pending = true,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                log::error!("Failed to tessellate the shapes of an Egui context");
+                let textures_delta = tessellation_cache.cancel_task();
+                render_output.textures_delta.append(textures_delta);
+                // Makes the next frame tessellate the shapes again.
+                tessellation_cache.options = None;
+            }
+        }
+    }
+    if pending {
         event.send(RequestRedraw);
     }
 }
 
-fn egui_to_winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<bevy::window::CursorIcon> {
+/// The output of a context that's applied to the window it's drawn to (see
+/// [`EguiSecondaryContext`]).
+struct WindowContextOutput {
+    // See `InputRecipient::order`.
+    order: (i32, bool),
+    cursor_icon: egui::CursorIcon,
+    // Whether the pointer is over an area of the context or the context is using it.
+    uses_pointer: bool,
+    ime_position: Option<bevy::math::Vec2>,
+    dragging: bool,
+}
+
+/// Space kept between the text cursor and the virtual keyboard, in logical pixels.
+const KEYBOARD_MARGIN: f32 = 8.0;
+
+/// Moves the UI of the primary window up while [`EguiVirtualKeyboard`] covers the text cursor,
+/// the offset is applied starting from the next frame.
+pub fn update_keyboard_offset_system(
+    virtual_keyboard: Res<EguiVirtualKeyboard>,
+    mut contexts: Query<
+        (
+            &mut EguiContext,
+            &EguiOutput,
+            &EguiRenderOutput,
+            &WindowSize,
+            &EguiContextSettings,
+        ),
+        With<PrimaryWindow>,
+    >,
+) {
+    let Ok((mut ctx, egui_output, render_output, window_size, context_settings)) =
+        contexts.get_single_mut()
+    else {
+        return;
+    };
+    let keyboard_offset = match (
+        virtual_keyboard.occluded_rect,
+        &egui_output.platform_output.ime,
+        render_output.screen_layout,
+    ) {
+        (Some(occluded_rect), Some(ime), Some(layout))
+            if virtual_keyboard.move_ui_into_view && !context_settings.display_only =>
+        {
+            // The position of the cursor if the UI wasn't moved, the layout is moved by whole
+            // physical pixels.
+            let applied_offset =
+                (ctx.keyboard_offset * window_size.scale_factor).round() / window_size.scale_factor;
+            let cursor_bottom = layout
+                .to_window_pos(window_size, ime.cursor_rect.left_bottom())
+                .y
+                + applied_offset;
+            (cursor_bottom + KEYBOARD_MARGIN - occluded_rect.min.y).max(0.0)
+        }
+        _ => 0.0,
+    };
+    if ctx.keyboard_offset != keyboard_offset {
+        ctx.keyboard_offset = keyboard_offset;
+        ctx.get_mut().request_repaint();
+    }
+}
+
+/// Shows the Android soft keyboard while a text field of the primary window has focus, and hides
+/// it once the focus is lost.
+///
+/// Winit shows the keyboard as well once [`Window::ime_enabled`] is set (on Android and iOS), but
+/// only as an implicit request, which Android may ignore. Requires the `android_soft_keyboard`
+/// feature, and one of the Android activity features of Bevy (`android-game-activity` is enabled
+/// by default).
+#[cfg(all(feature = "android_soft_keyboard", target_os = "android"))]
+pub fn update_android_soft_keyboard_system(
+    windows: Query<&Window, (With<PrimaryWindow>, bevy::prelude::Changed<Window>)>,
+    mut shown: Local<bool>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    if window.ime_enabled == *shown {
+        return;
+    }
+    let Some(android_app) = bevy::winit::ANDROID_APP.get() else {
+        return;
+    };
+    *shown = window.ime_enabled;
+    if *shown {
+        android_app.show_soft_input(false);
+    } else {
+        android_app.hide_soft_input(false);
+    }
+}
+
+/// Updates [`EguiWantsInput`] once all the contexts have finished their frames.
+pub fn update_egui_wants_input_system(
+    mut contexts: Query<(&mut EguiContext, &EguiContextSettings)>,
+    mut egui_wants_input: ResMut<EguiWantsInput>,
+) {
+    let mut wants_input = EguiWantsInput::default();
+    for (mut ctx, context_settings) in contexts.iter_mut() {
+        if context_settings.display_only {
+            continue;
+        }
+        let pointer_pos = ctx.get_mut().pointer_latest_pos();
+        let is_pointer_over_ui = pointer_pos.is_some_and(|pos| ctx.hit_test(pos).is_some());
+        let ctx = ctx.get_mut();
+        wants_input.wants_pointer_input |= is_pointer_over_ui || ctx.is_using_pointer();
+        wants_input.wants_keyboard_input |= ctx.wants_keyboard_input();
+        wants_input.is_pointer_over_area |= ctx.is_pointer_over_area();
+    }
+    egui_wants_input.set_if_neq(wants_input);
+}
+
+/// Minimized windows have zero physical size.
+fn is_minimized(window: &Window) -> bool {
+    window.physical_width() == 0 || window.physical_height() == 0
+}
+
+/// Ends the frame of a context, stores its render and platform output and sends
+/// [`EguiInteractionEvent`]s and the other output events. Returns the output of the viewports.
+///
+/// The paint jobs of the last frame are kept if the shapes haven't changed since. Nothing is
+/// tessellated for minimized windows, which have no paint jobs until they're restored.
+#[allow(clippy::too_many_arguments)]
+fn end_context_frame(
+    entity: Entity,
+    ctx: &mut EguiContext,
+    render_output: &mut EguiRenderOutput,
+    egui_output: &mut EguiOutput,
+    egui_settings: &EguiSettings,
+    output_events: &mut OutputEvents,
+    tessellation_cache: &mut TessellationCache,
+    minimized: bool,
+) -> egui::ViewportIdMap<egui::ViewportOutput> {
+    ctx.background_rects = painted_background_rects(ctx.get_mut());
+    let ctx = ctx.get_mut();
+    let full_output = ctx.end_frame();
+    let egui::FullOutput {
+        platform_output,
+        mut shapes,
+        textures_delta,
+        pixels_per_point,
+        viewport_output,
+    } = full_output;
+    if egui_settings.min_stroke_width_in_pixels > 0.0 {
+        let min_width = egui_settings.min_stroke_width_in_pixels / pixels_per_point;
+        for clipped_shape in &mut shapes {
+            widen_thin_strokes(&mut clipped_shape.shape, min_width);
+        }
+    }
+    // Texture changes (the font atlas growing, for example) may change the texture coordinates.
+    let options = ctx.tessellation_options(|options| *options);
+    let unchanged = textures_delta.is_empty()
+        && tessellation_cache.options == Some(options)
+        && tessellation_cache.pixels_per_point == pixels_per_point
+        && tessellation_cache.shapes == shapes;
+    if minimized {
+        // The last shapes are forgotten too, so the window is tessellated again once restored.
+        render_output
+            .textures_delta
+            .append(tessellation_cache.cancel_task());
+        *tessellation_cache = TessellationCache::default();
+        if !render_output.paint_jobs.is_empty() {
+            set_paint_jobs(render_output, Vec::new());
+        }
+    } else if !unchanged {
+        tessellation_cache.shapes.clone_from(&shapes);
+        tessellation_cache.pixels_per_point = pixels_per_point;
+        tessellation_cache.options = Some(options);
+        // Web builds can't wait for tasks, there tessellation always happens right away.
+        if egui_settings.threaded_tessellation && !cfg!(target_arch = "wasm32") {
+            tessellation_cache.spawn_task(ctx.clone(), shapes, pixels_per_point);
+        } else {
+            // A pending result would overwrite the newer paint jobs.
+            render_output
+                .textures_delta
+                .append(tessellation_cache.cancel_task());
+            let paint_jobs = ctx.tessellate(shapes, pixels_per_point);
+            set_paint_jobs(render_output, paint_jobs);
+        }
+    }
+    if tessellation_cache.task.is_some() {
+        tessellation_cache
+            .pending_textures_delta
+            .append(textures_delta);
+    } else {
+        render_output.textures_delta.append(textures_delta);
+    }
+
+    send_output_events(
+        entity,
+        &platform_output,
+        &viewport_output,
+        egui_output,
+        output_events,
+    );
+    // Egui outputs the IME state while a text field has focus.
+    egui_output.text_input_hints = platform_output
+        .ime
+        .and_then(|_| EguiTextInputHints::focused(ctx));
+    egui_output.focused_text =
+        EguiFocusedText::focused(ctx, &platform_output, egui_output.focused_text.as_ref());
+    egui_output.platform_output = platform_output;
+
+    viewport_output
+}
+
+/// Sends the events derived from the output of a context, before its previous output is replaced.
+fn send_output_events(
+    entity: Entity,
+    platform_output: &egui::PlatformOutput,
+    viewport_output: &egui::ViewportIdMap<egui::ViewportOutput>,
+    previous_output: &EguiOutput,
+    output_events: &mut OutputEvents,
+) {
+    output_events
+        .ev_interaction
+        .send_batch(
+            platform_output
+                .events
+                .iter()
+                .map(|event| EguiInteractionEvent {
+                    window: entity,
+                    event: event.clone(),
+                }),
+        );
+    if !platform_output.copied_text.is_empty() {
+        output_events.ev_copy.send(EguiCopyEvent {
+            window: entity,
+            text: platform_output.copied_text.clone(),
+        });
+    }
+    if let Some(open_url) = &platform_output.open_url {
+        output_events.ev_open_url.send(EguiOpenUrlEvent {
+            window: entity,
+            url: open_url.url.clone(),
+            new_tab: open_url.new_tab,
+        });
+    }
+    if let Some(ime) = platform_output.ime {
+        let previous_cursor_rect = previous_output
+            .platform_output
+            .ime
+            .map(|ime| ime.cursor_rect);
+        if previous_cursor_rect != Some(ime.cursor_rect) {
+            output_events
+                .ev_text_cursor_moved
+                .send(EguiTextCursorMoved {
+                    window: entity,
+                    cursor_rect: ime.cursor_rect,
+                });
+        }
+    }
+    // `Duration::MAX` means no repaint is needed.
+    if let Some(viewport_output) = viewport_output.get(&egui::ViewportId::ROOT) {
+        if viewport_output.repaint_delay < std::time::Duration::MAX {
+            output_events.ev_wants_repaint.send(EguiWantsRepaint {
+                window: entity,
+                delay: viewport_output.repaint_delay,
+            });
+        }
+    }
+}
+
+/// Asks Bevy to take a screenshot of a window, the result is stored in [`EguiScreenshots`].
+#[cfg(feature = "render")]
+fn request_screenshot(
+    window: Entity,
+    screenshot_manager: Option<&mut bevy::render::view::screenshot::ScreenshotManager>,
+    screenshots: &EguiScreenshots,
+) {
+    let Some(screenshot_manager) = screenshot_manager else {
+        log::warn!("Can't take a screenshot requested by Egui: `ScreenshotManager` doesn't exist");
+        return;
+    };
+    let screenshots = screenshots.clone();
+    let result =
+        screenshot_manager.take_screenshot(window, move |image| match color_image_from_screenshot(
+            &image,
+        ) {
+            Some(color_image) => screenshots.push(window, color_image),
+            None => log::warn!(
+                "Can't pass a screenshot to Egui: unsupported texture format {:?}",
+                image.texture_descriptor.format
+            ),
+        });
+    if let Err(err) = result {
+        log::warn!("Can't take a screenshot requested by Egui: {err}");
+    }
+}
+
+/// Converts a screenshot of a window to an Egui image.
+#[cfg(feature = "render")]
+fn color_image_from_screenshot(image: &bevy::render::texture::Image) -> Option<egui::ColorImage> {
+    use bevy::render::render_resource::TextureFormat;
+
+    let size = [image.width() as usize, image.height() as usize];
+    match image.texture_descriptor.format {
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            Some(egui::ColorImage::from_rgba_unmultiplied(size, &image.data))
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => {
+            let pixels = image
+                .data
+                .chunks_exact(4)
+                .map(|bgra| {
+                    egui::Color32::from_rgba_unmultiplied(bgra[2], bgra[1], bgra[0], bgra[3])
+                })
+                .collect();
+            Some(egui::ColorImage { size, pixels })
+        }
+        _ => None,
+    }
+}
+
+/// Handles the output that doesn't depend on a window: copied text and opened URLs.
+#[cfg_attr(not(feature = "open_url"), allow(unused_variables))]
+fn handle_platform_output(
+    platform_output: &egui::PlatformOutput,
+    egui_settings: &EguiSettings,
+    #[cfg(all(feature = "manage_clipboard", not(target_os = "android")))]
+    egui_clipboard: &mut crate::EguiClipboard,
+) {
+    #[cfg(all(
+        feature = "manage_clipboard",
+        not(target_os = "android"),
+        not(all(target_arch = "wasm32", not(web_sys_unstable_apis)))
+    ))]
+    if !platform_output.copied_text.is_empty() {
+        egui_clipboard.set_contents(&platform_output.copied_text);
+    }
+
+    #[cfg(feature = "open_url")]
+    if let Some(egui::output::OpenUrl { url, new_tab }) = &platform_output.open_url {
+        let target = if *new_tab {
+            "_blank"
+        } else {
+            egui_settings
+                .default_open_url_target
+                .as_deref()
+                .unwrap_or("_self")
+        };
+        if let Err(err) = webbrowser::open_browser_with_options(
+            webbrowser::Browser::Default,
+            url,
+            webbrowser::BrowserOptions::new().with_target_hint(target),
+        ) {
+            log::error!("Failed to open '{}': {:?}", url, err);
+        }
+    }
+}
+
+/// Collects the rects of visible shapes painted on background layers, before they are drained
+/// by [`egui::Context::end_frame`] (see [`EguiContext::hit_test`]).
+fn painted_background_rects(ctx: &egui::Context) -> Vec<egui::Rect> {
+    let background_layers = ctx.memory(|memory| {
+        memory
+            .layer_ids()
+            .filter(|layer_id| layer_id.order == egui::Order::Background)
+            .collect::<Vec<_>>()
+    });
+    ctx.graphics(|graphics| {
+        std::iter::once(egui::LayerId::background())
+            .chain(background_layers)
+            .filter_map(|layer_id| graphics.get(layer_id))
+            .flat_map(|paint_list| paint_list.all_entries())
+            .filter(|clipped_shape| match &clipped_shape.shape {
+                egui::Shape::Noop => false,
+                egui::Shape::Rect(rect) => {
+                    rect.fill != egui::Color32::TRANSPARENT || !rect.stroke.is_empty()
+                }
+                _ => true,
+            })
+            .map(|clipped_shape| {
+                clipped_shape
+                    .shape
+                    .visual_bounding_rect()
+                    .intersect(clipped_shape.clip_rect)
+            })
+            .filter(|rect| rect.is_positive())
+            .collect()
+    })
+}
+
+/// Widens strokes thinner than `min_width` (in points), see
+/// [`EguiSettings::min_stroke_width_in_pixels`].
+fn widen_thin_strokes(shape: &mut egui::Shape, min_width: f32) {
+    let widen = |width: &mut f32| {
+        if *width > 0.0 && *width < min_width {
+            *width = min_width;
+        }
+    };
+    match shape {
+        egui::Shape::Vec(shapes) => {
+            for shape in shapes {
+                widen_thin_strokes(shape, min_width);
+            }
+        }
+        egui::Shape::Circle(circle) => widen(&mut circle.stroke.width),
+        egui::Shape::Ellipse(ellipse) => widen(&mut ellipse.stroke.width),
+        egui::Shape::LineSegment { stroke, .. } => widen(&mut stroke.width),
+        egui::Shape::Path(path) => widen(&mut path.stroke.width),
+        egui::Shape::Rect(rect) => widen(&mut rect.stroke.width),
+        egui::Shape::QuadraticBezier(bezier) => widen(&mut bezier.stroke.width),
+        egui::Shape::CubicBezier(bezier) => widen(&mut bezier.stroke.width),
+        egui::Shape::Noop
+        | egui::Shape::Text(_)
+        | egui::Shape::Mesh(_)
+        | egui::Shape::Callback(_) => {}
+    }
+}
+
+/// Cursor state of a window that Egui changes and restores later.
+#[derive(Default)]
+pub struct WindowCursorState {
+    icon: CursorIconState,
+    drag: DragCursorState,
+}
+
+/// The icon applied to a window by Egui and the one the window had before, which is restored
+/// once Egui stops setting the icon.
+#[derive(Default)]
+pub struct CursorIconState(Option<(bevy::window::CursorIcon, bevy::window::CursorIcon)>);
+
+/// Applies the icon requested by Egui (`None` if it doesn't request one), and restores the
+/// icon set by the app once Egui stops requesting one.
+///
+/// The app may change the icon while Egui's one is applied, the latest one is restored.
+fn update_cursor_icon(
+    window: &mut Mut<Window>,
+    cursor_icon: Option<bevy::window::CursorIcon>,
+    state: &mut CursorIconState,
+) {
+    let current_icon = window.cursor.icon;
+    match cursor_icon {
+        Some(cursor_icon) => {
+            let (applied_icon, app_icon) = state.0.get_or_insert((current_icon, current_icon));
+            if current_icon != *applied_icon {
+                *app_icon = current_icon;
+            }
+            *applied_icon = cursor_icon;
+            // Setting the icon every frame makes it flicker on Windows.
+            if current_icon != cursor_icon {
+                window.cursor.icon = cursor_icon;
+            }
+        }
+        None => {
+            if let Some((applied_icon, app_icon)) = state.0.take() {
+                if current_icon == applied_icon && current_icon != app_icon {
+                    window.cursor.icon = app_icon;
+                }
+            }
+        }
+    }
+}
+
+/// Cursor state of a window saved while an Egui widget is being dragged.
+#[derive(Default)]
+pub struct DragCursorState(Option<(CursorGrabMode, bool)>);
+
+/// Confines or hides the cursor while an Egui widget is being dragged, and restores the saved
+/// state once the drag ends (see [`EguiContextSettings::confine_cursor_on_drag`]).
+fn update_drag_cursor(
+    window: &mut Mut<Window>,
+    context_settings: &EguiContextSettings,
+    dragging: bool,
+    state: &mut DragCursorState,
+) {
+    let confine = context_settings.confine_cursor_on_drag;
+    let hide = context_settings.hide_cursor_on_drag;
+    if dragging && (confine || hide) {
+        if state.0.is_none() {
+            state.0 = Some((window.cursor.grab_mode, window.cursor.visible));
+        }
+        if confine && window.cursor.grab_mode == CursorGrabMode::None {
+            window.cursor.grab_mode = CursorGrabMode::Confined;
+        }
+        if hide && window.cursor.visible {
+            window.cursor.visible = false;
+        }
+    } else if let Some((grab_mode, visible)) = state.0.take() {
+        window.cursor.grab_mode = grab_mode;
+        window.cursor.visible = visible;
+    }
+}
+
+pub(crate) fn egui_to_winit_cursor_icon(
+    cursor_icon: egui::CursorIcon,
+) -> Option<bevy::window::CursorIcon> {
     match cursor_icon {
         egui::CursorIcon::Default => Some(bevy::window::CursorIcon::Default),
         egui::CursorIcon::PointingHand => Some(bevy::window::CursorIcon::Pointer),
@@ -588,6 +3127,9 @@ fn egui_to_winit_cursor_icon(cursor_icon: egui::CursorIcon) -> Option<bevy::wind
 }
 
 /// Matches the implementation of <https://github.com/emilk/egui/blob/68b3ef7f6badfe893d3bbb1f791b481069d807d9/crates/egui-winit/src/lib.rs#L1005>.
+///
+/// Keys that don't have an [`egui::Key`] equivalent (such as `BrowserBack` or media keys) aren't
+/// passed to Egui, [`EguiUnmappedKeyEvent`] is sent for them instead.
 pub fn bevy_to_egui_key(key: &Key) -> Option<egui::Key> {
     let key = match key {
         Key::Character(str) => return egui::Key::from_name(str.as_str()),
@@ -628,12 +3170,56 @@ pub fn bevy_to_egui_key(key: &Key) -> Option<egui::Key> {
         Key::F18 => egui::Key::F18,
         Key::F19 => egui::Key::F19,
         Key::F20 => egui::Key::F20,
+        Key::F21 => egui::Key::F21,
+        Key::F22 => egui::Key::F22,
+        Key::F23 => egui::Key::F23,
+        Key::F24 => egui::Key::F24,
+        Key::F25 => egui::Key::F25,
+        Key::F26 => egui::Key::F26,
+        Key::F27 => egui::Key::F27,
+        Key::F28 => egui::Key::F28,
+        Key::F29 => egui::Key::F29,
+        Key::F30 => egui::Key::F30,
+        Key::F31 => egui::Key::F31,
+        Key::F32 => egui::Key::F32,
+        Key::F33 => egui::Key::F33,
+        Key::F34 => egui::Key::F34,
+        Key::F35 => egui::Key::F35,
+
+        Key::Cut => egui::Key::Cut,
+        Key::Copy => egui::Key::Copy,
+        Key::Paste => egui::Key::Paste,
 
         _ => return None,
     };
     Some(key)
 }
 
+/// Returns `true` for the keys without an [`egui::Key`] equivalent to send
+/// [`EguiUnmappedKeyEvent`] for, skipping the modifiers (Egui receives them as [`egui::Modifiers`]).
+fn is_unmapped_key(key: &Key) -> bool {
+    !matches!(
+        key,
+        Key::Character(_)
+            | Key::Unidentified(_)
+            | Key::Dead(_)
+            | Key::Alt
+            | Key::AltGraph
+            | Key::CapsLock
+            | Key::Control
+            | Key::Fn
+            | Key::FnLock
+            | Key::NumLock
+            | Key::ScrollLock
+            | Key::Shift
+            | Key::Symbol
+            | Key::SymbolLock
+            | Key::Meta
+            | Key::Hyper
+            | Key::Super
+    )
+}
+
 /// Matches the implementation of <https://github.com/emilk/egui/blob/68b3ef7f6badfe893d3bbb1f791b481069d807d9/crates/egui-winit/src/lib.rs#L1080>.
 pub fn bevy_to_egui_physical_key(key: &KeyCode) -> Option<egui::Key> {
     let key = match key {
@@ -656,8 +3242,8 @@ pub fn bevy_to_egui_physical_key(key: &KeyCode) -> Option<egui::Key> {
 
         // Punctuation
         KeyCode::Space => egui::Key::Space,
-        KeyCode::Comma => egui::Key::Comma,
-        KeyCode::Period => egui::Key::Period,
+        KeyCode::Comma | KeyCode::NumpadComma => egui::Key::Comma,
+        KeyCode::Period | KeyCode::NumpadDecimal => egui::Key::Period,
         // KeyCode::Colon => egui::Key::Colon, // NOTE: there is no physical colon key on an american keyboard
         KeyCode::Semicolon => egui::Key::Semicolon,
         KeyCode::Backslash => egui::Key::Backslash,
@@ -665,13 +3251,14 @@ pub fn bevy_to_egui_physical_key(key: &KeyCode) -> Option<egui::Key> {
         KeyCode::BracketLeft => egui::Key::OpenBracket,
         KeyCode::BracketRight => egui::Key::CloseBracket,
         KeyCode::Backquote => egui::Key::Backtick,
+        KeyCode::Quote => egui::Key::Quote,
 
         KeyCode::Cut => egui::Key::Cut,
         KeyCode::Copy => egui::Key::Copy,
         KeyCode::Paste => egui::Key::Paste,
         KeyCode::Minus | KeyCode::NumpadSubtract => egui::Key::Minus,
         KeyCode::NumpadAdd => egui::Key::Plus,
-        KeyCode::Equal => egui::Key::Equals,
+        KeyCode::Equal | KeyCode::NumpadEqual => egui::Key::Equals,
 
         KeyCode::Digit0 | KeyCode::Numpad0 => egui::Key::Num0,
         KeyCode::Digit1 | KeyCode::Numpad1 => egui::Key::Num1,
@@ -731,7 +3318,378 @@ pub fn bevy_to_egui_physical_key(key: &KeyCode) -> Option<egui::Key> {
         KeyCode::F18 => egui::Key::F18,
         KeyCode::F19 => egui::Key::F19,
         KeyCode::F20 => egui::Key::F20,
+        KeyCode::F21 => egui::Key::F21,
+        KeyCode::F22 => egui::Key::F22,
+        KeyCode::F23 => egui::Key::F23,
+        KeyCode::F24 => egui::Key::F24,
+        KeyCode::F25 => egui::Key::F25,
+        KeyCode::F26 => egui::Key::F26,
+        KeyCode::F27 => egui::Key::F27,
+        KeyCode::F28 => egui::Key::F28,
+        KeyCode::F29 => egui::Key::F29,
+        KeyCode::F30 => egui::Key::F30,
+        KeyCode::F31 => egui::Key::F31,
+        KeyCode::F32 => egui::Key::F32,
+        KeyCode::F33 => egui::Key::F33,
+        KeyCode::F34 => egui::Key::F34,
+        KeyCode::F35 => egui::Key::F35,
         _ => return None,
     };
     Some(key)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_cursor() {
+        let mut world = bevy::ecs::world::World::new();
+        let window = world.spawn(Window::default()).id();
+        let context_settings = EguiContextSettings {
+            confine_cursor_on_drag: true,
+            hide_cursor_on_drag: true,
+            ..Default::default()
+        };
+        let mut state = DragCursorState::default();
+
+        let mut update = |world: &mut bevy::ecs::world::World, dragging| {
+            let mut window = world.get_mut::<Window>(window).unwrap();
+            update_drag_cursor(&mut window, &context_settings, dragging, &mut state);
+            (window.cursor.grab_mode, window.cursor.visible)
+        };
+        assert_eq!(update(&mut world, false), (CursorGrabMode::None, true));
+        assert_eq!(update(&mut world, true), (CursorGrabMode::Confined, false));
+        assert_eq!(update(&mut world, true), (CursorGrabMode::Confined, false));
+        assert_eq!(update(&mut world, false), (CursorGrabMode::None, true));
+
+        // An app's own grab mode is kept and restored.
+        world.get_mut::<Window>(window).unwrap().cursor.grab_mode = CursorGrabMode::Locked;
+        assert_eq!(update(&mut world, true), (CursorGrabMode::Locked, false));
+        assert_eq!(update(&mut world, false), (CursorGrabMode::Locked, true));
+    }
+
+    #[test]
+    fn test_cursor_icon() {
+        use bevy::window::CursorIcon;
+
+        let mut world = bevy::ecs::world::World::new();
+        let window = world.spawn(Window::default()).id();
+        let mut state = CursorIconState::default();
+
+        let mut update = |world: &mut bevy::ecs::world::World, cursor_icon| {
+            let mut window = world.get_mut::<Window>(window).unwrap();
+            update_cursor_icon(&mut window, cursor_icon, &mut state);
+            window.cursor.icon
+        };
+        world.get_mut::<Window>(window).unwrap().cursor.icon = CursorIcon::Crosshair;
+        assert_eq!(update(&mut world, None), CursorIcon::Crosshair);
+        assert_eq!(update(&mut world, Some(CursorIcon::Text)), CursorIcon::Text);
+        assert_eq!(
+            update(&mut world, Some(CursorIcon::Default)),
+            CursorIcon::Default
+        );
+        assert_eq!(update(&mut world, None), CursorIcon::Crosshair);
+
+        // The icon set by the app while Egui's one is applied is restored.
+        assert_eq!(update(&mut world, Some(CursorIcon::Text)), CursorIcon::Text);
+        world.get_mut::<Window>(window).unwrap().cursor.icon = CursorIcon::Move;
+        assert_eq!(update(&mut world, Some(CursorIcon::Text)), CursorIcon::Text);
+        assert_eq!(update(&mut world, None), CursorIcon::Move);
+    }
+
+    #[test]
+    fn test_two_finger_gesture() {
+        let mut state = TouchGestureState::default();
+        state.touches.insert(1, egui::pos2(0.0, 0.0));
+        assert!(state.two_finger_gesture().is_none());
+
+        state.touches.insert(0, egui::pos2(10.0, 0.0));
+        let gesture = state.two_finger_gesture().unwrap();
+        assert_eq!(gesture.center, egui::pos2(5.0, 0.0));
+        assert_eq!(gesture.distance, 10.0);
+        assert!((gesture.angle.abs() - std::f32::consts::PI).abs() < 1e-6);
+
+        state.touches.insert(2, egui::pos2(20.0, 0.0));
+        assert!(state.two_finger_gesture().is_none());
+    }
+
+    #[test]
+    fn test_clipboard_shortcut() {
+        assert_eq!(
+            clipboard_shortcut(egui::Key::C, true),
+            Some(ClipboardShortcut::Copy)
+        );
+        assert_eq!(
+            clipboard_shortcut(egui::Key::X, true),
+            Some(ClipboardShortcut::Cut)
+        );
+        assert_eq!(
+            clipboard_shortcut(egui::Key::V, true),
+            Some(ClipboardShortcut::Paste)
+        );
+        assert_eq!(
+            clipboard_shortcut(egui::Key::Paste, false),
+            Some(ClipboardShortcut::Paste)
+        );
+        assert_eq!(clipboard_shortcut(egui::Key::C, false), None);
+        assert_eq!(clipboard_shortcut(egui::Key::A, true), None);
+    }
+
+    #[test]
+    fn test_finish_tessellation_doesnt_wait() {
+        use bevy::ecs::{event::Events, system::RunSystemOnce, world::World};
+
+        let mut world = World::new();
+        world.init_resource::<Events<RequestRedraw>>();
+        let entity = world
+            .spawn(EguiRenderOutput {
+                paint_jobs: vec![egui::ClippedPrimitive {
+                    clip_rect: egui::Rect::EVERYTHING,
+                    primitive: egui::epaint::Primitive::Mesh(egui::Mesh::default()),
+                }],
+                ..Default::default()
+            })
+            .id();
+        let font_delta = || egui::TexturesDelta {
+            free: vec![egui::TextureId::Managed(1)],
+            ..Default::default()
+        };
+        let (sender, receiver) = mpsc::channel();
+        let mut tessellation_caches = EguiTessellationCaches::default();
+        tessellation_caches.0.insert(
+            entity,
+            TessellationCache {
+                options: Some(Default::default()),
+                task: Some(SyncCell::new(receiver)),
+                pending_textures_delta: font_delta(),
+                ..Default::default()
+            },
+        );
+        world.insert_resource(tessellation_caches);
+        let render_output = |world: &World| world.get::<EguiRenderOutput>(entity).unwrap().clone();
+        let redraw_requests = |world: &mut World| {
+            let requests = world.resource::<Events<RequestRedraw>>().len();
+            world.resource_mut::<Events<RequestRedraw>>().clear();
+            requests
+        };
+
+        // The previous paint jobs are kept while the task is running, along with the textures.
+        world.run_system_once(finish_tessellation_system);
+        assert_eq!(render_output(&world).paint_jobs.len(), 1);
+        assert!(render_output(&world).textures_delta.is_empty());
+        assert_eq!(redraw_requests(&mut world), 1);
+
+        sender.send(Vec::new()).unwrap();
+        world.run_system_once(finish_tessellation_system);
+        assert!(render_output(&world).paint_jobs.is_empty());
+        assert_eq!(render_output(&world).textures_delta.free, font_delta().free);
+        assert_eq!(redraw_requests(&mut world), 0);
+        assert!(!world
+            .resource::<EguiTessellationCaches>()
+            .has_pending_tasks());
+
+        // A task that panicked is given up on, and the shapes are tessellated again.
+        let (sender, receiver) = mpsc::channel::<Vec<egui::ClippedPrimitive>>();
+        drop(sender);
+        let mut tessellation_caches = world.resource_mut::<EguiTessellationCaches>();
+        let tessellation_cache = tessellation_caches.0.get_mut(&entity).unwrap();
+        tessellation_cache.task = Some(SyncCell::new(receiver));
+        world.run_system_once(finish_tessellation_system);
+        let tessellation_caches = world.resource::<EguiTessellationCaches>();
+        assert!(!tessellation_caches.has_pending_tasks());
+        assert_eq!(tessellation_caches.0[&entity].options, None);
+    }
+
+    #[test]
+    fn test_widen_thin_strokes() {
+        let mut shape = egui::Shape::Vec(vec![
+            egui::Shape::line_segment(
+                [egui::pos2(0.0, 0.0), egui::pos2(10.0, 0.0)],
+                egui::Stroke::new(0.5, egui::Color32::WHITE),
+            ),
+            egui::Shape::rect_stroke(
+                egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(10.0, 10.0)),
+                0.0,
+                egui::Stroke::new(2.0, egui::Color32::WHITE),
+            ),
+            egui::Shape::circle_filled(egui::Pos2::ZERO, 5.0, egui::Color32::WHITE),
+        ]);
+        widen_thin_strokes(&mut shape, 1.0);
+
+        let egui::Shape::Vec(shapes) = shape else {
+            unreachable!()
+        };
+        let egui::Shape::LineSegment { stroke, .. } = &shapes[0] else {
+            unreachable!()
+        };
+        assert_eq!(stroke.width, 1.0);
+        let egui::Shape::Rect(rect) = &shapes[1] else {
+            unreachable!()
+        };
+        assert_eq!(rect.stroke.width, 2.0);
+        let egui::Shape::Circle(circle) = &shapes[2] else {
+            unreachable!()
+        };
+        // Shapes without a stroke stay without a stroke.
+        assert_eq!(circle.stroke.width, 0.0);
+    }
+
+    #[cfg(feature = "test_renderer")]
+    #[test]
+    fn test_min_stroke_width_renders_hairlines() {
+        use crate::{
+            test_renderer::{EguiTestRenderOutput, EguiTestRendererPlugin},
+            tests::test_app,
+            EguiContexts,
+        };
+
+        const LINES: [f32; 4] = [10.0, 20.25, 30.5, 40.75];
+
+        // Renders a grid of 0.5 point lines without feathering, and returns the non-transparent
+        // alpha values of the pixel rows around every line.
+        fn render_grid(scale_factor: f32, min_stroke_width_in_pixels: f32) -> Vec<Vec<u8>> {
+            let (mut app, _) = test_app();
+            app.add_plugins(EguiTestRendererPlugin).add_systems(
+                bevy::app::Update,
+                |mut contexts: EguiContexts| {
+                    let painter = contexts
+                        .ctx_mut()
+                        .layer_painter(egui::LayerId::background());
+                    for y in LINES {
+                        painter.hline(0.0..=100.0, y, egui::Stroke::new(0.5, egui::Color32::WHITE));
+                    }
+                },
+            );
+            {
+                let mut egui_settings = app.world_mut().resource_mut::<EguiSettings>();
+                egui_settings.scale_factor = scale_factor;
+                egui_settings.feathering = false;
+                egui_settings.min_stroke_width_in_pixels = min_stroke_width_in_pixels;
+            }
+            app.update();
+            app.update();
+
+            let mut outputs = app.world_mut().query::<&EguiTestRenderOutput>();
+            let image = &outputs.single(app.world()).image;
+            let x = (50.0 * scale_factor) as usize;
+            LINES
+                .iter()
+                .map(|y| {
+                    let row = (y * scale_factor) as usize;
+                    (row - 3..=row + 3)
+                        .map(|row| image[(x, row)].a())
+                        .filter(|&alpha| alpha > 0)
+                        .collect()
+                })
+                .collect()
+        }
+
+        // Every line covers exactly one opaque row of pixels.
+        for scale_factor in [1.0, 1.5] {
+            for rows in render_grid(scale_factor, 1.0) {
+                assert_eq!(rows, [255], "scale factor {scale_factor}");
+            }
+        }
+        // Without the minimum width, lines between pixel centers disappear.
+        assert!(render_grid(1.0, 0.0).iter().any(|rows| rows.is_empty()));
+    }
+
+    #[cfg(feature = "render")]
+    #[test]
+    fn test_color_image_from_screenshot() {
+        use bevy::render::{
+            render_asset::RenderAssetUsages,
+            render_resource::{Extent3d, TextureDimension, TextureFormat},
+            texture::Image,
+        };
+
+        let screenshot = |format| {
+            Image::new(
+                Extent3d {
+                    width: 2,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                vec![255, 0, 0, 255, 0, 0, 255, 255],
+                format,
+                RenderAssetUsages::default(),
+            )
+        };
+
+        let image =
+            color_image_from_screenshot(&screenshot(TextureFormat::Rgba8UnormSrgb)).unwrap();
+        assert_eq!(image.size, [2, 1]);
+        assert_eq!(image.pixels, [egui::Color32::RED, egui::Color32::BLUE]);
+        let image =
+            color_image_from_screenshot(&screenshot(TextureFormat::Bgra8UnormSrgb)).unwrap();
+        assert_eq!(image.pixels, [egui::Color32::BLUE, egui::Color32::RED]);
+        assert!(color_image_from_screenshot(&screenshot(TextureFormat::Rg16Float)).is_none());
+    }
+
+    #[test]
+    fn test_key_mapping() {
+        use bevy::input::keyboard::NativeKey;
+
+        let punctuation = [
+            (KeyCode::BracketLeft, "[", egui::Key::OpenBracket),
+            (KeyCode::BracketRight, "]", egui::Key::CloseBracket),
+            (KeyCode::Backquote, "`", egui::Key::Backtick),
+            (KeyCode::Minus, "-", egui::Key::Minus),
+            (KeyCode::Equal, "=", egui::Key::Equals),
+            (KeyCode::Comma, ",", egui::Key::Comma),
+            (KeyCode::Period, ".", egui::Key::Period),
+            (KeyCode::Semicolon, ";", egui::Key::Semicolon),
+            (KeyCode::Quote, "'", egui::Key::Quote),
+            (KeyCode::Backslash, "\\", egui::Key::Backslash),
+            (KeyCode::Slash, "/", egui::Key::Slash),
+        ];
+        for (key_code, character, egui_key) in punctuation {
+            assert_eq!(bevy_to_egui_physical_key(&key_code), Some(egui_key));
+            assert_eq!(
+                bevy_to_egui_key(&Key::Character(character.into())),
+                Some(egui_key)
+            );
+        }
+
+        let commands = [
+            (KeyCode::Cut, Key::Cut, egui::Key::Cut),
+            (KeyCode::Copy, Key::Copy, egui::Key::Copy),
+            (KeyCode::Paste, Key::Paste, egui::Key::Paste),
+            (KeyCode::F24, Key::F24, egui::Key::F24),
+        ];
+        for (key_code, key, egui_key) in commands {
+            assert_eq!(bevy_to_egui_physical_key(&key_code), Some(egui_key));
+            assert_eq!(bevy_to_egui_key(&key), Some(egui_key));
+        }
+
+        let numpad = [
+            (KeyCode::Numpad0, egui::Key::Num0),
+            (KeyCode::Numpad9, egui::Key::Num9),
+            (KeyCode::NumpadAdd, egui::Key::Plus),
+            (KeyCode::NumpadSubtract, egui::Key::Minus),
+            (KeyCode::NumpadDivide, egui::Key::Slash),
+            (KeyCode::NumpadDecimal, egui::Key::Period),
+            (KeyCode::NumpadComma, egui::Key::Comma),
+            (KeyCode::NumpadEqual, egui::Key::Equals),
+            (KeyCode::NumpadEnter, egui::Key::Enter),
+        ];
+        for (key_code, egui_key) in numpad {
+            assert_eq!(bevy_to_egui_physical_key(&key_code), Some(egui_key));
+        }
+
+        assert_eq!(
+            bevy_to_egui_key(&Key::Unidentified(NativeKey::Unidentified)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wrap_angle() {
+        use std::f32::consts::PI;
+        assert!((wrap_angle(1.5 * PI) + 0.5 * PI).abs() < 1e-6);
+        assert!((wrap_angle(-1.5 * PI) - 0.5 * PI).abs() < 1e-6);
+        assert!((wrap_angle(0.25) - 0.25).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::{EguiContext, EguiOutput, EguiPlugin, EguiRenderOutput, EguiWantsInput};
+use bevy::{
+    a11y::AccessibilityPlugin,
+    app::{App, PluginsState},
+    asset::AssetPlugin,
+    input::{
+        keyboard::{Key, KeyCode, KeyboardInput, NativeKeyCode},
+        mouse::{MouseButton, MouseButtonInput, MouseScrollUnit, MouseWheel},
+        ButtonState, InputPlugin,
+    },
+    math::Vec2,
+    prelude::{Entity, With},
+    time::TimeUpdateStrategy,
+    window::{CursorMoved, PrimaryWindow, WindowPlugin},
+    MinimalPlugins,
+};
+use std::time::Duration;
+
+/// The frame time of the app created by [`EguiTestHarness::new`].
+pub const TEST_FRAME_TIME: Duration = Duration::from_nanos(16_666_667);
+
+/// Runs an app with [`EguiPlugin`](struct@EguiPlugin) without a real window or a GPU, so that
+/// UI logic can be tested in CI.
+///
+/// Input is injected as Bevy events, which reach Egui during the next [`EguiTestHarness::update`],
+/// and the output of the last frame can be read from the context of the window
+/// ([`EguiTestHarness::ctx_mut`]) or from its components:
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{egui, test::EguiTestHarness, EguiContexts};
+///
+/// #[derive(Resource, Default)]
+/// struct Clicks(u32);
+///
+/// fn ui_system(mut contexts: EguiContexts, mut clicks: ResMut<Clicks>) {
+///     egui::Area::new("area".into())
+///         .fixed_pos(egui::pos2(100.0, 100.0))
+///         .show(contexts.ctx_mut(), |ui| {
+///             if ui.button("Click me").clicked() {
+///                 clicks.0 += 1;
+///             }
+///         });
+/// }
+///
+/// let mut harness = EguiTestHarness::new();
+/// harness
+///     .app_mut()
+///     .init_resource::<Clicks>()
+///     .add_systems(Update, ui_system);
+/// // Areas are invisible during the first frame, while their size is measured.
+/// harness.run_frames(2);
+///
+/// harness.click_at(Vec2::new(110.0, 105.0));
+/// assert_eq!(harness.app().world().resource::<Clicks>().0, 1);
+/// ```
+///
+/// The app created by [`EguiTestHarness::new`] advances time by [`TEST_FRAME_TIME`] every frame,
+/// so animations and double clicks don't depend on how fast the test runs.
+pub struct EguiTestHarness {
+    app: App,
+    window: Entity,
+}
+
+impl Default for EguiTestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EguiTestHarness {
+    /// Creates a harness for a headless app with [`EguiPlugin`](struct@EguiPlugin) and a primary
+    /// window.
+    ///
+    /// The app has [`MinimalPlugins`], [`AssetPlugin`], [`WindowPlugin`], [`InputPlugin`] and
+    /// [`AccessibilityPlugin`]. Nothing is rendered, but the paint jobs are still tessellated.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            WindowPlugin::default(),
+            InputPlugin,
+            AccessibilityPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TEST_FRAME_TIME));
+        // Added by `RenderPlugin` and `ImagePlugin`, which need a GPU.
+        #[cfg(feature = "render")]
+        {
+            use bevy::asset::AssetApp;
+            app.init_asset::<bevy::render::render_resource::Shader>()
+                .init_asset::<bevy::render::texture::Image>();
+        }
+        app.add_plugins(EguiPlugin);
+        Self::from_app(app)
+    }
+
+    /// Creates a harness for an app that already has [`EguiPlugin`](struct@EguiPlugin) and a
+    /// primary window.
+    ///
+    /// The plugins of the app are finished and its first frame is run, which creates the Egui
+    /// contexts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the app doesn't have a primary window.
+    #[must_use]
+    pub fn from_app(mut app: App) -> Self {
+        if app.plugins_state() == PluginsState::Ready {
+            app.finish();
+            app.cleanup();
+        }
+        let window = app
+            .world_mut()
+            .query_filtered::<Entity, With<PrimaryWindow>>()
+            .get_single(app.world())
+            .expect("the app of an `EguiTestHarness` must have a primary window");
+        app.update();
+        Self { app, window }
+    }
+
+    /// The app run by the harness.
+    #[must_use]
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// The app run by the harness, for adding systems and resources or sending other events.
+    #[must_use]
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// The primary window, which input is sent to.
+    #[must_use]
+    pub fn window(&self) -> Entity {
+        self.window
+    }
+
+    /// Runs a frame, which passes the input sent since the previous frame to Egui.
+    pub fn update(&mut self) -> &mut Self {
+        self.app.update();
+        self
+    }
+
+    /// Runs several frames.
+    pub fn run_frames(&mut self, frames: usize) -> &mut Self {
+        for _ in 0..frames {
+            self.app.update();
+        }
+        self
+    }
+
+    /// Moves the pointer to a position in logical pixels.
+    pub fn move_pointer(&mut self, position: Vec2) -> &mut Self {
+        self.app.world_mut().send_event(CursorMoved {
+            window: self.window,
+            position,
+            delta: None,
+        });
+        self
+    }
+
+    /// Presses a mouse button at the current pointer position.
+    pub fn press_button(&mut self, button: MouseButton) -> &mut Self {
+        self.send_button(button, ButtonState::Pressed)
+    }
+
+    /// Releases a mouse button at the current pointer position.
+    pub fn release_button(&mut self, button: MouseButton) -> &mut Self {
+        self.send_button(button, ButtonState::Released)
+    }
+
+    /// Moves the pointer to a position and clicks the left mouse button there.
+    ///
+    /// Unlike the other input methods, this one runs frames: one after moving the pointer, one
+    /// after pressing the button and one after releasing it, as a user would.
+    pub fn click_at(&mut self, position: Vec2) -> &mut Self {
+        self.move_pointer(position).update();
+        self.press_button(MouseButton::Left).update();
+        self.release_button(MouseButton::Left).update()
+    }
+
+    /// Scrolls the mouse wheel by a number of lines.
+    pub fn scroll(&mut self, lines: Vec2) -> &mut Self {
+        self.app.world_mut().send_event(MouseWheel {
+            unit: MouseScrollUnit::Line,
+            x: lines.x,
+            y: lines.y,
+            window: self.window,
+        });
+        self
+    }
+
+    /// Presses a key, without releasing it.
+    ///
+    /// Modifiers are pressed like any other key, for example with `KeyCode::ControlLeft` and
+    /// `Key::Control`.
+    pub fn press_key(&mut self, key_code: KeyCode, logical_key: Key) -> &mut Self {
+        self.send_key(key_code, logical_key, ButtonState::Pressed)
+    }
+
+    /// Releases a key.
+    pub fn release_key(&mut self, key_code: KeyCode, logical_key: Key) -> &mut Self {
+        self.send_key(key_code, logical_key, ButtonState::Released)
+    }
+
+    /// Types text into the focused widget, by pressing and releasing a key for every character.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for char in text.chars() {
+            let key = Key::Character(char.to_string().into());
+            let key_code = KeyCode::Unidentified(NativeKeyCode::Unidentified);
+            self.press_key(key_code, key.clone())
+                .release_key(key_code, key);
+        }
+        self
+    }
+
+    /// The Egui context of the window, for reading its memory (which widget has focus, for
+    /// example) or the rects of the last frame.
+    #[must_use]
+    pub fn ctx_mut(&mut self) -> &mut egui::Context {
+        self.app
+            .world_mut()
+            .get_mut::<EguiContext>(self.window)
+            .expect("the window of an `EguiTestHarness` must have an Egui context")
+            .into_inner()
+            .get_mut()
+    }
+
+    /// The platform output of the last frame of the window context.
+    #[must_use]
+    pub fn platform_output(&self) -> &egui::PlatformOutput {
+        &self
+            .app
+            .world()
+            .get::<EguiOutput>(self.window)
+            .expect("the window of an `EguiTestHarness` must have an Egui context")
+            .platform_output
+    }
+
+    /// The paint jobs and textures delta of the last frame of the window context.
+    #[must_use]
+    pub fn render_output(&self) -> &EguiRenderOutput {
+        self.app
+            .world()
+            .get::<EguiRenderOutput>(self.window)
+            .expect("the window of an `EguiTestHarness` must have an Egui context")
+    }
+
+    /// Whether Egui wanted input during the last frame.
+    #[must_use]
+    pub fn wants_input(&self) -> EguiWantsInput {
+        *self.app.world().resource::<EguiWantsInput>()
+    }
+
+    /// Makes Egui output the accessibility tree, as if an assistive technology was active.
+    ///
+    /// The tree is output starting from the next frame, see [`EguiTestHarness::accesskit_update`].
+    #[cfg(feature = "accesskit")]
+    pub fn enable_accesskit(&mut self) -> &mut Self {
+        self.app
+            .world()
+            .resource::<bevy::a11y::AccessibilityRequested>()
+            .set(true);
+        self
+    }
+
+    /// The accessibility tree of the last frame, if [`EguiTestHarness::enable_accesskit`] was
+    /// called.
+    #[cfg(feature = "accesskit")]
+    #[must_use]
+    pub fn accesskit_update(&self) -> Option<&egui::accesskit::TreeUpdate> {
+        self.platform_output().accesskit_update.as_ref()
+    }
+
+    fn send_button(&mut self, button: MouseButton, state: ButtonState) -> &mut Self {
+        self.app.world_mut().send_event(MouseButtonInput {
+            button,
+            state,
+            window: self.window,
+        });
+        self
+    }
+
+    fn send_key(&mut self, key_code: KeyCode, logical_key: Key, state: ButtonState) -> &mut Self {
+        self.app.world_mut().send_event(KeyboardInput {
+            key_code,
+            logical_key,
+            state,
+            window: self.window,
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EguiContexts;
+    use bevy::prelude::{ResMut, Resource};
+
+    #[test]
+    fn test_harness_types_into_focused_text_edit() {
+        #[derive(Resource, Default)]
+        struct Text {
+            value: String,
+            id: Option<egui::Id>,
+        }
+
+        fn ui_system(mut contexts: EguiContexts, mut text: ResMut<Text>) {
+            egui::Area::new("text".into())
+                .fixed_pos(egui::pos2(100.0, 100.0))
+                .show(contexts.ctx_mut(), |ui| {
+                    let text = &mut *text;
+                    text.id = Some(ui.text_edit_singleline(&mut text.value).id);
+                });
+        }
+
+        let mut harness = EguiTestHarness::new();
+        harness
+            .app_mut()
+            .init_resource::<Text>()
+            .add_systems(bevy::app::Update, ui_system);
+        harness.run_frames(2);
+        let id = harness.app().world().resource::<Text>().id.unwrap();
+        assert!(!harness.ctx_mut().memory(|memory| memory.has_focus(id)));
+
+        harness.click_at(bevy::math::Vec2::new(110.0, 105.0));
+        assert!(harness.ctx_mut().memory(|memory| memory.has_focus(id)));
+        assert!(harness.wants_input().wants_keyboard_input);
+
+        harness.type_text("hi!").update();
+        harness
+            .press_key(KeyCode::Backspace, Key::Backspace)
+            .release_key(KeyCode::Backspace, Key::Backspace)
+            .update();
+        assert_eq!(harness.app().world().resource::<Text>().value, "hi");
+        assert!(!harness.render_output().paint_jobs.is_empty());
+
+        // Clicking elsewhere removes the focus.
+        harness.click_at(bevy::math::Vec2::new(600.0, 400.0));
+        assert!(!harness.ctx_mut().memory(|memory| memory.has_focus(id)));
+    }
+}
@@ -0,0 +1,370 @@
+use crate::{EguiContext, EguiContextSettings, EguiRenderOutput, EguiSet};
+use bevy::{
+    app::{App, Plugin, PostUpdate},
+    prelude::{Commands, Component, Entity, IntoSystemConfigs, Query},
+    utils::HashMap,
+};
+
+/// Adds [`EguiTestRenderOutput`] to every Egui context, which stores the paint jobs of the
+/// last frame and the image they render to.
+///
+/// The image is rasterized on the CPU, so the plugin also works in headless apps without a GPU
+/// (or without the `render` feature). It's meant to be used in tests that check what a UI draws:
+///
+/// ```no_run,rust
+/// use bevy::prelude::*;
+/// use bevy_egui::{
+///     egui,
+///     test_renderer::{EguiTestRenderOutput, EguiTestRendererPlugin},
+///     EguiPlugin,
+/// };
+///
+/// fn assert_background(outputs: Query<&EguiTestRenderOutput>) {
+///     for output in outputs.iter() {
+///         assert_eq!(
+///             output.pixel_at(egui::pos2(10.0, 10.0)),
+///             Some(egui::Color32::from_gray(27)),
+///         );
+///     }
+/// }
+///
+/// App::new()
+///     .add_plugins(MinimalPlugins)
+///     .add_plugins((EguiPlugin, EguiTestRendererPlugin))
+///     .add_systems(Last, assert_background);
+/// ```
+///
+/// The rasterizer samples textures with the nearest filter and blends in gamma space, so the
+/// pixels are close to, but not exactly the same as, what the GPU renders.
+/// User textures aren't available to it and are drawn as if they were white.
+pub struct EguiTestRendererPlugin;
+
+impl Plugin for EguiTestRendererPlugin {
+    fn build(&self, app: &mut App) {
+        // Textures deltas are taken from the render output by the texture systems.
+        #[cfg(feature = "render")]
+        app.add_systems(
+            PostUpdate,
+            test_render_system
+                .after(EguiSet::EndPass)
+                .before(crate::update_egui_textures_system),
+        );
+        #[cfg(not(feature = "render"))]
+        app.add_systems(
+            PostUpdate,
+            (test_render_system, clear_textures_delta_system)
+                .chain()
+                .after(EguiSet::EndPass),
+        );
+    }
+}
+
+/// The last frame of an Egui context, rendered by [`EguiTestRendererPlugin`].
+#[derive(Component, Clone, Default)]
+pub struct EguiTestRenderOutput {
+    /// A copy of [`EguiRenderOutput::paint_jobs`].
+    pub paint_jobs: Vec<egui::ClippedPrimitive>,
+    /// The rendered frame, in physical pixels, with premultiplied alpha.
+    ///
+    /// Pixels that nothing was painted to are transparent.
+    pub image: egui::ColorImage,
+    /// The scale factor the frame was tessellated with.
+    pub pixels_per_point: f32,
+    textures: HashMap<egui::TextureId, egui::ColorImage>,
+}
+
+impl EguiTestRenderOutput {
+    /// Returns the rendered color at a position in logical pixels (Egui points), or `None` if
+    /// the position is outside the image.
+    pub fn pixel_at(&self, pos: egui::Pos2) -> Option<egui::Color32> {
+        let [width, height] = self.image.size;
+        let pos = pos * self.pixels_per_point;
+        if pos.x < 0.0 || pos.y < 0.0 {
+            return None;
+        }
+        let (x, y) = (pos.x as usize, pos.y as usize);
+        (x < width && y < height).then(|| self.image[(x, y)])
+    }
+
+    /// Returns the meshes that have a visible triangle covering a position in logical pixels.
+    pub fn meshes_at(&self, pos: egui::Pos2) -> impl Iterator<Item = &egui::epaint::Mesh> {
+        self.paint_jobs.iter().filter_map(move |paint_job| {
+            let egui::epaint::Primitive::Mesh(mesh) = &paint_job.primitive else {
+                return None;
+            };
+            if !paint_job.clip_rect.contains(pos) {
+                return None;
+            }
+            mesh.indices
+                .chunks_exact(3)
+                .any(|triangle| {
+                    let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize].pos);
+                    triangle_contains(a, b, c, pos)
+                })
+                .then_some(mesh)
+        })
+    }
+
+    pub(crate) fn update_textures(&mut self, textures_delta: &egui::TexturesDelta) {
+        for (texture_id, image_delta) in &textures_delta.set {
+            let image = as_color_image(&image_delta.image);
+            match image_delta.pos {
+                Some([x, y]) => {
+                    let Some(texture) = self.textures.get_mut(texture_id) else {
+                        continue;
+                    };
+                    for sy in 0..image.height() {
+                        for sx in 0..image.width() {
+                            texture[(x + sx, y + sy)] = image[(sx, sy)];
+                        }
+                    }
+                }
+                None => {
+                    self.textures.insert(*texture_id, image);
+                }
+            }
+        }
+    }
+
+    fn free_textures(&mut self, textures_delta: &egui::TexturesDelta) {
+        for texture_id in &textures_delta.free {
+            self.textures.remove(texture_id);
+        }
+    }
+
+    pub(crate) fn render(&mut self, screen_rect: egui::Rect, tint: egui::Rgba) {
+        // Blending happens in gamma space, so the tint is encoded the same way as vertex colors.
+        let [r, g, b, a] = tint
+            .to_srgba_unmultiplied()
+            .map(|value| value as f32 / 255.0);
+        let tint = [r * a, g * a, b * a, a];
+        let size = screen_rect.size() * self.pixels_per_point;
+        let (width, height) = (size.x.round() as usize, size.y.round() as usize);
+        let mut pixels = vec![[0.0; 4]; width * height];
+
+        for paint_job in &self.paint_jobs {
+            let egui::epaint::Primitive::Mesh(mesh) = &paint_job.primitive else {
+                continue;
+            };
+            let texture = self.textures.get(&mesh.texture_id);
+            let clip_min = (paint_job.clip_rect.min * self.pixels_per_point).round();
+            let clip_max = (paint_job.clip_rect.max * self.pixels_per_point).round();
+            let (clip_min_x, clip_min_y) =
+                (clip_min.x.max(0.0) as usize, clip_min.y.max(0.0) as usize);
+            let (clip_max_x, clip_max_y) = (
+                (clip_max.x.max(0.0) as usize).min(width),
+                (clip_max.y.max(0.0) as usize).min(height),
+            );
+
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| {
+                    let mut vertex = mesh.vertices[triangle[i] as usize];
+                    vertex.pos = vertex.pos * self.pixels_per_point;
+                    vertex
+                });
+                let area = edge(a.pos, b.pos, c.pos);
+                if area == 0.0 {
+                    continue;
+                }
+                // Makes the winding order consistent for the top-left fill rule.
+                let (b, c, area) = if area < 0.0 {
+                    (c, b, -area)
+                } else {
+                    (b, c, area)
+                };
+
+                let min = a.pos.min(b.pos).min(c.pos).floor();
+                let max = a.pos.max(b.pos).max(c.pos).ceil();
+                let (min_x, min_y) = (
+                    (min.x.max(0.0) as usize).max(clip_min_x),
+                    (min.y.max(0.0) as usize).max(clip_min_y),
+                );
+                let (max_x, max_y) = (
+                    (max.x.max(0.0) as usize).min(clip_max_x),
+                    (max.y.max(0.0) as usize).min(clip_max_y),
+                );
+
+                for y in min_y..max_y {
+                    for x in min_x..max_x {
+                        let p = egui::pos2(x as f32 + 0.5, y as f32 + 0.5);
+                        let weights = [(b, c), (c, a), (a, b)]
+                            .map(|(from, to)| (edge(from.pos, to.pos, p), from.pos, to.pos));
+                        if !weights.iter().all(|&(weight, from, to)| {
+                            weight > 0.0 || (weight == 0.0 && is_top_left(from, to))
+                        }) {
+                            continue;
+                        }
+                        let [wa, wb, wc] = weights.map(|(weight, _, _)| weight / area);
+
+                        let uv = a.uv.to_vec2() * wa + b.uv.to_vec2() * wb + c.uv.to_vec2() * wc;
+                        let texel = texture.map_or(egui::Color32::WHITE, |texture| {
+                            let tx =
+                                ((uv.x * texture.width() as f32) as usize).min(texture.width() - 1);
+                            let ty = ((uv.y * texture.height() as f32) as usize)
+                                .min(texture.height() - 1);
+                            texture[(tx, ty)]
+                        });
+                        let color: [f32; 4] = std::array::from_fn(|i| {
+                            let vertex_color = a.color[i] as f32 * wa
+                                + b.color[i] as f32 * wb
+                                + c.color[i] as f32 * wc;
+                            vertex_color / 255.0 * (texel[i] as f32 / 255.0) * tint[i]
+                        });
+
+                        // Premultiplied "over" blending, like the Egui pipeline does.
+                        let dest = &mut pixels[y * width + x];
+                        for i in 0..4 {
+                            dest[i] = color[i] + dest[i] * (1.0 - color[3]);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.image = egui::ColorImage {
+            size: [width, height],
+            pixels: pixels
+                .into_iter()
+                .map(|[r, g, b, a]| {
+                    let to_u8 = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    egui::Color32::from_rgba_premultiplied(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+                })
+                .collect(),
+        };
+    }
+}
+
+/// Renders the paint jobs of each context to its [`EguiTestRenderOutput`].
+pub fn test_render_system(
+    mut commands: Commands,
+    mut contexts: Query<(
+        Entity,
+        &mut EguiContext,
+        &EguiRenderOutput,
+        &EguiContextSettings,
+        Option<&mut EguiTestRenderOutput>,
+    )>,
+) {
+    for (entity, mut context, render_output, context_settings, test_render_output) in
+        contexts.iter_mut()
+    {
+        let mut inserted = None;
+        let test_render_output = match test_render_output {
+            Some(test_render_output) => test_render_output.into_inner(),
+            None => inserted.insert(EguiTestRenderOutput::default()),
+        };
+
+        let ctx = context.get_mut();
+        test_render_output.update_textures(&render_output.textures_delta);
+        test_render_output.paint_jobs = render_output.paint_jobs.clone();
+        test_render_output.pixels_per_point = ctx.pixels_per_point();
+        test_render_output.render(ctx.screen_rect(), context_settings.tint);
+        test_render_output.free_textures(&render_output.textures_delta);
+
+        if let Some(test_render_output) = inserted {
+            commands.entity(entity).insert(test_render_output);
+        }
+    }
+}
+
+// Without the render systems, nothing else consumes the textures delta.
+#[cfg(not(feature = "render"))]
+fn clear_textures_delta_system(mut render_outputs: Query<&mut EguiRenderOutput>) {
+    for mut render_output in render_outputs.iter_mut() {
+        render_output.textures_delta.clear();
+    }
+}
+
+fn as_color_image(image: &egui::ImageData) -> egui::ColorImage {
+    match image {
+        egui::ImageData::Color(image) => (**image).clone(),
+        egui::ImageData::Font(image) => egui::ColorImage {
+            size: image.size,
+            pixels: image.srgba_pixels(None).collect(),
+        },
+    }
+}
+
+/// Twice the signed area of the triangle `(from, to, p)`, positive if `p` is on the right side
+/// of the edge (with the Y axis pointing down).
+fn edge(from: egui::Pos2, to: egui::Pos2, p: egui::Pos2) -> f32 {
+    (to.x - from.x) * (p.y - from.y) - (to.y - from.y) * (p.x - from.x)
+}
+
+/// Pixels exactly on an edge are only filled if it's a top or a left one, so that the pixels
+/// shared by adjacent triangles aren't blended twice.
+fn is_top_left(from: egui::Pos2, to: egui::Pos2) -> bool {
+    let d = to - from;
+    (d.y == 0.0 && d.x > 0.0) || d.y < 0.0
+}
+
+fn triangle_contains(a: egui::Pos2, b: egui::Pos2, c: egui::Pos2, p: egui::Pos2) -> bool {
+    let weights = [edge(a, b, p), edge(b, c, p), edge(c, a, p)];
+    weights.iter().all(|&weight| weight >= 0.0) || weights.iter().all(|&weight| weight <= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::test_app, EguiContexts};
+    use bevy::prelude::{ResMut, Resource};
+
+    #[test]
+    fn test_renderer_draws_button_background() {
+        #[derive(Resource, Default)]
+        struct ButtonRect(Option<egui::Rect>);
+
+        fn ui_system(mut contexts: EguiContexts, mut button_rect: ResMut<ButtonRect>) {
+            egui::Area::new("button".into())
+                .fixed_pos(egui::pos2(100.0, 100.0))
+                .show(contexts.ctx_mut(), |ui| {
+                    button_rect.0 = Some(ui.add_sized([120.0, 40.0], egui::Button::new("Hi")).rect);
+                });
+        }
+
+        let (mut app, window) = test_app();
+        app.add_plugins(EguiTestRendererPlugin)
+            .init_resource::<ButtonRect>()
+            .add_systems(bevy::app::Update, ui_system);
+        // Areas are invisible during the first frame, while their size is measured.
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let button_rect = app.world().resource::<ButtonRect>().0.unwrap();
+        let mut outputs = app
+            .world_mut()
+            .query::<(&EguiContext, &EguiTestRenderOutput)>();
+        let (context, output) = outputs.single(app.world());
+        let fill = context.ctx.style().visuals.widgets.inactive.weak_bg_fill;
+
+        // Away from the rounded corners and the label.
+        let background_pos = button_rect.left_center() + egui::vec2(8.0, 0.0);
+        assert_eq!(output.pixel_at(background_pos), Some(fill));
+        assert_eq!(output.meshes_at(background_pos).count(), 1);
+        assert_eq!(
+            output.pixel_at(button_rect.left_top() - egui::vec2(8.0, 8.0)),
+            Some(egui::Color32::TRANSPARENT)
+        );
+        assert!(output.meshes_at(egui::pos2(10.0, 10.0)).next().is_none());
+
+        // The tint fades the whole context, with premultiplied alpha.
+        app.world_mut()
+            .get_mut::<EguiContextSettings>(window)
+            .unwrap()
+            .tint = egui::Rgba::from_white_alpha(0.5);
+        app.update();
+        let mut outputs = app.world_mut().query::<&EguiTestRenderOutput>();
+        let pixel = outputs
+            .single(app.world())
+            .pixel_at(background_pos)
+            .unwrap();
+        let expected = fill.gamma_multiply(0.5);
+        for i in 0..4 {
+            assert!(
+                pixel[i].abs_diff(expected[i]) <= 1,
+                "{pixel:?} != {expected:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,753 @@
+use crate::{
+    EguiAutocapitalize, EguiContextSettings, EguiEnterKeyHint, EguiInput, EguiInputMode,
+    EguiOutput, EguiTextInputHints, EguiVirtualKeyboard,
+};
+use bevy::{
+    log,
+    math::Rect,
+    prelude::{Entity, Has, Local, NonSend, NonSendMut, Query, ResMut, With},
+    utils::HashMap,
+    window::{PrimaryWindow, Window},
+};
+use crossbeam_channel::{Receiver, Sender};
+use std::{cell::RefCell, ops::Range, rc::Rc};
+use wasm_bindgen::prelude::*;
+
+/// Id of the text agent element.
+pub(crate) const TEXT_AGENT_ID: &str = "bevy_egui_text_agent";
+
+// The event listeners of the agent, by event name.
+type EventClosures = Vec<(&'static str, Closure<dyn FnMut(web_sys::Event)>)>;
+
+/// A hidden `<textarea>` element that receives the text typed into Egui text fields on web.
+///
+/// Browsers only start IME composition (typing CJK text, for example) in editable elements, so
+/// the agent is focused while a text field has focus (see [`update_text_agent_system`]). Its
+/// `compositionstart`, `compositionupdate` and `compositionend` events are sent as
+/// [`egui::Event::Ime`], which draws the composition inside the text field, and it's placed at
+/// the text cursor (also when the canvas is scaled with CSS), so the candidate window opens next
+/// to it. The canvas doesn't receive key
+/// events while the agent has focus, so the agent also sends the keys and the text that are
+/// typed without composition.
+///
+/// The `inputmode`, `autocapitalize`, `autocorrect` and `enterkeyhint` attributes of the agent
+/// follow the [`EguiTextInputHints`] of the focused text field, so mobile browsers show a matching
+/// keyboard. The agent also contains the text and the selection of the field (see
+/// [`EguiOutput::focused_text`]), which lets mobile keyboards suggest words and correct them.
+/// The changes keyboards make to it are sent as the key and text events that make the same
+/// changes in the field.
+#[derive(Default)]
+pub struct TextAgent {
+    input: Option<web_sys::HtmlTextAreaElement>,
+    hints: Option<EguiTextInputHints>,
+    // Shared with the event listeners, which compare the text of the agent with it.
+    value: Rc<RefCell<AgentValue>>,
+    // The canvas of the primary window.
+    canvas: Option<web_sys::HtmlElement>,
+    // The canvases of the other windows, `None` if they can't be found.
+    canvases: HashMap<Entity, Option<web_sys::HtmlElement>>,
+    // The window that the agent types into, the primary one if `None`.
+    target: Option<Entity>,
+    event_closures: EventClosures,
+    event_receiver: Option<Receiver<egui::Event>>,
+}
+
+impl TextAgent {
+    /// Removes the element and its event listeners, this can be useful for gracefully destroying
+    /// a Bevy instance in a page.
+    pub fn remove(&mut self) {
+        let Some(input) = self.input.take() else {
+            return;
+        };
+        for (event_name, closure) in std::mem::take(&mut self.event_closures) {
+            if let Err(err) = input
+                .remove_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+            {
+                log::error!("Failed to remove the \"{event_name}\" event listener: {err:?}");
+            }
+        }
+        input.remove();
+        self.hints = None;
+        *self.value.borrow_mut() = AgentValue::default();
+        self.canvas = None;
+        self.canvases.clear();
+        self.target = None;
+        self.event_receiver = None;
+    }
+
+    fn create(&mut self, canvas_selector: Option<&str>) -> Result<(), JsValue> {
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(|| JsValue::from_str("no document object"))?;
+        // Without a selector, Bevy appends its canvas to the page.
+        self.canvas =
+            crate::web_dom::query_selector_deep(&document, canvas_selector.unwrap_or("canvas"))?
+                .and_then(|canvas| canvas.dyn_into().ok());
+
+        // A text area, unlike an `<input>`, accepts line breaks typed on mobile keyboards.
+        let input: web_sys::HtmlTextAreaElement =
+            document.create_element("textarea")?.dyn_into()?;
+        input.set_id(TEXT_AGENT_ID);
+        input.set_attribute("autocomplete", "off")?;
+        set_hints(&input, EguiTextInputHints::default())?;
+        self.hints = Some(EguiTextInputHints::default());
+        let style = input.style();
+        for (property, value) in [
+            ("position", "fixed"),
+            ("left", "0px"),
+            ("top", "0px"),
+            ("width", "1px"),
+            ("height", "1px"),
+            ("padding", "0"),
+            ("border", "none"),
+            ("resize", "none"),
+            ("overflow", "hidden"),
+            ("outline", "none"),
+            ("opacity", "0"),
+            ("caret-color", "transparent"),
+            ("pointer-events", "none"),
+            // Smaller fonts make mobile browsers zoom in when the input is focused.
+            ("font-size", "16px"),
+        ] {
+            style.set_property(property, value)?;
+        }
+        document
+            .body()
+            .ok_or_else(|| JsValue::from_str("no body element"))?
+            .append_child(&input)?;
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.event_receiver = Some(rx);
+        self.input = Some(input.clone());
+        let is_macos = web_sys::window()
+            .and_then(|window| window.navigator().user_agent().ok())
+            .is_some_and(|user_agent| user_agent.to_ascii_lowercase().contains("mac"));
+
+        // The agent text when the composition started.
+        let composition_start = Rc::new(RefCell::new(AgentValue::default()));
+        self.subscribe("compositionstart", tx.clone(), {
+            let value = self.value.clone();
+            let composition_start = composition_start.clone();
+            move |_event, tx| {
+                let mut value = value.borrow_mut();
+                value.composing = true;
+                *composition_start.borrow_mut() = value.clone();
+                send(tx, egui::Event::Ime(egui::ImeEvent::Enabled));
+            }
+        })?;
+        self.subscribe("compositionupdate", tx.clone(), |event, tx| {
+            if let Some(data) = event
+                .dyn_ref::<web_sys::CompositionEvent>()
+                .and_then(|event| event.data())
+            {
+                send(tx, egui::Event::Ime(egui::ImeEvent::Preedit(data)));
+            }
+        })?;
+        self.subscribe("compositionend", tx.clone(), {
+            let input = input.clone();
+            let value = self.value.clone();
+            move |event, tx| {
+                // The text is committed with this event, not with the following `input` one,
+                // which finds the agent up to date.
+                let mut value = value.borrow_mut();
+                *value = AgentValue::read(&input);
+                let start = composition_start.borrow();
+                let (replaced, _) = text_change(&start.text, start.selection.clone(), &value.text);
+                if replaced == start.selection {
+                    if let Some(data) = event
+                        .dyn_ref::<web_sys::CompositionEvent>()
+                        .and_then(|event| event.data())
+                    {
+                        send(tx, egui::Event::Ime(egui::ImeEvent::Commit(data)));
+                    }
+                    return;
+                }
+                // Keyboards may compose a word that was already typed (to correct it), Egui only
+                // inserts compositions at the cursor. The composition is cleared instead, which
+                // leaves the field as it was when the composition started, apart from the
+                // selection, and the changes it made are typed.
+                send(tx, egui::Event::Ime(egui::ImeEvent::Preedit(String::new())));
+                send(tx, egui::Event::Ime(egui::ImeEvent::Disabled));
+                let mut previous: Vec<char> = start.text.chars().collect();
+                previous.drain(start.selection.clone());
+                let previous: String = previous.into_iter().collect();
+                let cursor = start.selection.start..start.selection.start;
+                let (replaced, inserted) = text_change(&previous, cursor.clone(), &value.text);
+                send_edit(tx, cursor, replaced, inserted);
+            }
+        })?;
+        self.subscribe("input", tx.clone(), {
+            let input = input.clone();
+            let value = self.value.clone();
+            move |event, tx| {
+                let Some(event) = event.dyn_ref::<web_sys::InputEvent>() else {
+                    return;
+                };
+                if event.is_composing() {
+                    return;
+                }
+                let mut value = value.borrow_mut();
+                let previous = std::mem::replace(&mut *value, AgentValue::read(&input));
+                let input_type = event.input_type();
+                // Egui receives pasted and cut text with the clipboard events, and undoes edits
+                // with the keyboard shortcuts.
+                if matches!(input_type.as_str(), "historyUndo" | "historyRedo")
+                    || cfg!(all(feature = "manage_clipboard", web_sys_unstable_apis))
+                        && matches!(input_type.as_str(), "insertFromPaste" | "deleteByCut")
+                {
+                    return;
+                }
+                // Mobile keyboards don't send key events for Enter, only the inserted line break.
+                if matches!(input_type.as_str(), "insertLineBreak" | "insertParagraph") {
+                    for pressed in [true, false] {
+                        send(
+                            tx,
+                            egui::Event::Key {
+                                key: egui::Key::Enter,
+                                physical_key: None,
+                                pressed,
+                                repeat: false,
+                                modifiers: egui::Modifiers::NONE,
+                            },
+                        );
+                    }
+                } else {
+                    let (replaced, inserted) =
+                        text_change(&previous.text, previous.selection.clone(), &value.text);
+                    send_edit(tx, previous.selection, replaced, inserted);
+                }
+            }
+        })?;
+        for (event_name, pressed) in [("keydown", true), ("keyup", false)] {
+            self.subscribe(event_name, tx.clone(), move |event, tx| {
+                let Some(event) = event.dyn_ref::<web_sys::KeyboardEvent>() else {
+                    return;
+                };
+                // Keys pressed during composition are handled by the IME.
+                if event.is_composing() || event.key_code() == 229 {
+                    return;
+                }
+                // Egui moves the focus between its widgets with Tab, and inserts line breaks and
+                // deletes text itself, the browser shouldn't move the focus out of the agent or
+                // edit it (the agent gets the text of the field afterwards).
+                if pressed
+                    && matches!(
+                        event.key().as_str(),
+                        "Tab" | "Enter" | "Backspace" | "Delete"
+                    )
+                {
+                    event.prevent_default();
+                }
+                let physical_key = physical_key_from_code(&event.code());
+                let Some(key) = egui::Key::from_name(&event.key()).or(physical_key) else {
+                    return;
+                };
+                let command = if is_macos {
+                    event.meta_key()
+                } else {
+                    event.ctrl_key()
+                };
+                send(
+                    tx,
+                    egui::Event::Key {
+                        key,
+                        physical_key,
+                        pressed,
+                        repeat: pressed && event.repeat(),
+                        modifiers: egui::Modifiers {
+                            alt: event.alt_key(),
+                            ctrl: event.ctrl_key(),
+                            shift: event.shift_key(),
+                            mac_cmd: is_macos && event.meta_key(),
+                            command,
+                        },
+                    },
+                );
+            })?;
+        }
+        Ok(())
+    }
+
+    fn subscribe(
+        &mut self,
+        event_name: &'static str,
+        tx: Sender<egui::Event>,
+        mut callback: impl FnMut(&web_sys::Event, &Sender<egui::Event>) + 'static,
+    ) -> Result<(), JsValue> {
+        let Some(input) = &self.input else {
+            return Ok(());
+        };
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::Event| {
+            callback(&event, &tx);
+        });
+        input.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())?;
+        self.event_closures.push((event_name, closure));
+        Ok(())
+    }
+
+    fn window_canvas(
+        &mut self,
+        entity: Entity,
+        window: &Window,
+        document: Option<&web_sys::Document>,
+    ) -> Option<web_sys::HtmlElement> {
+        self.canvases
+            .entry(entity)
+            .or_insert_with(|| {
+                let selector = window.canvas.as_deref()?;
+                crate::web_dom::query_selector_deep(document?, selector)
+                    .ok()
+                    .flatten()
+                    .and_then(|canvas| canvas.dyn_into().ok())
+            })
+            .clone()
+    }
+
+    fn is_focused(&self) -> bool {
+        web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.active_element())
+            .is_some_and(|element| element.id() == TEXT_AGENT_ID)
+    }
+
+    /// Sets the text and the selection of the agent, unless they are already set or the agent is
+    /// composing text.
+    fn set_value(&self, input: &web_sys::HtmlTextAreaElement, text: &str, selection: Range<usize>) {
+        let mut value = self.value.borrow_mut();
+        if value.composing || value.text == text && value.selection == selection {
+            return;
+        }
+        if value.text != text {
+            input.set_value(text);
+        }
+        let start = utf16_offset(text, selection.start);
+        let end = utf16_offset(text, selection.end);
+        if let Err(err) = input.set_selection_range(start, end) {
+            log::error!("Failed to set the selection of the text agent: {err:?}");
+        }
+        *value = AgentValue {
+            text: text.to_owned(),
+            selection,
+            composing: false,
+        };
+    }
+}
+
+/// The text and the selection of the agent, as they were after the last change.
+#[derive(Clone, Default)]
+struct AgentValue {
+    text: String,
+    // Selected chars.
+    selection: Range<usize>,
+    composing: bool,
+}
+
+impl AgentValue {
+    fn read(input: &web_sys::HtmlTextAreaElement) -> Self {
+        let text = input.value();
+        let offset = |offset: Result<Option<u32>, JsValue>| {
+            offset
+                .ok()
+                .flatten()
+                .map_or(text.chars().count(), |offset| char_offset(&text, offset))
+        };
+        let selection = offset(input.selection_start())..offset(input.selection_end());
+        Self {
+            text,
+            selection,
+            composing: false,
+        }
+    }
+}
+
+// Browsers count the characters of text areas in UTF-16 code units.
+fn utf16_offset(text: &str, chars: usize) -> u32 {
+    text.chars().take(chars).map(char::len_utf16).sum::<usize>() as u32
+}
+
+fn char_offset(text: &str, utf16_offset: u32) -> usize {
+    let mut offset = 0;
+    text.chars()
+        .take_while(|c| {
+            offset += c.len_utf16() as u32;
+            offset <= utf16_offset
+        })
+        .count()
+}
+
+/// Returns the chars of the previous text that were replaced, and the text that replaced them.
+///
+/// The same change can often be made at different positions (typing into `aa` gives `aaa`
+/// anywhere), the one that includes the previous selection is returned, which is where Egui
+/// makes changes.
+fn text_change(previous: &str, selection: Range<usize>, text: &str) -> (Range<usize>, String) {
+    let previous: Vec<char> = previous.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let selection = selection.start.min(previous.len())..selection.end.min(previous.len());
+    let suffix = previous[selection.end..]
+        .iter()
+        .rev()
+        .zip(text.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let prefix = previous[..selection.start]
+        .iter()
+        .zip(&text[..text.len() - suffix])
+        .take_while(|(a, b)| a == b)
+        .count();
+    (
+        prefix..previous.len() - suffix,
+        text[prefix..text.len() - suffix].iter().collect(),
+    )
+}
+
+/// Sends the events that replace the chars of a text field with the text, `selection` is the
+/// selection of the field, which the replaced chars include.
+fn send_edit(
+    tx: &Sender<egui::Event>,
+    selection: Range<usize>,
+    replaced: Range<usize>,
+    text: String,
+) {
+    let key = |key| {
+        for pressed in [true, false] {
+            send(
+                tx,
+                egui::Event::Key {
+                    key,
+                    physical_key: None,
+                    pressed,
+                    repeat: false,
+                    modifiers: egui::Modifiers::NONE,
+                },
+            );
+        }
+    };
+    if !selection.is_empty() {
+        key(egui::Key::Backspace);
+    }
+    for _ in replaced.start..selection.start {
+        key(egui::Key::Backspace);
+    }
+    for _ in selection.end..replaced.end {
+        key(egui::Key::Delete);
+    }
+    if !text.is_empty() {
+        send(tx, egui::Event::Text(text));
+    }
+}
+
+fn set_hints(
+    input: &web_sys::HtmlTextAreaElement,
+    hints: EguiTextInputHints,
+) -> Result<(), JsValue> {
+    let input_mode = match hints.input_mode {
+        EguiInputMode::Text => "text",
+        EguiInputMode::None => "none",
+        EguiInputMode::Numeric => "numeric",
+        EguiInputMode::Decimal => "decimal",
+        EguiInputMode::Tel => "tel",
+        EguiInputMode::Email => "email",
+        EguiInputMode::Url => "url",
+        EguiInputMode::Search => "search",
+    };
+    let autocapitalize = match hints.autocapitalize {
+        EguiAutocapitalize::Off => "off",
+        EguiAutocapitalize::Sentences => "sentences",
+        EguiAutocapitalize::Words => "words",
+        EguiAutocapitalize::Characters => "characters",
+    };
+    input.set_attribute("inputmode", input_mode)?;
+    input.set_attribute("autocapitalize", autocapitalize)?;
+    // `autocorrect` is only supported by Safari, other browsers correct words while spell
+    // checking.
+    input.set_attribute("autocorrect", if hints.autocorrect { "on" } else { "off" })?;
+    input.set_spellcheck(hints.autocorrect);
+    match hints.enter_key_hint {
+        Some(enter_key_hint) => {
+            let enter_key_hint = match enter_key_hint {
+                EguiEnterKeyHint::Enter => "enter",
+                EguiEnterKeyHint::Done => "done",
+                EguiEnterKeyHint::Go => "go",
+                EguiEnterKeyHint::Next => "next",
+                EguiEnterKeyHint::Previous => "previous",
+                EguiEnterKeyHint::Search => "search",
+                EguiEnterKeyHint::Send => "send",
+            };
+            input.set_attribute("enterkeyhint", enter_key_hint)?;
+        }
+        None => input.remove_attribute("enterkeyhint")?,
+    }
+    Ok(())
+}
+
+fn send(tx: &Sender<egui::Event>, event: egui::Event) {
+    if tx.send(event).is_err() {
+        log::error!("Failed to send a text agent event: channel is disconnected");
+    }
+}
+
+/// Maps `KeyboardEvent.code` to the key at that position on a US keyboard.
+fn physical_key_from_code(code: &str) -> Option<egui::Key> {
+    let key = match code {
+        "BracketLeft" => egui::Key::OpenBracket,
+        "BracketRight" => egui::Key::CloseBracket,
+        "NumpadAdd" => egui::Key::Plus,
+        "NumpadSubtract" => egui::Key::Minus,
+        "NumpadDivide" => egui::Key::Slash,
+        "NumpadDecimal" => egui::Key::Period,
+        "NumpadComma" => egui::Key::Comma,
+        "NumpadEnter" => egui::Key::Enter,
+        _ => return egui::Key::from_name(code.strip_prefix("Key").unwrap_or(code)),
+    };
+    Some(key)
+}
+
+/// Creates the text agent once the primary window exists and passes the events it received to
+/// the context of the window it's typing into (see [`update_text_agent_system`]).
+pub fn process_text_agent_input_system(
+    mut text_agent: NonSendMut<TextAgent>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut contexts: Query<(
+        Entity,
+        &mut EguiInput,
+        &EguiContextSettings,
+        Has<PrimaryWindow>,
+    )>,
+    mut created: Local<bool>,
+) {
+    if !*created {
+        let Ok(window) = primary_window.get_single() else {
+            return;
+        };
+        *created = true;
+        if let Err(err) = text_agent.create(window.canvas.as_deref()) {
+            log::error!("Failed to create the text agent: {err:?}");
+            text_agent.remove();
+        }
+    }
+
+    let Some(rx) = &text_agent.event_receiver else {
+        return;
+    };
+    let target = contexts.iter_mut().find(|(entity, _, _, is_primary)| {
+        text_agent
+            .target
+            .map_or(*is_primary, |target| target == *entity)
+    });
+    let Some((_, mut egui_input, context_settings, _)) = target else {
+        // Drop the events, they were typed into a window that doesn't exist anymore.
+        rx.try_iter().for_each(drop);
+        return;
+    };
+    for event in rx.try_iter() {
+        if !context_settings.display_only {
+            egui_input.events.push(event);
+        }
+    }
+}
+
+/// Focuses the text agent while a text field has focus, places it at the text cursor and applies
+/// the [`EguiTextInputHints`] of the field.
+///
+/// With several windows (canvases), the agent types into the window whose canvas was focused
+/// last. The canvases of windows other than the primary one are found by their
+/// [`Window::canvas`] selector, the agent isn't used for windows without one.
+pub fn update_text_agent_system(
+    mut text_agent: NonSendMut<TextAgent>,
+    contexts: Query<(
+        Entity,
+        &Window,
+        &EguiOutput,
+        &EguiContextSettings,
+        Has<PrimaryWindow>,
+    )>,
+) {
+    let Some(input) = text_agent.input.clone() else {
+        return;
+    };
+    text_agent
+        .canvases
+        .retain(|entity, _| contexts.contains(*entity));
+    let document = web_sys::window().and_then(|window| window.document());
+    let active_element = document.as_ref().and_then(crate::web_dom::active_element);
+    let mut focused = text_agent.is_focused();
+
+    // The windows that have a focused text field, with their canvases.
+    let mut candidates = Vec::new();
+    for (entity, window, egui_output, context_settings, is_primary) in contexts.iter() {
+        if egui_output.platform_output.ime.is_none() || context_settings.display_only {
+            continue;
+        }
+        let canvas = if is_primary {
+            text_agent.canvas.clone()
+        } else {
+            let Some(canvas) = text_agent.window_canvas(entity, window, document.as_ref()) else {
+                continue;
+            };
+            Some(canvas)
+        };
+        candidates.push((entity, window, egui_output, is_primary, canvas));
+    }
+    let target = candidates
+        .iter()
+        .position(|(.., canvas)| {
+            canvas.is_some() && active_element.as_ref() == canvas.as_ref().map(|canvas| &**canvas)
+        })
+        .or_else(|| {
+            candidates
+                .iter()
+                .position(|(entity, ..)| focused && text_agent.target == Some(*entity))
+        })
+        .or_else(|| {
+            candidates
+                .iter()
+                .position(|(.., is_primary, _)| *is_primary)
+        })
+        .or((!candidates.is_empty()).then_some(0))
+        .map(|index| candidates.swap_remove(index));
+
+    let Some((entity, window, egui_output, _, canvas)) = target else {
+        text_agent.set_value(&input, "", 0..0);
+        if focused {
+            let _ = input.blur();
+            let canvas = text_agent
+                .target
+                .and_then(|target| text_agent.canvases.get(&target).cloned().flatten())
+                .or_else(|| text_agent.canvas.clone());
+            if let Some(canvas) = canvas {
+                let _ = canvas.focus();
+            }
+        }
+        return;
+    };
+    if text_agent.target != Some(entity) {
+        text_agent.target = Some(entity);
+        // The agent was typing into another window.
+        if focused {
+            let _ = input.blur();
+            focused = false;
+        }
+    }
+
+    let hints = egui_output.text_input_hints.unwrap_or_default();
+    if text_agent.hints != Some(hints) {
+        text_agent.hints = Some(hints);
+        if let Err(err) = set_hints(&input, hints) {
+            log::error!("Failed to set the text input hints of the text agent: {err:?}");
+        }
+        // Browsers only pick the keyboard when an element gains focus, focus moving
+        // between text fields doesn't move it out of the agent.
+        if focused {
+            let _ = input.blur();
+            focused = false;
+        }
+    }
+
+    // Mobile keyboards suggest words from the text around the cursor.
+    match &egui_output.focused_text {
+        Some(focused_text) => {
+            text_agent.set_value(&input, &focused_text.text, focused_text.selection.clone());
+        }
+        None => text_agent.set_value(&input, "", 0..0),
+    }
+
+    // `Window::ime_position` is already set to the text cursor, in logical pixels (taking the
+    // scale factor and the screen layout of the context into account). The canvas may be
+    // scaled with CSS, so it's mapped onto the content box of the canvas.
+    let (left, top, scale_x, scale_y) = canvas.as_ref().map_or((0.0, 0.0, 1.0, 1.0), |canvas| {
+        let rect = canvas.get_bounding_client_rect();
+        let scale = |css_size: i32, logical_size: f32| {
+            if css_size > 0 && logical_size > 0.0 {
+                css_size as f64 / logical_size as f64
+            } else {
+                1.0
+            }
+        };
+        (
+            rect.left() + canvas.client_left() as f64,
+            rect.top() + canvas.client_top() as f64,
+            scale(canvas.client_width(), window.width()),
+            scale(canvas.client_height(), window.height()),
+        )
+    });
+    let style = input.style();
+    let _ = style.set_property(
+        "left",
+        &format!("{}px", left + window.ime_position.x as f64 * scale_x),
+    );
+    let _ = style.set_property(
+        "top",
+        &format!("{}px", top + window.ime_position.y as f64 * scale_y),
+    );
+
+    // Don't take the focus from other elements of the page.
+    let canvas_focused = active_element.is_none_or(|element| {
+        canvas.as_ref().is_none_or(|canvas| element == **canvas)
+            || element.tag_name().eq_ignore_ascii_case("body")
+    });
+    if !focused && canvas_focused {
+        if let Err(err) = input.focus() {
+            log::error!("Failed to focus the text agent: {err:?}");
+        }
+    }
+}
+
+/// The visual viewport shrinking by less than this (in CSS pixels) isn't considered to be caused
+/// by a virtual keyboard (but by browser toolbars, for example).
+const MIN_KEYBOARD_HEIGHT: f64 = 100.0;
+
+/// Updates [`EguiVirtualKeyboard::occluded_rect`] from the `visualViewport` of the page, which
+/// shrinks when the virtual keyboard is shown.
+pub fn update_virtual_keyboard_system(
+    text_agent: NonSend<TextAgent>,
+    mut virtual_keyboard: ResMut<EguiVirtualKeyboard>,
+) {
+    let (Some(canvas), Some(viewport)) = (&text_agent.canvas, visual_viewport()) else {
+        return;
+    };
+    // Pinch zooming shrinks the visual viewport as well, keep the last state while the page
+    // is zoomed.
+    if (viewport.scale - 1.0).abs() > 0.01 {
+        return;
+    }
+    // The keyboard covers the bottom of the page, anything below the visual viewport.
+    let canvas_rect = canvas.get_bounding_client_rect();
+    let keyboard_top = viewport.offset_top + viewport.height - canvas_rect.top();
+    let occluded_rect = (keyboard_top < canvas_rect.height() - MIN_KEYBOARD_HEIGHT).then(|| {
+        Rect::new(
+            0.0,
+            keyboard_top.max(0.0) as f32,
+            canvas_rect.width() as f32,
+            canvas_rect.height() as f32,
+        )
+    });
+    if virtual_keyboard.occluded_rect != occluded_rect {
+        virtual_keyboard.occluded_rect = occluded_rect;
+    }
+}
+
+struct VisualViewport {
+    offset_top: f64,
+    height: f64,
+    scale: f64,
+}
+
+// `web_sys` doesn't have bindings for the Visual Viewport API in all the supported versions.
+fn visual_viewport() -> Option<VisualViewport> {
+    let window = web_sys::window()?;
+    let viewport = js_sys::Reflect::get(&window, &JsValue::from_str("visualViewport")).ok()?;
+    if viewport.is_undefined() || viewport.is_null() {
+        return None;
+    }
+    let property = |name| {
+        js_sys::Reflect::get(&viewport, &JsValue::from_str(name))
+            .ok()
+            .and_then(|value| value.as_f64())
+    };
+    Some(VisualViewport {
+        offset_top: property("offsetTop")?,
+        height: property("height")?,
+        scale: property("scale")?,
+    })
+}
@@ -0,0 +1,447 @@
+use bevy::prelude::Resource;
+use std::ops::Range;
+
+/// The on-screen keyboard of the primary window.
+///
+/// While the keyboard covers the text cursor of a focused text field, the UI of the primary
+/// window is moved up until the cursor is visible again, unless
+/// [`EguiVirtualKeyboard::move_ui_into_view`] is disabled.
+///
+/// On web, [`EguiVirtualKeyboard::occluded_rect`] is updated from the `visualViewport` of the
+/// page. Other platforms don't report the area covered by the keyboard to Bevy, apps can set it
+/// from the window insets of the platform (the IME insets of Android, for example).
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct EguiVirtualKeyboard {
+    /// Area of the primary window covered by the keyboard, in logical pixels, `None` if the
+    /// keyboard is hidden. Apps can use it to keep their own UI above the keyboard as well.
+    pub occluded_rect: Option<bevy::math::Rect>,
+    /// Whether to move the UI up while the keyboard covers the text cursor (`true` by default).
+    pub move_ui_into_view: bool,
+}
+
+impl Default for EguiVirtualKeyboard {
+    fn default() -> Self {
+        Self {
+            occluded_rect: None,
+            move_ui_into_view: true,
+        }
+    }
+}
+
+/// Hints for the on-screen keyboard shown for a text field: a numeric keyboard for number fields,
+/// no autocorrection for passwords, and so on.
+///
+/// Egui doesn't tell what a text field is for, so apps attach the hints to the response of the
+/// field with [`EguiTextInputHints::attach`], and the hints of the focused field are output in
+/// [`EguiOutput::text_input_hints`](crate::EguiOutput::text_input_hints):
+///
+/// ```rust
+/// use bevy_egui::{egui, EguiTextInputHints};
+///
+/// fn age_field(ui: &mut egui::Ui, age: &mut String) {
+///     let response = ui.text_edit_singleline(age);
+///     EguiTextInputHints::numeric().attach(&response);
+/// }
+/// ```
+///
+/// On web, the hints set the `inputmode`, `autocapitalize`, `autocorrect` and `enterkeyhint`
+/// attributes of the hidden text area that receives the typed text, fields without hints use
+/// [`EguiTextInputHints::default`]. Winit doesn't expose these settings on other platforms, there
+/// apps can read the hints to show a custom keyboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EguiTextInputHints {
+    /// The kind of keyboard to show.
+    pub input_mode: EguiInputMode,
+    /// Which letters are capitalized automatically ([`EguiAutocapitalize::Off`] by default).
+    pub autocapitalize: EguiAutocapitalize,
+    /// Whether the keyboard may correct the typed words and suggest new ones (`false` by default).
+    pub autocorrect: bool,
+    /// The label of the Enter key, `None` lets the platform choose.
+    pub enter_key_hint: Option<EguiEnterKeyHint>,
+}
+
+impl EguiTextInputHints {
+    /// Hints for prose: sentences are capitalized and words are corrected.
+    #[must_use]
+    pub fn text() -> Self {
+        Self {
+            autocapitalize: EguiAutocapitalize::Sentences,
+            autocorrect: true,
+            ..Default::default()
+        }
+    }
+
+    /// Hints for integers, shows a keyboard with digits.
+    #[must_use]
+    pub fn numeric() -> Self {
+        Self {
+            input_mode: EguiInputMode::Numeric,
+            ..Default::default()
+        }
+    }
+
+    /// Hints for fractional numbers, shows a keyboard with digits and a decimal separator.
+    #[must_use]
+    pub fn decimal() -> Self {
+        Self {
+            input_mode: EguiInputMode::Decimal,
+            ..Default::default()
+        }
+    }
+
+    /// Hints for email addresses.
+    #[must_use]
+    pub fn email() -> Self {
+        Self {
+            input_mode: EguiInputMode::Email,
+            ..Default::default()
+        }
+    }
+
+    /// Hints for URLs.
+    #[must_use]
+    pub fn url() -> Self {
+        Self {
+            input_mode: EguiInputMode::Url,
+            ..Default::default()
+        }
+    }
+
+    /// Hints for search fields, the Enter key is labeled as search.
+    #[must_use]
+    pub fn search() -> Self {
+        Self {
+            input_mode: EguiInputMode::Search,
+            enter_key_hint: Some(EguiEnterKeyHint::Search),
+            ..Default::default()
+        }
+    }
+
+    /// Hints for passwords: nothing is capitalized or corrected, so the keyboard doesn't learn or
+    /// suggest the typed text.
+    #[must_use]
+    pub fn password() -> Self {
+        Self {
+            autocapitalize: EguiAutocapitalize::Off,
+            autocorrect: false,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the label of the Enter key.
+    #[must_use]
+    pub fn with_enter_key_hint(mut self, enter_key_hint: EguiEnterKeyHint) -> Self {
+        self.enter_key_hint = Some(enter_key_hint);
+        self
+    }
+
+    /// Attaches the hints to a text field, they're kept until they're attached again.
+    pub fn attach(self, response: &egui::Response) {
+        response
+            .ctx
+            .data_mut(|data| data.insert_temp(response.id, self));
+    }
+
+    /// Returns the hints of the focused widget, [`EguiTextInputHints::default`] if none were
+    /// attached to it, or `None` if no widget has focus.
+    #[must_use]
+    pub fn focused(ctx: &egui::Context) -> Option<Self> {
+        let id = ctx.memory(|memory| memory.focused())?;
+        Some(ctx.data(|data| data.get_temp(id)).unwrap_or_default())
+    }
+}
+
+/// The kind of on-screen keyboard to show for a text field, like the `inputmode` HTML attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EguiInputMode {
+    /// A keyboard for any text.
+    #[default]
+    Text,
+    /// No keyboard, for fields that have their own input UI.
+    None,
+    /// Digits.
+    Numeric,
+    /// Digits and a decimal separator.
+    Decimal,
+    /// Digits, `*` and `#`.
+    Tel,
+    /// A keyboard with `@` and `.`.
+    Email,
+    /// A keyboard with `/` and `.`.
+    Url,
+    /// A keyboard for search queries.
+    Search,
+}
+
+/// Which letters of a text field are capitalized automatically, like the `autocapitalize` HTML
+/// attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EguiAutocapitalize {
+    /// Nothing is capitalized.
+    #[default]
+    Off,
+    /// The first letter of every sentence.
+    Sentences,
+    /// The first letter of every word.
+    Words,
+    /// All the letters.
+    Characters,
+}
+
+/// The label of the Enter key of an on-screen keyboard, like the `enterkeyhint` HTML attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EguiEnterKeyHint {
+    /// Inserts a new line.
+    Enter,
+    /// Finishes the input.
+    Done,
+    /// Goes to what was typed.
+    Go,
+    /// Moves to the next field.
+    Next,
+    /// Moves to the previous field.
+    Previous,
+    /// Searches for what was typed.
+    Search,
+    /// Sends what was typed.
+    Send,
+}
+
+/// The contents of the focused text field, output in
+/// [`EguiOutput::focused_text`](crate::EguiOutput::focused_text).
+///
+/// Egui doesn't keep the text of a field, it's only known from the output events the field sends
+/// when it's clicked, gains focus with the keyboard or its text changes (see
+/// [`egui::output::OutputEvent`]), so the contents are `None` until then. Characters of password
+/// fields are masked.
+///
+/// On web, the text agent is filled with the contents, so mobile keyboards can suggest words
+/// and move the cursor like in a browser text field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EguiFocusedText {
+    /// Id of the text field.
+    pub id: egui::Id,
+    /// Text of the field.
+    pub text: String,
+    /// The selected characters (indices of chars, not bytes), an empty range at the text cursor
+    /// if nothing is selected.
+    pub selection: Range<usize>,
+}
+
+impl EguiFocusedText {
+    /// Returns the contents of the field that has focus after a pass, `previous` is the value
+    /// returned for the previous pass.
+    pub(crate) fn focused(
+        ctx: &egui::Context,
+        platform_output: &egui::PlatformOutput,
+        previous: Option<&Self>,
+    ) -> Option<Self> {
+        // Egui outputs the IME state while a text field has focus.
+        platform_output.ime?;
+        let id = ctx.memory(|memory| memory.focused())?;
+        let text = platform_output
+            .events
+            .iter()
+            .rev()
+            .map(egui::output::OutputEvent::widget_info)
+            .filter(|info| info.typ == egui::WidgetType::TextEdit)
+            .find_map(|info| info.current_text_value.clone())
+            .or_else(|| {
+                previous
+                    .filter(|previous| previous.id == id)
+                    .map(|previous| previous.text.clone())
+            })?;
+        let len = text.chars().count();
+        let selection = egui::TextEdit::load_state(ctx, id)
+            .and_then(|state| state.cursor.char_range())
+            .map_or(len..len, |range| {
+                let [start, end] = range.sorted();
+                start.index.min(len)..end.index.min(len)
+            });
+        Some(Self {
+            id,
+            text,
+            selection,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::test_app, EguiContext, EguiContexts, EguiInput, EguiOutput, EguiRenderOutput,
+        EguiSet,
+    };
+    use bevy::prelude::*;
+
+    #[test]
+    fn test_virtual_keyboard_moves_ui() {
+        fn ui_system(mut contexts: EguiContexts) {
+            egui::Area::new("text".into())
+                .fixed_pos(egui::pos2(100.0, 600.0))
+                .show(contexts.ctx_mut(), |ui| {
+                    let mut text = String::new();
+                    ui.text_edit_singleline(&mut text).request_focus();
+                });
+        }
+
+        let (mut app, window) = test_app();
+        app.add_systems(bevy::app::Update, ui_system);
+        app.update();
+        app.update();
+        let keyboard_offset = |app: &App| {
+            app.world()
+                .get::<EguiContext>(window)
+                .unwrap()
+                .keyboard_offset
+        };
+        assert_eq!(keyboard_offset(&app), 0.0);
+
+        app.world_mut()
+            .resource_mut::<EguiVirtualKeyboard>()
+            .occluded_rect = Some(bevy::math::Rect::new(0.0, 400.0, 1280.0, 720.0));
+        app.update();
+        app.update();
+        assert!(keyboard_offset(&app) > 200.0);
+        let layout = app
+            .world()
+            .get::<EguiRenderOutput>(window)
+            .unwrap()
+            .screen_layout
+            .unwrap();
+        assert_eq!(layout.offset.y, -keyboard_offset(&app).round());
+        // The offset settles instead of following its own rounding.
+        let settled_offset = keyboard_offset(&app);
+        app.update();
+        assert_eq!(keyboard_offset(&app), settled_offset);
+        // The text cursor is right above the keyboard.
+        let ime_position = app.world().get::<Window>(window).unwrap().ime_position;
+        assert!((ime_position.y - (400.0 - 8.0)).abs() < 1.0);
+
+        app.world_mut()
+            .resource_mut::<EguiVirtualKeyboard>()
+            .occluded_rect = None;
+        app.update();
+        assert_eq!(keyboard_offset(&app), 0.0);
+    }
+
+    #[test]
+    fn test_text_input_hints() {
+        #[derive(Resource)]
+        struct FocusedField(Option<usize>);
+
+        fn ui_system(mut contexts: EguiContexts, focused_field: Res<FocusedField>) {
+            egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+                let mut text = String::new();
+                let plain = ui.text_edit_singleline(&mut text);
+                let number = ui.text_edit_singleline(&mut text);
+                EguiTextInputHints::numeric().attach(&number);
+                match focused_field.0 {
+                    Some(0) => plain.request_focus(),
+                    Some(_) => number.request_focus(),
+                    None => plain.surrender_focus(),
+                }
+            });
+        }
+
+        let (mut app, window) = test_app();
+        app.insert_resource(FocusedField(Some(1)))
+            .add_systems(bevy::app::Update, ui_system);
+        let hints = |app: &App| {
+            app.world()
+                .get::<EguiOutput>(window)
+                .unwrap()
+                .text_input_hints
+        };
+        app.update();
+        app.update();
+        assert_eq!(hints(&app), Some(EguiTextInputHints::numeric()));
+
+        app.insert_resource(FocusedField(Some(0)));
+        app.update();
+        app.update();
+        assert_eq!(hints(&app), Some(EguiTextInputHints::default()));
+
+        app.insert_resource(FocusedField(None));
+        app.update();
+        app.update();
+        assert_eq!(hints(&app), None);
+    }
+
+    #[test]
+    fn test_focused_text() {
+        #[derive(Resource)]
+        struct Focused(bool);
+
+        #[derive(Resource, Default)]
+        struct PendingEvents(Vec<egui::Event>);
+
+        fn ui_system(mut contexts: EguiContexts, focused: Res<Focused>, mut text: Local<String>) {
+            egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
+                let response = ui.text_edit_singleline(&mut *text);
+                if focused.0 {
+                    response.request_focus();
+                } else {
+                    response.surrender_focus();
+                }
+            });
+        }
+
+        fn send_events_system(
+            mut pending: ResMut<PendingEvents>,
+            mut egui_input: Query<&mut EguiInput>,
+        ) {
+            for mut egui_input in egui_input.iter_mut() {
+                egui_input.events.append(&mut pending.0);
+            }
+        }
+
+        let (mut app, window) = test_app();
+        app.insert_resource(Focused(true))
+            .init_resource::<PendingEvents>()
+            .add_systems(Update, ui_system)
+            .add_systems(
+                PreUpdate,
+                send_events_system
+                    .after(EguiSet::ProcessInput)
+                    .before(EguiSet::BeginPass),
+            );
+        let update = |app: &mut App, events: Vec<egui::Event>| {
+            app.world_mut().resource_mut::<PendingEvents>().0 = events;
+            app.update();
+            app.world()
+                .get::<EguiOutput>(window)
+                .unwrap()
+                .focused_text
+                .clone()
+                .map(|focused_text| (focused_text.text, focused_text.selection))
+        };
+        let key = |key| egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        };
+
+        update(&mut app, Vec::new());
+        // A field focused with `Response::request_focus` doesn't send an event, its text is
+        // unknown until it changes.
+        assert_eq!(update(&mut app, Vec::new()), None);
+        assert_eq!(
+            update(&mut app, vec![egui::Event::Text("añb".into())]),
+            Some(("añb".into(), 3..3))
+        );
+        // Moving the cursor doesn't change the text, which is kept from the previous passes.
+        assert_eq!(
+            update(&mut app, vec![key(egui::Key::ArrowLeft)]),
+            Some(("añb".into(), 2..2))
+        );
+        assert_eq!(update(&mut app, Vec::new()), Some(("añb".into(), 2..2)));
+
+        app.insert_resource(Focused(false));
+        update(&mut app, Vec::new());
+        assert_eq!(update(&mut app, Vec::new()), None);
+    }
+}
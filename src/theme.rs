@@ -0,0 +1,194 @@
+use crate::{systems::apply_style_settings, EguiContext, EguiSettings};
+use bevy::{
+    asset::{
+        io::Reader, Asset, AssetEvent, AssetLoader, Assets, AsyncReadExt, Handle, LoadContext,
+    },
+    prelude::{Component, DetectChanges, EventReader, Query, Ref, Res},
+    reflect::TypePath,
+    utils::HashSet,
+};
+
+/// An [`egui::Style`] (including its [`egui::Visuals`]) loaded from a `.theme.ron` or
+/// `.theme.json` file by [`EguiThemeLoader`].
+///
+/// Fields missing from a file keep their default values, so a theme only has to list what it
+/// changes. The defaults are the dark visuals: to make a light theme, start from the output of
+/// [`EguiTheme::to_ron`] with [`egui::Visuals::light`].
+#[derive(Asset, TypePath, Clone, Debug, Default)]
+pub struct EguiTheme {
+    /// The style that's applied to the contexts.
+    pub style: egui::Style,
+}
+
+impl EguiTheme {
+    /// Parses a theme in the RON format.
+    pub fn from_ron(ron: &str) -> Result<Self, EguiThemeLoaderError> {
+        let style = ron::de::from_str(ron).map_err(EguiThemeLoaderError::Ron)?;
+        Ok(Self { style })
+    }
+
+    /// Parses a theme in the JSON format.
+    pub fn from_json(json: &str) -> Result<Self, EguiThemeLoaderError> {
+        let style = serde_json::from_str(json).map_err(EguiThemeLoaderError::Json)?;
+        Ok(Self { style })
+    }
+
+    /// Serializes the theme in the RON format, which is useful for exporting the current style
+    /// of a context as a starting point.
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(&self.style, ron::ser::PrettyConfig::default())
+    }
+}
+
+/// Errors that occur while loading an [`EguiTheme`].
+#[derive(Debug)]
+pub enum EguiThemeLoaderError {
+    /// The file couldn't be read.
+    Io(std::io::Error),
+    /// The file isn't valid UTF-8.
+    Utf8(std::str::Utf8Error),
+    /// The RON file couldn't be parsed.
+    Ron(ron::error::SpannedError),
+    /// The JSON file couldn't be parsed.
+    Json(serde_json::Error),
+}
+
+impl std::fmt::Display for EguiThemeLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read the theme: {err}"),
+            Self::Utf8(err) => write!(f, "the theme isn't valid UTF-8: {err}"),
+            Self::Ron(err) => write!(f, "failed to parse the theme: {err}"),
+            Self::Json(err) => write!(f, "failed to parse the theme: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for EguiThemeLoaderError {}
+
+/// Loads `.theme.ron` and `.theme.json` files as [`EguiTheme`]s.
+#[derive(Default)]
+pub struct EguiThemeLoader;
+
+impl AssetLoader for EguiThemeLoader {
+    type Asset = EguiTheme;
+    type Settings = ();
+    type Error = EguiThemeLoaderError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<EguiTheme, EguiThemeLoaderError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(EguiThemeLoaderError::Io)?;
+        let text = std::str::from_utf8(&bytes).map_err(EguiThemeLoaderError::Utf8)?;
+        let is_json = load_context
+            .path()
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        if is_json {
+            EguiTheme::from_json(text)
+        } else {
+            EguiTheme::from_ron(text)
+        }
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron", "theme.json"]
+    }
+}
+
+/// Applies an [`EguiTheme`] to the context of the entity (a window or an
+/// [`EguiRenderToTexture`](crate::EguiRenderToTexture) entity).
+///
+/// The theme is applied once it's loaded, and again each time the asset changes: with the
+/// `file_watcher` Bevy feature, saving the file updates the UI while the app is running.
+/// Style settings of [`EguiSettings`] (tooltip delays, for example) are kept.
+///
+/// ```rust
+/// use bevy::{prelude::*, window::PrimaryWindow};
+/// use bevy_egui::theme::EguiContextTheme;
+///
+/// fn setup(
+///     mut commands: Commands,
+///     asset_server: Res<AssetServer>,
+///     primary_window: Query<Entity, With<PrimaryWindow>>,
+/// ) {
+///     commands
+///         .entity(primary_window.single())
+///         .insert(EguiContextTheme(asset_server.load("ui/dark.theme.ron")));
+/// }
+/// ```
+#[derive(Component, Clone, Debug, Default)]
+pub struct EguiContextTheme(pub Handle<EguiTheme>);
+
+/// Applies [`EguiContextTheme`]s that have been added, changed or reloaded.
+pub fn apply_egui_themes_system(
+    egui_settings: Res<EguiSettings>,
+    themes: Res<Assets<EguiTheme>>,
+    mut theme_events: EventReader<AssetEvent<EguiTheme>>,
+    mut contexts: Query<(&mut EguiContext, Ref<EguiContextTheme>)>,
+) {
+    let mut loaded_themes = HashSet::new();
+    for event in theme_events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            loaded_themes.insert(*id);
+        }
+    }
+
+    for (mut ctx, context_theme) in contexts.iter_mut() {
+        if !context_theme.is_changed() && !loaded_themes.contains(&context_theme.0.id()) {
+            continue;
+        }
+        let Some(theme) = themes.get(&context_theme.0) else {
+            continue;
+        };
+        let ctx = ctx.get_mut();
+        ctx.set_style(theme.style.clone());
+        apply_style_settings(ctx, &egui_settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::test_app;
+
+    #[test]
+    fn test_egui_theme() {
+        let (mut app, window) = test_app();
+        app.insert_resource(EguiSettings {
+            tooltip_delay: 1.0,
+            ..Default::default()
+        });
+        app.update();
+
+        let theme = EguiTheme::from_ron(
+            "(visuals: (dark_mode: false), spacing: (item_spacing: (x: 3.0, y: 4.0)))",
+        )
+        .unwrap();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<EguiTheme>>()
+            .add(theme);
+        app.world_mut()
+            .entity_mut(window)
+            .insert(EguiContextTheme(handle));
+        app.update();
+
+        let style = app
+            .world_mut()
+            .get_mut::<EguiContext>(window)
+            .unwrap()
+            .get_mut()
+            .style();
+        assert!(!style.visuals.dark_mode);
+        assert_eq!(style.spacing.item_spacing, egui::vec2(3.0, 4.0));
+        assert_eq!(style.interaction.tooltip_delay, 1.0);
+    }
+}
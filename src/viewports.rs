@@ -0,0 +1,301 @@
+use crate::{
+    EguiContext, EguiContextSettings, EguiInput, EguiInputHooks, EguiOutput, EguiRenderOutput,
+    WindowSize,
+};
+#[cfg(feature = "render")]
+use bevy::render::extract_component::ExtractComponent;
+use bevy::{
+    ecs::query::QueryData,
+    math::IVec2,
+    prelude::{Commands, Component, Entity, Local, Query, Res},
+    utils::{hashbrown::hash_map::Entry, HashMap, HashSet},
+    window::{CursorGrabMode, Window, WindowLevel, WindowMode, WindowPosition},
+};
+use std::sync::Arc;
+
+/// Is inserted into the windows spawned for deferred Egui viewports (see
+/// [`EguiContextSettings::embed_viewports`]).
+///
+/// A viewport window shares the [`egui::Context`] of the context that shows the viewport, so
+/// [`EguiContexts::ctx_for_window_mut`](crate::EguiContexts::ctx_for_window_mut) returns the
+/// same context for both. Its UI is built by the callback passed to
+/// [`egui::Context::show_viewport_deferred`], which is run during [`EguiSet::EndPass`](crate::EguiSet::EndPass).
+#[derive(Component, Clone)]
+#[cfg_attr(feature = "render", derive(ExtractComponent))]
+pub struct EguiViewport {
+    /// Entity of the context that shows the viewport.
+    pub context: Entity,
+    /// Id of the viewport.
+    pub id: egui::ViewportId,
+    builder: egui::ViewportBuilder,
+    ui_cb: Arc<egui::DeferredViewportUiCallback>,
+    // Commands the viewport sent to itself during its last frame.
+    pub(crate) commands: Vec<egui::ViewportCommand>,
+}
+
+/// Queries the window contexts that show viewports, and the windows of the viewports.
+#[derive(QueryData)]
+#[query_data(mutable)]
+pub struct ViewportContextQuery {
+    entity: Entity,
+    ctx: &'static mut EguiContext,
+    egui_input: &'static mut EguiInput,
+    egui_output: &'static mut EguiOutput,
+    render_output: &'static mut EguiRenderOutput,
+    context_settings: &'static EguiContextSettings,
+    window: &'static mut Window,
+    viewport: Option<&'static mut EguiViewport>,
+}
+
+/// Viewports that [`update_viewport_windows_system`] has spawned windows for.
+#[derive(Default)]
+pub struct ShownViewports {
+    windows: HashMap<(Entity, egui::ViewportId), Entity>,
+    // Viewports whose windows were closed by the user while the app still shows them.
+    closed: HashSet<(Entity, egui::ViewportId)>,
+}
+
+/// Spawns windows for the deferred viewports shown by contexts with
+/// [`EguiContextSettings::embed_viewports`] disabled, applies viewport builder changes and
+/// [`egui::ViewportCommand`]s to them, and despawns them once their viewports aren't shown.
+///
+/// Closing a viewport window or sending [`egui::ViewportCommand::Close`] to a viewport passes
+/// [`egui::ViewportEvent::Close`] to it, the app stops showing the viewport if
+/// [`egui::ViewportInfo::close_requested`] is `true` to close it. As Bevy despawns windows
+/// closed by the user, such viewports aren't spawned again until the app stops showing them.
+pub fn update_viewport_windows_system(
+    mut commands: Commands,
+    mut contexts: Query<ViewportContextQuery>,
+    input_hooks: Res<EguiInputHooks>,
+    mut shown_viewports: Local<ShownViewports>,
+) {
+    let mut outputs: HashMap<(Entity, egui::ViewportId), egui::ViewportOutput> = HashMap::new();
+    for mut context in contexts.iter_mut() {
+        // Viewports shown by viewport windows belong to the same context.
+        let owner = context
+            .viewport
+            .as_ref()
+            .map_or(context.entity, |viewport| viewport.context);
+        for (id, output) in std::mem::take(&mut context.egui_output.viewport_output) {
+            if output.class != egui::ViewportClass::Deferred {
+                continue;
+            }
+            match outputs.entry((owner, id)) {
+                Entry::Occupied(mut entry) => entry.get_mut().append(output),
+                Entry::Vacant(entry) => {
+                    entry.insert(output);
+                }
+            }
+        }
+    }
+    outputs.retain(|_, output| output.viewport_ui_cb.is_some());
+
+    // Viewports stay closed until the app stops showing them.
+    shown_viewports
+        .closed
+        .retain(|key| outputs.contains_key(key));
+    let mut user_closed = Vec::new();
+    shown_viewports.windows.retain(|key, window| {
+        let exists = contexts.contains(*window);
+        if !exists && outputs.contains_key(key) {
+            user_closed.push(*key);
+        }
+        exists
+    });
+    for key in user_closed {
+        shown_viewports.closed.insert(key);
+        if let Ok(mut owner) = contexts.get_mut(key.0) {
+            run_closed_viewport_ui(&mut owner, key.1, &outputs[&key], &input_hooks);
+        }
+    }
+
+    let owner_settings: HashMap<Entity, EguiContextSettings> = contexts
+        .iter()
+        .filter(|context| context.viewport.is_none())
+        .map(|context| (context.entity, context.context_settings.clone()))
+        .collect();
+    for mut context in contexts.iter_mut() {
+        let Some(viewport) = context.viewport.as_deref_mut() else {
+            continue;
+        };
+        let key = (viewport.context, viewport.id);
+        let Some(output) = outputs.remove(&key) else {
+            commands.entity(context.entity).despawn();
+            shown_viewports.windows.remove(&key);
+            continue;
+        };
+        let (mut viewport_commands, _) = viewport.builder.patch(output.builder);
+        viewport_commands.extend(output.commands);
+        viewport_commands.append(&mut viewport.commands);
+        if let Some(ui_cb) = output.viewport_ui_cb {
+            viewport.ui_cb = ui_cb;
+        }
+
+        let scale_factor = context.window.scale_factor();
+        for command in viewport_commands {
+            if command == egui::ViewportCommand::Close {
+                // Is moved to the viewport's id when the frame begins.
+                context
+                    .egui_input
+                    .viewports
+                    .entry(egui::ViewportId::ROOT)
+                    .or_default()
+                    .events
+                    .push(egui::ViewportEvent::Close);
+            } else {
+                apply_viewport_command(&mut context.window, command, scale_factor);
+            }
+        }
+        // Settings that apply to the shared context would fight each other if they differed.
+        if let Some(settings) = owner_settings.get(&key.0) {
+            if settings != context.context_settings {
+                commands.entity(context.entity).insert(settings.clone());
+            }
+        }
+    }
+
+    for (key, output) in outputs {
+        if shown_viewports.closed.contains(&key) {
+            continue;
+        }
+        let (Some(ui_cb), Ok(owner)) = (output.viewport_ui_cb, contexts.get(key.0)) else {
+            continue;
+        };
+        let mut window = Window::default();
+        let scale_factor = owner.window.scale_factor();
+        let (builder_commands, _) = egui::ViewportBuilder::default().patch(output.builder.clone());
+        for command in builder_commands.into_iter().chain(output.commands) {
+            apply_viewport_command(&mut window, command, scale_factor);
+        }
+        let ctx = EguiContext {
+            ctx: owner.ctx.ctx.clone(),
+            ..Default::default()
+        };
+        let window_entity = commands
+            .spawn((
+                window,
+                ctx,
+                EguiRenderOutput::default(),
+                EguiInput::default(),
+                EguiOutput::default(),
+                WindowSize::default(),
+                owner.context_settings.clone(),
+                EguiViewport {
+                    context: key.0,
+                    id: key.1,
+                    builder: output.builder,
+                    ui_cb,
+                    commands: Vec::new(),
+                },
+            ))
+            .id();
+        shown_viewports.windows.insert(key, window_entity);
+    }
+}
+
+// Runs a frame of a viewport whose window was closed by the user, so that the app sees the
+// close request.
+fn run_closed_viewport_ui(
+    owner: &mut ViewportContextQueryItem,
+    viewport_id: egui::ViewportId,
+    output: &egui::ViewportOutput,
+    input_hooks: &EguiInputHooks,
+) {
+    let Some(ui_cb) = &output.viewport_ui_cb else {
+        return;
+    };
+    let mut raw_input = egui::RawInput {
+        viewport_id,
+        ..Default::default()
+    };
+    raw_input.viewports.insert(
+        viewport_id,
+        egui::ViewportInfo {
+            events: vec![egui::ViewportEvent::Close],
+            ..Default::default()
+        },
+    );
+    input_hooks.run(owner.entity, &mut raw_input);
+    let ctx = owner.ctx.get_mut();
+    ctx.begin_frame(raw_input);
+    ui_cb(ctx);
+    // Textures are shared by all windows of the context.
+    let full_output = ctx.end_frame();
+    owner
+        .render_output
+        .textures_delta
+        .append(full_output.textures_delta);
+}
+
+/// Begins a frame of a viewport window and runs the viewport's callback.
+pub(crate) fn run_viewport_ui(
+    window_entity: Entity,
+    ctx: &mut EguiContext,
+    egui_input: &mut EguiInput,
+    viewport: &EguiViewport,
+    input_hooks: &EguiInputHooks,
+) {
+    let mut raw_input = egui_input.take();
+    raw_input.viewport_id = viewport.id;
+    // Window infos are stored for the root viewport by `update_window_contexts_system`.
+    if let Some(info) = raw_input.viewports.remove(&egui::ViewportId::ROOT) {
+        raw_input.viewports.insert(viewport.id, info);
+    }
+    input_hooks.run(window_entity, &mut raw_input);
+    let ctx = ctx.get_mut();
+    ctx.begin_frame(raw_input);
+    (viewport.ui_cb)(ctx);
+}
+
+fn apply_viewport_command(window: &mut Window, command: egui::ViewportCommand, scale_factor: f32) {
+    match command {
+        egui::ViewportCommand::Title(title) => window.title = title,
+        egui::ViewportCommand::Visible(visible) => window.visible = visible,
+        egui::ViewportCommand::Transparent(transparent) => window.transparent = transparent,
+        egui::ViewportCommand::OuterPosition(pos) => {
+            let pos = pos * scale_factor;
+            window.position = WindowPosition::At(IVec2::new(pos.x as i32, pos.y as i32));
+        }
+        egui::ViewportCommand::InnerSize(size) => window.resolution.set(size.x, size.y),
+        egui::ViewportCommand::MinInnerSize(size) => {
+            window.resize_constraints.min_width = size.x;
+            window.resize_constraints.min_height = size.y;
+        }
+        egui::ViewportCommand::MaxInnerSize(size) => {
+            window.resize_constraints.max_width = size.x;
+            window.resize_constraints.max_height = size.y;
+        }
+        egui::ViewportCommand::Resizable(resizable) => window.resizable = resizable,
+        egui::ViewportCommand::Decorations(decorations) => window.decorations = decorations,
+        egui::ViewportCommand::Minimized(minimized) => window.set_minimized(minimized),
+        egui::ViewportCommand::Maximized(maximized) => window.set_maximized(maximized),
+        egui::ViewportCommand::Fullscreen(fullscreen) => {
+            window.mode = if fullscreen {
+                WindowMode::BorderlessFullscreen
+            } else {
+                WindowMode::Windowed
+            };
+        }
+        egui::ViewportCommand::WindowLevel(level) => {
+            window.window_level = match level {
+                egui::WindowLevel::Normal => WindowLevel::Normal,
+                egui::WindowLevel::AlwaysOnBottom => WindowLevel::AlwaysOnBottom,
+                egui::WindowLevel::AlwaysOnTop => WindowLevel::AlwaysOnTop,
+            };
+        }
+        egui::ViewportCommand::Focus => window.focused = true,
+        egui::ViewportCommand::CursorVisible(visible) => window.cursor.visible = visible,
+        egui::ViewportCommand::CursorGrab(grab) => {
+            window.cursor.grab_mode = match grab {
+                egui::viewport::CursorGrab::None => CursorGrabMode::None,
+                egui::viewport::CursorGrab::Confined => CursorGrabMode::Confined,
+                egui::viewport::CursorGrab::Locked => CursorGrabMode::Locked,
+            };
+        }
+        egui::ViewportCommand::MousePassthrough(passthrough) => {
+            window.cursor.hit_test = !passthrough;
+        }
+        // Other commands either have no `Window` equivalent or are handled elsewhere.
+        _ => {}
+    }
+}
@@ -1,25 +1,52 @@
 use crate::EguiClipboard;
-use bevy::{log, prelude::*};
+#[cfg(feature = "render")]
+use crate::PastedImage;
+use bevy::{log, prelude::*, window::PrimaryWindow};
 use crossbeam_channel::{Receiver, Sender};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 
 /// Startup system to initialize web clipboard events.
+///
+/// If the primary window has a [`Window::canvas`] selector, only the events targeting that canvas
+/// are handled, so that copying and pasting in other page content doesn't reach Egui.
+/// Otherwise, the events of the whole page are handled.
 pub fn startup_setup_web_events(
     mut egui_clipboard: ResMut<EguiClipboard>,
     mut subscribed_events: NonSendMut<SubscribedEvents>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
 ) {
+    subscribed_events.canvas_selector = primary_window
+        .get_single()
+        .ok()
+        .and_then(|window| window.canvas.clone());
     let (tx, rx) = crossbeam_channel::unbounded();
     egui_clipboard.clipboard.event_receiver = Some(rx);
+    egui_clipboard.clipboard.event_sender = Some(tx.clone());
+    #[cfg(feature = "render")]
+    let (image_tx, image_rx) = crossbeam_channel::unbounded();
+    #[cfg(feature = "render")]
+    {
+        egui_clipboard.clipboard.image_receiver = Some(image_rx);
+    }
     setup_clipboard_copy(&mut subscribed_events, tx.clone());
     setup_clipboard_cut(&mut subscribed_events, tx.clone());
-    setup_clipboard_paste(&mut subscribed_events, tx);
+    setup_clipboard_paste(
+        &mut subscribed_events,
+        tx,
+        #[cfg(feature = "render")]
+        image_tx,
+    );
 }
 
 /// Internal implementation of `[crate::EguiClipboard]` for web.
 #[derive(Default)]
 pub struct WebClipboard {
     event_receiver: Option<Receiver<WebClipboardEvent>>,
+    // Sends the contents read by `request_paste`.
+    event_sender: Option<Sender<WebClipboardEvent>>,
+    #[cfg(feature = "render")]
+    image_receiver: Option<Receiver<PastedImage>>,
     contents: Option<String>,
 }
 
@@ -30,7 +57,8 @@ pub enum WebClipboardEvent {
     Cut,
     /// Is sent whenever the `copy` event listener is called.
     Copy,
-    /// Is sent whenever the `paste` event listener is called, includes the plain text content.
+    /// Is sent whenever the `paste` event listener is called or the text requested with
+    /// [`WebClipboard::request_paste`] is read, includes the plain text content.
     Paste(String),
 }
 
@@ -53,6 +81,32 @@ impl WebClipboard {
         self.contents.clone()
     }
 
+    /// Writes an image to the clipboard via the Web API, encoded as PNG.
+    pub fn set_image(&mut self, image: &egui::ColorImage) {
+        if let Err(err) = clipboard_copy_image(image) {
+            log::warn!("Failed to write an image to clipboard: {err}");
+        }
+    }
+
+    /// Takes the images decoded by the `paste` listener since the last call.
+    #[cfg(feature = "render")]
+    pub(crate) fn take_pasted_images(&self) -> Vec<PastedImage> {
+        self.image_receiver
+            .as_ref()
+            .map(|rx| rx.try_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Reads the clipboard text via the Web API, the text is received as a
+    /// [`WebClipboardEvent::Paste`] event once it's read.
+    pub fn request_paste(&self) {
+        let Some(tx) = self.event_sender.clone() else {
+            log::error!("Web clipboard event sender isn't initialized");
+            return;
+        };
+        clipboard_paste(tx);
+    }
+
     /// Receives a clipboard event sent by the `copy`/`cut`/`paste` listeners.
     pub fn try_receive_clipboard_event(&self) -> Option<WebClipboardEvent> {
         let Some(rx) = &self.event_receiver else {
@@ -75,6 +129,8 @@ impl WebClipboard {
 #[derive(Default)]
 pub struct SubscribedEvents {
     event_closures: Vec<EventClosure>,
+    /// The canvas the events are filtered by, see [`startup_setup_web_events`].
+    canvas_selector: Option<String>,
 }
 
 impl SubscribedEvents {
@@ -105,89 +161,149 @@ struct EventClosure {
     closure: Closure<dyn FnMut(web_sys::ClipboardEvent)>,
 }
 
-fn setup_clipboard_copy(subscribed_events: &mut SubscribedEvents, tx: Sender<WebClipboardEvent>) {
-    let Some(window) = web_sys::window() else {
-        log::error!("Failed to add the \"copy\" listener: no window object");
-        return;
-    };
-    let Some(document) = window.document() else {
-        log::error!("Failed to add the \"copy\" listener: no document object");
-        return;
-    };
+/// Errors that prevent the web clipboard from working, they are logged instead of panicking
+/// to let the rest of the app run (in a worker or a page without a document, for example).
+#[derive(Debug)]
+enum WebClipboardError {
+    /// The global `window` object isn't available.
+    NoWindow,
+    /// The window doesn't have a document.
+    NoDocument,
+    /// The 2D context of a canvas isn't available.
+    NoCanvasContext,
+    /// An object returned by a Web API has an unexpected type.
+    UnexpectedType(&'static str),
+    /// A Web API call threw an exception.
+    Js(JsValue),
+}
 
-    let closure = Closure::<dyn FnMut(_)>::new(move |_event: web_sys::ClipboardEvent| {
-        if tx.send(WebClipboardEvent::Copy).is_err() {
-            log::error!("Failed to send a \"copy\" event: channel is disconnected");
+impl std::fmt::Display for WebClipboardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoWindow => write!(f, "no window object"),
+            Self::NoDocument => write!(f, "no document object"),
+            Self::NoCanvasContext => write!(f, "no 2d canvas context"),
+            Self::UnexpectedType(expected) => write!(f, "expected an object of type {expected}"),
+            Self::Js(value) => write!(f, "{}", string_from_js_value(value)),
         }
-    });
+    }
+}
 
-    let listener = closure.as_ref().unchecked_ref();
+impl From<JsValue> for WebClipboardError {
+    fn from(value: JsValue) -> Self {
+        Self::Js(value)
+    }
+}
 
-    if let Err(err) = document.add_event_listener_with_callback("copy", listener) {
-        log::error!(
-            "Failed to add the \"copy\" event listener: {}",
-            string_from_js_value(&err)
-        );
-        drop(closure);
-        return;
-    };
-    subscribed_events.event_closures.push(EventClosure {
-        target: <web_sys::Document as std::convert::AsRef<web_sys::EventTarget>>::as_ref(&document)
-            .clone(),
-        event_name: "copy".to_owned(),
-        closure,
-    });
+fn window() -> Result<web_sys::Window, WebClipboardError> {
+    web_sys::window().ok_or(WebClipboardError::NoWindow)
 }
 
-fn setup_clipboard_cut(subscribed_events: &mut SubscribedEvents, tx: Sender<WebClipboardEvent>) {
-    let Some(window) = web_sys::window() else {
-        log::error!("Failed to add the \"cut\" listener: no window object");
-        return;
-    };
-    let Some(document) = window.document() else {
-        log::error!("Failed to add the \"cut\" listener: no document object");
-        return;
-    };
+fn document() -> Result<web_sys::Document, WebClipboardError> {
+    window()?.document().ok_or(WebClipboardError::NoDocument)
+}
 
-    let closure = Closure::<dyn FnMut(_)>::new(move |_event: web_sys::ClipboardEvent| {
-        if tx.send(WebClipboardEvent::Cut).is_err() {
-            log::error!("Failed to send a \"cut\" event: channel is disconnected");
+/// Adds a clipboard event listener to the document, logs an error if it fails.
+fn subscribe(
+    subscribed_events: &mut SubscribedEvents,
+    event_name: &str,
+    mut callback: impl FnMut(web_sys::ClipboardEvent) + 'static,
+) {
+    let canvas_selector = subscribed_events.canvas_selector.clone();
+    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::ClipboardEvent| {
+        if let Some(canvas_selector) = &canvas_selector {
+            if !targets_canvas(&event, canvas_selector) {
+                return;
+            }
         }
+        callback(event);
     });
+    let result = document().and_then(|document| {
+        document.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())?;
+        Ok(document)
+    });
+    match result {
+        Ok(document) => subscribed_events.event_closures.push(EventClosure {
+            target: <web_sys::Document as std::convert::AsRef<web_sys::EventTarget>>::as_ref(
+                &document,
+            )
+            .clone(),
+            event_name: event_name.to_owned(),
+            closure,
+        }),
+        Err(err) => log::error!("Failed to add the \"{event_name}\" event listener: {err}"),
+    }
+}
 
-    let listener = closure.as_ref().unchecked_ref();
-
-    if let Err(err) = document.add_event_listener_with_callback("cut", listener) {
-        log::error!(
-            "Failed to add the \"cut\" event listener: {}",
-            string_from_js_value(&err)
-        );
-        drop(closure);
-        return;
+/// Returns `true` if the event was dispatched to the element matching the selector (or to the
+/// [`TextAgent`](crate::text_agent::TextAgent), which has the focus while a text field does).
+fn targets_canvas(event: &web_sys::ClipboardEvent, canvas_selector: &str) -> bool {
+    let Some(element) = crate::web_dom::event_target_element(event) else {
+        return false;
     };
-    subscribed_events.event_closures.push(EventClosure {
-        target: <web_sys::Document as std::convert::AsRef<web_sys::EventTarget>>::as_ref(&document)
-            .clone(),
-        event_name: "cut".to_owned(),
-        closure,
+    if element.id() == crate::text_agent::TEXT_AGENT_ID {
+        return true;
+    }
+    match element.matches(canvas_selector) {
+        Ok(matches) => matches,
+        Err(err) => {
+            log::error!(
+                "Invalid canvas selector \"{canvas_selector}\": {}",
+                string_from_js_value(&err)
+            );
+            false
+        }
+    }
+}
+
+fn setup_clipboard_copy(subscribed_events: &mut SubscribedEvents, tx: Sender<WebClipboardEvent>) {
+    subscribe(subscribed_events, "copy", move |_event| {
+        if tx.send(WebClipboardEvent::Copy).is_err() {
+            log::error!("Failed to send a \"copy\" event: channel is disconnected");
+        }
     });
 }
 
-fn setup_clipboard_paste(subscribed_events: &mut SubscribedEvents, tx: Sender<WebClipboardEvent>) {
-    let Some(window) = web_sys::window() else {
-        log::error!("Failed to add the \"paste\" listener: no window object");
-        return;
-    };
-    let Some(document) = window.document() else {
-        log::error!("Failed to add the \"paste\" listener: no document object");
-        return;
-    };
+fn setup_clipboard_cut(subscribed_events: &mut SubscribedEvents, tx: Sender<WebClipboardEvent>) {
+    subscribe(subscribed_events, "cut", move |_event| {
+        if tx.send(WebClipboardEvent::Cut).is_err() {
+            log::error!("Failed to send a \"cut\" event: channel is disconnected");
+        }
+    });
+}
 
-    let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::ClipboardEvent| {
+fn setup_clipboard_paste(
+    subscribed_events: &mut SubscribedEvents,
+    tx: Sender<WebClipboardEvent>,
+    #[cfg(feature = "render")] image_tx: Sender<PastedImage>,
+) {
+    subscribe(subscribed_events, "paste", move |event| {
         let Some(clipboard_data) = event.clipboard_data() else {
             log::error!("Failed to access clipboard data");
             return;
         };
+        #[cfg(feature = "render")]
+        if let Some(files) = clipboard_data.files() {
+            for file in (0..files.length()).filter_map(|i| files.get(i)) {
+                if !file.type_().starts_with("image/") {
+                    continue;
+                }
+                // Decoding is asynchronous, the image is sent once it's ready.
+                let image_tx = image_tx.clone();
+                spawn_local(async move {
+                    match decode_image(&file).await {
+                        Ok(image) => {
+                            if image_tx.send(image).is_err() {
+                                log::error!(
+                                    "Failed to send a pasted image: channel is disconnected"
+                                );
+                            }
+                        }
+                        Err(err) => log::warn!("Failed to decode a pasted image: {err}"),
+                    }
+                });
+            }
+        }
         match clipboard_data.get_data("text/plain") {
             Ok(data) => {
                 if tx.send(WebClipboardEvent::Paste(data)).is_err() {
@@ -202,39 +318,56 @@ fn setup_clipboard_paste(subscribed_events: &mut SubscribedEvents, tx: Sender<We
             }
         }
     });
+}
 
-    let listener = closure.as_ref().unchecked_ref();
+/// Decodes an image with the browser by drawing it onto a canvas.
+#[cfg(feature = "render")]
+async fn decode_image(blob: &web_sys::Blob) -> Result<PastedImage, WebClipboardError> {
+    let window = window()?;
+    let document = window.document().ok_or(WebClipboardError::NoDocument)?;
 
-    if let Err(err) = document.add_event_listener_with_callback("paste", listener) {
-        log::error!(
-            "Failed to add the \"paste\" event listener: {}",
-            string_from_js_value(&err)
-        );
-        drop(closure);
-        return;
-    };
-    subscribed_events.event_closures.push(EventClosure {
-        target: <web_sys::Document as std::convert::AsRef<web_sys::EventTarget>>::as_ref(&document)
-            .clone(),
-        event_name: "paste".to_owned(),
-        closure,
-    });
+    let bitmap: web_sys::ImageBitmap =
+        wasm_bindgen_futures::JsFuture::from(window.create_image_bitmap_with_blob(blob)?)
+            .await?
+            .dyn_into()
+            .map_err(|_| WebClipboardError::UnexpectedType("ImageBitmap"))?;
+    let (width, height) = (bitmap.width(), bitmap.height());
+
+    let canvas: web_sys::HtmlCanvasElement = document
+        .create_element("canvas")?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("HtmlCanvasElement"))?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context: web_sys::CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or(WebClipboardError::NoCanvasContext)?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("CanvasRenderingContext2d"))?;
+    context.draw_image_with_image_bitmap(&bitmap, 0.0, 0.0)?;
+    bitmap.close();
+    let image_data = context.get_image_data(0, 0, width as i32, height as i32)?;
+
+    Ok(PastedImage {
+        window: None,
+        width,
+        height,
+        rgba: image_data.data().0,
+    })
 }
 
 /// Sets contents of the clipboard via the Web API.
 fn clipboard_copy(contents: String) {
     spawn_local(async move {
-        let Some(window) = web_sys::window() else {
-            log::warn!("Failed to access the window object");
-            return;
-        };
-
-        let nav = window.navigator();
-        let Some(clipboard) = nav.clipboard() else {
-            log::warn!("Failed to access clipboard");
-            return;
+        let window = match window() {
+            Ok(window) => window,
+            Err(err) => {
+                log::warn!("Failed to access clipboard: {err}");
+                return;
+            }
         };
 
+        let clipboard = window.navigator().clipboard();
         let promise = clipboard.write_text(&contents);
         if let Err(err) = wasm_bindgen_futures::JsFuture::from(promise).await {
             log::warn!(
@@ -245,6 +378,99 @@ fn clipboard_copy(contents: String) {
     });
 }
 
+/// Draws an image onto a canvas and writes the canvas to the clipboard as PNG.
+fn clipboard_copy_image(image: &egui::ColorImage) -> Result<(), WebClipboardError> {
+    let window = window()?;
+    let document = window.document().ok_or(WebClipboardError::NoDocument)?;
+    let clipboard = window.navigator().clipboard();
+
+    let [width, height] = image.size;
+    let canvas: web_sys::HtmlCanvasElement = document
+        .create_element("canvas")?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("HtmlCanvasElement"))?;
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+    let context: web_sys::CanvasRenderingContext2d = canvas
+        .get_context("2d")?
+        .ok_or(WebClipboardError::NoCanvasContext)?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("CanvasRenderingContext2d"))?;
+    let rgba: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|color| color.to_srgba_unmultiplied())
+        .collect();
+    let image_data = web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+        wasm_bindgen::Clamped(&rgba),
+        width as u32,
+        height as u32,
+    )?;
+    context.put_image_data(&image_data, 0, 0)?;
+
+    // `ClipboardItem` accepts a promise, so the item is created while the page still has the
+    // user activation that the write requires.
+    let png = js_sys::Promise::new(&mut |resolve, _reject| {
+        if let Err(err) = canvas.to_blob_with_type(&resolve, "image/png") {
+            log::warn!("Failed to encode an image: {}", string_from_js_value(&err));
+        }
+    });
+    let items = js_sys::Object::new();
+    js_sys::Reflect::set(&items, &"image/png".into(), &png)?;
+    // `web-sys` bindings of `ClipboardItem` differ between versions, so it's constructed directly.
+    let clipboard_item_class: js_sys::Function =
+        js_sys::Reflect::get(&window, &"ClipboardItem".into())?
+            .dyn_into()
+            .map_err(|_| WebClipboardError::UnexpectedType("ClipboardItem"))?;
+    let clipboard_item =
+        js_sys::Reflect::construct(&clipboard_item_class, &js_sys::Array::of1(&items))?;
+    let write: js_sys::Function = js_sys::Reflect::get(&clipboard, &"write".into())?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("Function"))?;
+    let promise: js_sys::Promise = write
+        .call1(&clipboard, &js_sys::Array::of1(&clipboard_item))?
+        .dyn_into()
+        .map_err(|_| WebClipboardError::UnexpectedType("Promise"))?;
+    spawn_local(async move {
+        if let Err(err) = wasm_bindgen_futures::JsFuture::from(promise).await {
+            log::warn!(
+                "Failed to write an image to clipboard: {}",
+                string_from_js_value(&err)
+            );
+        }
+    });
+    Ok(())
+}
+
+/// Reads contents of the clipboard via the Web API and sends them as a `paste` event.
+fn clipboard_paste(tx: Sender<WebClipboardEvent>) {
+    spawn_local(async move {
+        let window = match window() {
+            Ok(window) => window,
+            Err(err) => {
+                log::warn!("Failed to access clipboard: {err}");
+                return;
+            }
+        };
+
+        let clipboard = window.navigator().clipboard();
+        // Rejects if the user denies the permission or the page isn't focused.
+        let promise = clipboard.read_text();
+        match wasm_bindgen_futures::JsFuture::from(promise).await {
+            Ok(contents) => {
+                let contents = contents.as_string().unwrap_or_default();
+                if tx.send(WebClipboardEvent::Paste(contents)).is_err() {
+                    log::error!("Failed to send the clipboard contents: channel is disconnected");
+                }
+            }
+            Err(err) => log::warn!(
+                "Failed to read from clipboard: {}",
+                string_from_js_value(&err)
+            ),
+        }
+    });
+}
+
 fn string_from_js_value(value: &JsValue) -> String {
     value.as_string().unwrap_or_else(|| format!("{value:#?}"))
 }
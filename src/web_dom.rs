@@ -0,0 +1,56 @@
+use wasm_bindgen::{JsCast, JsValue};
+
+/// Returns the first element that matches the selector in the document, or in the open shadow
+/// roots of the page if the document doesn't have one.
+///
+/// This finds the canvas of an app embedded in a web component, which `document.querySelector`
+/// doesn't look into.
+pub(crate) fn query_selector_deep(
+    document: &web_sys::Document,
+    selector: &str,
+) -> Result<Option<web_sys::Element>, JsValue> {
+    if let Some(element) = document.query_selector(selector)? {
+        return Ok(Some(element));
+    }
+    let mut shadow_roots = shadow_roots(&document.query_selector_all("*")?);
+    while let Some(shadow_root) = shadow_roots.pop() {
+        if let Some(element) = shadow_root.query_selector(selector)? {
+            return Ok(Some(element));
+        }
+        shadow_roots.extend(self::shadow_roots(&shadow_root.query_selector_all("*")?));
+    }
+    Ok(None)
+}
+
+fn shadow_roots(nodes: &web_sys::NodeList) -> Vec<web_sys::ShadowRoot> {
+    (0..nodes.length())
+        .filter_map(|index| nodes.get(index))
+        .filter_map(|node| node.dyn_into::<web_sys::Element>().ok())
+        .filter_map(|element| element.shadow_root())
+        .collect()
+}
+
+/// Returns the element an event was dispatched to.
+///
+/// Listeners of the document see events dispatched inside a shadow root as if they were
+/// dispatched to its host, the original target is the first element of the composed path.
+pub(crate) fn event_target_element(event: &web_sys::Event) -> Option<web_sys::Element> {
+    event
+        .composed_path()
+        .iter()
+        .find_map(|target| target.dyn_into::<web_sys::Element>().ok())
+        .or_else(|| event.target()?.dyn_into().ok())
+}
+
+/// Returns the focused element, looking into the open shadow roots that contain it (the
+/// document only reports their hosts).
+pub(crate) fn active_element(document: &web_sys::Document) -> Option<web_sys::Element> {
+    let mut element = document.active_element()?;
+    while let Some(inner) = element
+        .shadow_root()
+        .and_then(|shadow_root| shadow_root.active_element())
+    {
+        element = inner;
+    }
+    Some(element)
+}
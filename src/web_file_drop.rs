@@ -0,0 +1,184 @@
+use crate::{EguiContextSettings, EguiInput};
+use bevy::{
+    log,
+    prelude::{Local, NonSendMut, Query, With},
+    window::{PrimaryWindow, Window},
+};
+use std::{cell::RefCell, rc::Rc};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::spawn_local;
+
+#[derive(Default)]
+struct WebFileDropState {
+    hovered_files: Vec<egui::HoveredFile>,
+    hovered_files_changed: bool,
+    // Files whose contents have been read since the last frame.
+    dropped_files: Vec<egui::DroppedFile>,
+}
+
+// A listener of the document, with its event name.
+type DropListener = (
+    web_sys::Document,
+    &'static str,
+    Closure<dyn FnMut(web_sys::DragEvent)>,
+);
+
+/// Stores the `dragover`, `dragleave` and `drop` listeners that pass files dropped onto the page
+/// to the primary window's context, as winit doesn't report them on web.
+#[derive(Default)]
+pub struct WebFileDropListener {
+    state: Rc<RefCell<WebFileDropState>>,
+    listeners: Vec<DropListener>,
+}
+
+impl WebFileDropListener {
+    /// Removes the listeners, this can be useful for gracefully destroying a Bevy instance
+    /// in a page.
+    pub fn unsubscribe(&mut self) {
+        for (document, event_name, closure) in self.listeners.drain(..) {
+            if let Err(err) = document
+                .remove_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+            {
+                log::error!("Failed to remove the {event_name:?} event listener: {err:?}");
+            }
+        }
+    }
+
+    fn subscribe(&mut self, canvas_selector: Option<String>) {
+        let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+            log::error!("Failed to add the file drop event listeners: no document object");
+            return;
+        };
+
+        let state = self.state.clone();
+        self.add_listener(
+            &document,
+            "dragover",
+            canvas_selector.clone(),
+            move |event| {
+                // Files can only be dropped if the default action of `dragover` is prevented.
+                event.prevent_default();
+                let Some(items) = event
+                    .data_transfer()
+                    .map(|data_transfer| data_transfer.items())
+                else {
+                    return;
+                };
+                let hovered_files = (0..items.length())
+                    .filter_map(|index| items.get(index))
+                    .filter(|item| item.kind() == "file")
+                    .map(|item| egui::HoveredFile {
+                        path: None,
+                        mime: item.type_(),
+                    })
+                    .collect::<Vec<_>>();
+                let mut state = state.borrow_mut();
+                if state.hovered_files != hovered_files {
+                    state.hovered_files = hovered_files;
+                    state.hovered_files_changed = true;
+                }
+            },
+        );
+
+        let state = self.state.clone();
+        self.add_listener(&document, "dragleave", canvas_selector.clone(), move |_| {
+            let mut state = state.borrow_mut();
+            state.hovered_files.clear();
+            state.hovered_files_changed = true;
+        });
+
+        let state = self.state.clone();
+        self.add_listener(&document, "drop", canvas_selector, move |event| {
+            // Keep the browser from opening the file.
+            event.prevent_default();
+            {
+                let mut state = state.borrow_mut();
+                state.hovered_files.clear();
+                state.hovered_files_changed = true;
+            }
+            let Some(files) = event
+                .data_transfer()
+                .and_then(|data_transfer| data_transfer.files())
+            else {
+                return;
+            };
+            for file in (0..files.length()).filter_map(|index| files.get(index)) {
+                // Reading is asynchronous, the file is passed to Egui once it's read.
+                let state = state.clone();
+                spawn_local(async move {
+                    match read_file(&file).await {
+                        Ok(dropped_file) => state.borrow_mut().dropped_files.push(dropped_file),
+                        Err(err) => log::warn!("Failed to read a dropped file: {err:?}"),
+                    }
+                });
+            }
+        });
+    }
+
+    fn add_listener(
+        &mut self,
+        document: &web_sys::Document,
+        event_name: &'static str,
+        canvas_selector: Option<String>,
+        mut listener: impl FnMut(web_sys::DragEvent) + 'static,
+    ) {
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::DragEvent| {
+            // Like the clipboard events, only handle the files dropped onto the Bevy canvas
+            // if it's known.
+            if let Some(canvas_selector) = &canvas_selector {
+                let targets_canvas = crate::web_dom::event_target_element(&event)
+                    .is_some_and(|element| element.matches(canvas_selector).unwrap_or(false));
+                if !targets_canvas {
+                    return;
+                }
+            }
+            listener(event);
+        });
+        match document
+            .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        {
+            Ok(()) => self.listeners.push((document.clone(), event_name, closure)),
+            Err(err) => log::error!("Failed to add the {event_name:?} event listener: {err:?}"),
+        }
+    }
+}
+
+async fn read_file(file: &web_sys::File) -> Result<egui::DroppedFile, JsValue> {
+    let buffer = wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+    Ok(egui::DroppedFile {
+        path: None,
+        name: file.name(),
+        mime: file.type_(),
+        last_modified: Some(
+            std::time::UNIX_EPOCH + std::time::Duration::from_millis(file.last_modified() as u64),
+        ),
+        bytes: Some(bytes.into()),
+    })
+}
+
+/// Adds the file drop listeners once the primary window exists and passes the hovered and
+/// dropped files to its context.
+pub fn process_web_file_drop_system(
+    mut listener: NonSendMut<WebFileDropListener>,
+    mut primary_window: Query<(&Window, &mut EguiInput, &EguiContextSettings), With<PrimaryWindow>>,
+    mut subscribed: Local<bool>,
+) {
+    let Ok((window, mut egui_input, context_settings)) = primary_window.get_single_mut() else {
+        return;
+    };
+    if !*subscribed {
+        *subscribed = true;
+        listener.subscribe(window.canvas.clone());
+    }
+
+    let mut state = listener.state.borrow_mut();
+    let dropped_files = std::mem::take(&mut state.dropped_files);
+    if context_settings.display_only {
+        return;
+    }
+    if std::mem::take(&mut state.hovered_files_changed) {
+        egui_input.hovered_files = state.hovered_files.clone();
+    }
+    egui_input.dropped_files.extend(dropped_files);
+}
@@ -0,0 +1,254 @@
+use crate::EguiWantsInput;
+use bevy::{
+    log,
+    prelude::{DetectChanges, Local, NonSendMut, Query, Res, Resource, With},
+    window::{PrimaryWindow, Window},
+};
+use std::{borrow::Cow, cell::RefCell, rc::Rc};
+use wasm_bindgen::prelude::*;
+
+/// A resource that controls which key combinations have their default browser action prevented
+/// (saving the page with Ctrl+S, or scrolling it with Space, for example) while Egui wants
+/// keyboard input.
+///
+/// When Egui doesn't want keyboard input, the combinations reach the page as usual, so the
+/// browser (or other page scripts) can still handle the shortcuts of the game.
+///
+/// Winit prevents the default action of every key event unless
+/// [`Window::prevent_default_event_handling`] is disabled, so the resource only makes a
+/// difference for windows where it is.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_egui::web_keyboard::{WebKeyboardSettings, WebShortcut};
+///
+/// fn setup(mut web_keyboard_settings: ResMut<WebKeyboardSettings>) {
+///     // Let the browser open the developer tools even while typing in Egui.
+///     web_keyboard_settings
+///         .prevent_default
+///         .retain(|shortcut| shortcut.code != "F12");
+///     web_keyboard_settings
+///         .prevent_default
+///         .push(WebShortcut::command("KeyD"));
+/// }
+/// ```
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct WebKeyboardSettings {
+    /// The combinations to prevent the default action of.
+    ///
+    /// Defaults to Tab, Space, Backspace, arrow keys, Page Up/Down, Home, End, the function
+    /// keys, and Ctrl/Cmd with F, G, O, P and S.
+    pub prevent_default: Vec<WebShortcut>,
+    /// Whether to prevent the default action of the browser and media keys which don't have an
+    /// [`egui::Key`] equivalent (navigating back with `BrowserBack`, for example), even when Egui
+    /// doesn't want keyboard input, so that the app can handle them with
+    /// [`EguiUnmappedKeyEvent`](crate::EguiUnmappedKeyEvent) instead.
+    ///
+    /// Is `false` by default, to not take the navigation of the page away from the user
+    /// unexpectedly.
+    pub capture_unmapped_keys: bool,
+}
+
+/// [`KeyboardEvent.code`](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code)
+/// of the keys of [`WebKeyboardSettings::capture_unmapped_keys`].
+const UNMAPPED_KEY_CODES: &[&str] = &[
+    "BrowserBack",
+    "BrowserFavorites",
+    "BrowserForward",
+    "BrowserHome",
+    "BrowserRefresh",
+    "BrowserSearch",
+    "BrowserStop",
+    "MediaPlayPause",
+    "MediaSelect",
+    "MediaStop",
+    "MediaTrackNext",
+    "MediaTrackPrevious",
+    "AudioVolumeDown",
+    "AudioVolumeMute",
+    "AudioVolumeUp",
+];
+
+impl Default for WebKeyboardSettings {
+    fn default() -> Self {
+        let keys = [
+            "Tab",
+            "Space",
+            "Backspace",
+            "ArrowUp",
+            "ArrowDown",
+            "ArrowLeft",
+            "ArrowRight",
+            "PageUp",
+            "PageDown",
+            "Home",
+            "End",
+            "F1",
+            "F3",
+            "F5",
+            "F6",
+            "F7",
+            "F10",
+            "F11",
+            "F12",
+        ];
+        let commands = ["KeyF", "KeyG", "KeyO", "KeyP", "KeyS"];
+        Self {
+            prevent_default: keys
+                .into_iter()
+                .map(WebShortcut::key)
+                .chain(commands.into_iter().map(WebShortcut::command))
+                .collect(),
+            capture_unmapped_keys: false,
+        }
+    }
+}
+
+/// A key combination of [`WebKeyboardSettings`].
+///
+/// Modifiers that aren't required are ignored: `WebShortcut::key("Tab")` matches Shift+Tab as
+/// well.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebShortcut {
+    /// [`KeyboardEvent.code`](https://developer.mozilla.org/en-US/docs/Web/API/KeyboardEvent/code)
+    /// of the key, such as `KeyS` or `F5`. Codes don't depend on the keyboard layout.
+    pub code: Cow<'static, str>,
+    /// Whether Ctrl (or Cmd) has to be held.
+    pub command: bool,
+    /// Whether Shift has to be held.
+    pub shift: bool,
+    /// Whether Alt has to be held.
+    pub alt: bool,
+}
+
+impl WebShortcut {
+    /// A key pressed with any modifiers.
+    #[must_use]
+    pub fn key(code: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            code: code.into(),
+            command: false,
+            shift: false,
+            alt: false,
+        }
+    }
+
+    /// A key pressed with Ctrl (or Cmd).
+    #[must_use]
+    pub fn command(code: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            command: true,
+            ..Self::key(code)
+        }
+    }
+
+    /// Returns `true` if the event is a press of the combination.
+    #[must_use]
+    pub fn matches(&self, event: &web_sys::KeyboardEvent) -> bool {
+        event.code() == self.code
+            && (!self.command || event.ctrl_key() || event.meta_key())
+            && (!self.shift || event.shift_key())
+            && (!self.alt || event.alt_key())
+    }
+}
+
+#[derive(Default)]
+struct WebKeyboardState {
+    prevent_default: Vec<WebShortcut>,
+    capture_unmapped_keys: bool,
+    egui_wants_keyboard_input: bool,
+}
+
+// The listener, with the document it's added to.
+type KeydownListener = (
+    web_sys::Document,
+    Closure<dyn FnMut(web_sys::KeyboardEvent)>,
+);
+
+/// Stores the `keydown` listener of [`WebKeyboardSettings`].
+#[derive(Default)]
+pub struct WebKeyboardListener {
+    state: Rc<RefCell<WebKeyboardState>>,
+    listener: Option<KeydownListener>,
+}
+
+impl WebKeyboardListener {
+    /// Removes the listener, this can be useful for gracefully destroying a Bevy instance
+    /// in a page.
+    pub fn unsubscribe(&mut self) {
+        let Some((document, closure)) = self.listener.take() else {
+            return;
+        };
+        if let Err(err) = document
+            .remove_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        {
+            log::error!("Failed to remove the \"keydown\" event listener: {err:?}");
+        }
+    }
+
+    fn subscribe(&mut self, canvas_selector: Option<String>) {
+        let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+            log::error!("Failed to add the \"keydown\" event listener: no document object");
+            return;
+        };
+        let state = self.state.clone();
+        let closure = Closure::<dyn FnMut(_)>::new(move |event: web_sys::KeyboardEvent| {
+            let state = state.borrow();
+            let captured =
+                state.capture_unmapped_keys && UNMAPPED_KEY_CODES.contains(&event.code().as_str());
+            if !state.egui_wants_keyboard_input && !captured {
+                return;
+            }
+            if let Some(canvas_selector) = &canvas_selector {
+                let targets_canvas =
+                    crate::web_dom::event_target_element(&event).is_some_and(|element| {
+                        element.id() == crate::text_agent::TEXT_AGENT_ID
+                            || element.matches(canvas_selector).unwrap_or(false)
+                    });
+                if !targets_canvas {
+                    return;
+                }
+            }
+            if captured
+                || state
+                    .prevent_default
+                    .iter()
+                    .any(|shortcut| shortcut.matches(&event))
+            {
+                event.prevent_default();
+            }
+        });
+        match document.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+        {
+            Ok(()) => self.listener = Some((document, closure)),
+            Err(err) => log::error!("Failed to add the \"keydown\" event listener: {err:?}"),
+        }
+    }
+}
+
+/// Adds the `keydown` listener once the primary window exists and passes it the settings and
+/// whether Egui wants keyboard input.
+pub fn update_web_keyboard_system(
+    web_keyboard_settings: Res<WebKeyboardSettings>,
+    egui_wants_input: Res<EguiWantsInput>,
+    primary_window: Query<&Window, With<PrimaryWindow>>,
+    mut listener: NonSendMut<WebKeyboardListener>,
+    mut subscribed: Local<bool>,
+) {
+    if !*subscribed {
+        let Ok(window) = primary_window.get_single() else {
+            return;
+        };
+        *subscribed = true;
+        // Like the clipboard events, only handle the keys of the Bevy canvas (and the text agent)
+        // if it's known.
+        listener.subscribe(window.canvas.clone());
+    }
+
+    let mut state = listener.state.borrow_mut();
+    if web_keyboard_settings.is_changed() {
+        state.prevent_default = web_keyboard_settings.prevent_default.clone();
+        state.capture_unmapped_keys = web_keyboard_settings.capture_unmapped_keys;
+    }
+    state.egui_wants_keyboard_input = egui_wants_input.wants_keyboard_input;
+}